@@ -0,0 +1,261 @@
+//! Zone-level analysis of entries which are valid on their own, but
+//! likely mistakes in the context of the whole zone.
+//!
+//! Unlike the [`conflicts`](crate::conflicts) module, the findings of this
+//! module do not prevent a zone from being published, but are worth
+//! reporting back to the owners of the records involved, which is why
+//! each finding carries the [`EntrySource`]s of the entries involved.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use kubizone_common::{Class, FullyQualifiedDomainName, Type};
+use thiserror::Error;
+
+use crate::v1alpha1::{EntrySource, ZoneEntry};
+
+/// Suspicious entry or combination of entries within a zone.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Finding {
+    /// Identical records were published from different namespaces,
+    /// which usually means two teams claim the same name.
+    #[error("{fqdn} {class} {type_} {rdata} is published from {} namespaces", sources.len())]
+    DuplicateAcrossNamespaces {
+        fqdn: FullyQualifiedDomainName,
+        type_: Type,
+        class: Class,
+        rdata: String,
+        sources: Vec<EntrySource>,
+    },
+    /// The entry is at or below the delegation of a child zone, and will
+    /// never be served, since resolvers are referred to the child zone.
+    #[error("{fqdn} {type_} is shadowed by the delegation of {cut}")]
+    Shadowed {
+        fqdn: FullyQualifiedDomainName,
+        type_: Type,
+        entry_source: Option<EntrySource>,
+        cut: FullyQualifiedDomainName,
+        cut_source: Option<EntrySource>,
+    },
+}
+
+/// Analyze the entries of the zone at `origin`.
+///
+/// Any `NS` entry below `origin` is treated as the delegation of a child
+/// zone. The `NS` and `DS` records of the delegation itself, and glue
+/// records for its nameservers are not considered shadowed.
+pub fn analyze(origin: &FullyQualifiedDomainName, entries: &[ZoneEntry]) -> Vec<Finding> {
+    let mut findings = duplicates_across_namespaces(entries);
+    findings.extend(shadowed(origin, entries));
+    findings
+}
+
+fn duplicates_across_namespaces(entries: &[ZoneEntry]) -> Vec<Finding> {
+    let mut records: BTreeMap<_, Vec<&EntrySource>> = BTreeMap::new();
+    for entry in entries {
+        if let Some(source) = &entry.source {
+            records
+                .entry((&entry.fqdn, entry.type_, entry.class, &entry.rdata))
+                .or_default()
+                .push(source);
+        }
+    }
+
+    records
+        .into_iter()
+        .filter(|(_, sources)| {
+            let namespaces: BTreeSet<_> = sources.iter().map(|source| &source.namespace).collect();
+            namespaces.len() > 1
+        })
+        .map(
+            |((fqdn, type_, class, rdata), sources)| Finding::DuplicateAcrossNamespaces {
+                fqdn: fqdn.clone(),
+                type_,
+                class,
+                rdata: rdata.clone(),
+                sources: sources.into_iter().cloned().collect(),
+            },
+        )
+        .collect()
+}
+
+/// Check if `entry` belongs to the delegation at `cut`, rather than being shadowed by it.
+fn is_part_of_delegation(
+    entry: &ZoneEntry,
+    cut: &FullyQualifiedDomainName,
+    ns: &[&ZoneEntry],
+) -> bool {
+    if entry.fqdn == *cut && matches!(entry.type_, Type::NS | Type::DS | Type::RRSIG | Type::NSEC) {
+        return true;
+    }
+
+    let is_glue = matches!(entry.type_, Type::A | Type::AAAA);
+    is_glue
+        && ns.iter().any(|ns| {
+            ns.fqdn == *cut
+                && FullyQualifiedDomainName::try_from(ns.rdata.as_str())
+                    .is_ok_and(|nameserver| nameserver == entry.fqdn)
+        })
+}
+
+fn shadowed(origin: &FullyQualifiedDomainName, entries: &[ZoneEntry]) -> Vec<Finding> {
+    let ns: Vec<_> = entries
+        .iter()
+        .filter(|entry| entry.type_ == Type::NS && entry.fqdn.is_subdomain_of(origin))
+        .collect();
+
+    entries
+        .iter()
+        .filter_map(|entry| {
+            // The topmost delegation is the one resolvers are referred to.
+            let cut = ns
+                .iter()
+                .filter(|cut| entry.fqdn == cut.fqdn || entry.fqdn.is_subdomain_of(&cut.fqdn))
+                .min_by_key(|cut| cut.fqdn.len())?;
+
+            if is_part_of_delegation(entry, &cut.fqdn, &ns) {
+                return None;
+            }
+
+            Some(Finding::Shadowed {
+                fqdn: entry.fqdn.clone(),
+                type_: entry.type_,
+                entry_source: entry.source.clone(),
+                cut: cut.fqdn.clone(),
+                cut_source: cut.source.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use kubizone_common::{Class, FullyQualifiedDomainName, Type};
+
+    use crate::v1alpha1::{EntrySource, ZoneEntry};
+
+    use super::{analyze, Finding};
+
+    fn fqdn(fqdn: &str) -> FullyQualifiedDomainName {
+        FullyQualifiedDomainName::try_from(fqdn).unwrap()
+    }
+
+    fn entry(name: &str, type_: Type, rdata: &str, source: (&str, &str, &str)) -> ZoneEntry {
+        let (kind, namespace, name_) = source;
+
+        ZoneEntry {
+            fqdn: fqdn(name),
+            type_,
+            class: Class::IN,
+            ttl: 300,
+            rdata: rdata.to_string(),
+            response_policy: None,
+            weight: None,
+            policy: None,
+            views: Vec::new(),
+            source: Some(EntrySource {
+                kind: kind.to_string(),
+                namespace: Some(namespace.to_string()),
+                name: name_.to_string(),
+                uid: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_duplicates_across_namespaces() {
+        let web = entry(
+            "www.example.org.",
+            Type::A,
+            "192.0.2.1",
+            ("Record", "web", "www"),
+        );
+        let shop = entry(
+            "www.example.org.",
+            Type::A,
+            "192.0.2.1",
+            ("Record", "shop", "www"),
+        );
+        let web_again = entry(
+            "www.example.org.",
+            Type::A,
+            "192.0.2.1",
+            ("Record", "web", "www-2"),
+        );
+
+        assert!(analyze(&fqdn("example.org."), &[web.clone(), web_again]).is_empty());
+        assert_eq!(
+            analyze(&fqdn("example.org."), &[web.clone(), shop.clone()]),
+            vec![Finding::DuplicateAcrossNamespaces {
+                fqdn: fqdn("www.example.org."),
+                type_: Type::A,
+                class: Class::IN,
+                rdata: String::from("192.0.2.1"),
+                sources: vec![web.source.unwrap(), shop.source.unwrap()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_shadowed_records() {
+        let delegation = ("Zone", "dev", "dev-example-org");
+        let cut = entry(
+            "dev.example.org.",
+            Type::NS,
+            "ns1.dev.example.org.",
+            delegation,
+        );
+        let entries = [
+            entry(
+                "example.org.",
+                Type::NS,
+                "ns1.example.org.",
+                ("Zone", "dns", "example-org"),
+            ),
+            cut.clone(),
+            entry(
+                "dev.example.org.",
+                Type::DS,
+                "12345 13 2 abcdef",
+                delegation,
+            ),
+            entry("ns1.dev.example.org.", Type::A, "192.0.2.53", delegation),
+            entry(
+                "dev.example.org.",
+                Type::A,
+                "192.0.2.1",
+                ("Record", "web", "dev"),
+            ),
+            entry(
+                "api.dev.example.org.",
+                Type::A,
+                "192.0.2.2",
+                ("Record", "web", "api"),
+            ),
+            entry(
+                "www.example.org.",
+                Type::A,
+                "192.0.2.3",
+                ("Record", "web", "www"),
+            ),
+        ];
+
+        let shadowed: Vec<_> = analyze(&fqdn("example.org."), &entries)
+            .into_iter()
+            .map(|finding| match finding {
+                Finding::Shadowed {
+                    fqdn: name,
+                    cut: cut_name,
+                    cut_source,
+                    ..
+                } => {
+                    assert_eq!(cut_name, fqdn("dev.example.org."));
+                    assert_eq!(cut_source, cut.source);
+                    name.to_string()
+                }
+                finding => panic!("unexpected finding {finding}"),
+            })
+            .collect();
+
+        assert_eq!(shadowed, ["dev.example.org.", "api.dev.example.org."]);
+    }
+}
@@ -0,0 +1,234 @@
+//! [proptest](https://docs.rs/proptest) strategies for kubizone resources.
+//!
+//! [`Arbitrary`] is implemented for [`ZoneSpec`], [`RecordSpec`],
+//! [`Delegation`] and [`ZoneRef`], producing values which are accepted by
+//! the API server: domain names and patterns are well-formed, partially
+//! qualified domain names always come with a `zoneRef`, and record data
+//! always matches the record type.
+//!
+//! ```rust
+//! use kubizone_crds::v1alpha1::RecordSpec;
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(spec in any::<RecordSpec>())| {
+//!     prop_assert!(spec.parse_rdata().is_ok());
+//! });
+//! ```
+
+use kubizone_common::{DomainName, FullyQualifiedDomainName, Pattern, Type};
+use proptest::{
+    arbitrary::Arbitrary,
+    collection::vec,
+    option,
+    prelude::*,
+    strategy::{BoxedStrategy, Strategy},
+};
+
+use crate::{
+    rdata::RData,
+    v1alpha1::{
+        defaults, Delegation, RecordDelegation, RecordSpec, ZonePatterns, ZoneRef, ZoneSpec,
+    },
+};
+
+/// Single domain name label, such as `www`.
+pub fn label() -> impl Strategy<Value = String> {
+    "[a-z](-?[a-z0-9]){0,8}"
+}
+
+/// Name of a kubernetes resource or namespace, such as `example-org`.
+pub fn resource_name() -> impl Strategy<Value = String> {
+    "[a-z](-?[a-z0-9]){0,14}"
+}
+
+/// Fully qualified domain name of one to four labels.
+pub fn fqdn() -> impl Strategy<Value = FullyQualifiedDomainName> {
+    vec(label(), 1..=4).prop_map(|labels| {
+        // Unwrap safety: Labels are always valid domain segments.
+        FullyQualifiedDomainName::try_from(format!("{}.", labels.join("."))).unwrap()
+    })
+}
+
+/// Partially qualified domain name of one to three labels.
+pub fn partial_domain_name() -> impl Strategy<Value = DomainName> {
+    vec(label(), 1..=3).prop_map(|labels| {
+        // Unwrap safety: Labels are always valid domain segments.
+        DomainName::try_from(labels.join(".")).unwrap()
+    })
+}
+
+/// Glob pattern such as `*.example`, relative to the zone it is used in.
+pub fn pattern() -> impl Strategy<Value = Pattern> {
+    vec(prop_oneof![Just(String::from("*")), label()], 1..=3).prop_map(|segments| {
+        // Unwrap safety: Labels and wildcards are always valid pattern segments.
+        Pattern::try_from(segments.join(".").as_str()).unwrap()
+    })
+}
+
+/// Domain name and matching zone reference: fully qualified names never
+/// have a zone reference, and partially qualified names always do.
+fn domain_name_and_zone_ref() -> impl Strategy<Value = (DomainName, Option<ZoneRef>)> {
+    prop_oneof![
+        fqdn().prop_map(|fqdn| (DomainName::from(fqdn), None)),
+        (partial_domain_name(), any::<ZoneRef>())
+            .prop_map(|(domain_name, zone_ref)| (domain_name, Some(zone_ref))),
+    ]
+}
+
+/// Time-to-Live within the bounds accepted by zones.
+fn ttl() -> impl Strategy<Value = u32> {
+    30..=86400u32
+}
+
+/// Record data of a handful of common types.
+pub fn rdata() -> impl Strategy<Value = RData> {
+    prop_oneof![
+        any::<std::net::Ipv4Addr>().prop_map(RData::A),
+        any::<std::net::Ipv6Addr>().prop_map(RData::AAAA),
+        fqdn().prop_map(|fqdn| RData::CNAME(fqdn.into())),
+        (any::<u16>(), fqdn()).prop_map(|(preference, exchange)| RData::MX {
+            preference,
+            exchange: exchange.into(),
+        }),
+        (any::<u16>(), any::<u16>(), any::<u16>(), fqdn()).prop_map(
+            |(priority, weight, port, target)| RData::SRV {
+                priority,
+                weight,
+                port,
+                target: target.into(),
+            }
+        ),
+        vec("[a-zA-Z0-9 =;.-]{0,32}", 1..=3).prop_map(RData::TXT),
+    ]
+}
+
+impl Arbitrary for ZoneRef {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (resource_name(), option::of(resource_name()))
+            .prop_map(|(name, namespace)| ZoneRef { name, namespace })
+            .boxed()
+    }
+}
+
+impl Arbitrary for Delegation {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        let record_delegation = (
+            pattern(),
+            vec(
+                prop_oneof![Just(Type::A), Just(Type::AAAA), Just(Type::TXT)],
+                0..=2,
+            ),
+            option::of((ttl(), ttl())),
+        )
+            .prop_map(|(pattern, types, ttl_bounds)| RecordDelegation {
+                pattern: pattern.into(),
+                types,
+                min_ttl: ttl_bounds.map(|(a, b)| a.min(b)),
+                max_ttl: ttl_bounds.map(|(a, b)| a.max(b)),
+            });
+
+        (
+            vec(resource_name(), 0..=3),
+            vec(pattern(), 0..=3),
+            vec(record_delegation, 0..=3),
+            vec(pattern(), 0..=1),
+        )
+            .prop_map(|(namespaces, zones, records, deny_zones)| Delegation {
+                namespaces,
+                namespace_selector: None,
+                zones: ZonePatterns::Glob {
+                    allow: zones,
+                    deny: deny_zones,
+                },
+                records,
+                deny_records: vec![],
+                require_approval: false,
+            })
+            .boxed()
+    }
+}
+
+impl Arbitrary for ZoneSpec {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (
+            domain_name_and_zone_ref(),
+            vec(any::<Delegation>(), 0..=2),
+            vec(fqdn(), 0..=2),
+            ttl(),
+            any::<bool>(),
+        )
+            .prop_map(
+                |((domain_name, zone_ref), delegations, nameservers, ttl, paused)| ZoneSpec {
+                    domain_name,
+                    zone_ref,
+                    delegations,
+                    generate_ns_records: !nameservers.is_empty(),
+                    nameservers,
+                    ttl,
+                    paused,
+                    refresh: defaults::REFRESH,
+                    retry: defaults::RETRY,
+                    expire: defaults::EXPIRE,
+                    negative_response_cache: defaults::NEGATIVE_RESPONSE_CACHE,
+                    ..Default::default()
+                },
+            )
+            .boxed()
+    }
+}
+
+impl Arbitrary for RecordSpec {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        (domain_name_and_zone_ref(), rdata(), option::of(ttl()))
+            .prop_map(|((domain_name, zone_ref), rdata, ttl)| RecordSpec {
+                domain_name,
+                zone_ref,
+                type_: rdata.type_(),
+                rdata: rdata.to_string(),
+                ttl,
+                ..Default::default()
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use crate::v1alpha1::{Delegation, RecordSpec, ZoneSpec};
+
+    proptest! {
+        #[test]
+        fn test_arbitrary_zone_spec(spec in any::<ZoneSpec>()) {
+            prop_assert_eq!(spec.domain_name.is_fully_qualified(), spec.zone_ref.is_none());
+
+            let json = serde_json::to_string(&spec).unwrap();
+            prop_assert_eq!(serde_json::from_str::<ZoneSpec>(&json).unwrap(), spec);
+        }
+
+        #[test]
+        fn test_arbitrary_record_spec(spec in any::<RecordSpec>()) {
+            prop_assert!(spec.parse_rdata().is_ok());
+            prop_assert_eq!(spec.domain_name.is_fully_qualified(), spec.zone_ref.is_none());
+        }
+
+        #[test]
+        fn test_arbitrary_delegation(delegation in any::<Delegation>()) {
+            let json = serde_json::to_string(&delegation).unwrap();
+            prop_assert_eq!(serde_json::from_str::<Delegation>(&json).unwrap(), delegation);
+        }
+    }
+}
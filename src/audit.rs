@@ -0,0 +1,330 @@
+//! Structured audit events for delegation decisions.
+//!
+//! Every time a zone accepts or rejects a record or sub-zone, controllers
+//! can produce a [`DelegationDecision`] and [`emit`](DelegationDecision::emit)
+//! it. Events are emitted as single-line JSON on the [`AUDIT_TARGET`] tracing
+//! target, so they can be filtered out and shipped separately from regular logs.
+//!
+//! The serialized format is considered stable: fields may be added, but
+//! existing fields will not be renamed or removed within an API version.
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use kube::Resource;
+use kubizone_common::FullyQualifiedDomainName;
+use serde::{Deserialize, Serialize};
+use tracing::*;
+
+use crate::v1alpha1::{CompiledDelegations, DelegationDenied, DomainExt, Record, Zone};
+
+/// Tracing target used when emitting audit events.
+pub const AUDIT_TARGET: &str = "kubizone::audit";
+
+/// Reference to a kubernetes object involved in a delegation decision.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditedObject {
+    pub kind: String,
+    pub namespace: Option<String>,
+    pub name: String,
+    pub uid: Option<String>,
+    pub fqdn: Option<FullyQualifiedDomainName>,
+}
+
+impl AuditedObject {
+    pub fn new<K>(object: &K) -> Self
+    where
+        K: Resource<DynamicType = ()> + DomainExt,
+    {
+        AuditedObject {
+            kind: K::kind(&()).into_owned(),
+            namespace: object.namespace(),
+            name: object.name_any(),
+            uid: object.uid(),
+            fqdn: object.fqdn().cloned(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Allowed,
+    Denied,
+}
+
+/// Location of the rule within `.spec.delegations` which allowed a decision.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchedRule {
+    /// Index of the matching delegation.
+    pub delegation: usize,
+
+    /// Index of the matching entry in the delegation's `records`
+    /// or `zones` list, depending on what was being delegated.
+    pub entry: usize,
+}
+
+/// Reason a zone denied a delegation, derived from [`DelegationDenied`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DenialReason {
+    /// Name of the [`DelegationDenied`] variant, such as `ExplicitlyDenied`.
+    pub code: String,
+
+    /// Human-readable description of the denial.
+    pub message: String,
+
+    /// Deny pattern which matched, for [`DelegationDenied::ExplicitlyDenied`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
+
+impl From<&DelegationDenied> for DenialReason {
+    fn from(denied: &DelegationDenied) -> Self {
+        let code = match denied {
+            DelegationDenied::ParentFqdnMissing => "ParentFqdnMissing",
+            DelegationDenied::FqdnMissing => "FqdnMissing",
+            DelegationDenied::NotSubdomain { .. } => "NotSubdomain",
+            DelegationDenied::SelfDelegation => "SelfDelegation",
+            DelegationDenied::NamespaceNotDelegated { .. } => "NamespaceNotDelegated",
+            DelegationDenied::NamespaceLabelsRequired { .. } => "NamespaceLabelsRequired",
+            DelegationDenied::TypeNotAllowed { .. } => "TypeNotAllowed",
+            DelegationDenied::NoMatchingPattern { .. } => "NoMatchingPattern",
+            DelegationDenied::TtlOutOfRange { .. } => "TtlOutOfRange",
+            DelegationDenied::TooDeep { .. } => "TooDeep",
+            DelegationDenied::ExplicitlyDenied { .. } => "ExplicitlyDenied",
+            DelegationDenied::ApprovalRequired { .. } => "ApprovalRequired",
+        };
+
+        DenialReason {
+            code: code.to_string(),
+            message: denied.to_string(),
+            pattern: match denied {
+                DelegationDenied::ExplicitlyDenied { pattern, .. } => Some(pattern.to_string()),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// A single accept or deny decision made by a zone.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationDecision {
+    pub timestamp: DateTime<Utc>,
+
+    /// User on whose behalf the decision was made, if known.
+    pub subject: Option<String>,
+
+    /// The record or sub-zone seeking delegation.
+    pub record: AuditedObject,
+
+    /// The zone making the decision.
+    pub zone: AuditedObject,
+
+    pub outcome: Outcome,
+
+    /// Rule which allowed the delegation. Always empty for denials.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_rule: Option<MatchedRule>,
+
+    /// Why the delegation was denied. Always empty for allowed delegations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<DenialReason>,
+}
+
+impl DelegationDecision {
+    /// Evaluate `zone`'s delegations against `record` and record the decision.
+    ///
+    /// Provide the `namespace_labels` of the record's namespace, so that the
+    /// `namespaceSelector` of delegations can be evaluated, see
+    /// [`Zone::validate_record_with_labels`].
+    pub fn for_record(
+        zone: &Zone,
+        record: &Record,
+        namespace_labels: Option<&BTreeMap<String, String>>,
+        subject: Option<String>,
+    ) -> Self {
+        let result = match CompiledDelegations::new(zone) {
+            Some(delegations) => delegations.evaluate_record(record, namespace_labels),
+            None => Err(DelegationDenied::ParentFqdnMissing),
+        };
+
+        Self::new(zone, record, subject, result)
+    }
+
+    /// Evaluate `zone`'s delegations against the sub-zone `child` and record the decision.
+    ///
+    /// Provide the `namespace_labels` of the sub-zone's namespace, so that the
+    /// `namespaceSelector` of delegations can be evaluated, see
+    /// [`Zone::validate_zone_with_labels`].
+    pub fn for_zone(
+        zone: &Zone,
+        child: &Zone,
+        namespace_labels: Option<&BTreeMap<String, String>>,
+        subject: Option<String>,
+    ) -> Self {
+        let result = match CompiledDelegations::new(zone) {
+            Some(delegations) => delegations.evaluate_zone(child, namespace_labels),
+            None => Err(DelegationDenied::ParentFqdnMissing),
+        };
+
+        Self::new(zone, child, subject, result)
+    }
+
+    fn new<K>(
+        zone: &Zone,
+        record: &K,
+        subject: Option<String>,
+        result: Result<MatchedRule, DelegationDenied>,
+    ) -> Self
+    where
+        K: Resource<DynamicType = ()> + DomainExt,
+    {
+        DelegationDecision {
+            timestamp: Utc::now(),
+            subject,
+            record: AuditedObject::new(record),
+            zone: AuditedObject::new(zone),
+            outcome: match result {
+                Ok(_) => Outcome::Allowed,
+                Err(_) => Outcome::Denied,
+            },
+            matched_rule: result.as_ref().ok().copied(),
+            reason: result.as_ref().err().map(DenialReason::from),
+        }
+    }
+
+    /// Serialize the decision as a single line of JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Emit the decision as an event on the [`AUDIT_TARGET`] target.
+    pub fn emit(&self) {
+        match self.to_json() {
+            Ok(decision) => info!(target: AUDIT_TARGET, decision = %decision),
+            Err(err) => warn!(target: AUDIT_TARGET, "failed to serialize decision: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use kubizone_common::Pattern;
+
+    use crate::v1alpha1::{DelegationPolicy, LabelSelector, RecordDelegation, ZonePatterns};
+
+    use super::{DelegationDecision, MatchedRule, Outcome};
+
+    fn rule(pattern: &str) -> RecordDelegation {
+        RecordDelegation {
+            pattern: Pattern::try_from(pattern).unwrap().into(),
+            types: vec![],
+            min_ttl: None,
+            max_ttl: None,
+        }
+    }
+
+    #[test]
+    fn test_record_decision() {
+        let mut zone = crate::test_util::delegated_zone();
+        let record = crate::test_util::delegated_record();
+
+        let mut second = zone.spec.delegations[0].clone();
+        second.records = vec![rule("mail"), rule("www")];
+        zone.spec.delegations[0].records.clear();
+        zone.spec.delegations.push(second);
+
+        let decision = DelegationDecision::for_record(&zone, &record, None, None);
+        assert_eq!(decision.outcome, Outcome::Allowed);
+        assert_eq!(
+            decision.matched_rule,
+            Some(MatchedRule {
+                delegation: 1,
+                entry: 1
+            })
+        );
+
+        // Deny rules of any delegation take precedence.
+        zone.spec.delegations[0].deny_records = vec![rule("www")];
+        let decision = DelegationDecision::for_record(&zone, &record, None, None);
+        assert_eq!(decision.outcome, Outcome::Denied);
+        assert_eq!(decision.matched_rule, None);
+        let reason = decision.reason.as_ref().unwrap();
+        assert_eq!(reason.code, "ExplicitlyDenied");
+        assert_eq!(reason.pattern.as_deref(), Some("www."));
+        assert!(!decision.to_json().unwrap().contains("matchedRule"));
+
+        let parsed: DelegationDecision =
+            serde_json::from_str(&decision.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, decision);
+
+        // Unless the first matching delegation decides.
+        zone.spec.delegation_policy = DelegationPolicy::FirstMatch;
+        zone.spec.delegations[0].deny_records = vec![rule("mail")];
+        zone.spec.delegations[0].records = vec![rule("www")];
+        zone.spec.delegations[1].deny_records = vec![rule("www")];
+        let decision = DelegationDecision::for_record(&zone, &record, None, None);
+        assert_eq!(
+            decision.matched_rule,
+            Some(MatchedRule {
+                delegation: 0,
+                entry: 0
+            })
+        );
+
+        let parsed: DelegationDecision =
+            serde_json::from_str(&decision.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, decision);
+    }
+
+    #[test]
+    fn test_namespace_selector() {
+        let mut zone = crate::test_util::delegated_zone();
+        let record = crate::test_util::delegated_record();
+
+        zone.spec.delegations[0].namespaces.clear();
+        zone.spec.delegations[0].namespace_selector = Some(LabelSelector {
+            match_labels: BTreeMap::from([(String::from("tenant"), String::from("blue"))]),
+            match_expressions: vec![],
+        });
+
+        let blue = BTreeMap::from([(String::from("tenant"), String::from("blue"))]);
+        let decision = DelegationDecision::for_record(&zone, &record, Some(&blue), None);
+        assert_eq!(decision.outcome, Outcome::Allowed);
+
+        let decision = DelegationDecision::for_record(&zone, &record, None, None);
+        assert_eq!(decision.outcome, Outcome::Denied);
+        assert_eq!(decision.reason.unwrap().code, "NamespaceLabelsRequired");
+    }
+
+    #[test]
+    fn test_zone_decision() {
+        let zone = crate::test_util::delegated_zone();
+        let child = crate::test_util::nested_sub_zone();
+
+        let decision =
+            DelegationDecision::for_zone(&zone, &child, None, Some(String::from("alice")));
+        assert_eq!(decision.subject.as_deref(), Some("alice"));
+        assert_eq!(decision.record.kind, "Zone");
+        assert_eq!(
+            decision.matched_rule,
+            Some(MatchedRule {
+                delegation: 0,
+                entry: 0
+            })
+        );
+        assert_eq!(decision.reason, None);
+
+        let mut denying = zone.clone();
+        if let ZonePatterns::Glob { allow, deny } = &mut denying.spec.delegations[0].zones {
+            *deny = allow.clone();
+        }
+        let decision = DelegationDecision::for_zone(&denying, &child, None, None);
+        assert_eq!(decision.outcome, Outcome::Denied);
+    }
+}
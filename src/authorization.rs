@@ -0,0 +1,290 @@
+//! RBAC-backed authorization of delegations.
+//!
+//! Delegations only describe which namespaces may insert records and zones
+//! into a zone. Clusters which also want to restrict *who* may do so can use
+//! the helpers in this module to combine delegation evaluation with a
+//! SubjectAccessReview, asking the API server whether the requesting user
+//! is allowed to [`USE_VERB`] the target zone.
+
+use std::collections::BTreeMap;
+
+use k8s_openapi::api::{
+    authentication::v1::UserInfo,
+    authorization::v1::{ResourceAttributes, SubjectAccessReview, SubjectAccessReviewSpec},
+    core::v1::Namespace,
+};
+use kube::{api::PostParams, Api, Client, Resource, ResourceExt};
+use tracing::*;
+
+use crate::v1alpha1::{DelegationDenied, Record, Zone};
+
+/// Verb which users must be granted on a Zone, in order to insert
+/// records or sub-zones into it.
+pub const USE_VERB: &str = "use";
+
+/// Outcome of an authorization check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Authorization {
+    /// Both RBAC and the zone's delegations allow the operation.
+    Allowed,
+    /// The user is not allowed to use the zone.
+    DeniedByRbac { reason: Option<String> },
+    /// The user may use the zone, but its delegations do not allow the operation.
+    DeniedByDelegation { reason: DelegationDenied },
+}
+
+impl Authorization {
+    pub fn is_allowed(&self) -> bool {
+        *self == Authorization::Allowed
+    }
+}
+
+/// Ask the API server whether `user` is allowed to [`USE_VERB`] the given zone.
+///
+/// Delegations are not evaluated, so the result is either
+/// [`Authorization::Allowed`] or [`Authorization::DeniedByRbac`].
+pub async fn can_use_zone(
+    client: Client,
+    user: &UserInfo,
+    zone: &Zone,
+) -> Result<Authorization, kube::Error> {
+    let review = Api::<SubjectAccessReview>::all(client)
+        .create(&PostParams::default(), &use_zone_review(user, zone))
+        .await?;
+
+    Ok(rbac_authorization(review))
+}
+
+/// Construct the SubjectAccessReview asking whether `user` may
+/// [`USE_VERB`] `zone`.
+fn use_zone_review(user: &UserInfo, zone: &Zone) -> SubjectAccessReview {
+    SubjectAccessReview {
+        spec: SubjectAccessReviewSpec {
+            user: user.username.clone(),
+            groups: user.groups.clone(),
+            uid: user.uid.clone(),
+            extra: user.extra.clone(),
+            resource_attributes: Some(ResourceAttributes {
+                group: Some(Zone::group(&()).into_owned()),
+                version: Some(Zone::version(&()).into_owned()),
+                resource: Some(Zone::plural(&()).into_owned()),
+                verb: Some(USE_VERB.to_string()),
+                name: Some(zone.name_any()),
+                namespace: zone.namespace(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Interpret the status of a completed SubjectAccessReview. Reviews
+/// without a status are treated as denied.
+fn rbac_authorization(review: SubjectAccessReview) -> Authorization {
+    match review.status {
+        Some(status) if status.allowed => Authorization::Allowed,
+        Some(status) => Authorization::DeniedByRbac {
+            reason: status.reason.or(status.evaluation_error),
+        },
+        None => Authorization::DeniedByRbac { reason: None },
+    }
+}
+
+/// Fetch the labels of `namespace`, for evaluating the `namespaceSelector` of
+/// delegations. Namespaces which don't exist have no labels.
+pub async fn namespace_labels(
+    client: Client,
+    namespace: &str,
+) -> Result<BTreeMap<String, String>, kube::Error> {
+    Ok(Api::<Namespace>::all(client)
+        .get_opt(namespace)
+        .await?
+        .map(|namespace| namespace.labels().clone())
+        .unwrap_or_default())
+}
+
+/// Authorize `user` to insert `record` into `zone`, requiring both RBAC
+/// permission to use the zone and a matching delegation.
+///
+/// Records awaiting approval are allowed, since approval only controls
+/// whether they are published.
+pub async fn authorize_record(
+    client: Client,
+    user: &UserInfo,
+    zone: &Zone,
+    record: &Record,
+) -> Result<Authorization, kube::Error> {
+    let rbac = can_use_zone(client.clone(), user, zone).await?;
+    if !rbac.is_allowed() {
+        debug!("user {:?} is not allowed to use zone {zone}", user.username);
+        return Ok(rbac);
+    }
+
+    let labels = namespace_labels(client, &record.namespace().unwrap_or_default()).await?;
+    Ok(delegation_authorization(
+        match zone.validate_record_with_labels(record, &labels) {
+            Err(DelegationDenied::ApprovalRequired { .. }) => Ok(()),
+            result => result,
+        },
+    ))
+}
+
+/// Authorize `user` to insert `child` as a sub-zone of `zone`, requiring both
+/// RBAC permission to use the zone and a matching delegation.
+pub async fn authorize_zone(
+    client: Client,
+    user: &UserInfo,
+    zone: &Zone,
+    child: &Zone,
+) -> Result<Authorization, kube::Error> {
+    let rbac = can_use_zone(client.clone(), user, zone).await?;
+    if !rbac.is_allowed() {
+        debug!("user {:?} is not allowed to use zone {zone}", user.username);
+        return Ok(rbac);
+    }
+
+    let labels = namespace_labels(client, &child.namespace().unwrap_or_default()).await?;
+    Ok(delegation_authorization(
+        zone.validate_zone_with_labels(child, &labels),
+    ))
+}
+
+fn delegation_authorization(result: Result<(), DelegationDenied>) -> Authorization {
+    match result {
+        Ok(()) => Authorization::Allowed,
+        Err(reason) => {
+            debug!("delegation denied: {reason}");
+            Authorization::DeniedByDelegation { reason }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use k8s_openapi::api::{
+        authentication::v1::UserInfo,
+        authorization::v1::{SubjectAccessReview, SubjectAccessReviewStatus},
+    };
+    use kube::ResourceExt;
+
+    use crate::{
+        test_util::{delegated_record, delegated_zone, nested_sub_zone},
+        v1alpha1::{DelegationDenied, LabelSelector},
+    };
+
+    use super::{
+        delegation_authorization, rbac_authorization, use_zone_review, Authorization, USE_VERB,
+    };
+
+    fn review(allowed: bool, reason: Option<&str>) -> SubjectAccessReview {
+        SubjectAccessReview {
+            status: Some(SubjectAccessReviewStatus {
+                allowed,
+                reason: reason.map(String::from),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_use_zone_review() {
+        let user = UserInfo {
+            username: Some(String::from("alice")),
+            groups: Some(vec![String::from("dns-admins")]),
+            ..Default::default()
+        };
+
+        let zone = delegated_zone();
+        let review = use_zone_review(&user, &zone);
+        assert_eq!(review.spec.user.as_deref(), Some("alice"));
+        assert_eq!(review.spec.groups, user.groups);
+
+        let attributes = review.spec.resource_attributes.unwrap();
+        assert_eq!(attributes.verb.as_deref(), Some(USE_VERB));
+        assert_eq!(attributes.resource.as_deref(), Some("zones"));
+        assert_eq!(attributes.name.as_deref(), Some(zone.name_any().as_str()));
+        assert_eq!(attributes.namespace.as_deref(), Some("dns"));
+    }
+
+    #[test]
+    fn test_rbac_authorization() {
+        assert_eq!(
+            rbac_authorization(review(true, None)),
+            Authorization::Allowed
+        );
+        assert_eq!(
+            rbac_authorization(review(false, Some("no RBAC policy matched"))),
+            Authorization::DeniedByRbac {
+                reason: Some(String::from("no RBAC policy matched"))
+            }
+        );
+        assert_eq!(
+            rbac_authorization(SubjectAccessReview::default()),
+            Authorization::DeniedByRbac { reason: None }
+        );
+    }
+
+    #[test]
+    fn test_delegation_authorization() {
+        let zone = delegated_zone();
+        let labels = BTreeMap::new();
+
+        assert!(delegation_authorization(
+            zone.validate_record_with_labels(&delegated_record(), &labels)
+        )
+        .is_allowed());
+        assert!(delegation_authorization(
+            zone.validate_zone_with_labels(&nested_sub_zone(), &labels)
+        )
+        .is_allowed());
+
+        let mut record = delegated_record();
+        record.metadata.namespace = Some(String::from("other"));
+        assert_eq!(
+            delegation_authorization(zone.validate_record_with_labels(&record, &labels)),
+            Authorization::DeniedByDelegation {
+                reason: DelegationDenied::NamespaceNotDelegated {
+                    namespace: String::from("other")
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn test_namespace_selector() {
+        let mut zone = delegated_zone();
+        zone.spec.delegations[0].namespaces = vec![];
+        zone.spec.delegations[0].namespace_selector = Some(LabelSelector {
+            match_labels: BTreeMap::from([(String::from("tenant"), String::from("blue"))]),
+            match_expressions: vec![],
+        });
+
+        let blue = BTreeMap::from([(String::from("tenant"), String::from("blue"))]);
+        let red = BTreeMap::from([(String::from("tenant"), String::from("red"))]);
+
+        assert!(delegation_authorization(
+            zone.validate_record_with_labels(&delegated_record(), &blue)
+        )
+        .is_allowed());
+        assert_eq!(
+            delegation_authorization(zone.validate_record_with_labels(&delegated_record(), &red)),
+            Authorization::DeniedByDelegation {
+                reason: DelegationDenied::NamespaceNotDelegated {
+                    namespace: delegated_record().namespace().unwrap()
+                }
+            }
+        );
+        assert_eq!(
+            delegation_authorization(zone.validate_zone_with_labels(&nested_sub_zone(), &red)),
+            Authorization::DeniedByDelegation {
+                reason: DelegationDenied::NamespaceNotDelegated {
+                    namespace: nested_sub_zone().namespace().unwrap()
+                }
+            }
+        );
+    }
+}
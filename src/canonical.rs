@@ -0,0 +1,421 @@
+//! Canonical ordering of zone entries, as defined by
+//! [RFC 4034 §6](https://datatracker.ietf.org/doc/html/rfc4034#section-6).
+//!
+//! Owner names are ordered label by label, starting from the root, with each
+//! label compared case-insensitively as a string of octets. Entries of the
+//! same name are ordered by class and type code, and finally by their data
+//! in canonical wire format.
+//!
+//! Producing entries in this order makes rendered zonefiles stable, and
+//! matches the order in which DNSSEC signers process records.
+//!
+//! ```rust
+//! # use kubizone_crds::{canonical::canonical_cmp, v1alpha1::ZoneEntry};
+//! let mut entries: Vec<ZoneEntry> = [
+//!     "z.example.org. 300 IN A 192.0.2.1",
+//!     "example.org. 300 IN MX 10 mail.example.org.",
+//!     "*.example.org. 300 IN A 192.0.2.2",
+//!     "example.org. 300 IN A 192.0.2.3",
+//! ]
+//! .iter()
+//! .map(|line| line.parse().unwrap())
+//! .collect();
+//!
+//! entries.sort_by(canonical_cmp);
+//!
+//! let names: Vec<_> = entries.iter().map(ToString::to_string).collect();
+//! assert_eq!(
+//!     names,
+//!     [
+//!         "example.org. 300 IN A 192.0.2.3",
+//!         "example.org. 300 IN MX 10 mail.example.org.",
+//!         "*.example.org. 300 IN A 192.0.2.2",
+//!         "z.example.org. 300 IN A 192.0.2.1",
+//!     ]
+//! );
+//! ```
+
+use std::cmp::Ordering;
+
+use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, Type};
+
+use crate::{
+    rdata::{split_txt, RData},
+    v1alpha1::ZoneEntry,
+};
+
+/// Compare two entries in canonical order.
+///
+/// Entries which are equal in canonical order, such as ones differing only
+/// in TTL, views or routing policy, are ordered by their TTL, so that
+/// sorting is deterministic in all but bookkeeping fields.
+pub fn canonical_cmp(a: &ZoneEntry, b: &ZoneEntry) -> Ordering {
+    record_cmp(a, b).then_with(|| a.ttl.cmp(&b.ttl))
+}
+
+/// Like [`canonical_cmp`], but without the TTL tie-break, so that entries
+/// describing the same resource record compare equal.
+pub(crate) fn record_cmp(a: &ZoneEntry, b: &ZoneEntry) -> Ordering {
+    canonical_name_cmp(&a.fqdn, &b.fqdn)
+        .then_with(|| class_code(a.class).cmp(&class_code(b.class)))
+        .then_with(|| type_code(a.type_).cmp(&type_code(b.type_)))
+        .then_with(|| canonical_rdata(a.type_, &a.rdata).cmp(&canonical_rdata(b.type_, &b.rdata)))
+}
+
+/// Compare two domain names in canonical order.
+///
+/// Names are compared label by label from the root, so that all names
+/// within a zone sort after its apex, and a name sorts immediately before
+/// its subdomains.
+pub fn canonical_name_cmp(a: &FullyQualifiedDomainName, b: &FullyQualifiedDomainName) -> Ordering {
+    name_key(a).cmp(&name_key(b))
+}
+
+/// Lowercased labels of `name`, starting from the root.
+fn name_key(name: &FullyQualifiedDomainName) -> Vec<Vec<u8>> {
+    name.iter()
+        .rev()
+        .map(|label| label.as_ref().to_ascii_lowercase().into_bytes())
+        .collect()
+}
+
+/// Compare the fields of entries which are not part of the record itself,
+/// for ordering entries which are equal in [`canonical_cmp`].
+pub(crate) fn metadata_cmp(a: &ZoneEntry, b: &ZoneEntry) -> Ordering {
+    a.views
+        .cmp(&b.views)
+        .then_with(|| a.response_policy.cmp(&b.response_policy))
+        .then_with(|| a.weight.cmp(&b.weight))
+        .then_with(|| a.policy.cmp(&b.policy))
+}
+
+/// Key ordering entries by [`canonical_cmp`], then by [`metadata_cmp`].
+///
+/// Computing the key converts the rdata to wire format once, rather than
+/// on every comparison, so sorting with `sort_by_cached_key(sort_key)` is
+/// cheaper than sorting with the comparators.
+pub(crate) fn sort_key(entry: &ZoneEntry) -> impl Ord {
+    (
+        name_key(&entry.fqdn),
+        class_code(entry.class),
+        type_code(entry.type_),
+        canonical_rdata(entry.type_, &entry.rdata),
+        entry.ttl,
+        entry.views.clone(),
+        entry.response_policy,
+        entry.weight,
+        entry.policy.clone(),
+    )
+}
+
+/// Numeric code of a record type, as assigned by IANA.
+pub fn type_code(type_: Type) -> u16 {
+    match type_ {
+        Type::A => 1,
+        Type::NS => 2,
+        Type::CNAME => 5,
+        Type::SOA => 6,
+        Type::PTR => 12,
+        Type::HINFO => 13,
+        Type::MX => 15,
+        Type::TXT => 16,
+        Type::RP => 17,
+        Type::AFSDB => 18,
+        Type::SIG => 24,
+        Type::KEY => 25,
+        Type::AAAA => 28,
+        Type::LOC => 29,
+        Type::SRV => 33,
+        Type::NAPTR => 35,
+        Type::KX => 36,
+        Type::CERT => 37,
+        Type::DNAME => 39,
+        Type::APL => 42,
+        Type::DS => 43,
+        Type::SSHFP => 44,
+        Type::IPSECKEY => 45,
+        Type::RRSIG => 46,
+        Type::NSEC => 47,
+        Type::DNSKEY => 48,
+        Type::DHCID => 49,
+        Type::NSEC3 => 50,
+        Type::NSEC3PARAM => 51,
+        Type::TLSA => 52,
+        Type::SMIMEA => 53,
+        Type::HIP => 55,
+        Type::CDS => 59,
+        Type::CDNSKEY => 60,
+        Type::OPENPGPKEY => 61,
+        Type::CSYNC => 62,
+        Type::ZONEMD => 63,
+        Type::SVCB => 64,
+        Type::HTTPS => 65,
+        Type::EUI48 => 108,
+        Type::EUI64 => 109,
+        Type::TKEY => 249,
+        Type::TSIG => 250,
+        Type::URI => 256,
+        Type::CAA => 257,
+        Type::TA => 32768,
+        Type::DLV => 32769,
+    }
+}
+
+/// Every record type, in order of their [`type_code`].
+const TYPES: [Type; 47] = [
+    Type::A,
+    Type::NS,
+    Type::CNAME,
+    Type::SOA,
+    Type::PTR,
+    Type::HINFO,
+    Type::MX,
+    Type::TXT,
+    Type::RP,
+    Type::AFSDB,
+    Type::SIG,
+    Type::KEY,
+    Type::AAAA,
+    Type::LOC,
+    Type::SRV,
+    Type::NAPTR,
+    Type::KX,
+    Type::CERT,
+    Type::DNAME,
+    Type::APL,
+    Type::DS,
+    Type::SSHFP,
+    Type::IPSECKEY,
+    Type::RRSIG,
+    Type::NSEC,
+    Type::DNSKEY,
+    Type::DHCID,
+    Type::NSEC3,
+    Type::NSEC3PARAM,
+    Type::TLSA,
+    Type::SMIMEA,
+    Type::HIP,
+    Type::CDS,
+    Type::CDNSKEY,
+    Type::OPENPGPKEY,
+    Type::CSYNC,
+    Type::ZONEMD,
+    Type::SVCB,
+    Type::HTTPS,
+    Type::EUI48,
+    Type::EUI64,
+    Type::TKEY,
+    Type::TSIG,
+    Type::URI,
+    Type::CAA,
+    Type::TA,
+    Type::DLV,
+];
+
+/// Record type with the given numeric code, if supported.
+pub fn type_from_code(code: u16) -> Option<Type> {
+    TYPES.into_iter().find(|type_| type_code(*type_) == code)
+}
+
+/// Numeric code of a record class.
+pub fn class_code(class: Class) -> u16 {
+    match class {
+        Class::IN => 1,
+        Class::CH => 3,
+        Class::HS => 4,
+    }
+}
+
+/// Record class with the given numeric code, if supported.
+pub fn class_from_code(code: u16) -> Option<Class> {
+    [Class::IN, Class::CH, Class::HS]
+        .into_iter()
+        .find(|class| class_code(*class) == code)
+}
+
+/// Record data in canonical wire format, for types with a typed
+/// [`RData`] representation. Other types, and data which fails to parse,
+/// fall back to the octets of their trimmed text form.
+fn canonical_rdata(type_: Type, rdata: &str) -> Vec<u8> {
+    let Ok(parsed) = RData::parse(type_, rdata) else {
+        return rdata.trim().as_bytes().to_vec();
+    };
+
+    let mut wire = Vec::new();
+    match parsed {
+        RData::A(address) => wire.extend(address.octets()),
+        RData::AAAA(address) => wire.extend(address.octets()),
+        RData::CNAME(name) | RData::DNAME(name) | RData::NS(name) | RData::PTR(name) => {
+            write_name(&mut wire, &name)
+        }
+        RData::MX {
+            preference,
+            exchange,
+        } => {
+            wire.extend(preference.to_be_bytes());
+            write_name(&mut wire, &exchange);
+        }
+        RData::SRV {
+            priority,
+            weight,
+            port,
+            target,
+        } => {
+            wire.extend(priority.to_be_bytes());
+            wire.extend(weight.to_be_bytes());
+            wire.extend(port.to_be_bytes());
+            write_name(&mut wire, &target);
+        }
+        RData::TXT(strings) => {
+            for string in split_txt(&strings) {
+                write_character_string(&mut wire, &string);
+            }
+        }
+        RData::CAA { flags, tag, value } => {
+            wire.push(flags);
+            write_character_string(&mut wire, &tag);
+            wire.extend(value.as_bytes());
+        }
+        RData::Other { data, .. } => wire.extend(data.as_bytes()),
+    }
+
+    wire
+}
+
+/// Write `name` as a sequence of length-prefixed, lowercased labels.
+fn write_name(wire: &mut Vec<u8>, name: &DomainName) {
+    for label in name
+        .to_string()
+        .split('.')
+        .filter(|label| !label.is_empty())
+    {
+        write_character_string(wire, &label.to_ascii_lowercase());
+    }
+
+    wire.push(0);
+}
+
+/// Write `value` prefixed by its length, which callers keep within 255 octets.
+fn write_character_string(wire: &mut Vec<u8>, value: &str) {
+    wire.push(value.len() as u8);
+    wire.extend(value.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use kubizone_common::FullyQualifiedDomainName;
+
+    use crate::v1alpha1::{ViewRef, ZoneEntry};
+
+    use super::{
+        canonical_cmp, canonical_name_cmp, metadata_cmp, sort_key, type_code, type_from_code, TYPES,
+    };
+
+    fn fqdn(name: &str) -> FullyQualifiedDomainName {
+        FullyQualifiedDomainName::try_from(name).unwrap()
+    }
+
+    fn entry(line: &str) -> ZoneEntry {
+        line.parse().unwrap()
+    }
+
+    #[test]
+    fn test_canonical_name_order() {
+        // The example from RFC 4034 §6.1, less names with escapes.
+        let ordered = [
+            "example.",
+            "a.example.",
+            "yljkjljk.a.example.",
+            "Z.a.example.",
+            "zABC.a.EXAMPLE.",
+            "z.example.",
+            "*.z.example.",
+        ];
+
+        for pair in ordered.windows(2) {
+            assert_eq!(
+                canonical_name_cmp(&fqdn(pair[0]), &fqdn(pair[1])),
+                Ordering::Less,
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+
+        assert_eq!(
+            canonical_name_cmp(&fqdn("WWW.example."), &fqdn("www.example.")),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn test_canonical_entry_order() {
+        let ordered = [
+            "example.org. 300 IN A 192.0.2.1",
+            "example.org. 300 IN A 192.0.2.10",
+            "example.org. 300 IN NS ns1.example.org.",
+            "example.org. 300 IN MX 5 z.example.org.",
+            "example.org. 300 IN MX 10 a.example.org.",
+            r#"example.org. 300 IN TXT "b""#,
+            r#"example.org. 60 IN TXT "aa""#,
+            "example.org. 300 IN AAAA 2001:db8::1",
+            "example.org. 300 CH A 192.0.2.1",
+            "www.example.org. 60 IN A 192.0.2.1",
+            "www.example.org. 300 IN A 192.0.2.1",
+        ];
+
+        for pair in ordered.windows(2) {
+            assert_eq!(
+                canonical_cmp(&entry(pair[0]), &entry(pair[1])),
+                Ordering::Less,
+                "{} < {}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[test]
+    fn test_sort_key() {
+        let mut weighted = entry("www.example.org. 300 IN A 192.0.2.1");
+        weighted.weight = Some(10);
+        let mut internal = entry("www.example.org. 300 IN A 192.0.2.1");
+        internal.views = vec![ViewRef {
+            name: String::from("internal"),
+            namespace: Some(String::from("default")),
+        }];
+
+        let entries = [
+            entry("www.example.org. 300 IN A 192.0.2.1"),
+            internal,
+            weighted,
+            entry("Www.example.org. 60 IN A 192.0.2.1"),
+            entry(r#"example.org. 300 IN TXT "b""#),
+            entry("example.org. 300 IN A 192.0.2.10"),
+        ];
+
+        for a in &entries {
+            for b in &entries {
+                assert_eq!(
+                    sort_key(a).cmp(&sort_key(b)),
+                    canonical_cmp(a, b).then_with(|| metadata_cmp(a, b)),
+                    "{a} <=> {b}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_type_codes() {
+        for type_ in TYPES {
+            assert_eq!(type_from_code(type_code(type_)), Some(type_));
+        }
+
+        assert!(TYPES
+            .windows(2)
+            .all(|pair| type_code(pair[0]) < type_code(pair[1])));
+        assert_eq!(type_from_code(0), None);
+    }
+}
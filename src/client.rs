@@ -0,0 +1,112 @@
+//! Queries for the resources beneath and above a zone.
+//!
+//! Records and sub-zones are linked to their parent zone by the controller
+//! through the [`PARENT_ZONE_LABEL`], whose value must be encoded exactly
+//! as the controller does. [`ZoneClientExt`] builds the label selectors, so
+//! consumers do not have to.
+//!
+//! ```rust,no_run
+//! # use kube::Client;
+//! # use kubizone_crds::{client::ZoneClientExt, v1alpha1::Zone};
+//! # async fn list(client: Client, zone: Zone) -> Result<(), kube::Error> {
+//! for record in zone.records(&client).await? {
+//!     println!("{record}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+
+use kube::{api::ListParams, Api, Client, ResourceExt};
+
+use crate::{
+    v1alpha1::{DomainExt, Record, Zone},
+    PARENT_ZONE_LABEL,
+};
+
+/// Label selector matching the records and sub-zones of `zone`.
+///
+/// Resources labelled with the deprecated `name.namespace` format are
+/// matched as well.
+pub fn parent_zone_selector(zone: &Zone) -> String {
+    let zone_ref = zone.zone_ref();
+    let legacy = zone_ref.as_legacy_label();
+
+    match zone_ref.as_label() {
+        Some(label) => format!("{PARENT_ZONE_LABEL} in ({label}, {legacy})"),
+        None => format!("{PARENT_ZONE_LABEL}={legacy}"),
+    }
+}
+
+/// Extension trait for listing the resources beneath a [`Zone`].
+///
+/// Only resources which the controller has already adopted into the zone
+/// are returned, in any namespace. The returned futures are `Send`, so they
+/// can be spawned onto multi-threaded runtimes.
+pub trait ZoneClientExt {
+    /// List the records of the zone.
+    fn records(
+        &self,
+        client: &Client,
+    ) -> impl Future<Output = Result<Vec<Record>, kube::Error>> + Send;
+
+    /// List the direct sub-zones of the zone.
+    fn child_zones(
+        &self,
+        client: &Client,
+    ) -> impl Future<Output = Result<Vec<Zone>, kube::Error>> + Send;
+}
+
+impl ZoneClientExt for Zone {
+    async fn records(&self, client: &Client) -> Result<Vec<Record>, kube::Error> {
+        let params = ListParams::default().labels(&parent_zone_selector(self));
+
+        Ok(Api::<Record>::all(client.clone())
+            .list(&params)
+            .await?
+            .items)
+    }
+
+    async fn child_zones(&self, client: &Client) -> Result<Vec<Zone>, kube::Error> {
+        let params = ListParams::default().labels(&parent_zone_selector(self));
+
+        Ok(Api::<Zone>::all(client.clone()).list(&params).await?.items)
+    }
+}
+
+impl Zone {
+    /// Fetch the parent zone of this zone.
+    ///
+    /// The parent is the zone referenced by `spec.zoneRef`, in the namespace
+    /// of this zone unless the reference specifies one. Zones without a
+    /// `zoneRef` fall back to the parent recorded in the [`PARENT_ZONE_LABEL`]
+    /// by the controller. Returns `None` if the zone has no parent, or the
+    /// parent does not exist.
+    pub async fn resolve_parent(&self, client: &Client) -> Result<Option<Zone>, kube::Error> {
+        let Some(zone_ref) = self.spec.zone_ref.clone().or_else(|| self.parent()) else {
+            return Ok(None);
+        };
+
+        let namespace = self.namespace().unwrap_or_default();
+
+        Api::<Zone>::namespaced(client.clone(), zone_ref.namespace_or(&namespace))
+            .get_opt(&zone_ref.name)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_util::delegated_zone, PARENT_ZONE_LABEL};
+
+    use super::parent_zone_selector;
+
+    #[test]
+    fn test_parent_zone_selector() {
+        assert_eq!(
+            parent_zone_selector(&delegated_zone()),
+            format!("{PARENT_ZONE_LABEL} in (dns_example-org, example-org.dns)")
+        );
+    }
+}
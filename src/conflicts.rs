@@ -0,0 +1,304 @@
+//! Detection of zone entries which cannot be published together.
+//!
+//! Delegations decide whether a record may be placed in a zone, but not
+//! whether it is compatible with the other records of the zone. A CNAME
+//! for instance may not share its name with any other data
+//! ([RFC 1034 section 3.6.2](https://datatracker.ietf.org/doc/html/rfc1034#section-3.6.2)),
+//! so it can never be placed at the apex of a zone, where the SOA and NS
+//! records live. Publishing such zones is undefined behaviour at best, so
+//! controllers should [`check`] the entries of a zone, and mark it as
+//! [`Degraded`](crate::v1alpha1::ZoneConditionType::Degraded) instead.
+//!
+//! Entries limited to disjoint [views](crate::v1alpha1::ZoneEntry::views)
+//! are never published together, and therefore never conflict.
+//!
+//! ```rust
+//! # use kubizone_common::{Class, FullyQualifiedDomainName, Type};
+//! # use kubizone_crds::{conflicts::{self, Conflict}, v1alpha1::ZoneEntry};
+//! let entry = |type_, rdata: &str| ZoneEntry {
+//!     fqdn: FullyQualifiedDomainName::try_from("www.example.org.").unwrap(),
+//!     type_,
+//!     class: Class::IN,
+//!     ttl: 300,
+//!     rdata: rdata.to_string(),
+//!     response_policy: None,
+//!     weight: None,
+//!     policy: None,
+//!     views: Vec::new(),
+//!     source: None,
+//! };
+//!
+//! let conflicts = conflicts::check(&[
+//!     entry(Type::CNAME, "web.example.org."),
+//!     entry(Type::A, "192.0.2.1"),
+//! ]);
+//!
+//! assert!(matches!(conflicts[..], [Conflict::CnameAndOtherData { .. }]));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use kubizone_common::{Class, FullyQualifiedDomainName, Type};
+use thiserror::Error;
+
+use crate::v1alpha1::ZoneEntry;
+
+/// Combination of zone entries which cannot be published together.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum Conflict {
+    #[error("{fqdn} has a CNAME record alongside {}", display_types(types))]
+    CnameAndOtherData {
+        fqdn: FullyQualifiedDomainName,
+        types: Vec<Type>,
+    },
+    #[error("{fqdn} has more than one CNAME record")]
+    MultipleCnames { fqdn: FullyQualifiedDomainName },
+    #[error("{fqdn} is the apex of a zone, and cannot have a CNAME record")]
+    CnameAtApex { fqdn: FullyQualifiedDomainName },
+    #[error("{fqdn} has duplicate {class} {type_} records with data {rdata}")]
+    Duplicate {
+        fqdn: FullyQualifiedDomainName,
+        type_: Type,
+        class: Class,
+        rdata: String,
+    },
+    #[error("zone has more than one SOA record, at {}", display_names(fqdns))]
+    MultipleSoa {
+        fqdns: Vec<FullyQualifiedDomainName>,
+    },
+}
+
+fn display_types(types: &[Type]) -> String {
+    types
+        .iter()
+        .map(Type::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn display_names(fqdns: &[FullyQualifiedDomainName]) -> String {
+    fqdns
+        .iter()
+        .map(FullyQualifiedDomainName::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Check if the two entries are published in at least one common view.
+fn shares_view(a: &ZoneEntry, b: &ZoneEntry) -> bool {
+    a.views.is_empty() || b.views.is_empty() || a.views.iter().any(|view| b.views.contains(view))
+}
+
+/// DNSSEC types which are allowed to coexist with a CNAME, per
+/// [RFC 4035 section 2.5](https://datatracker.ietf.org/doc/html/rfc4035#section-2.5).
+fn may_accompany_cname(type_: Type) -> bool {
+    matches!(type_, Type::CNAME | Type::RRSIG | Type::NSEC)
+}
+
+/// Find all conflicts among the entries of a zone.
+///
+/// The apex of the zone is identified by its SOA record. Use [`check_at`]
+/// for entries which do not include the SOA record, such as those of a
+/// [`ZoneStatus`](crate::v1alpha1::ZoneStatus).
+///
+/// Record data is compared verbatim, so `192.0.2.1` and `192.000.002.001`
+/// are not considered duplicates.
+pub fn check(entries: &[ZoneEntry]) -> Vec<Conflict> {
+    check_entries(None, entries)
+}
+
+/// Like [`check`], but also treats `origin` as the apex of the zone.
+pub fn check_at(origin: &FullyQualifiedDomainName, entries: &[ZoneEntry]) -> Vec<Conflict> {
+    check_entries(Some(origin), entries)
+}
+
+fn check_entries(
+    origin: Option<&FullyQualifiedDomainName>,
+    entries: &[ZoneEntry],
+) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+
+    let mut names: BTreeMap<&FullyQualifiedDomainName, Vec<&ZoneEntry>> = BTreeMap::new();
+    for entry in entries {
+        names.entry(&entry.fqdn).or_default().push(entry);
+    }
+
+    let soas: Vec<_> = entries
+        .iter()
+        .filter(|entry| entry.type_ == Type::SOA)
+        .map(|entry| entry.fqdn.clone())
+        .collect();
+
+    let apexes: BTreeSet<_> = soas.iter().chain(origin).collect();
+
+    if soas.len() > 1 {
+        conflicts.push(Conflict::MultipleSoa {
+            fqdns: soas.clone(),
+        });
+    }
+
+    for (fqdn, entries) in names {
+        let cnames: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.type_ == Type::CNAME)
+            .collect();
+
+        if !cnames.is_empty() && apexes.contains(fqdn) {
+            conflicts.push(Conflict::CnameAtApex { fqdn: fqdn.clone() });
+        }
+
+        let types: BTreeSet<_> = entries
+            .iter()
+            .filter(|entry| !may_accompany_cname(entry.type_))
+            .filter(|entry| cnames.iter().any(|cname| shares_view(cname, entry)))
+            .map(|entry| entry.type_)
+            .collect();
+
+        if !types.is_empty() {
+            conflicts.push(Conflict::CnameAndOtherData {
+                fqdn: fqdn.clone(),
+                types: types.into_iter().collect(),
+            });
+        }
+
+        let multiple_cnames = cnames.iter().enumerate().any(|(i, a)| {
+            cnames[i + 1..]
+                .iter()
+                .any(|b| a.rdata != b.rdata && shares_view(a, b))
+        });
+
+        if multiple_cnames {
+            conflicts.push(Conflict::MultipleCnames { fqdn: fqdn.clone() });
+        }
+
+        let mut duplicates = BTreeSet::new();
+        for (i, a) in entries.iter().enumerate() {
+            let duplicated = entries[i + 1..].iter().any(|b| {
+                a.type_ == b.type_ && a.class == b.class && a.rdata == b.rdata && shares_view(a, b)
+            });
+
+            if duplicated && duplicates.insert((a.type_, a.class, &a.rdata)) {
+                conflicts.push(Conflict::Duplicate {
+                    fqdn: fqdn.clone(),
+                    type_: a.type_,
+                    class: a.class,
+                    rdata: a.rdata.clone(),
+                });
+            }
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use kubizone_common::{Class, FullyQualifiedDomainName, Type};
+
+    use crate::v1alpha1::{ViewRef, ZoneEntry};
+
+    use super::{check, check_at, Conflict};
+
+    fn view(name: &str) -> ViewRef {
+        ViewRef {
+            name: name.to_string(),
+            namespace: Some(String::from("default")),
+        }
+    }
+
+    fn entry(fqdn: &str, type_: Type, rdata: &str) -> ZoneEntry {
+        ZoneEntry {
+            fqdn: FullyQualifiedDomainName::try_from(fqdn).unwrap(),
+            type_,
+            class: Class::IN,
+            ttl: 300,
+            rdata: rdata.to_string(),
+            response_policy: None,
+            weight: None,
+            policy: None,
+            views: Vec::new(),
+            source: None,
+        }
+    }
+
+    fn fqdn(fqdn: &str) -> FullyQualifiedDomainName {
+        FullyQualifiedDomainName::try_from(fqdn).unwrap()
+    }
+
+    #[test]
+    fn test_valid_zone() {
+        assert!(check(&[
+            entry("example.org.", Type::SOA, "ns1 hostmaster 1 2 3 4 5"),
+            entry("example.org.", Type::A, "192.0.2.1"),
+            entry("example.org.", Type::A, "192.0.2.2"),
+            entry("www.example.org.", Type::CNAME, "example.org."),
+            entry("www.example.org.", Type::RRSIG, "CNAME 13 3 300 ..."),
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn test_cname_conflicts() {
+        assert_eq!(
+            check_at(
+                &fqdn("example.org."),
+                &[
+                    entry("example.org.", Type::CNAME, "example.com."),
+                    entry("www.example.org.", Type::CNAME, "example.org."),
+                    entry("www.example.org.", Type::CNAME, "example.com."),
+                    entry("www.example.org.", Type::TXT, "hello"),
+                    entry("www.example.org.", Type::A, "192.0.2.1"),
+                ]
+            ),
+            vec![
+                Conflict::CnameAtApex {
+                    fqdn: fqdn("example.org.")
+                },
+                Conflict::CnameAndOtherData {
+                    fqdn: fqdn("www.example.org."),
+                    types: vec![Type::A, Type::TXT],
+                },
+                Conflict::MultipleCnames {
+                    fqdn: fqdn("www.example.org.")
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cname_in_separate_view() {
+        let mut internal = entry("www.example.org.", Type::CNAME, "internal.example.org.");
+        internal.views = vec![view("internal")];
+        let mut external = entry("www.example.org.", Type::A, "192.0.2.1");
+        external.views = vec![view("external")];
+
+        assert!(check(&[internal.clone(), external.clone()]).is_empty());
+
+        external.views.push(view("internal"));
+        assert_eq!(check(&[internal, external]).len(), 1);
+    }
+
+    #[test]
+    fn test_duplicates_and_soa() {
+        assert_eq!(
+            check(&[
+                entry("example.org.", Type::SOA, "ns1 hostmaster 1 2 3 4 5"),
+                entry("sub.example.org.", Type::SOA, "ns1 hostmaster 1 2 3 4 5"),
+                entry("www.example.org.", Type::A, "192.0.2.1"),
+                entry("www.example.org.", Type::A, "192.0.2.1"),
+                entry("www.example.org.", Type::A, "192.0.2.1"),
+            ]),
+            vec![
+                Conflict::MultipleSoa {
+                    fqdns: vec![fqdn("example.org."), fqdn("sub.example.org.")]
+                },
+                Conflict::Duplicate {
+                    fqdn: fqdn("www.example.org."),
+                    type_: Type::A,
+                    class: Class::IN,
+                    rdata: String::from("192.0.2.1"),
+                },
+            ]
+        );
+    }
+}
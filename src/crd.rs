@@ -0,0 +1,243 @@
+//! Custom Resource Definitions spanning multiple API versions.
+//!
+//! Resources which exist in more than one API version must be installed as a
+//! single CustomResourceDefinition listing every version, with exactly one of
+//! them marked as the storage version.
+//!
+//! Until a conversion webhook exists, the API server can only convert between
+//! versions by rewriting `apiVersion`, which would corrupt `v1alpha2` objects
+//! since their schema differs from `v1alpha1`. Versions other than
+//! [`STORAGE_VERSION`] are therefore listed, but not served. Use the `From`
+//! conversions in [`v1alpha2`] to work with them client-side.
+//!
+//! Some constraints, such as the relationships between SOA timers, or the
+//! immutability of `domainName`, can't be expressed through the derived
+//! schemas, and are instead added to the CRDs produced here as CEL
+//! validation rules. Prefer these functions over calling
+//! [`CustomResourceExt::crd`] directly.
+
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::{
+    CustomResourceDefinition, ValidationRule,
+};
+use kube::{
+    core::crd::{merge_crds, MergeError},
+    CustomResourceExt,
+};
+
+use crate::{v1alpha1, v1alpha2};
+
+/// API version in which resources are persisted.
+pub const STORAGE_VERSION: &str = "v1alpha1";
+
+/// Zone CustomResourceDefinition containing all versions, of which only
+/// the storage version is served.
+pub fn zone() -> Result<CustomResourceDefinition, MergeError> {
+    merge_crds(vec![zone_v1alpha1(), zone_v1alpha2()], STORAGE_VERSION).map(serve_storage_only)
+}
+
+/// Record CustomResourceDefinition containing all versions, of which only
+/// the storage version is served.
+pub fn record() -> Result<CustomResourceDefinition, MergeError> {
+    merge_crds(vec![record_v1alpha1(), record_v1alpha2()], STORAGE_VERSION).map(serve_storage_only)
+}
+
+/// Stop serving every version except [`STORAGE_VERSION`], since there is
+/// no conversion webhook to translate between them.
+fn serve_storage_only(mut crd: CustomResourceDefinition) -> CustomResourceDefinition {
+    for version in &mut crd.spec.versions {
+        version.served = version.name == STORAGE_VERSION;
+    }
+    crd
+}
+
+/// Zone CustomResourceDefinition containing only the `v1alpha1` version.
+pub fn zone_v1alpha1() -> CustomResourceDefinition {
+    let mut crd = v1alpha1::Zone::crd();
+    add_validations(&mut crd, &["spec"], soa_validations());
+    add_validations(&mut crd, &["spec"], domain_name_validations());
+    crd
+}
+
+fn zone_v1alpha2() -> CustomResourceDefinition {
+    let mut crd = v1alpha2::Zone::crd();
+    add_validations(&mut crd, &["spec", "soa"], soa_validations());
+    add_validations(&mut crd, &["spec"], domain_name_validations());
+    crd
+}
+
+/// Record CustomResourceDefinition containing only the `v1alpha1` version.
+pub fn record_v1alpha1() -> CustomResourceDefinition {
+    let mut crd = v1alpha1::Record::crd();
+    add_validations(&mut crd, &["spec"], domain_name_validations());
+    add_validations(&mut crd, &["spec", "activeWindow"], active_validations());
+    crd
+}
+
+fn record_v1alpha2() -> CustomResourceDefinition {
+    let mut crd = v1alpha2::Record::crd();
+    add_validations(&mut crd, &["spec"], domain_name_validations());
+    add_validations(&mut crd, &["spec", "activeWindow"], active_validations());
+    crd
+}
+
+/// DNSSECKey CustomResourceDefinition, which only exists as `v1alpha1`.
+pub fn dnssec_key() -> CustomResourceDefinition {
+    let mut crd = v1alpha1::DNSSECKey::crd();
+    add_validations(&mut crd, &["spec"], key_lifetime_validations());
+    crd
+}
+
+/// Makes `domainName` immutable, since renaming breaks the fully qualified
+/// domain name and serial history of the zone.
+///
+/// CEL rules can't read annotations, so there is no escape hatch: objects
+/// must be deleted and recreated under their new domain name.
+fn domain_name_validations() -> Vec<ValidationRule> {
+    vec![ValidationRule {
+        rule: String::from("self.domainName == oldSelf.domainName"),
+        message: Some(String::from(
+            "domainName is immutable, recreate the object to rename it",
+        )),
+        reason: Some(String::from("FieldValueForbidden")),
+        ..Default::default()
+    }]
+}
+
+/// Active windows of records must start before they end, and recurring
+/// windows must have both a start and an end.
+fn active_validations() -> Vec<ValidationRule> {
+    vec![
+        ValidationRule {
+            rule: String::from(
+                "!has(self.start) || !has(self.end) || timestamp(self.start) < timestamp(self.end)",
+            ),
+            message: Some(String::from("activeWindow start must be before end")),
+            ..Default::default()
+        },
+        ValidationRule {
+            rule: String::from(
+                "!has(self.recurrence) || self.recurrence == 'Never' || (has(self.start) && has(self.end))",
+            ),
+            message: Some(String::from(
+                "recurring activeWindows must have both a start and an end",
+            )),
+            ..Default::default()
+        },
+    ]
+}
+
+/// Keys must be activated before they are retired.
+fn key_lifetime_validations() -> Vec<ValidationRule> {
+    vec![ValidationRule {
+        rule: String::from(
+            "!has(self.activationTime) || !has(self.retirementTime) || timestamp(self.activationTime) < timestamp(self.retirementTime)",
+        ),
+        message: Some(String::from("activationTime must be before retirementTime")),
+        ..Default::default()
+    }]
+}
+
+/// Invariants between the SOA timers, as documented on [`v1alpha1::ZoneSpec`].
+fn soa_validations() -> Vec<ValidationRule> {
+    vec![
+        ValidationRule {
+            rule: String::from("self.retry < self.refresh"),
+            message: Some(String::from("retry must be less than refresh")),
+            ..Default::default()
+        },
+        ValidationRule {
+            rule: String::from("self.expire > self.refresh + self.retry"),
+            message: Some(String::from(
+                "expire must be greater than the sum of refresh and retry",
+            )),
+            ..Default::default()
+        },
+    ]
+}
+
+/// Append CEL validation `rules` to the schema at `path` within every version of the CRD.
+fn add_validations(crd: &mut CustomResourceDefinition, path: &[&str], rules: Vec<ValidationRule>) {
+    for version in &mut crd.spec.versions {
+        let Some(mut schema) = version
+            .schema
+            .as_mut()
+            .and_then(|schema| schema.open_api_v3_schema.as_mut())
+        else {
+            continue;
+        };
+
+        for property in path {
+            // Unwrap safety: Paths are fixed in this module, and name properties
+            // of the schemas derived from this crate's own types, which the
+            // tests below generate for every version.
+            schema = schema
+                .properties
+                .as_mut()
+                .and_then(|properties| properties.get_mut(*property))
+                .expect("validation paths must name properties of the generated schema");
+        }
+
+        schema
+            .x_kubernetes_validations
+            .get_or_insert_with(Vec::new)
+            .extend(rules.iter().cloned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dnssec_key, record, zone, STORAGE_VERSION};
+
+    #[test]
+    fn test_only_storage_version_served() {
+        for crd in [zone().unwrap(), record().unwrap()] {
+            assert_eq!(crd.spec.versions.len(), 2);
+            for version in &crd.spec.versions {
+                assert_eq!(version.served, version.name == STORAGE_VERSION);
+                assert_eq!(version.storage, version.name == STORAGE_VERSION);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dnssec_key_validations() {
+        let crd = dnssec_key();
+        let spec = &crd.spec.versions[0]
+            .schema
+            .as_ref()
+            .unwrap()
+            .open_api_v3_schema
+            .as_ref()
+            .unwrap()
+            .properties
+            .as_ref()
+            .unwrap()["spec"];
+
+        assert_eq!(spec.x_kubernetes_validations.as_ref().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_domain_name_immutable() {
+        for crd in [zone().unwrap(), record().unwrap()] {
+            for version in &crd.spec.versions {
+                let spec = &version
+                    .schema
+                    .as_ref()
+                    .unwrap()
+                    .open_api_v3_schema
+                    .as_ref()
+                    .unwrap()
+                    .properties
+                    .as_ref()
+                    .unwrap()["spec"];
+
+                assert!(spec
+                    .x_kubernetes_validations
+                    .as_ref()
+                    .unwrap()
+                    .iter()
+                    .any(|rule| rule.rule == "self.domainName == oldSelf.domainName"));
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Panic-free parsing of domain names.
+//!
+//! [`DomainName::try_from`] panics on some invalid partially qualified
+//! names, such as `bad name` or `-x`, instead of returning an error. Names
+//! from untrusted input, like zonefiles, rdata or the names of external
+//! records, must be parsed with [`parse_domain_name`] instead.
+//!
+//! ```rust
+//! # use kubizone_crds::domain_name::parse_domain_name;
+//! assert!(parse_domain_name("www.example.org.").is_ok());
+//! assert!(parse_domain_name("www").is_ok());
+//! assert!(parse_domain_name("bad name").is_err());
+//! ```
+
+use kubizone_common::{
+    error::{FullyQualifiedDomainNameError, PartiallyQualifiedDomainNameError},
+    DomainName, FullyQualifiedDomainName, PartiallyQualifiedDomainName,
+};
+
+use crate::errors::DomainNameError;
+
+/// Parse `value` as a fully qualified domain name if it ends with a dot,
+/// and as a partially qualified one otherwise.
+pub fn parse_domain_name(value: &str) -> Result<DomainName, DomainNameError> {
+    if value.ends_with('.') {
+        return FullyQualifiedDomainName::try_from(value)
+            .map(DomainName::Full)
+            .map_err(|err| match err {
+                FullyQualifiedDomainNameError::SegmentError(err) => DomainNameError::Segment(err),
+                // Names with a trailing dot are never partially qualified.
+                FullyQualifiedDomainNameError::NonLeadingWildcard
+                | FullyQualifiedDomainNameError::DomainIsPartiallyQualified => {
+                    DomainNameError::NonLeadingWildcard
+                }
+            });
+    }
+
+    PartiallyQualifiedDomainName::try_from(value)
+        .map(DomainName::Partial)
+        .map_err(|err| match err {
+            PartiallyQualifiedDomainNameError::SegmentError(err) => DomainNameError::Segment(err),
+            // Names without a trailing dot are never fully qualified.
+            PartiallyQualifiedDomainNameError::NonLeadingWildcard
+            | PartiallyQualifiedDomainNameError::DomainIsFullyQualified => {
+                DomainNameError::NonLeadingWildcard
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use kubizone_common::DomainName;
+
+    use crate::errors::DomainNameError;
+
+    use super::parse_domain_name;
+
+    #[test]
+    fn test_parse_domain_name() {
+        assert!(matches!(
+            parse_domain_name("www.example.org."),
+            Ok(DomainName::Full(_))
+        ));
+        assert!(matches!(
+            parse_domain_name("www"),
+            Ok(DomainName::Partial(_))
+        ));
+
+        for invalid in ["bad name", "-x", "a..b", "", "bad name.", "www.*"] {
+            assert!(parse_domain_name(invalid).is_err(), "{invalid}");
+        }
+
+        assert_eq!(
+            parse_domain_name("www.*.example"),
+            Err(DomainNameError::NonLeadingWildcard)
+        );
+    }
+}
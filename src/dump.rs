@@ -1,9 +1,26 @@
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
 use kube::{CustomResourceExt, Resource};
 use std::path::PathBuf;
 
 fn main() {
-    write_to_path::<kubizone_crds::v1alpha1::Record>().unwrap();
-    write_to_path::<kubizone_crds::v1alpha1::Zone>().unwrap();
+    write_merged_to_path(kubizone_crds::crd::zone().unwrap()).unwrap();
+    write_merged_to_path(kubizone_crds::crd::record().unwrap()).unwrap();
+
+    write_crd("v1alpha1", kubizone_crds::crd::record_v1alpha1()).unwrap();
+    write_crd("v1alpha1", kubizone_crds::crd::zone_v1alpha1()).unwrap();
+    write_crd("v1alpha1", kubizone_crds::crd::dnssec_key()).unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::DnsFailoverPool>().unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::ServiceRecord>().unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::IngressRecord>().unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::GatewayRecord>().unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::HealthCheckedRecord>().unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::RecordPool>().unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::RecordTemplate>().unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::FailoverPolicy>().unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::ReverseZone>().unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::SecondaryZone>().unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::DNSView>().unwrap();
+    write_to_path::<kubizone_crds::v1alpha1::ZoneData>().unwrap();
 }
 
 fn serialize_crd<C>() -> Result<String, serde_yaml::Error>
@@ -29,3 +46,31 @@ where
 
     Ok(())
 }
+
+/// Write a CRD spanning multiple versions to crds/<group>/<Kind>.yaml
+fn write_merged_to_path(crd: CustomResourceDefinition) -> Result<(), std::io::Error> {
+    let directory = PathBuf::from("crds").join(&crd.spec.group);
+
+    std::fs::create_dir_all(&directory)?;
+
+    std::fs::write(
+        directory.join(format!("{name}.yaml", name = crd.spec.names.kind)),
+        format!("---\n{}", serde_yaml::to_string(&crd).unwrap()),
+    )?;
+
+    Ok(())
+}
+
+/// Write a single-version CRD to crds/<group>/<version>/<Kind>.yaml
+fn write_crd(version: &str, crd: CustomResourceDefinition) -> Result<(), std::io::Error> {
+    let directory = PathBuf::from("crds").join(&crd.spec.group).join(version);
+
+    std::fs::create_dir_all(&directory)?;
+
+    std::fs::write(
+        directory.join(format!("{name}.yaml", name = crd.spec.names.kind)),
+        format!("---\n{}", serde_yaml::to_string(&crd).unwrap()),
+    )?;
+
+    Ok(())
+}
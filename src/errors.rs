@@ -0,0 +1,117 @@
+//! Errors shared between the resources and helpers of this crate.
+//!
+//! Errors which only concern a single type, such as
+//! [`RnameError`](crate::v1alpha1::RnameError), live next to that type instead.
+
+use kubizone_common::{error::DomainSegmentError, DomainName};
+use thiserror::Error;
+
+use crate::{
+    rdata::RDataError,
+    v1alpha1::{BuilderError, DelegationDenied, DnssecError, RoutingPolicyError},
+};
+
+/// Produced when parsing a [`ZoneRef`](crate::v1alpha1::ZoneRef) from its `name.namespace` shorthand.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ZoneRefParseError {
+    #[error("zone reference is empty")]
+    Empty,
+    #[error("zone reference {0} has an empty name")]
+    EmptyName(String),
+    #[error("zone reference {0} has an empty namespace")]
+    EmptyNamespace(String),
+    #[error("{0} is not a valid RFC 1123 subdomain, as required of zone names")]
+    InvalidName(String),
+    #[error("{0} is not a valid RFC 1123 label, as required of namespaces")]
+    InvalidNamespace(String),
+}
+
+/// Produced when converting a [`ZoneRef`](crate::v1alpha1::ZoneRef) into an
+/// [`ObjectRef`](kube::runtime::reflector::ObjectRef).
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ObjectRefError {
+    #[error("zone reference {0} has no namespace, use ZoneRef::to_object_ref to default it")]
+    MissingNamespace(String),
+}
+
+/// Produced by [`parse_domain_name`](crate::domain_name::parse_domain_name).
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum DomainNameError {
+    #[error("{0}")]
+    Segment(#[from] DomainSegmentError),
+    #[error("non-leading wildcard segment")]
+    NonLeadingWildcard,
+}
+
+/// Produced when a zone cannot decide whether to accept a record or sub-zone,
+/// or decides not to.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum DelegationError {
+    #[error("zone has no fully qualified domain name yet")]
+    MissingFqdn,
+    #[error(transparent)]
+    Denied(#[from] DelegationDenied),
+}
+
+/// Produced by [`ZoneSpec::validate`](crate::v1alpha1::ZoneSpec::validate)
+/// for specs which the API server accepts, but which cannot be served.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error("{0} is neither fully qualified, nor has a zoneRef")]
+    MissingParent(DomainName),
+    #[error("{0} is fully qualified, and cannot also have a zoneRef")]
+    ConflictingParent(DomainName),
+    #[error("ttl of {ttl}s is below the minimum of {minimum}s")]
+    TtlTooLow { ttl: u32, minimum: u32 },
+    #[error("denyRecords rule {rule} of delegation {delegation} sets minTtl or maxTtl, which deny rules ignore")]
+    DenyRuleTtl { delegation: usize, rule: usize },
+    #[error(transparent)]
+    Dnssec(#[from] DnssecError),
+}
+
+/// Produced by [`RecordSpec::validate`](crate::v1alpha1::RecordSpec::validate).
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum RecordValidationError {
+    #[error("invalid record data: {0}")]
+    RData(#[from] RDataError),
+    #[error(transparent)]
+    Policy(#[from] RoutingPolicyError),
+}
+
+/// Produced by [`render_zonefile`](crate::zonefile::render_zonefile).
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum RenderError {
+    #[error("zone has no fully qualified domain name yet")]
+    MissingFqdn,
+    #[error("zone has no serial yet")]
+    MissingSerial,
+    #[error("entries of the zone are stored in zone data {0}, and must be joined first")]
+    EntriesInZoneData(String),
+}
+
+/// Produced by [`substitute`](crate::template::substitute).
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum SubstitutionError {
+    #[error("variable {0} is not defined")]
+    Undefined(String),
+    #[error("unterminated placeholder in {0}")]
+    Unterminated(String),
+}
+
+/// Produced by [`RecordTemplate::render`](crate::v1alpha1::RecordTemplate::render).
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum RecordTemplateError {
+    #[error(transparent)]
+    Substitution(#[from] SubstitutionError),
+    #[error("rendered record is invalid: {0}")]
+    Invalid(#[from] BuilderError),
+}
+
+/// Produced by [`set_owner_zone`](crate::set_owner_zone).
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum OwnerError {
+    #[error("zone {owner} cannot own objects in namespace {namespace}")]
+    CrossNamespace { owner: String, namespace: String },
+    #[error("zone has no uid, and must be fetched from the API server first")]
+    MissingUid,
+}
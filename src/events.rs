@@ -0,0 +1,216 @@
+//! Kubernetes events for decisions made by kubizone controllers.
+//!
+//! [`EventRecorder`] is a thin wrapper over kube's [`Recorder`], which
+//! publishes events about any object, rather than a single one. The
+//! `emit_*` functions turn the errors produced by validation into events
+//! with consistent reasons and notes, so that `kubectl describe` shows
+//! users why their records and zones were not published, regardless of
+//! which controller rejected them.
+//!
+//! ```rust,no_run
+//! # use kube::{runtime::events::Reporter, Client};
+//! # use kubizone_crds::{events::{self, EventRecorder}, v1alpha1::{Record, Zone}};
+//! # async fn reconcile(client: Client, zone: Zone, record: Record) -> Result<(), kube::Error> {
+//! let recorder = EventRecorder::new(client, Reporter::from("kubizone"));
+//!
+//! if let Err(denial) = zone.validate_record(&record) {
+//!     events::emit_record_rejected(&recorder, &record, &denial).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt::Display;
+
+use kube::{
+    runtime::events::{Event, EventType, Recorder, Reporter},
+    Client, Resource,
+};
+
+use crate::{
+    errors::ValidationError,
+    v1alpha1::{DelegationDenied, Record, Zone},
+};
+
+/// Programmatic identifier of an event, in CamelCase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EventReason {
+    /// A record was rejected by the delegations of its zone.
+    RecordRejected,
+    /// A zone was rejected by the delegations of its parent zone.
+    ZoneRejected,
+    /// The parent zone of a record or zone could not be found.
+    MissingParent,
+    /// The spec of a zone failed validation.
+    InvalidSpec,
+    /// The entries of a zone changed, and its serial was bumped.
+    SerialBumped,
+}
+
+impl EventReason {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            EventReason::RecordRejected => "RecordRejected",
+            EventReason::ZoneRejected => "ZoneRejected",
+            EventReason::MissingParent => "MissingParent",
+            EventReason::InvalidSpec => "InvalidSpec",
+            EventReason::SerialBumped => "SerialBumped",
+        }
+    }
+
+    /// Action the controller was taking when the event occurred.
+    pub const fn action(&self) -> &'static str {
+        match self {
+            EventReason::RecordRejected | EventReason::ZoneRejected => "Delegating",
+            EventReason::MissingParent => "ResolvingParent",
+            EventReason::InvalidSpec => "Validating",
+            EventReason::SerialBumped => "Serializing",
+        }
+    }
+
+    /// Whether events with this reason warrant the attention of users.
+    pub const fn type_(&self) -> EventType {
+        match self {
+            EventReason::SerialBumped => EventType::Normal,
+            _ => EventType::Warning,
+        }
+    }
+}
+
+impl Display for EventReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Publishes events about arbitrary objects on behalf of a controller.
+#[derive(Clone)]
+pub struct EventRecorder {
+    client: Client,
+    reporter: Reporter,
+}
+
+impl EventRecorder {
+    pub fn new(client: Client, reporter: Reporter) -> Self {
+        EventRecorder { client, reporter }
+    }
+
+    /// Publish an event about `object`, with the type and action implied by `reason`.
+    pub async fn publish<K>(
+        &self,
+        object: &K,
+        reason: EventReason,
+        note: impl Into<String>,
+    ) -> Result<(), kube::Error>
+    where
+        K: Resource<DynamicType = ()>,
+    {
+        Recorder::new(
+            self.client.clone(),
+            self.reporter.clone(),
+            object.object_ref(&()),
+        )
+        .publish(event(reason, note.into()))
+        .await
+    }
+}
+
+fn event(reason: EventReason, note: String) -> Event {
+    Event {
+        type_: reason.type_(),
+        reason: reason.name().to_string(),
+        note: Some(note),
+        action: reason.action().to_string(),
+        secondary: None,
+    }
+}
+
+/// Record that `record` was rejected by the delegations of its zone.
+pub async fn emit_record_rejected(
+    recorder: &EventRecorder,
+    record: &Record,
+    denial: &DelegationDenied,
+) -> Result<(), kube::Error> {
+    recorder
+        .publish(
+            record,
+            EventReason::RecordRejected,
+            format!("record was rejected by its zone: {denial}"),
+        )
+        .await
+}
+
+/// Record that `zone` was rejected by the delegations of its parent zone.
+pub async fn emit_zone_rejected(
+    recorder: &EventRecorder,
+    zone: &Zone,
+    denial: &DelegationDenied,
+) -> Result<(), kube::Error> {
+    recorder
+        .publish(
+            zone,
+            EventReason::ZoneRejected,
+            format!("zone was rejected by its parent zone: {denial}"),
+        )
+        .await
+}
+
+/// Record that the parent zone of `object` could not be found.
+pub async fn emit_missing_parent<K>(recorder: &EventRecorder, object: &K) -> Result<(), kube::Error>
+where
+    K: Resource<DynamicType = ()>,
+{
+    recorder
+        .publish(
+            object,
+            EventReason::MissingParent,
+            "parent zone does not exist, or has not been assigned a fully qualified domain name",
+        )
+        .await
+}
+
+/// Record that the spec of `zone` failed [`ZoneSpec::validate`](crate::v1alpha1::ZoneSpec::validate).
+pub async fn emit_invalid_zone(
+    recorder: &EventRecorder,
+    zone: &Zone,
+    err: &ValidationError,
+) -> Result<(), kube::Error> {
+    recorder
+        .publish(zone, EventReason::InvalidSpec, err.to_string())
+        .await
+}
+
+/// Record that the serial of `zone` was bumped from `previous` to `serial`.
+pub async fn emit_serial_bumped(
+    recorder: &EventRecorder,
+    zone: &Zone,
+    previous: Option<u32>,
+    serial: u32,
+) -> Result<(), kube::Error> {
+    let note = match previous {
+        Some(previous) => format!("serial bumped from {previous} to {serial}"),
+        None => format!("serial set to {serial}"),
+    };
+
+    recorder
+        .publish(zone, EventReason::SerialBumped, note)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use kube::runtime::events::EventType;
+
+    use super::{event, EventReason};
+
+    #[test]
+    fn test_event() {
+        let event = event(EventReason::RecordRejected, String::from("denied"));
+        assert_eq!(event.reason, "RecordRejected");
+        assert_eq!(event.action, "Delegating");
+        assert_eq!(event.type_, EventType::Warning);
+        assert_eq!(event.note.as_deref(), Some("denied"));
+
+        assert_eq!(EventReason::SerialBumped.type_(), EventType::Normal);
+    }
+}
@@ -0,0 +1,343 @@
+//! Compatibility with [external-dns](https://github.com/kubernetes-sigs/external-dns).
+//!
+//! Mirrors the `Endpoint` type and `DNSEndpoint` custom resource of
+//! external-dns, along with conversions to and from kubizone records, so
+//! clusters can migrate between the two systems or run them side by side.
+//!
+//! external-dns groups all targets of a name and type into a single
+//! endpoint, and writes names without the trailing dot, while kubizone
+//! uses one record per value and fully qualified names. TXT records
+//! holding several strings become one target per string.
+//!
+//! The [`ResponsePolicy`] of records is carried in the
+//! [`RESPONSE_POLICY_PROPERTY`] provider specific property. Weights and
+//! routing policies have no external-dns equivalent, and are only carried
+//! by [`ZoneEntry`].
+
+use std::collections::BTreeMap;
+
+use kube::CustomResource;
+use kubizone_common::{FullyQualifiedDomainName, Type};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    domain_name::parse_domain_name,
+    rdata::{write_quoted, RData},
+    v1alpha1::{DomainExt, Record, RecordSpec, ResponsePolicy, ZoneEntry},
+    zonefile,
+};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ExternalDnsError {
+    #[error("record does not yet have a fully qualified domain name")]
+    MissingFqdn,
+    #[error("invalid domain name {name}: {reason}")]
+    InvalidDomainName { name: String, reason: String },
+    #[error("record type {0} is not supported")]
+    UnsupportedType(String),
+    #[error("invalid response policy {0}, expected Fixed, RoundRobin or Random")]
+    InvalidResponsePolicy(String),
+}
+
+/// Provider specific property holding the [`ResponsePolicy`] of an endpoint.
+pub const RESPONSE_POLICY_PROPERTY: &str = "kubi.zone/response-policy";
+
+/// DNS record as understood by external-dns.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoint {
+    /// Domain name, without the trailing dot.
+    pub dns_name: String,
+
+    /// Values of all records of this name and type.
+    #[serde(default)]
+    pub targets: Vec<String>,
+
+    pub record_type: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub set_identifier: Option<String>,
+
+    /// Time-to-Live in seconds, or `0` if unset.
+    #[serde(default, rename = "recordTTL")]
+    pub record_ttl: i64,
+
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provider_specific: Vec<ProviderSpecificProperty>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, PartialEq, Eq)]
+pub struct ProviderSpecificProperty {
+    pub name: String,
+    pub value: String,
+}
+
+/// The `DNSEndpoint` custom resource of external-dns.
+#[derive(CustomResource, Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
+#[kube(
+    group = "externaldns.k8s.io",
+    version = "v1alpha1",
+    kind = "DNSEndpoint",
+    namespaced
+)]
+#[kube(status = "DNSEndpointStatus")]
+#[serde(rename_all = "camelCase")]
+pub struct DNSEndpointSpec {
+    #[serde(default)]
+    pub endpoints: Vec<Endpoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DNSEndpointStatus {
+    #[serde(default)]
+    pub observed_generation: i64,
+}
+
+impl Endpoint {
+    /// Group zone entries into endpoints, one per distinct name and type,
+    /// in order of first appearance. The TTL and response policy of each
+    /// endpoint are taken from the first of its entries.
+    pub fn from_entries(entries: &[ZoneEntry]) -> Vec<Endpoint> {
+        let mut endpoints: Vec<Endpoint> = Vec::new();
+
+        for entry in entries {
+            let dns_name = dns_name(&entry.fqdn);
+            let record_type = entry.type_.to_string();
+            let targets = targets(entry.type_, &entry.rdata);
+
+            match endpoints.iter_mut().find(|endpoint| {
+                endpoint.dns_name == dns_name && endpoint.record_type == record_type
+            }) {
+                Some(endpoint) => endpoint.targets.extend(targets),
+                None => endpoints.push(Endpoint {
+                    dns_name,
+                    targets,
+                    record_type,
+                    record_ttl: i64::from(entry.ttl),
+                    provider_specific: provider_specific(entry.response_policy),
+                    ..Default::default()
+                }),
+            }
+        }
+
+        endpoints
+    }
+
+    /// Convert the endpoint into one record spec per target.
+    ///
+    /// Endpoints without a TTL produce records inheriting the TTL of their zone.
+    pub fn to_record_specs(&self) -> Result<Vec<RecordSpec>, ExternalDnsError> {
+        let name = format!("{}.", self.dns_name.trim_end_matches('.'));
+        let domain_name = parse_domain_name(name.as_str()).map_err(|err| {
+            ExternalDnsError::InvalidDomainName {
+                name,
+                reason: err.to_string(),
+            }
+        })?;
+
+        let type_ = zonefile::parse_type(&self.record_type)
+            .ok_or_else(|| ExternalDnsError::UnsupportedType(self.record_type.clone()))?;
+
+        let ttl = u32::try_from(self.record_ttl).ok().filter(|ttl| *ttl > 0);
+        let response_policy = self.response_policy()?;
+
+        Ok(self
+            .targets
+            .iter()
+            .map(|target| RecordSpec {
+                domain_name: domain_name.clone(),
+                type_,
+                ttl,
+                rdata: rdata(type_, target),
+                response_policy,
+                ..Default::default()
+            })
+            .collect())
+    }
+
+    /// Response policy held by the [`RESPONSE_POLICY_PROPERTY`] of the endpoint.
+    pub fn response_policy(&self) -> Result<Option<ResponsePolicy>, ExternalDnsError> {
+        self.provider_specific
+            .iter()
+            .find(|property| property.name == RESPONSE_POLICY_PROPERTY)
+            .map(|property| {
+                serde_json::from_value(serde_json::Value::String(property.value.clone()))
+                    .map_err(|_| ExternalDnsError::InvalidResponsePolicy(property.value.clone()))
+            })
+            .transpose()
+    }
+}
+
+/// Converts a single record into an endpoint, with a single target unless
+/// the record is a TXT record holding several strings.
+impl TryFrom<&Record> for Endpoint {
+    type Error = ExternalDnsError;
+
+    fn try_from(record: &Record) -> Result<Self, Self::Error> {
+        let fqdn = record.fqdn().ok_or(ExternalDnsError::MissingFqdn)?;
+
+        Ok(Endpoint {
+            dns_name: dns_name(fqdn),
+            targets: targets(record.spec.type_, &record.spec.rdata),
+            record_type: record.spec.type_.to_string(),
+            record_ttl: record.spec.ttl.map(i64::from).unwrap_or_default(),
+            provider_specific: provider_specific(record.spec.response_policy),
+            ..Default::default()
+        })
+    }
+}
+
+impl DNSEndpoint {
+    /// Convert all endpoints into record specs.
+    pub fn to_record_specs(&self) -> Result<Vec<RecordSpec>, ExternalDnsError> {
+        let mut specs = Vec::new();
+        for endpoint in &self.spec.endpoints {
+            specs.extend(endpoint.to_record_specs()?);
+        }
+
+        Ok(specs)
+    }
+}
+
+fn dns_name(fqdn: &FullyQualifiedDomainName) -> String {
+    fqdn.to_string().trim_end_matches('.').to_string()
+}
+
+fn provider_specific(response_policy: Option<ResponsePolicy>) -> Vec<ProviderSpecificProperty> {
+    response_policy
+        .and_then(|policy| match serde_json::to_value(policy) {
+            Ok(serde_json::Value::String(value)) => Some(ProviderSpecificProperty {
+                name: RESPONSE_POLICY_PROPERTY.to_string(),
+                value,
+            }),
+            _ => None,
+        })
+        .into_iter()
+        .collect()
+}
+
+/// external-dns stores TXT targets unquoted, one string per target.
+fn targets(type_: Type, rdata: &str) -> Vec<String> {
+    match RData::parse(type_, rdata) {
+        Ok(RData::TXT(strings)) if rdata.trim_start().starts_with('"') => strings,
+        _ => vec![rdata.trim().to_string()],
+    }
+}
+
+fn rdata(type_: Type, target: &str) -> String {
+    if type_ != Type::TXT {
+        return target.to_string();
+    }
+
+    let mut rdata = String::new();
+    // Unwrap safety: Writing to a String cannot fail.
+    write_quoted(&mut rdata, target).unwrap();
+    rdata
+}
+
+#[cfg(test)]
+mod tests {
+    use kubizone_common::Type;
+
+    use crate::v1alpha1::{ResponsePolicy, ZoneEntry};
+
+    use super::{
+        DNSEndpoint, DNSEndpointSpec, Endpoint, ExternalDnsError, ProviderSpecificProperty,
+        RESPONSE_POLICY_PROPERTY,
+    };
+
+    #[test]
+    fn test_endpoint_conversion() {
+        let entries: Vec<ZoneEntry> = [
+            "www.example.org. 300 IN A 192.168.0.1",
+            "www.example.org. 300 IN A 192.168.0.2",
+            r#"example.org. 60 IN TXT "v=spf1 -all""#,
+            "www.example.org. 300 IN AAAA 2001:db8::1",
+            r#"example.org. 60 IN TXT "first" "second""#,
+        ]
+        .iter()
+        .map(|line| line.parse().unwrap())
+        .collect();
+
+        let endpoints = Endpoint::from_entries(&entries);
+        assert_eq!(endpoints.len(), 3);
+        assert_eq!(endpoints[0].dns_name, "www.example.org");
+        assert_eq!(endpoints[0].targets, vec!["192.168.0.1", "192.168.0.2"]);
+        assert_eq!(endpoints[0].record_ttl, 300);
+        assert_eq!(endpoints[1].targets, vec!["v=spf1 -all", "first", "second"]);
+
+        let endpoint = DNSEndpoint::new(
+            "example",
+            DNSEndpointSpec {
+                endpoints: endpoints.clone(),
+            },
+        );
+
+        let specs = endpoint.to_record_specs().unwrap();
+        assert_eq!(specs.len(), 6);
+        assert_eq!(
+            specs[0].to_string(),
+            "www.example.org. 300 IN A 192.168.0.1"
+        );
+        assert_eq!(specs[2].type_, Type::TXT);
+        assert_eq!(specs[2].rdata, r#""v=spf1 -all""#);
+
+        let serialized = serde_json::to_value(&endpoints[0]).unwrap();
+        assert_eq!(serialized["dnsName"], "www.example.org");
+        assert_eq!(serialized["recordTTL"], 300);
+        assert_eq!(serialized["recordType"], "A");
+    }
+
+    #[test]
+    fn test_response_policy() {
+        let mut entry: ZoneEntry = "www.example.org. 300 IN A 192.0.2.1".parse().unwrap();
+        entry.response_policy = Some(ResponsePolicy::RoundRobin);
+
+        let endpoints = Endpoint::from_entries(&[entry]);
+        assert_eq!(
+            endpoints[0].provider_specific,
+            vec![ProviderSpecificProperty {
+                name: RESPONSE_POLICY_PROPERTY.to_string(),
+                value: String::from("RoundRobin"),
+            }]
+        );
+
+        let specs = endpoints[0].to_record_specs().unwrap();
+        assert_eq!(specs[0].response_policy, Some(ResponsePolicy::RoundRobin));
+
+        let mut invalid = endpoints[0].clone();
+        invalid.provider_specific[0].value = String::from("Sticky");
+        assert_eq!(
+            invalid.to_record_specs(),
+            Err(ExternalDnsError::InvalidResponsePolicy(String::from(
+                "Sticky"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_invalid_dns_name() {
+        let endpoint = DNSEndpoint::new(
+            "example",
+            DNSEndpointSpec {
+                endpoints: vec![Endpoint {
+                    dns_name: String::from("-x"),
+                    targets: vec![String::from("192.0.2.1")],
+                    record_type: String::from("A"),
+                    ..Default::default()
+                }],
+            },
+        );
+
+        assert!(matches!(
+            endpoint.to_record_specs(),
+            Err(ExternalDnsError::InvalidDomainName { name, .. }) if name == "-x."
+        ));
+    }
+}
@@ -0,0 +1,176 @@
+//! Conversions between kubizone types and [hickory-dns](https://github.com/hickory-dns/hickory-dns)
+//! resource records, so resolvers and servers built on hickory can
+//! consume zones directly.
+//!
+//! Record data is converted through its zonefile representation, so any
+//! type supported by both kubizone and hickory can be converted.
+
+use std::str::FromStr;
+
+use hickory_proto::{
+    error::ProtoError,
+    rr::{DNSClass, Name, RData, Record, RecordType},
+    serialize::txt::{ParseError, RDataParser},
+};
+use kubizone_common::{Class, Type};
+use thiserror::Error;
+
+use crate::{
+    domain_name::parse_domain_name,
+    rdata::write_quoted,
+    v1alpha1::{RecordSpec, ZoneEntry},
+    zonefile,
+};
+
+#[derive(Error, Debug)]
+pub enum HickoryError {
+    #[error("invalid name {name}: {source}")]
+    InvalidName { name: String, source: ProtoError },
+    #[error("invalid domain name {name}: {reason}")]
+    InvalidDomainName { name: String, reason: String },
+    #[error("record type {0} is not supported")]
+    UnsupportedType(String),
+    #[error("record class {0} is not supported")]
+    UnsupportedClass(DNSClass),
+    #[error("invalid record data: {0}")]
+    InvalidRdata(#[from] ParseError),
+    #[error("record has no data")]
+    MissingRdata,
+}
+
+impl TryFrom<&ZoneEntry> for Record {
+    type Error = HickoryError;
+
+    fn try_from(entry: &ZoneEntry) -> Result<Self, Self::Error> {
+        let fqdn = entry.fqdn.to_string();
+        let name = Name::from_ascii(&fqdn)
+            .map_err(|source| HickoryError::InvalidName { name: fqdn, source })?;
+
+        let record_type = RecordType::from_str(&entry.type_.to_string())
+            .map_err(|_| HickoryError::UnsupportedType(entry.type_.to_string()))?;
+
+        // Normalize the record data, so unquoted TXT data is parsed as a single string.
+        let mut rdata = String::new();
+        // Unwrap safety: Writing to a String cannot fail.
+        zonefile::write_rdata(&mut rdata, entry.type_, &entry.rdata).unwrap();
+
+        let mut record =
+            Record::from_rdata(name, entry.ttl, RData::try_from_str(record_type, &rdata)?);
+
+        record.set_dns_class(match entry.class {
+            Class::IN => DNSClass::IN,
+            Class::CH => DNSClass::CH,
+            Class::HS => DNSClass::HS,
+        });
+
+        Ok(record)
+    }
+}
+
+impl TryFrom<&Record> for RecordSpec {
+    type Error = HickoryError;
+
+    fn try_from(record: &Record) -> Result<Self, Self::Error> {
+        let name = record.name().to_ascii();
+        let domain_name =
+            parse_domain_name(name.as_str()).map_err(|err| HickoryError::InvalidDomainName {
+                name,
+                reason: err.to_string(),
+            })?;
+
+        let type_: Type = zonefile::parse_type(&record.record_type().to_string())
+            .ok_or_else(|| HickoryError::UnsupportedType(record.record_type().to_string()))?;
+
+        let class = match record.dns_class() {
+            DNSClass::IN => Class::IN,
+            DNSClass::CH => Class::CH,
+            DNSClass::HS => Class::HS,
+            other => return Err(HickoryError::UnsupportedClass(other)),
+        };
+
+        Ok(RecordSpec {
+            domain_name,
+            type_,
+            class,
+            ttl: Some(record.ttl()),
+            rdata: rdata_to_string(record.data().ok_or(HickoryError::MissingRdata)?),
+            ..Default::default()
+        })
+    }
+}
+
+/// Hickory displays TXT data as the concatenation of its strings, which
+/// would merge multiple strings into one, so they are quoted individually.
+fn rdata_to_string(rdata: &RData) -> String {
+    let RData::TXT(txt) = rdata else {
+        return rdata.to_string();
+    };
+
+    let mut out = String::new();
+    for (i, string) in txt.iter().enumerate() {
+        if i > 0 {
+            out.push(' ');
+        }
+
+        // Unwrap safety: Writing to a String cannot fail.
+        write_quoted(&mut out, &String::from_utf8_lossy(string)).unwrap();
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use hickory_proto::rr::{rdata::A, DNSClass, Name, RData, Record, RecordType};
+
+    use crate::v1alpha1::{RecordSpec, ZoneEntry};
+
+    use super::HickoryError;
+
+    #[test]
+    fn test_hickory_roundtrip() {
+        for line in [
+            "www.example.org. 300 IN A 192.168.0.1",
+            "example.org. 300 IN MX 10 mail.example.org.",
+            r#"example.org. 300 IN TXT "v=spf1 -all""#,
+            r#"example.org. 300 IN TXT "first" "quote \" here""#,
+            r#"example.org. 300 IN CAA 0 issue "letsencrypt.org""#,
+            "_sip._tcp.example.org. 300 IN SRV 10 5 5060 sip.example.org.",
+        ] {
+            let entry: ZoneEntry = line.parse().unwrap();
+            let record: Record = (&entry).try_into().unwrap();
+
+            assert_eq!(record.name().to_ascii(), entry.fqdn.to_string());
+            assert_eq!(record.ttl(), 300);
+            assert_eq!(record.dns_class(), DNSClass::IN);
+
+            let spec = RecordSpec::try_from(&record).unwrap();
+            assert_eq!(spec.type_, entry.type_);
+            assert_eq!(spec.to_string(), line);
+        }
+
+        // Unquoted TXT data is a single string.
+        let mut entry: ZoneEntry = r#"example.org. 300 IN TXT "ignored""#.parse().unwrap();
+        entry.rdata = String::from("v=spf1 -all");
+        let record: Record = (&entry).try_into().unwrap();
+        assert_eq!(record.record_type(), RecordType::TXT);
+        assert_eq!(
+            RecordSpec::try_from(&record).unwrap().rdata,
+            r#""v=spf1 -all""#
+        );
+    }
+
+    #[test]
+    fn test_invalid_name() {
+        let record = Record::from_rdata(
+            Name::from_labels([&b"bad name"[..], b"example", b"org"]).unwrap(),
+            300,
+            RData::A(A::new(192, 0, 2, 1)),
+        );
+
+        assert!(matches!(
+            RecordSpec::try_from(&record),
+            Err(HickoryError::InvalidDomainName { .. })
+        ));
+    }
+}
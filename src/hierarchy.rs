@@ -0,0 +1,157 @@
+//! Analysis of the hierarchy formed by zones referencing their parent
+//! through `spec.zoneRef`.
+//!
+//! The fully qualified domain name of a zone with a `zoneRef` is derived
+//! from that of its parent, so a chain of references must eventually end
+//! in a zone with a fully qualified domain name. Chains which instead loop
+//! back onto themselves never do, and are reported by [`detect_cycles`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use kube::ResourceExt;
+use thiserror::Error;
+
+use crate::v1alpha1::{Zone, ZoneRef};
+
+/// Zones referencing each other in a loop, such as `a → b → a`.
+///
+/// The zones are listed in reference order, starting with the smallest
+/// [`ZoneRef`], and always include their namespace.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("zone reference cycle: {}", display_cycle(zones))]
+pub struct Cycle {
+    pub zones: Vec<ZoneRef>,
+}
+
+impl Cycle {
+    /// Check if the cycle consists of a single zone referencing itself.
+    pub fn is_self_reference(&self) -> bool {
+        self.zones.len() == 1
+    }
+
+    /// Check if `zone` is part of the cycle.
+    pub fn contains(&self, zone: &ZoneRef) -> bool {
+        self.zones.contains(zone)
+    }
+}
+
+fn display_cycle(zones: &[ZoneRef]) -> String {
+    // Close the loop, so self-references read as `a → a`.
+    zones
+        .iter()
+        .chain(zones.first())
+        .map(ZoneRef::to_string)
+        .collect::<Vec<_>>()
+        .join(" → ")
+}
+
+/// Follow the `spec.zoneRef` chains of `zones`, and report every cycle
+/// among them, including zones referencing themselves.
+///
+/// References to zones outside of `zones` end a chain, as do zones
+/// without a `zoneRef`.
+pub fn detect_cycles(zones: &[Zone]) -> Vec<Cycle> {
+    let parents: BTreeMap<ZoneRef, ZoneRef> = zones
+        .iter()
+        .filter_map(|zone| {
+            let namespace = zone.namespace().unwrap_or_default();
+            let parent = zone.spec.zone_ref.as_ref()?;
+
+            Some((
+                zone.zone_ref(),
+                ZoneRef {
+                    name: parent.name.clone(),
+                    namespace: Some(parent.namespace_or(&namespace).to_string()),
+                },
+            ))
+        })
+        .collect();
+
+    let mut visited = BTreeSet::new();
+    let mut cycles = Vec::new();
+
+    for start in parents.keys() {
+        let mut path: Vec<&ZoneRef> = Vec::new();
+        let mut current = start;
+
+        while visited.insert(current) {
+            path.push(current);
+
+            let Some(parent) = parents.get(current) else {
+                break;
+            };
+
+            if let Some(position) = path.iter().position(|zone| *zone == parent) {
+                let mut zones: Vec<_> = path[position..].iter().map(|&zone| zone.clone()).collect();
+
+                // Unwrap safety: A cycle contains at least the current zone.
+                let smallest = zones
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, zone)| *zone)
+                    .map(|(i, _)| i)
+                    .unwrap();
+                zones.rotate_left(smallest);
+
+                cycles.push(Cycle { zones });
+                break;
+            }
+
+            current = parent;
+        }
+    }
+
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1alpha1::{Zone, ZoneRef};
+
+    use super::detect_cycles;
+
+    fn zone(name: &str, parent: Option<&str>) -> Zone {
+        let builder = match parent {
+            Some(parent) => Zone::builder(name).zone_ref(parent.parse().unwrap()),
+            None => Zone::builder(&format!("{name}.")),
+        };
+
+        let mut zone = builder.namespace("dns").build().unwrap();
+        zone.metadata.name = Some(name.to_string());
+        zone
+    }
+
+    fn zone_ref(name: &str) -> ZoneRef {
+        format!("{name}.dns").parse().unwrap()
+    }
+
+    #[test]
+    fn test_detect_cycles() {
+        let zones = [
+            zone("org", None),
+            zone("example", Some("org")),
+            zone("a", Some("b")),
+            zone("b", Some("c.dns")),
+            zone("c", Some("a")),
+            zone("d", Some("a")),
+            zone("self", Some("self")),
+            zone("other", Some("self.web")),
+        ];
+
+        let cycles = detect_cycles(&zones);
+        assert_eq!(cycles.len(), 2);
+
+        assert_eq!(
+            cycles[0].zones,
+            [zone_ref("a"), zone_ref("b"), zone_ref("c")]
+        );
+        assert_eq!(
+            cycles[0].to_string(),
+            "zone reference cycle: dns/a → dns/b → dns/c → dns/a"
+        );
+        assert!(!cycles[0].contains(&zone_ref("d")));
+
+        assert!(cycles[1].is_self_reference());
+        assert_eq!(cycles[1].zones, [zone_ref("self")]);
+    }
+}
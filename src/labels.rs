@@ -0,0 +1,176 @@
+//! Well-known label and annotation keys used by kubizone controllers.
+//!
+//! All keys live in the `kubi.zone` group, or `dev.kubi.zone` with the `dev`
+//! feature enabled, except for [`MANAGED_BY_LABEL`], which is the standard
+//! kubernetes label. [`LabelsExt`] reads and writes them on any resource, so
+//! that controllers agree on their encoding.
+//!
+//! ```rust
+//! # use kubizone_common::Type;
+//! # use kubizone_crds::{labels::LabelsExt, v1alpha1::Record};
+//! let mut record = Record::builder("www.example.org.", Type::A, "192.0.2.1")
+//!     .build()
+//!     .unwrap();
+//! record.set_managed_by("kubizone");
+//! record.set_dry_run(true);
+//!
+//! assert_eq!(record.managed_by(), Some("kubizone"));
+//! assert!(record.is_dry_run());
+//! ```
+
+use kube::ResourceExt;
+
+use crate::v1alpha1::ZoneRef;
+
+/// Label holding the parent zone of a record or sub-zone, see [`ZoneRef::as_label`].
+#[cfg(feature = "dev")]
+pub const PARENT_ZONE_LABEL: &str = "dev.kubi.zone/parent-zone";
+#[cfg(not(feature = "dev"))]
+pub const PARENT_ZONE_LABEL: &str = "kubi.zone/parent-zone";
+
+/// Annotation holding the parent zone as a JSON-serialized [`ZoneRef`], such
+/// as `{"name": "example-org", "namespace": "dns"}`.
+///
+/// Unlike the [`PARENT_ZONE_LABEL`], annotation values are not limited to 63
+/// characters or a restricted character set, so any zone can be referenced.
+/// The parent zone cannot be used in label selectors however.
+#[cfg(feature = "dev")]
+pub const PARENT_ZONE_ANNOTATION: &str = "dev.kubi.zone/parent-zone-ref";
+#[cfg(not(feature = "dev"))]
+pub const PARENT_ZONE_ANNOTATION: &str = "kubi.zone/parent-zone-ref";
+
+/// Standard kubernetes label naming the controller managing a resource.
+pub const MANAGED_BY_LABEL: &str = "app.kubernetes.io/managed-by";
+
+/// Annotation holding the hash of the record data last published for a
+/// resource, for detecting changes without comparing full specs.
+#[cfg(feature = "dev")]
+pub const RECORD_HASH_ANNOTATION: &str = "dev.kubi.zone/record-hash";
+#[cfg(not(feature = "dev"))]
+pub const RECORD_HASH_ANNOTATION: &str = "kubi.zone/record-hash";
+
+/// Annotation holding the zone serial a resource was last published with.
+#[cfg(feature = "dev")]
+pub const SERIAL_ANNOTATION: &str = "dev.kubi.zone/serial";
+#[cfg(not(feature = "dev"))]
+pub const SERIAL_ANNOTATION: &str = "kubi.zone/serial";
+
+/// Annotation which, when set to `true`, asks controllers to evaluate a
+/// resource without publishing it.
+#[cfg(feature = "dev")]
+pub const DRY_RUN_ANNOTATION: &str = "dev.kubi.zone/dry-run";
+#[cfg(not(feature = "dev"))]
+pub const DRY_RUN_ANNOTATION: &str = "kubi.zone/dry-run";
+
+/// Typed access to the well-known labels and annotations of any resource.
+///
+/// Getters return `None` if the key is absent or its value cannot be parsed.
+pub trait LabelsExt: ResourceExt {
+    /// Read the [`PARENT_ZONE_LABEL`], see [`ZoneRef::from_label`].
+    fn parent_zone_label(&self) -> Option<ZoneRef> {
+        self.labels()
+            .get(PARENT_ZONE_LABEL)
+            .map(|label| ZoneRef::from_label(label))
+    }
+
+    /// Write the [`PARENT_ZONE_LABEL`], see [`ZoneRef::as_label`].
+    ///
+    /// References without a namespace are resolved against the namespace of
+    /// this resource. The label is removed if neither has a namespace.
+    fn set_parent_zone_label(&mut self, parent: &ZoneRef) {
+        match parent.within(self.namespace().as_deref()).as_label() {
+            Some(label) => self
+                .labels_mut()
+                .insert(PARENT_ZONE_LABEL.to_string(), label),
+            None => self.labels_mut().remove(PARENT_ZONE_LABEL),
+        };
+    }
+
+    /// Read the [`MANAGED_BY_LABEL`].
+    fn managed_by(&self) -> Option<&str> {
+        self.labels().get(MANAGED_BY_LABEL).map(String::as_str)
+    }
+
+    /// Write the [`MANAGED_BY_LABEL`].
+    fn set_managed_by(&mut self, manager: &str) {
+        self.labels_mut()
+            .insert(MANAGED_BY_LABEL.to_string(), manager.to_string());
+    }
+
+    /// Read the [`RECORD_HASH_ANNOTATION`].
+    fn record_hash_annotation(&self) -> Option<&str> {
+        self.annotations()
+            .get(RECORD_HASH_ANNOTATION)
+            .map(String::as_str)
+    }
+
+    /// Write the [`RECORD_HASH_ANNOTATION`].
+    fn set_record_hash_annotation(&mut self, hash: &str) {
+        self.annotations_mut()
+            .insert(RECORD_HASH_ANNOTATION.to_string(), hash.to_string());
+    }
+
+    /// Read the [`SERIAL_ANNOTATION`].
+    fn serial_annotation(&self) -> Option<u32> {
+        self.annotations().get(SERIAL_ANNOTATION)?.parse().ok()
+    }
+
+    /// Write the [`SERIAL_ANNOTATION`].
+    fn set_serial_annotation(&mut self, serial: u32) {
+        self.annotations_mut()
+            .insert(SERIAL_ANNOTATION.to_string(), serial.to_string());
+    }
+
+    /// Check if the [`DRY_RUN_ANNOTATION`] is set to `true`.
+    fn is_dry_run(&self) -> bool {
+        self.annotations()
+            .get(DRY_RUN_ANNOTATION)
+            .is_some_and(|value| value == "true")
+    }
+
+    /// Set or remove the [`DRY_RUN_ANNOTATION`].
+    fn set_dry_run(&mut self, dry_run: bool) {
+        if dry_run {
+            self.annotations_mut()
+                .insert(DRY_RUN_ANNOTATION.to_string(), String::from("true"));
+        } else {
+            self.annotations_mut().remove(DRY_RUN_ANNOTATION);
+        }
+    }
+}
+
+impl<K: ResourceExt> LabelsExt for K {}
+
+#[cfg(test)]
+mod tests {
+    use kube::ResourceExt;
+
+    use crate::{test_util::delegated_record, v1alpha1::ZoneRef};
+
+    use super::{LabelsExt, PARENT_ZONE_LABEL, SERIAL_ANNOTATION};
+
+    #[test]
+    fn test_labels_round_trip() {
+        let mut record = delegated_record();
+        let parent: ZoneRef = "example.org.dns".parse().unwrap();
+
+        record.set_parent_zone_label(&parent);
+        record.set_record_hash_annotation("a1b2c3");
+        record.set_serial_annotation(2024010101);
+
+        assert_eq!(record.labels()[PARENT_ZONE_LABEL], "dns_example.org");
+        assert_eq!(record.parent_zone_label(), Some(parent));
+        assert_eq!(record.record_hash_annotation(), Some("a1b2c3"));
+        assert_eq!(record.serial_annotation(), Some(2024010101));
+
+        record.set_dry_run(true);
+        assert!(record.is_dry_run());
+        record.set_dry_run(false);
+        assert!(!record.is_dry_run());
+
+        record
+            .annotations_mut()
+            .insert(SERIAL_ANNOTATION.to_string(), String::from("latest"));
+        assert_eq!(record.serial_annotation(), None);
+    }
+}
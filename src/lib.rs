@@ -1,17 +1,57 @@
 use std::{fmt::Debug, hash::Hash};
 
+pub mod analysis;
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+pub mod audit;
+pub mod authorization;
+pub mod canonical;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod conflicts;
+pub mod crd;
+pub mod domain_name;
+pub mod errors;
+pub mod events;
+#[cfg(feature = "external-dns")]
+pub mod external_dns;
+#[cfg(feature = "hickory")]
+pub mod hickory;
+pub mod hierarchy;
+pub mod labels;
+pub mod otel;
+pub mod pattern;
+pub mod rdata;
+pub mod reverse;
+pub mod runtime;
+pub mod schema;
+pub mod serial;
+pub mod template;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod v1alpha1;
+pub mod v1alpha2;
+#[cfg(feature = "webhook")]
+pub mod webhook;
+pub mod zone_entries;
+pub mod zonefile;
+#[cfg(feature = "hickory")]
+pub mod zonemd;
 
+use errors::OwnerError;
 use kube::{runtime::reflector::ObjectRef, Resource, ResourceExt};
 use serde::de::DeserializeOwned;
-
-#[cfg(feature = "dev")]
-pub const PARENT_ZONE_LABEL: &str = "dev.kubi.zone/parent-zone";
-#[cfg(not(feature = "dev"))]
-pub const PARENT_ZONE_LABEL: &str = "kubi.zone/parent-zone";
+use v1alpha1::{Zone, ZoneRef};
 
 pub use kubizone_common;
+pub use labels::{PARENT_ZONE_ANNOTATION, PARENT_ZONE_LABEL};
 
+/// Map objects to the parent referenced by `label`, such as the [`PARENT_ZONE_LABEL`],
+/// for use with [`Controller::watches`](kube::runtime::Controller::watches).
+///
+/// Labels without a namespace, such as `example-org`, refer to the namespace
+/// of the object, or to a cluster-scoped parent if the object is itself
+/// cluster-scoped.
 pub fn watch_reference<Parent, K>(label: &'static str) -> impl Fn(K) -> Option<ObjectRef<Parent>>
 where
     K: ResourceExt,
@@ -19,10 +59,139 @@ where
     Parent::DynamicType: Default + Debug + Clone + Eq + Hash,
 {
     |object| {
-        let parent = object.labels().get(label)?;
+        let parent = ZoneRef::from_label(object.labels().get(label)?);
+
+        Some(parent.to_object_ref(object.namespace().as_deref()))
+    }
+}
+
+/// Like [`watch_reference`], but reads the parent from a JSON-serialized
+/// [`ZoneRef`] in the given annotation, such as the [`PARENT_ZONE_ANNOTATION`].
+///
+/// References without a namespace are resolved as in [`watch_reference`].
+pub fn watch_annotation_reference<Parent, K>(
+    annotation: &'static str,
+) -> impl Fn(K) -> Option<ObjectRef<Parent>>
+where
+    K: ResourceExt,
+    Parent: Clone + Resource + DeserializeOwned + Debug + Send + 'static,
+    Parent::DynamicType: Default + Debug + Clone + Eq + Hash,
+{
+    |object| {
+        let parent = ZoneRef::from_annotation(object.annotations().get(annotation)?)?;
+
+        Some(parent.to_object_ref(object.namespace().as_deref()))
+    }
+}
+
+/// Like [`watch_reference`], but maps objects to the owner of type `Parent`
+/// among their ownerReferences, see [`set_owner_zone`].
+pub fn watch_reference_owned<Parent, K>() -> impl Fn(K) -> Option<ObjectRef<Parent>>
+where
+    K: ResourceExt,
+    Parent: Clone + Resource<DynamicType = ()> + DeserializeOwned + Debug + Send + 'static,
+{
+    |object| owner_of(&object)
+}
+
+fn owner_of<Parent, K>(object: &K) -> Option<ObjectRef<Parent>>
+where
+    K: ResourceExt,
+    Parent: Resource<DynamicType = ()>,
+{
+    let namespace = object.namespace();
+
+    object
+        .owner_references()
+        .iter()
+        .find_map(|owner| ObjectRef::from_owner_ref(namespace.as_deref(), owner, ()))
+}
+
+/// Read the parent zone of `object`, from the [`PARENT_ZONE_ANNOTATION`] if
+/// present, then the [`PARENT_ZONE_LABEL`], and finally the Zone among its
+/// ownerReferences.
+pub fn parent_reference<K: ResourceExt>(object: &K) -> Option<ZoneRef> {
+    if let Some(annotation) = object.annotations().get(PARENT_ZONE_ANNOTATION) {
+        return ZoneRef::from_annotation(annotation);
+    }
+
+    if let Some(label) = object.labels().get(PARENT_ZONE_LABEL) {
+        return Some(ZoneRef::from_label(label));
+    }
+
+    owner_zone(object)
+}
+
+/// Read the Zone among the ownerReferences of `object`, if any.
+///
+/// Owners are always in the namespace of the objects they own.
+pub fn owner_zone<K: ResourceExt>(object: &K) -> Option<ZoneRef> {
+    let owner = owner_of::<Zone, K>(object)?;
+
+    Some(ZoneRef {
+        name: owner.name,
+        namespace: owner.namespace,
+    })
+}
+
+/// Make `zone` the controlling owner of `object`, so that kubernetes
+/// garbage collects `object` when the zone is deleted.
+///
+/// Any previous Zone owner is replaced. Kubernetes does not allow owners
+/// in other namespaces, so such sub-zones and records must be tracked with
+/// the [`PARENT_ZONE_LABEL`] or [`PARENT_ZONE_ANNOTATION`] instead.
+pub fn set_owner_zone<K: ResourceExt>(object: &mut K, zone: &Zone) -> Result<(), OwnerError> {
+    if object.namespace() != zone.namespace() {
+        return Err(OwnerError::CrossNamespace {
+            owner: zone.to_string(),
+            namespace: object.namespace().unwrap_or_default(),
+        });
+    }
+
+    let owner = zone
+        .controller_owner_ref(&())
+        .ok_or(OwnerError::MissingUid)?;
+
+    let owners = object.owner_references_mut();
+    owners.retain(|existing| {
+        existing.api_version != owner.api_version || existing.kind != owner.kind
+    });
+    owners.push(owner);
+
+    Ok(())
+}
+
+/// Store `parent` as the parent zone of `object` in the [`PARENT_ZONE_ANNOTATION`].
+pub fn set_parent_annotation<K: ResourceExt>(object: &mut K, parent: &ZoneRef) {
+    object
+        .annotations_mut()
+        .insert(PARENT_ZONE_ANNOTATION.to_string(), parent.to_annotation());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        v1alpha1::{Record, Zone},
+        watch_reference, PARENT_ZONE_LABEL,
+    };
+
+    #[test]
+    fn test_watch_reference() {
+        let mut record = Record::builder("www.example.org.", kubizone_common::Type::A, "192.0.2.1")
+            .build()
+            .unwrap();
+        let watch = watch_reference::<Zone, Record>(PARENT_ZONE_LABEL);
+
+        for (label, namespace) in [("dns_example.org", Some("dns")), ("example-org", None)] {
+            record.metadata.labels =
+                Some([(PARENT_ZONE_LABEL.to_string(), label.to_string())].into());
 
-        let (name, namespace) = parent.split_once('.')?;
+            let parent = watch(record.clone()).unwrap();
+            assert_eq!(parent.namespace.as_deref(), namespace);
+        }
 
-        Some(ObjectRef::new(name).within(namespace))
+        // Labels without a namespace refer to the namespace of the object.
+        record.metadata.namespace = Some(String::from("web"));
+        assert_eq!(watch(record).unwrap().namespace.as_deref(), Some("web"));
     }
 }
@@ -0,0 +1,42 @@
+//! Span attribute helpers for the `otel` feature.
+//!
+//! Functions annotated with `#[instrument]` under the `otel` feature declare
+//! the attributes below as empty fields, and fill them in using these helpers
+//! once the values are known. Without the feature, the helpers do nothing.
+
+use kubizone_common::FullyQualifiedDomainName;
+
+/// Fully qualified domain name of the zone making a decision.
+pub const ZONE_FQDN: &str = "kubizone.zone.fqdn";
+
+/// Fully qualified domain name of the record or sub-zone being evaluated.
+pub const RECORD_FQDN: &str = "kubizone.record.fqdn";
+
+/// Outcome of the operation, either `allowed` or `denied`.
+pub const OUTCOME: &str = "kubizone.outcome";
+
+/// Name of the object being operated on.
+pub const NAME: &str = "kubizone.name";
+
+/// Namespace of the object being operated on, if any.
+pub const NAMESPACE: &str = "kubizone.namespace";
+
+pub(crate) fn record_fqdn(attribute: &'static str, fqdn: &FullyQualifiedDomainName) {
+    #[cfg(feature = "otel")]
+    tracing::Span::current().record(attribute, tracing::field::display(fqdn));
+
+    #[cfg(not(feature = "otel"))]
+    let _ = (attribute, fqdn);
+}
+
+pub(crate) fn record_outcome(allowed: bool) -> bool {
+    #[cfg(feature = "otel")]
+    tracing::Span::current().record(OUTCOME, if allowed { "allowed" } else { "denied" });
+
+    allowed
+}
+
+pub(crate) fn record_result<T, E>(result: Result<T, E>) -> Result<T, E> {
+    record_outcome(result.is_ok());
+    result
+}
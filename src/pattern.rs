@@ -0,0 +1,556 @@
+//! Domain name pattern matching.
+//!
+//! Delegations store their patterns as [`kubizone_common::Pattern`], relative
+//! to the zone they belong to. This module exposes the same matching rules
+//! for absolute patterns, extended with multi-label wildcards and escaping,
+//! so that controllers and tooling don't have to re-implement them.
+//!
+//! # Syntax
+//!
+//! A pattern is a sequence of labels separated by `.`, matched label-by-label
+//! against the labels of a fully qualified domain name. The trailing `.` is
+//! optional, since patterns are always matched against the entire domain, and
+//! `.` on its own matches only the root. Matching is case-insensitive.
+//!
+//! * `*` inside a label matches any (possibly empty) sequence of characters
+//!   within that label, so `web-*` matches `web-1` but not `web-1.internal`.
+//!   A label may contain any number of wildcards.
+//! * `**` as a whole label matches zero or more labels.
+//! * `*` as the *leftmost* label matches one or more labels. This mirrors
+//!   delegation patterns, where `*` matches any subdomain of the zone at any
+//!   depth, and makes `*.example.org.` equivalent to `**.*.example.org.`.
+//! * `\` escapes the following character, so `\*` matches a literal `*`
+//!   and `\.` a literal `.` within a label.
+//!
+//! Delegation patterns can be matched with this module by appending the
+//! zone's fully qualified domain name, with identical results, except for two
+//! edge cases where [`Pattern::matches`](kubizone_common::Pattern::matches)
+//! is more lenient: a standalone `*` label which is not the leftmost
+//! label matches everything to its left, and a label like `a*a` matches `a`.
+//!
+//! ```
+//! # use kubizone_crds::pattern::domain_matches_pattern;
+//! # use kubizone_crds::kubizone_common::FullyQualifiedDomainName;
+//! let domain = FullyQualifiedDomainName::try_from("www.dev.example.org.").unwrap();
+//!
+//! assert_eq!(domain_matches_pattern("*.example.org.", &domain), Ok(true));
+//! assert_eq!(domain_matches_pattern("*.dev.example.org.", &domain), Ok(true));
+//! assert_eq!(domain_matches_pattern("www.**.org.", &domain), Ok(true));
+//! assert_eq!(domain_matches_pattern("www.*.org.", &domain), Ok(false));
+//! ```
+//!
+//! Delegations may alternatively use regular expressions, see [`DomainRegex`].
+
+use std::{fmt::Display, hash::Hash, str::FromStr};
+
+use kubizone_common::FullyQualifiedDomainName;
+use regex::Regex;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum GlobError {
+    #[error("pattern is an empty string")]
+    EmptyString,
+    #[error("empty label at position {0}")]
+    EmptyLabel(usize),
+    #[error("pattern ends with an unterminated escape")]
+    TrailingEscape,
+}
+
+/// Parsed domain name pattern. See the [module documentation](self) for syntax.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GlobPattern {
+    labels: Vec<Label>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Label {
+    /// `**`, matching zero or more labels.
+    Any,
+    /// Single label, possibly containing wildcards.
+    Glob(Vec<Piece>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Piece {
+    Literal(char),
+    Wildcard,
+}
+
+impl GlobPattern {
+    /// Check if the pattern matches the given domain.
+    pub fn matches(&self, domain: &FullyQualifiedDomainName) -> bool {
+        let domain: Vec<String> = domain
+            .iter()
+            .map(|label| label.as_ref().to_ascii_lowercase())
+            .collect();
+
+        let labels = match self.labels.first() {
+            // A leftmost `*` is shorthand for `**.*`.
+            Some(Label::Glob(pieces)) if pieces == &[Piece::Wildcard] => {
+                let mut labels = Vec::with_capacity(self.labels.len() + 1);
+                labels.push(Label::Any);
+                labels.extend(self.labels.iter().cloned());
+                labels
+            }
+            _ => self.labels.clone(),
+        };
+
+        match_labels(&labels, &domain)
+    }
+}
+
+/// Check if `domain` matches `pattern`. See the [module documentation](self) for syntax.
+///
+/// Parse the pattern into a [`GlobPattern`] instead, when matching it against many domains.
+pub fn domain_matches_pattern(
+    pattern: &str,
+    domain: &FullyQualifiedDomainName,
+) -> Result<bool, GlobError> {
+    Ok(GlobPattern::from_str(pattern)?.matches(domain))
+}
+
+/// Check if `namespace` matches the namespace `pattern` of a delegation.
+///
+/// Unlike domain patterns, namespace patterns are matched against a single
+/// namespace name: `*` matches any (possibly empty) sequence of characters,
+/// so `team-*` matches `team-a` and `team-`, but not `teams`, and `*` on its
+/// own matches every namespace. Every other character must match exactly.
+///
+/// ```
+/// # use kubizone_crds::pattern::namespace_matches_pattern;
+/// assert!(namespace_matches_pattern("team-*", "team-payments"));
+/// assert!(namespace_matches_pattern("*-prod", "shop-prod"));
+/// assert!(!namespace_matches_pattern("team-*", "kube-system"));
+/// ```
+pub fn namespace_matches_pattern(pattern: &str, namespace: &str) -> bool {
+    if !pattern.contains('*') {
+        return pattern == namespace;
+    }
+
+    let pieces: Vec<_> = pattern
+        .chars()
+        .map(|c| match c {
+            '*' => Piece::Wildcard,
+            c => Piece::Literal(c),
+        })
+        .collect();
+
+    match_label(&pieces, &namespace.chars().collect::<Vec<_>>())
+}
+
+fn match_labels(pattern: &[Label], domain: &[String]) -> bool {
+    let domain: Vec<Vec<char>> = domain.iter().map(|label| label.chars().collect()).collect();
+
+    match_wildcards(
+        pattern,
+        &domain,
+        |label| matches!(label, Label::Any),
+        |label, candidate| match label {
+            Label::Any => true,
+            Label::Glob(pieces) => match_label(pieces, candidate),
+        },
+    )
+}
+
+fn match_label(pieces: &[Piece], label: &[char]) -> bool {
+    match_wildcards(
+        pieces,
+        label,
+        |piece| matches!(piece, Piece::Wildcard),
+        |piece, c| match piece {
+            Piece::Wildcard => true,
+            Piece::Literal(literal) => literal == c,
+        },
+    )
+}
+
+/// Match `input` against `pattern`, where items for which `is_wildcard`
+/// holds match any (possibly empty) run of input, and every other item
+/// matches exactly one element for which `matches` holds.
+///
+/// Greedy matching which, on a mismatch, only ever backtracks to the most
+/// recent wildcard. Since the items between two wildcards always consume a
+/// fixed number of elements, matching them at their earliest position
+/// never rules out a match, which bounds the running time to
+/// `O(pattern.len() * input.len())` regardless of the number of wildcards.
+fn match_wildcards<P, T>(
+    pattern: &[P],
+    input: &[T],
+    is_wildcard: impl Fn(&P) -> bool,
+    matches: impl Fn(&P, &T) -> bool,
+) -> bool {
+    let (mut p, mut i) = (0, 0);
+    // Position of the last wildcard, and of the input it was resumed at.
+    let mut backtrack = None;
+
+    while i < input.len() {
+        if p < pattern.len() && is_wildcard(&pattern[p]) {
+            backtrack = Some((p, i));
+            p += 1;
+        } else if p < pattern.len() && matches(&pattern[p], &input[i]) {
+            p += 1;
+            i += 1;
+        } else if let Some((wildcard, resumed)) = backtrack {
+            // Let the wildcard consume one more element, and retry.
+            backtrack = Some((wildcard, resumed + 1));
+            p = wildcard + 1;
+            i = resumed + 1;
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(is_wildcard)
+}
+
+impl FromStr for GlobPattern {
+    type Err = GlobError;
+
+    fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+        if pattern.is_empty() {
+            return Err(GlobError::EmptyString);
+        }
+
+        if pattern == "." {
+            return Ok(GlobPattern { labels: Vec::new() });
+        }
+
+        let mut labels = Vec::new();
+        let mut pieces = Vec::new();
+        let mut raw = String::new();
+        let mut chars = pattern.chars();
+
+        let mut finish = |pieces: &mut Vec<Piece>, raw: &mut String| {
+            if pieces.is_empty() {
+                return Err(GlobError::EmptyLabel(labels.len() + 1));
+            }
+
+            labels.push(if raw == "**" {
+                Label::Any
+            } else {
+                Label::Glob(std::mem::take(pieces))
+            });
+
+            pieces.clear();
+            raw.clear();
+            Ok(())
+        };
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    let escaped = chars.next().ok_or(GlobError::TrailingEscape)?;
+                    pieces.push(Piece::Literal(escaped.to_ascii_lowercase()));
+                    raw.push('\\');
+                    raw.push(escaped);
+                }
+                '.' => finish(&mut pieces, &mut raw)?,
+                '*' => {
+                    pieces.push(Piece::Wildcard);
+                    raw.push('*');
+                }
+                c => {
+                    pieces.push(Piece::Literal(c.to_ascii_lowercase()));
+                    raw.push(c);
+                }
+            }
+        }
+
+        // The trailing dot is optional, but if present leaves no label behind.
+        if !pieces.is_empty() {
+            finish(&mut pieces, &mut raw)?;
+        }
+
+        Ok(GlobPattern { labels })
+    }
+}
+
+impl TryFrom<&str> for GlobPattern {
+    type Error = GlobError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        GlobPattern::from_str(value)
+    }
+}
+
+/// Formats the pattern in its canonical, fully qualified form.
+impl Display for GlobPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.labels.is_empty() {
+            return f.write_str(".");
+        }
+
+        for label in &self.labels {
+            match label {
+                Label::Any => f.write_str("**")?,
+                Label::Glob(pieces) => {
+                    for piece in pieces {
+                        match piece {
+                            Piece::Wildcard => f.write_str("*")?,
+                            Piece::Literal(c @ ('*' | '.' | '\\')) => write!(f, "\\{c}")?,
+                            Piece::Literal(c) => write!(f, "{c}")?,
+                        }
+                    }
+                }
+            }
+            f.write_str(".")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Regular expression matched against fully qualified domain names.
+///
+/// The expression must match the *entire* name including the trailing
+/// dot, as if wrapped in `^` and `$`, so `api\.example\.org\.` does not
+/// also match `myapi.example.org.`.
+#[derive(Clone, Debug)]
+pub struct DomainRegex {
+    source: String,
+    regex: Regex,
+}
+
+impl DomainRegex {
+    /// Check if the expression matches the given domain.
+    pub fn matches(&self, domain: &FullyQualifiedDomainName) -> bool {
+        self.regex.is_match(&domain.to_string())
+    }
+
+    /// The expression as originally written.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl FromStr for DomainRegex {
+    type Err = regex::Error;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Ok(DomainRegex {
+            source: source.to_string(),
+            regex: Regex::new(&format!("^(?:{source})$"))?,
+        })
+    }
+}
+
+impl TryFrom<&str> for DomainRegex {
+    type Error = regex::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        DomainRegex::from_str(value)
+    }
+}
+
+impl Display for DomainRegex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+// Compiled expressions can't be compared, but the source they were built from can.
+impl PartialEq for DomainRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for DomainRegex {}
+
+impl PartialOrd for DomainRegex {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for DomainRegex {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.source.cmp(&other.source)
+    }
+}
+
+impl Hash for DomainRegex {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.source.hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use kubizone_common::{FullyQualifiedDomainName, Pattern};
+
+    use super::{
+        domain_matches_pattern, namespace_matches_pattern, DomainRegex, GlobError, GlobPattern,
+    };
+
+    fn fqdn(domain: &str) -> FullyQualifiedDomainName {
+        FullyQualifiedDomainName::try_from(domain).unwrap()
+    }
+
+    #[test]
+    fn test_glob_matching() {
+        for (pattern, domain, expected) in [
+            // Literals
+            ("example.org.", "example.org.", true),
+            ("example.org", "example.org.", true),
+            ("EXAMPLE.org.", "example.org.", true),
+            ("example.org.", "www.example.org.", false),
+            ("www.example.org.", "example.org.", false),
+            (".", "org.", false),
+            // Single-label wildcards
+            ("web-*.example.org.", "web-1.example.org.", true),
+            ("web-*.example.org.", "web.example.org.", false),
+            ("web-*.example.org.", "a.web-1.example.org.", false),
+            ("*-*-db.example.org.", "eu-west-db.example.org.", true),
+            ("*-*-db.example.org.", "eu-db.example.org.", false),
+            ("a*a.example.org.", "a.example.org.", false),
+            ("www.*.org.", "www.example.org.", true),
+            ("www.*.org.", "www.dev.example.org.", false),
+            // Leftmost wildcard spans one or more labels.
+            ("*.example.org.", "www.example.org.", true),
+            ("*.example.org.", "a.b.c.example.org.", true),
+            ("*.example.org.", "example.org.", false),
+            // Multi-label wildcards
+            ("**.example.org.", "example.org.", true),
+            ("**.example.org.", "a.b.example.org.", true),
+            ("www.**.org.", "www.org.", true),
+            ("www.**.org.", "www.a.b.org.", true),
+            ("www.**.org.", "www.a.b.com.", false),
+            ("**", "www.example.org.", true),
+            ("**.*.**", "org.", true),
+            ("**.*.*.**", "org.", false),
+            // Escaping
+            (r"\*.example.org.", "www.example.org.", false),
+            (r"\*\*.example.org.", "www.example.org.", false),
+        ] {
+            assert_eq!(
+                domain_matches_pattern(pattern, &fqdn(domain)),
+                Ok(expected),
+                "{pattern} matches {domain}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_namespace_matching() {
+        for (pattern, namespace, expected) in [
+            ("team-*", "team-a", true),
+            ("team-*", "team-", true),
+            ("team-*", "teams", false),
+            ("*", "kube-system", true),
+            ("*-prod", "shop-prod", true),
+            ("*-prod", "shop-prod-1", false),
+            ("a*b*c", "aXbYbZc", true),
+            ("a*b*c", "aXcYb", false),
+            ("**", "", true),
+            ("web", "web", true),
+            ("web", "webs", false),
+        ] {
+            assert_eq!(
+                namespace_matches_pattern(pattern, namespace),
+                expected,
+                "{pattern} matches {namespace}"
+            );
+        }
+    }
+
+    /// Patterns are user-controlled, so matching must not take time
+    /// exponential in the number of wildcards.
+    #[test]
+    fn test_wildcard_matching_is_not_exponential() {
+        let start = Instant::now();
+
+        let pattern = format!("{}b", "*a".repeat(30));
+        let namespace = "a".repeat(63);
+        assert!(!namespace_matches_pattern(&pattern, &namespace));
+
+        let domain = fqdn(&format!("{}.example.org.", "a".repeat(63)));
+        assert_eq!(domain_matches_pattern(&pattern, &domain), Ok(false));
+
+        let labels = fqdn(&format!("{}example.org.", "a.".repeat(100)));
+        let pattern = format!("{}b.example.org.", "**.a.".repeat(30));
+        assert_eq!(domain_matches_pattern(&pattern, &labels), Ok(false));
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_glob_errors() {
+        assert_eq!(GlobPattern::try_from(""), Err(GlobError::EmptyString));
+        assert_eq!(
+            GlobPattern::try_from("www..org."),
+            Err(GlobError::EmptyLabel(2))
+        );
+        assert_eq!(
+            GlobPattern::try_from(".example.org."),
+            Err(GlobError::EmptyLabel(1))
+        );
+        assert_eq!(
+            GlobPattern::try_from("example\\"),
+            Err(GlobError::TrailingEscape)
+        );
+    }
+
+    #[test]
+    fn test_glob_display() {
+        for (pattern, canonical) in [
+            (".", "."),
+            ("Example.org", "example.org."),
+            ("**.web-*.example.org.", "**.web-*.example.org."),
+            (r"\*\..example.org.", r"\*\..example.org."),
+        ] {
+            let parsed = GlobPattern::try_from(pattern).unwrap();
+            assert_eq!(parsed.to_string(), canonical);
+            assert_eq!(GlobPattern::try_from(canonical).unwrap(), parsed);
+        }
+    }
+
+    /// Delegation patterns anchored to their zone must
+    /// behave identically when matched as globs.
+    #[test]
+    fn test_delegation_pattern_equivalence() {
+        let origin = fqdn("example.org.");
+
+        let patterns = ["*", "www", "*.dev", "web-*", "*-db.x", "a.b", "*.*.dev"];
+        let domains = [
+            "example.org.",
+            "www.example.org.",
+            "a.www.example.org.",
+            "dev.example.org.",
+            "www.dev.example.org.",
+            "a.b.dev.example.org.",
+            "web-1.example.org.",
+            "web.example.org.",
+            "eu-db.x.example.org.",
+            "a.b.example.org.",
+            "www.example.com.",
+        ];
+
+        for pattern in patterns {
+            let delegation = Pattern::try_from(pattern).unwrap().with_origin(&origin);
+            let glob = GlobPattern::try_from(format!("{pattern}.{origin}").as_str()).unwrap();
+
+            for domain in domains {
+                let domain = fqdn(domain);
+                assert_eq!(
+                    glob.matches(&domain),
+                    delegation.matches(&domain),
+                    "{pattern} matches {domain}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_domain_regex() {
+        let regex = DomainRegex::try_from(r"[a-z]{3}-\d+\.internal\.example\.org\.").unwrap();
+
+        assert!(regex.matches(&fqdn("abc-12.internal.example.org.")));
+        assert!(!regex.matches(&fqdn("abcd-12.internal.example.org.")));
+        assert!(!regex.matches(&fqdn("x.abc-12.internal.example.org.")));
+        assert!(DomainRegex::try_from("(unclosed").is_err());
+    }
+}
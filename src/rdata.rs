@@ -0,0 +1,710 @@
+//! Typed representation of record data.
+//!
+//! Records store their data as a free-form string in `.spec.rdata`, exactly
+//! as it would appear in a zonefile. [`RData`] parses that string according
+//! to the record's type, so consumers don't have to re-implement parsing of
+//! priorities, ports, quoted strings and so on.
+
+use std::{
+    fmt::{Display, Write},
+    net::{Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use kubizone_common::{DomainName, Type};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain_name::parse_domain_name;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RDataError {
+    #[error("invalid ipv4 address: {0}")]
+    InvalidIpv4Address(String),
+    #[error("invalid ipv6 address: {0}")]
+    InvalidIpv6Address(String),
+    #[error("invalid domain name {name}: {reason}")]
+    InvalidDomainName { name: String, reason: String },
+    #[error("invalid integer: {0}")]
+    InvalidInteger(String),
+    #[error("expected {expected} fields, found {found}")]
+    FieldCount { expected: usize, found: usize },
+    #[error("unterminated quoted string")]
+    UnterminatedString,
+    #[error("invalid escape sequence: {0}")]
+    InvalidEscape(String),
+    #[error("invalid CAA tag: {0}")]
+    InvalidCaaTag(String),
+    #[error("invalid value for CAA tag {tag}: {value}")]
+    InvalidCaaValue { tag: String, value: String },
+    #[error("invalid hexadecimal string: {0}")]
+    InvalidHex(String),
+    #[error("record data is {length} octets long, exceeding the maximum of {MAX_RDATA_LENGTH}")]
+    TooLong { length: usize },
+}
+
+/// Longest character-string, such as each of the strings of TXT data, in octets.
+pub const MAX_CHARACTER_STRING_LENGTH: usize = 255;
+
+/// Longest record data on the wire, in octets.
+pub const MAX_RDATA_LENGTH: usize = 65535;
+
+/// Data of an SRV record, see [`RecordSpec::srv`](crate::v1alpha1::RecordSpec::srv).
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SrvData {
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    pub target: DomainName,
+}
+
+/// Data of a CAA record, see [`RecordSpec::caa`](crate::v1alpha1::RecordSpec::caa).
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CaaData {
+    pub flags: u8,
+    /// Property tag, such as `issue`, always lowercase.
+    pub tag: String,
+    pub value: String,
+}
+
+impl CaaData {
+    /// Check if the issuer critical flag is set, meaning certificate
+    /// authorities must refuse to issue if they do not understand the tag.
+    pub fn is_critical(&self) -> bool {
+        self.flags & 0x80 != 0
+    }
+}
+
+/// Parsed record data.
+///
+/// Types without a dedicated variant are kept verbatim in [`RData::Other`].
+#[allow(clippy::upper_case_acronyms)]
+#[derive(
+    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum RData {
+    A(Ipv4Addr),
+    AAAA(Ipv6Addr),
+    CNAME(DomainName),
+    DNAME(DomainName),
+    NS(DomainName),
+    PTR(DomainName),
+    MX {
+        preference: u16,
+        exchange: DomainName,
+    },
+    SRV {
+        priority: u16,
+        weight: u16,
+        port: u16,
+        target: DomainName,
+    },
+    /// One or more character-strings.
+    TXT(Vec<String>),
+    CAA {
+        flags: u8,
+        tag: String,
+        value: String,
+    },
+    Other {
+        #[serde(rename = "type")]
+        type_: Type,
+        data: String,
+    },
+}
+
+impl RData {
+    /// Parse zonefile-formatted record data belonging to a record of the given type.
+    pub fn parse(type_: Type, rdata: &str) -> Result<Self, RDataError> {
+        let rdata = rdata.trim();
+
+        Ok(match type_ {
+            Type::A => RData::A(
+                Ipv4Addr::from_str(rdata)
+                    .map_err(|_| RDataError::InvalidIpv4Address(rdata.to_string()))?,
+            ),
+            Type::AAAA => RData::AAAA(
+                Ipv6Addr::from_str(rdata)
+                    .map_err(|_| RDataError::InvalidIpv6Address(rdata.to_string()))?,
+            ),
+            Type::CNAME => RData::CNAME(domain_name(rdata)?),
+            Type::DNAME => RData::DNAME(domain_name(rdata)?),
+            Type::NS => RData::NS(domain_name(rdata)?),
+            Type::PTR => RData::PTR(domain_name(rdata)?),
+            Type::MX => {
+                let [preference, exchange] = fields(rdata)?;
+                RData::MX {
+                    preference: integer(&preference)?,
+                    exchange: domain_name(&exchange)?,
+                }
+            }
+            Type::SRV => {
+                let [priority, weight, port, target] = fields(rdata)?;
+                RData::SRV {
+                    priority: integer(&priority)?,
+                    weight: integer(&weight)?,
+                    port: integer(&port)?,
+                    target: domain_name(&target)?,
+                }
+            }
+            Type::TXT => RData::TXT(tokenize(rdata)?),
+            Type::CAA => {
+                let [flags, tag, value] = fields(rdata)?;
+
+                if tag.is_empty()
+                    || tag.len() > MAX_CAA_TAG_LENGTH
+                    || !tag.chars().all(|c| c.is_ascii_alphanumeric())
+                {
+                    return Err(RDataError::InvalidCaaTag(tag));
+                }
+
+                RData::CAA {
+                    flags: integer(&flags)?,
+                    tag: tag.to_ascii_lowercase(),
+                    value,
+                }
+            }
+            type_ => RData::Other {
+                type_,
+                data: rdata.to_string(),
+            },
+        })
+    }
+
+    /// Check that zonefile-formatted record data is well-formed for a record
+    /// of the given type.
+    ///
+    /// Stricter than [`RData::parse`], which keeps the data of types without
+    /// a dedicated variant verbatim. This also checks the fields of common
+    /// types such as SSHFP, TLSA, DS and NAPTR, and the values of the CAA
+    /// tags defined by RFC 8659. Data of other types is not checked.
+    ///
+    /// TXT data is checked against [`MAX_RDATA_LENGTH`]. Strings longer than
+    /// [`MAX_CHARACTER_STRING_LENGTH`] are accepted, since they are split when
+    /// rendered, see [`split_character_strings`].
+    pub fn validate(type_: Type, rdata: &str) -> Result<(), RDataError> {
+        match RData::parse(type_, rdata)? {
+            RData::TXT(strings) => {
+                // Each character-string is prefixed by its length on the wire.
+                let length = split_txt(&strings)
+                    .iter()
+                    .map(|string| string.len() + 1)
+                    .sum();
+
+                if length > MAX_RDATA_LENGTH {
+                    return Err(RDataError::TooLong { length });
+                }
+
+                Ok(())
+            }
+            RData::CAA { tag, value, .. } => validate_caa_value(&tag, &value),
+            RData::Other { type_, data } => validate_other(type_, &data),
+            _ => Ok(()),
+        }
+    }
+
+    /// TXT data holding `value`, split into as many character-strings as
+    /// necessary, see [`split_character_strings`].
+    ///
+    /// ```rust
+    /// # use kubizone_crds::rdata::RData;
+    /// let dkim = format!("v=DKIM1; k=rsa; p={}", "A".repeat(300));
+    /// let txt = RData::txt(&dkim);
+    ///
+    /// assert_eq!(txt.to_string().matches('"').count(), 4);
+    /// assert_eq!(txt.txt_value(), Some(dkim));
+    /// ```
+    pub fn txt(value: &str) -> Self {
+        RData::TXT(split_character_strings(value))
+    }
+
+    /// Join the character-strings of TXT data back into a single value,
+    /// the way SPF and DKIM consumers read them.
+    pub fn txt_value(&self) -> Option<String> {
+        match self {
+            RData::TXT(strings) => Some(strings.concat()),
+            _ => None,
+        }
+    }
+
+    /// Preference and exchange of MX data.
+    pub fn mx(self) -> Option<(u16, DomainName)> {
+        match self {
+            RData::MX {
+                preference,
+                exchange,
+            } => Some((preference, exchange)),
+            _ => None,
+        }
+    }
+
+    /// Fields of SRV data.
+    pub fn srv(self) -> Option<SrvData> {
+        match self {
+            RData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => Some(SrvData {
+                priority,
+                weight,
+                port,
+                target,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Fields of CAA data.
+    pub fn caa(self) -> Option<CaaData> {
+        match self {
+            RData::CAA { flags, tag, value } => Some(CaaData { flags, tag, value }),
+            _ => None,
+        }
+    }
+
+    /// Type of record this data belongs to.
+    pub fn type_(&self) -> Type {
+        match self {
+            RData::A(_) => Type::A,
+            RData::AAAA(_) => Type::AAAA,
+            RData::CNAME(_) => Type::CNAME,
+            RData::DNAME(_) => Type::DNAME,
+            RData::NS(_) => Type::NS,
+            RData::PTR(_) => Type::PTR,
+            RData::MX { .. } => Type::MX,
+            RData::SRV { .. } => Type::SRV,
+            RData::TXT(_) => Type::TXT,
+            RData::CAA { .. } => Type::CAA,
+            RData::Other { type_, .. } => *type_,
+        }
+    }
+}
+
+/// Formats the data as it would appear in a zonefile.
+impl Display for RData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RData::A(address) => write!(f, "{address}"),
+            RData::AAAA(address) => write!(f, "{address}"),
+            RData::CNAME(name) | RData::DNAME(name) | RData::NS(name) | RData::PTR(name) => {
+                write!(f, "{name}")
+            }
+            RData::MX {
+                preference,
+                exchange,
+            } => write!(f, "{preference} {exchange}"),
+            RData::SRV {
+                priority,
+                weight,
+                port,
+                target,
+            } => write!(f, "{priority} {weight} {port} {target}"),
+            RData::TXT(strings) => {
+                for (i, string) in strings.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(' ')?;
+                    }
+                    write_quoted(f, string)?;
+                }
+                Ok(())
+            }
+            RData::CAA { flags, tag, value } => {
+                write!(f, "{flags} {tag} ")?;
+                write_quoted(f, value)
+            }
+            RData::Other { data, .. } => f.write_str(data),
+        }
+    }
+}
+
+/// Split `value` into character-strings of at most [`MAX_CHARACTER_STRING_LENGTH`]
+/// octets, without breaking up multi-byte characters.
+///
+/// Empty values produce a single empty string.
+pub fn split_character_strings(value: &str) -> Vec<String> {
+    let mut strings = Vec::new();
+    let mut rest = value;
+
+    while rest.len() > MAX_CHARACTER_STRING_LENGTH {
+        let mut end = MAX_CHARACTER_STRING_LENGTH;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let (string, tail) = rest.split_at(end);
+        strings.push(string.to_string());
+        rest = tail;
+    }
+
+    strings.push(rest.to_string());
+    strings
+}
+
+/// Split each of `strings` which is too long to be a single character-string.
+pub(crate) fn split_txt(strings: &[String]) -> Vec<String> {
+    strings
+        .iter()
+        .flat_map(|string| split_character_strings(string))
+        .collect()
+}
+
+/// Longest CAA tag allowed by RFC 8659.
+const MAX_CAA_TAG_LENGTH: usize = 15;
+
+fn validate_caa_value(tag: &str, value: &str) -> Result<(), RDataError> {
+    let valid = match tag {
+        // An optional issuer domain name, followed by parameters.
+        "issue" | "issuewild" | "issuemail" => {
+            let issuer = value.split(';').next().unwrap_or_default().trim();
+            issuer.is_empty() || domain_name(issuer).is_ok()
+        }
+        "iodef" => ["mailto:", "http://", "https://"]
+            .iter()
+            .any(|scheme| value.starts_with(scheme) && value.len() > scheme.len()),
+        _ => true,
+    };
+
+    if !valid {
+        return Err(RDataError::InvalidCaaValue {
+            tag: tag.to_string(),
+            value: value.to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+fn validate_other(type_: Type, rdata: &str) -> Result<(), RDataError> {
+    match type_ {
+        Type::AFSDB | Type::KX => {
+            let [preference, host] = fields(rdata)?;
+            integer::<u16>(&preference)?;
+            domain_name(&host)?;
+        }
+        Type::DS | Type::CDS => {
+            let [key_tag, algorithm, digest_type, digest] = fields(rdata)?;
+            integer::<u16>(&key_tag)?;
+            integer::<u8>(&algorithm)?;
+            integer::<u8>(&digest_type)?;
+            hex(&digest)?;
+        }
+        Type::HINFO => {
+            fields::<2>(rdata)?;
+        }
+        Type::NAPTR => {
+            let [order, preference, _flags, _services, _regexp, replacement] = fields(rdata)?;
+            integer::<u16>(&order)?;
+            integer::<u16>(&preference)?;
+            domain_name(&replacement)?;
+        }
+        Type::SOA => {
+            let [mname, rname, serial, refresh, retry, expire, minimum] = fields(rdata)?;
+            domain_name(&mname)?;
+            domain_name(&rname)?;
+            for value in [serial, refresh, retry, expire, minimum] {
+                integer::<u32>(&value)?;
+            }
+        }
+        Type::SSHFP => {
+            let [algorithm, fingerprint_type, fingerprint] = fields(rdata)?;
+            integer::<u8>(&algorithm)?;
+            integer::<u8>(&fingerprint_type)?;
+            hex(&fingerprint)?;
+        }
+        Type::TLSA | Type::SMIMEA => {
+            let [usage, selector, matching_type, data] = fields(rdata)?;
+            integer::<u8>(&usage)?;
+            integer::<u8>(&selector)?;
+            integer::<u8>(&matching_type)?;
+            hex(&data)?;
+        }
+        Type::URI => {
+            let [priority, weight, _target] = fields(rdata)?;
+            integer::<u16>(&priority)?;
+            integer::<u16>(&weight)?;
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/// Write `value` as a quoted character-string, escaping quotes and backslashes.
+///
+/// Octets outside of printable ASCII, including those of multi-byte UTF-8
+/// characters, are written as `\DDD` decimal escapes, as described in
+/// [RFC 1035 §5.1](https://datatracker.ietf.org/doc/html/rfc1035#section-5.1).
+pub(crate) fn write_quoted<W: Write>(f: &mut W, value: &str) -> std::fmt::Result {
+    f.write_char('"')?;
+    for byte in value.bytes() {
+        match byte {
+            b'"' | b'\\' => write!(f, "\\{}", char::from(byte))?,
+            b' '..=b'~' => f.write_char(char::from(byte))?,
+            _ => write!(f, "\\{byte:03}")?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Split rdata into whitespace-separated fields, treating quoted
+/// strings as single fields and resolving backslash escapes, including
+/// `\DDD` decimal escapes.
+pub(crate) fn tokenize(rdata: &str) -> Result<Vec<String>, RDataError> {
+    let mut tokens = Vec::new();
+    let mut chars = rdata.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let Some(first) = chars.next() else {
+            return Ok(tokens);
+        };
+
+        let quoted = first == '"';
+        let mut token = Vec::new();
+        let push = |token: &mut Vec<u8>, c: char| {
+            token.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+        };
+
+        if !quoted {
+            push(&mut token, first);
+        }
+
+        loop {
+            match chars.next() {
+                Some('\\') => match chars.next() {
+                    Some(digit) if digit.is_ascii_digit() => {
+                        let digits: String = [Some(digit), chars.next(), chars.next()]
+                            .into_iter()
+                            .flatten()
+                            .collect();
+
+                        match digits.parse::<u8>() {
+                            Ok(byte) if digits.len() == 3 => token.push(byte),
+                            _ => return Err(RDataError::InvalidEscape(format!("\\{digits}"))),
+                        }
+                    }
+                    Some(escaped) => push(&mut token, escaped),
+                    None if quoted => return Err(RDataError::UnterminatedString),
+                    None => break,
+                },
+                Some('"') if quoted => break,
+                Some(c) if !quoted && c.is_whitespace() => break,
+                Some(c) => push(&mut token, c),
+                None if quoted => return Err(RDataError::UnterminatedString),
+                None => break,
+            }
+        }
+
+        let token = String::from_utf8(token).map_err(|err| {
+            RDataError::InvalidEscape(String::from_utf8_lossy(err.as_bytes()).into_owned())
+        })?;
+        tokens.push(token);
+    }
+}
+
+fn fields<const N: usize>(rdata: &str) -> Result<[String; N], RDataError> {
+    let tokens = tokenize(rdata)?;
+    let found = tokens.len();
+
+    tokens
+        .try_into()
+        .map_err(|_| RDataError::FieldCount { expected: N, found })
+}
+
+fn integer<T: FromStr>(value: &str) -> Result<T, RDataError> {
+    value
+        .parse()
+        .map_err(|_| RDataError::InvalidInteger(value.to_string()))
+}
+
+fn hex(value: &str) -> Result<(), RDataError> {
+    if value.is_empty() || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(RDataError::InvalidHex(value.to_string()));
+    }
+
+    Ok(())
+}
+
+fn domain_name(value: &str) -> Result<DomainName, RDataError> {
+    let invalid = |reason: String| RDataError::InvalidDomainName {
+        name: value.to_string(),
+        reason,
+    };
+
+    parse_domain_name(value).map_err(|err| invalid(err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use kubizone_common::{DomainName, Type};
+
+    use super::{split_character_strings, RData, RDataError, MAX_RDATA_LENGTH};
+
+    #[test]
+    fn test_parse_rdata() {
+        assert_eq!(
+            RData::parse(Type::A, "192.168.0.1"),
+            Ok(RData::A("192.168.0.1".parse().unwrap()))
+        );
+
+        assert!(matches!(
+            RData::parse(Type::AAAA, "192.168.0.1"),
+            Err(RDataError::InvalidIpv6Address(_))
+        ));
+
+        assert_eq!(
+            RData::parse(Type::MX, "10 mail1.example.org."),
+            Ok(RData::MX {
+                preference: 10,
+                exchange: DomainName::try_from("mail1.example.org.").unwrap()
+            })
+        );
+
+        assert_eq!(
+            RData::parse(Type::SRV, "10 5 5060"),
+            Err(RDataError::FieldCount {
+                expected: 4,
+                found: 3
+            })
+        );
+
+        assert_eq!(
+            RData::parse(Type::TXT, r#""v=spf1 -all" "quote \" here""#),
+            Ok(RData::TXT(vec![
+                String::from("v=spf1 -all"),
+                String::from("quote \" here")
+            ]))
+        );
+
+        assert_eq!(
+            RData::parse(Type::CAA, r#"0 issue "letsencrypt.org""#),
+            Ok(RData::CAA {
+                flags: 0,
+                tag: String::from("issue"),
+                value: String::from("letsencrypt.org")
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_rdata() {
+        for (type_, rdata) in [
+            (Type::MX, "10 mail.example.org."),
+            (
+                Type::CAA,
+                r#"0 issue "letsencrypt.org; validationmethods=dns-01""#,
+            ),
+            (Type::CAA, r#"0 issuewild ";""#),
+            (Type::CAA, r#"0 iodef "mailto:security@example.org""#),
+            (Type::SSHFP, "4 2 123456789ABCDEF0"),
+            (Type::TLSA, "3 1 1 0123456789abcdef"),
+            (
+                Type::DS,
+                "60485 5 1 2BB183AF5F22588179A53B0A98631FAD1A292118",
+            ),
+            (
+                Type::NAPTR,
+                r#"100 10 "S" "SIP+D2U" "" _sip._udp.example.org."#,
+            ),
+            (Type::SOA, "ns1 hostmaster 1 2 3 4 5"),
+            (Type::HIP, "anything goes"),
+        ] {
+            assert_eq!(RData::validate(type_, rdata), Ok(()), "{type_} {rdata}");
+        }
+
+        assert_eq!(
+            RData::validate(Type::A, "2001:db8::1"),
+            Err(RDataError::InvalidIpv4Address(String::from("2001:db8::1")))
+        );
+        assert_eq!(
+            RData::validate(Type::CAA, r#"0 issue "not a domain""#),
+            Err(RDataError::InvalidCaaValue {
+                tag: String::from("issue"),
+                value: String::from("not a domain")
+            })
+        );
+        assert!(matches!(
+            RData::validate(Type::CAA, r#"0 averyveryverylongtag "x""#),
+            Err(RDataError::InvalidCaaTag(_))
+        ));
+        assert_eq!(
+            RData::validate(Type::SSHFP, "1 1 xyz"),
+            Err(RDataError::InvalidHex(String::from("xyz")))
+        );
+        assert_eq!(
+            RData::validate(Type::TLSA, "3 1 1"),
+            Err(RDataError::FieldCount {
+                expected: 4,
+                found: 3
+            })
+        );
+        assert_eq!(
+            RData::validate(Type::DS, "70000 5 1 AB"),
+            Err(RDataError::InvalidInteger(String::from("70000")))
+        );
+    }
+
+    #[test]
+    fn test_txt_length() {
+        assert_eq!(split_character_strings(""), [""]);
+        assert_eq!(split_character_strings("v=spf1 -all"), ["v=spf1 -all"]);
+
+        let strings = split_character_strings(&"a".repeat(600));
+        assert_eq!(
+            strings.iter().map(String::len).collect::<Vec<_>>(),
+            [255, 255, 90]
+        );
+
+        // Multi-byte characters are not split across strings.
+        let strings = split_character_strings(&format!("{}é", "a".repeat(254)));
+        assert_eq!(strings[0].len(), 254);
+        assert_eq!(strings[1], "é");
+
+        let long = format!("\"{}\"", "a".repeat(1000));
+        assert_eq!(RData::validate(Type::TXT, &long), Ok(()));
+        assert_eq!(
+            RData::parse(Type::TXT, &long)
+                .unwrap()
+                .txt_value()
+                .unwrap()
+                .len(),
+            1000
+        );
+
+        assert_eq!(
+            RData::validate(Type::TXT, &"a".repeat(MAX_RDATA_LENGTH)),
+            Err(RDataError::TooLong { length: 65792 })
+        );
+    }
+
+    #[test]
+    fn test_rdata_roundtrip() {
+        for (type_, rdata) in [
+            (Type::AAAA, "2001:db8::1"),
+            (Type::CNAME, "www.example.org."),
+            (Type::SRV, "10 5 5060 sip.example.org."),
+            (Type::TXT, r#""hello \\ world" "second""#),
+            (Type::TXT, r#""tab\009 caf\195\169""#),
+            (Type::CAA, r#"128 iodef "mailto:security@example.org""#),
+            (Type::SSHFP, "1 1 123456789abcdef"),
+        ] {
+            let parsed = RData::parse(type_, rdata).unwrap();
+            assert_eq!(parsed.type_(), type_);
+            assert_eq!(parsed.to_string(), rdata);
+        }
+
+        assert_eq!(
+            RData::parse(Type::TXT, r#""tab\009 caf\195\169""#).unwrap(),
+            RData::TXT(vec![String::from("tab\t café")])
+        );
+        assert_eq!(
+            RData::parse(Type::TXT, r#""\256""#),
+            Err(RDataError::InvalidEscape(String::from("\\256")))
+        );
+        assert!(RData::parse(Type::TXT, r#""\255""#).is_err());
+    }
+}
@@ -0,0 +1,544 @@
+//! Reverse DNS zone names.
+//!
+//! Reverse lookups use names under `in-addr.arpa.` for IPv4 and `ip6.arpa.`
+//! for IPv6, with the address written backwards, one label per octet or
+//! nibble respectively. Zones can therefore only be delegated on octet
+//! (IPv4) or nibble (IPv6) boundaries. IPv4 networks smaller than a /24
+//! are delegated through the classless scheme of
+//! [RFC 2317](https://datatracker.ietf.org/doc/html/rfc2317), which names
+//! the zone after the range of addresses it covers.
+//!
+//! [`ptr_record_for`] derives the PTR record matching an A or AAAA record,
+//! for controllers keeping forward and reverse zones consistent.
+
+use std::net::{IpAddr, Ipv6Addr};
+
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+use kubizone_common::{
+    error::FullyQualifiedDomainNameError, DomainName, FullyQualifiedDomainName, Type,
+};
+use thiserror::Error;
+
+use crate::v1alpha1::{ClasslessStyle, RecordSpec};
+
+/// Suffix under which IPv4 reverse names live.
+pub const IN_ADDR_ARPA: &str = "in-addr.arpa.";
+
+/// Suffix under which IPv6 reverse names live.
+pub const IP6_ARPA: &str = "ip6.arpa.";
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ReverseError {
+    #[error("invalid CIDR {0}")]
+    InvalidCidr(String),
+    #[error("{0} has host bits set, use {1} instead")]
+    HostBitsSet(IpNet, IpNet),
+    /// Only classless IPv4 networks smaller than a /24 can be delegated
+    /// without aligning to a label boundary.
+    #[error("prefix length of {0} is not on a reverse zone boundary")]
+    UnalignedPrefix(IpNet),
+    #[error("{0}")]
+    Domain(#[from] FullyQualifiedDomainNameError),
+}
+
+/// Parse a network in CIDR notation, such as `192.0.2.0/24`.
+///
+/// The address must be the network address, without any host bits set.
+pub fn parse_cidr(cidr: &str) -> Result<IpNet, ReverseError> {
+    let net: IpNet = cidr
+        .trim()
+        .parse()
+        .map_err(|_| ReverseError::InvalidCidr(cidr.to_string()))?;
+
+    if net.addr() != net.network() {
+        return Err(ReverseError::HostBitsSet(net, net.trunc()));
+    }
+
+    Ok(net)
+}
+
+/// Name of the reverse zone covering exactly the addresses of `net`.
+///
+/// `style` selects the naming of RFC 2317 classless zones, and is only
+/// relevant for IPv4 networks with a prefix length between 25 and 31.
+/// Networks which are not on a delegation boundary must first be split
+/// with [`reverse_zone_networks`].
+pub fn reverse_zone_name(
+    net: &IpNet,
+    style: ClasslessStyle,
+) -> Result<FullyQualifiedDomainName, ReverseError> {
+    let name = match net {
+        IpNet::V4(net) => ipv4_zone_name(net, style),
+        IpNet::V6(net) => ipv6_zone_name(net),
+    }
+    .ok_or(ReverseError::UnalignedPrefix(*net))?;
+
+    Ok(FullyQualifiedDomainName::try_from(name)?)
+}
+
+/// Reverse name of a single address, such as `1.2.0.192.in-addr.arpa.`
+/// for `192.0.2.1`.
+pub fn ptr_name(address: IpAddr) -> FullyQualifiedDomainName {
+    let name = match address {
+        IpAddr::V4(address) => ipv4_labels(&address.octets()),
+        IpAddr::V6(address) => ipv6_labels(&address, 32),
+    };
+
+    // Unwrap safety: Reverse names consist solely of numeric and hex labels.
+    FullyQualifiedDomainName::try_from(name).unwrap()
+}
+
+/// Address of a PTR name produced by [`ptr_name`].
+///
+/// Returns `None` for names which are not the reverse name of a single
+/// address, including the names of reverse zones.
+pub fn address_from_ptr_name(name: &FullyQualifiedDomainName) -> Option<IpAddr> {
+    match net_from_reverse_name(name, ClasslessStyle::default())? {
+        IpNet::V4(net) if net.prefix_len() == 32 => Some(IpAddr::V4(net.addr())),
+        IpNet::V6(net) if net.prefix_len() == 128 => Some(IpAddr::V6(net.addr())),
+        _ => None,
+    }
+}
+
+/// Network covered by a reverse name, the inverse of [`reverse_zone_name`].
+///
+/// Classless IPv4 names are interpreted according to `style`. Names which
+/// are not under `in-addr.arpa.` or `ip6.arpa.`, or which have malformed
+/// labels, return `None`.
+pub fn net_from_reverse_name(
+    name: &FullyQualifiedDomainName,
+    style: ClasslessStyle,
+) -> Option<IpNet> {
+    let name = name.to_string().to_ascii_lowercase();
+
+    if let Some(labels) = strip_reverse_suffix(&name, IN_ADDR_ARPA) {
+        ipv4_net_from_labels(&labels, style).map(IpNet::V4)
+    } else if let Some(labels) = strip_reverse_suffix(&name, IP6_ARPA) {
+        ipv6_net_from_labels(&labels).map(IpNet::V6)
+    } else {
+        None
+    }
+}
+
+/// Labels in front of `suffix`, most significant first.
+fn strip_reverse_suffix<'a>(name: &'a str, suffix: &str) -> Option<Vec<&'a str>> {
+    if name == suffix {
+        return Some(Vec::new());
+    }
+
+    let prefix = name.strip_suffix(suffix)?.strip_suffix('.')?;
+    Some(prefix.split('.').rev().collect())
+}
+
+fn ipv4_net_from_labels(labels: &[&str], style: ClasslessStyle) -> Option<Ipv4Net> {
+    if let [network @ .., last] = labels {
+        if last.contains('-') {
+            let network = ipv4_net_from_labels(network, style)?;
+            return classless_net(network, last, style);
+        }
+    }
+
+    if labels.len() > 4 {
+        return None;
+    }
+
+    let mut octets = [0u8; 4];
+    for (octet, label) in octets.iter_mut().zip(labels) {
+        // Leading zeros would make the name ambiguous.
+        if label.len() > 1 && label.starts_with('0') {
+            return None;
+        }
+
+        *octet = label.parse().ok()?;
+    }
+
+    Ipv4Net::new(octets.into(), labels.len() as u8 * 8).ok()
+}
+
+/// Parse the first label of an RFC 2317 classless zone name, within the /24 `network`.
+fn classless_net(network: Ipv4Net, label: &str, style: ClasslessStyle) -> Option<Ipv4Net> {
+    if network.prefix_len() != 24 {
+        return None;
+    }
+
+    let (first, second) = label.split_once('-')?;
+    let first: u8 = first.parse().ok()?;
+    let second: u8 = second.parse().ok()?;
+
+    let prefix = match style {
+        ClasslessStyle::Prefix => second,
+        ClasslessStyle::Range => {
+            let size = u16::from(second).checked_sub(u16::from(first))? + 1;
+            if !size.is_power_of_two() {
+                return None;
+            }
+
+            32 - size.trailing_zeros() as u8
+        }
+    };
+
+    if !(25..=31).contains(&prefix) {
+        return None;
+    }
+
+    let [a, b, c, _] = network.addr().octets();
+    let net = Ipv4Net::new([a, b, c, first].into(), prefix).ok()?;
+
+    // The first address must be the network address.
+    (net.network() == net.addr()).then_some(net)
+}
+
+fn ipv6_net_from_labels(labels: &[&str]) -> Option<Ipv6Net> {
+    if labels.len() > 32 {
+        return None;
+    }
+
+    let mut bits = 0u128;
+    for (i, label) in labels.iter().enumerate() {
+        if label.len() != 1 {
+            return None;
+        }
+
+        let nibble = u128::from_str_radix(label, 16).ok()?;
+        bits |= nibble << (124 - 4 * i);
+    }
+
+    Ipv6Net::new(Ipv6Addr::from(bits), labels.len() as u8 * 4).ok()
+}
+
+/// Shortest prefix length, no shorter than that of `net`, at which a
+/// reverse zone can be delegated.
+///
+/// This is the next multiple of 8 for IPv4 and 4 for IPv6, except for
+/// IPv4 networks longer than a /24, which are delegated as classless
+/// zones of their own prefix length.
+pub fn delegation_boundary(net: &IpNet) -> u8 {
+    let prefix = net.prefix_len();
+
+    match net {
+        IpNet::V4(_) if prefix > 24 => prefix,
+        IpNet::V4(_) => prefix.div_ceil(8) * 8,
+        IpNet::V6(_) => prefix.div_ceil(4) * 4,
+    }
+}
+
+/// Split `net` into the reverse zones needed to cover it.
+///
+/// Networks on a delegation boundary are returned as is, while a network
+/// such as `10.0.0.0/22` is split into the four /24 networks it consists of.
+pub fn reverse_zone_networks(net: &IpNet) -> Vec<IpNet> {
+    // Unwrap safety: The boundary is never shorter than the prefix, nor
+    // longer than the address.
+    net.trunc()
+        .subnets(delegation_boundary(net))
+        .unwrap()
+        .collect()
+}
+
+/// PTR record pointing the address of an A or AAAA record back at its name.
+///
+/// Returns `None` for other record types, for records whose rdata is not a
+/// valid address of its type, and for records whose domain name is not
+/// fully qualified, since the PTR record's rdata would be unknown. The
+/// PTR record has no zone reference, as it belongs to whichever reverse
+/// zone covers the address.
+pub fn ptr_record_for(record: &RecordSpec) -> Option<RecordSpec> {
+    let address: IpAddr = match record.type_ {
+        Type::A => IpAddr::V4(record.rdata.trim().parse().ok()?),
+        Type::AAAA => IpAddr::V6(record.rdata.trim().parse().ok()?),
+        _ => return None,
+    };
+
+    let fqdn = record.domain_name.as_full()?;
+
+    Some(RecordSpec {
+        domain_name: DomainName::Full(ptr_name(address)),
+        type_: Type::PTR,
+        class: record.class,
+        ttl: record.ttl,
+        rdata: fqdn.to_string(),
+        enabled: record.enabled,
+        ..Default::default()
+    })
+}
+
+fn ipv4_zone_name(net: &Ipv4Net, style: ClasslessStyle) -> Option<String> {
+    let octets = net.network().octets();
+    let prefix = usize::from(net.prefix_len());
+
+    if prefix % 8 == 0 {
+        return Some(ipv4_labels(&octets[..prefix / 8]));
+    }
+
+    if prefix < 24 {
+        return None;
+    }
+
+    let first = octets[3];
+    let classless = match style {
+        ClasslessStyle::Prefix => format!("{first}-{prefix}"),
+        ClasslessStyle::Range => {
+            let last = net.broadcast().octets()[3];
+            format!("{first}-{last}")
+        }
+    };
+
+    Some(format!("{classless}.{}", ipv4_labels(&octets[..3])))
+}
+
+fn ipv4_labels(octets: &[u8]) -> String {
+    let mut name = String::new();
+    for octet in octets.iter().rev() {
+        name.push_str(&format!("{octet}."));
+    }
+
+    name.push_str(IN_ADDR_ARPA);
+    name
+}
+
+fn ipv6_zone_name(net: &Ipv6Net) -> Option<String> {
+    let prefix = usize::from(net.prefix_len());
+    if prefix % 4 != 0 {
+        return None;
+    }
+
+    Some(ipv6_labels(&net.network(), prefix / 4))
+}
+
+/// The first `nibbles` nibbles of `address`, in reverse order.
+fn ipv6_labels(address: &Ipv6Addr, nibbles: usize) -> String {
+    let bits = u128::from(*address);
+
+    let mut name = String::new();
+    for i in (0..nibbles).rev() {
+        let nibble = (bits >> (124 - 4 * i)) & 0xf;
+        name.push_str(&format!("{nibble:x}."));
+    }
+
+    name.push_str(IP6_ARPA);
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use kubizone_common::Type;
+
+    use crate::v1alpha1::{ClasslessStyle, RecordSpec};
+
+    use std::net::IpAddr;
+
+    use kubizone_common::FullyQualifiedDomainName;
+
+    use super::{
+        address_from_ptr_name, delegation_boundary, net_from_reverse_name, parse_cidr, ptr_name,
+        ptr_record_for, reverse_zone_name, reverse_zone_networks, ReverseError,
+    };
+
+    fn zone(cidr: &str, style: ClasslessStyle) -> Result<String, ReverseError> {
+        reverse_zone_name(&parse_cidr(cidr)?, style).map(|name| name.to_string())
+    }
+
+    #[test]
+    fn test_reverse_zone_name() {
+        let style = ClasslessStyle::Prefix;
+
+        assert_eq!(
+            zone("192.0.2.0/24", style).unwrap(),
+            "2.0.192.in-addr.arpa."
+        );
+        assert_eq!(zone("10.0.0.0/8", style).unwrap(), "10.in-addr.arpa.");
+        assert_eq!(zone("0.0.0.0/0", style).unwrap(), "in-addr.arpa.");
+        assert_eq!(
+            zone("192.0.2.1/32", style).unwrap(),
+            "1.2.0.192.in-addr.arpa."
+        );
+
+        // RFC 2317 classless delegation.
+        assert_eq!(
+            zone("192.0.2.64/26", style).unwrap(),
+            "64-26.2.0.192.in-addr.arpa."
+        );
+        assert_eq!(
+            zone("192.0.2.64/26", ClasslessStyle::Range).unwrap(),
+            "64-127.2.0.192.in-addr.arpa."
+        );
+
+        assert_eq!(
+            zone("2001:db8::/32", style).unwrap(),
+            "8.b.d.0.1.0.0.2.ip6.arpa."
+        );
+        assert_eq!(
+            zone("2001:db8:abcd:12::/64", style).unwrap(),
+            "2.1.0.0.d.c.b.a.8.b.d.0.1.0.0.2.ip6.arpa."
+        );
+
+        assert!(matches!(
+            zone("10.0.0.0/22", style),
+            Err(ReverseError::UnalignedPrefix(_))
+        ));
+        assert!(matches!(
+            zone("2001:db8::/30", style),
+            Err(ReverseError::UnalignedPrefix(_))
+        ));
+        assert!(matches!(
+            zone("192.0.2.1/24", style),
+            Err(ReverseError::HostBitsSet(..))
+        ));
+        assert!(matches!(
+            zone("192.0.2.0", style),
+            Err(ReverseError::InvalidCidr(_))
+        ));
+    }
+
+    #[test]
+    fn test_ptr_record_for() {
+        let record: RecordSpec = "www.example.org. 300 IN A 192.0.2.1".parse().unwrap();
+        let ptr = ptr_record_for(&record).unwrap();
+        assert_eq!(
+            ptr.to_string(),
+            "1.2.0.192.in-addr.arpa. 300 IN PTR www.example.org."
+        );
+
+        let record: RecordSpec = "www.example.org. IN AAAA 2001:db8::1".parse().unwrap();
+        let ptr = ptr_record_for(&record).unwrap();
+        assert_eq!(ptr.type_, Type::PTR);
+        assert_eq!(
+            ptr.domain_name.to_string(),
+            "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."
+        );
+
+        // Mismatched address families, other types and relative names have no PTR.
+        let mut record: RecordSpec = "www.example.org. IN A 192.0.2.1".parse().unwrap();
+        record.rdata = String::from("2001:db8::1");
+        assert!(ptr_record_for(&record).is_none());
+        let record: RecordSpec = "www.example.org. IN CNAME example.org.".parse().unwrap();
+        assert!(ptr_record_for(&record).is_none());
+        let record: RecordSpec = "www IN A 192.0.2.1".parse().unwrap();
+        assert!(ptr_record_for(&record).is_none());
+    }
+
+    #[test]
+    fn test_ptr_name_roundtrip() {
+        for address in [
+            "0.0.0.0",
+            "192.0.2.1",
+            "255.255.255.255",
+            "::",
+            "::1",
+            "2001:db8::1",
+            "2001:db8:abcd:12:ffff:0:1:abcd",
+        ] {
+            let address: IpAddr = address.parse().unwrap();
+            let name = ptr_name(address);
+            assert_eq!(address_from_ptr_name(&name), Some(address), "{name}");
+        }
+
+        assert_eq!(
+            ptr_name("2001:db8::abcd".parse().unwrap()).to_string(),
+            "d.c.b.a.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa."
+        );
+
+        let name = |name: &str| FullyQualifiedDomainName::try_from(name).unwrap();
+
+        // Zone names, foreign names and malformed labels are not addresses.
+        assert_eq!(address_from_ptr_name(&name("2.0.192.in-addr.arpa.")), None);
+        assert_eq!(address_from_ptr_name(&name("1.2.0.192.example.org.")), None);
+        assert_eq!(
+            address_from_ptr_name(&name("1.2.0.256.in-addr.arpa.")),
+            None
+        );
+        assert_eq!(
+            address_from_ptr_name(&name("1.2.00.192.in-addr.arpa.")),
+            None
+        );
+        assert_eq!(
+            address_from_ptr_name(&name("5.1.2.0.192.in-addr.arpa.")),
+            None
+        );
+        assert_eq!(
+            address_from_ptr_name(&name("10.8.b.d.0.1.0.0.2.ip6.arpa.")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_reverse_name_roundtrip() {
+        for cidr in [
+            "0.0.0.0/0",
+            "10.0.0.0/8",
+            "172.16.0.0/16",
+            "192.0.2.0/24",
+            "192.0.2.128/25",
+            "192.0.2.64/26",
+            "192.0.2.252/30",
+            "192.0.2.6/31",
+            "::/0",
+            "2001:db8::/32",
+            "2001:db8:8000::/36",
+            "2001:db8:abcd:12::/64",
+        ] {
+            let net = parse_cidr(cidr).unwrap();
+            for style in [ClasslessStyle::Prefix, ClasslessStyle::Range] {
+                let name = reverse_zone_name(&net, style).unwrap();
+                assert_eq!(net_from_reverse_name(&name, style), Some(net), "{name}");
+            }
+        }
+
+        let name = |name: &str| FullyQualifiedDomainName::try_from(name).unwrap();
+
+        // Classless labels must describe an aligned network.
+        assert_eq!(
+            net_from_reverse_name(&name("65-26.2.0.192.in-addr.arpa."), ClasslessStyle::Prefix),
+            None
+        );
+        assert_eq!(
+            net_from_reverse_name(&name("64-100.2.0.192.in-addr.arpa."), ClasslessStyle::Range),
+            None
+        );
+        assert_eq!(
+            net_from_reverse_name(&name("0-24.2.0.192.in-addr.arpa."), ClasslessStyle::Prefix),
+            None
+        );
+        assert_eq!(
+            net_from_reverse_name(&name("0-24.0.192.in-addr.arpa."), ClasslessStyle::Prefix),
+            None
+        );
+    }
+
+    #[test]
+    fn test_delegation_boundary() {
+        let boundary = |cidr: &str| delegation_boundary(&parse_cidr(cidr).unwrap());
+
+        assert_eq!(boundary("0.0.0.0/0"), 0);
+        assert_eq!(boundary("10.0.0.0/8"), 8);
+        assert_eq!(boundary("10.0.0.0/9"), 16);
+        assert_eq!(boundary("10.0.0.0/22"), 24);
+        assert_eq!(boundary("192.0.2.0/24"), 24);
+        assert_eq!(boundary("192.0.2.0/27"), 27);
+        assert_eq!(boundary("2001:db8::/30"), 32);
+        assert_eq!(boundary("2001:db8::/48"), 48);
+        assert_eq!(boundary("2001:db8::/127"), 128);
+
+        let networks = |cidr: &str| -> Vec<String> {
+            reverse_zone_networks(&parse_cidr(cidr).unwrap())
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        };
+
+        assert_eq!(
+            networks("10.0.0.0/22"),
+            vec!["10.0.0.0/24", "10.0.1.0/24", "10.0.2.0/24", "10.0.3.0/24"]
+        );
+        assert_eq!(networks("192.0.2.64/26"), vec!["192.0.2.64/26"]);
+        assert_eq!(
+            networks("2001:db8::/31"),
+            vec!["2001:db8::/32", "2001:db9::/32"]
+        );
+
+        // Every split network has a reverse zone name.
+        for net in reverse_zone_networks(&parse_cidr("172.16.0.0/15").unwrap()) {
+            assert!(reverse_zone_name(&net, ClasslessStyle::Prefix).is_ok());
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! Ready-made watch configurations for kubizone controllers.
+//!
+//! Zones depend on their records and sub-zones, and records and sub-zones
+//! depend on the zone they are placed in. The mappers in this module
+//! translate changes to one into reconciliations of the other, and
+//! [`zone_controller`] and [`record_controller`] wire them all up.
+//!
+//! ```rust,no_run
+//! # use kube::{runtime::watcher, Client};
+//! # use kubizone_crds::runtime;
+//! # async fn run(client: Client) {
+//! let controller = runtime::zone_controller(client, watcher::Config::default());
+//! # }
+//! ```
+
+use futures::{stream, StreamExt};
+use kube::{
+    runtime::{
+        reflector::{self, ObjectRef, Store},
+        watcher, Controller, WatchStreamExt,
+    },
+    Api, Client, ResourceExt,
+};
+
+use crate::{
+    v1alpha1::{DomainExt, Record, Zone, ZoneRef},
+    watch_reference, PARENT_ZONE_LABEL,
+};
+
+/// Check if a resource in `namespace` with the given `zone_ref` and adopted
+/// `parent` belongs beneath `zone`.
+fn is_beneath(
+    zone: &ZoneRef,
+    namespace: &str,
+    zone_ref: Option<&ZoneRef>,
+    parent: Option<ZoneRef>,
+) -> bool {
+    let referenced = zone_ref.is_some_and(|zone_ref| {
+        zone_ref.name == zone.name
+            && zone.namespace.as_deref() == Some(zone_ref.namespace_or(namespace))
+    });
+
+    referenced || parent.as_ref() == Some(zone)
+}
+
+/// Map records and sub-zones to the zone they have been adopted into,
+/// for reconciling zones when their contents change.
+pub fn parent_zone<K: ResourceExt>() -> impl Fn(K) -> Option<ObjectRef<Zone>> {
+    watch_reference::<Zone, K>(PARENT_ZONE_LABEL)
+}
+
+/// Map zones to the records beneath them in `store`, for reconciling
+/// records when their zone changes.
+///
+/// Records are beneath a zone if they reference it, or have been adopted
+/// into it.
+pub fn records(store: Store<Record>) -> impl Fn(Zone) -> Vec<ObjectRef<Record>> {
+    move |zone| {
+        let zone_ref = zone.zone_ref();
+
+        store
+            .state()
+            .into_iter()
+            .filter(|record| {
+                is_beneath(
+                    &zone_ref,
+                    &record.namespace().unwrap_or_default(),
+                    record.spec.zone_ref.as_ref(),
+                    record.parent(),
+                )
+            })
+            .map(|record| ObjectRef::from_obj(record.as_ref()))
+            .collect()
+    }
+}
+
+/// Map zones to their direct sub-zones in `store`, for reconciling
+/// sub-zones when their parent changes.
+pub fn child_zones(store: Store<Zone>) -> impl Fn(Zone) -> Vec<ObjectRef<Zone>> {
+    move |zone| {
+        let zone_ref = zone.zone_ref();
+
+        store
+            .state()
+            .into_iter()
+            .filter(|child| {
+                is_beneath(
+                    &zone_ref,
+                    &child.namespace().unwrap_or_default(),
+                    child.spec.zone_ref.as_ref(),
+                    child.parent(),
+                )
+            })
+            .map(|child| ObjectRef::from_obj(child.as_ref()))
+            .collect()
+    }
+}
+
+/// Controller for zones, which also reconciles zones when their records
+/// or sub-zones change, and sub-zones when their parent changes.
+///
+/// Zones are watched only once. Changes to a zone are fanned out to its
+/// sub-zones and parent from the same reflector which drives the controller.
+pub fn zone_controller(client: Client, config: watcher::Config) -> Controller<Zone> {
+    let (store, writer) = reflector::store();
+
+    let parent_of_zone = parent_zone();
+    let child_zones = child_zones(store.clone());
+    let reader = store.clone();
+    let related = move |zone: &Zone| -> Vec<Zone> {
+        child_zones(zone.clone())
+            .into_iter()
+            .chain(parent_of_zone(zone.clone()))
+            .filter_map(|zone_ref| reader.get(&zone_ref))
+            .map(|zone| zone.as_ref().clone())
+            .collect()
+    };
+
+    let zones = watcher(Api::<Zone>::all(client.clone()), config.clone())
+        .reflect(writer)
+        .applied_objects()
+        .flat_map(move |zone| {
+            let related = zone.as_ref().map(&related).unwrap_or_default();
+
+            stream::iter(std::iter::once(zone).chain(related.into_iter().map(Ok)))
+        });
+
+    Controller::for_stream(zones, store).watches(
+        Api::<Record>::all(client),
+        config,
+        parent_zone::<Record>(),
+    )
+}
+
+/// Controller for records, which also reconciles records when their zone changes.
+pub fn record_controller(client: Client, config: watcher::Config) -> Controller<Record> {
+    let controller = Controller::new(Api::<Record>::all(client.clone()), config.clone());
+    let store = controller.store();
+
+    controller.watches(Api::<Zone>::all(client), config, records(store))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::v1alpha1::ZoneRef;
+
+    use super::is_beneath;
+
+    #[test]
+    fn test_is_beneath() {
+        let zone = "example-org.dns".parse::<ZoneRef>().unwrap();
+
+        assert!(is_beneath(
+            &zone,
+            "dns",
+            Some(&"example-org".parse().unwrap()),
+            None
+        ));
+        assert!(is_beneath(&zone, "web", Some(&zone), None));
+        assert!(!is_beneath(
+            &zone,
+            "web",
+            Some(&"example-org".parse().unwrap()),
+            None
+        ));
+        assert!(is_beneath(&zone, "web", None, Some(zone.clone())));
+        assert!(!is_beneath(&zone, "web", None, None));
+    }
+}
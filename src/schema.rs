@@ -0,0 +1,157 @@
+//! JSON schemas for domain names, delegation patterns and networks.
+//!
+//! The [`JsonSchema`](schemars::JsonSchema) implementations of
+//! [`kubizone_common`] describe domain names as plain strings. The schemas
+//! here constrain them further, so that typos are rejected by the API server
+//! at `kubectl apply` time, instead of surfacing at reconcile time. They are
+//! deliberately more lenient than the parsers, which remain authoritative.
+//!
+//! Used through `#[schemars(schema_with = "...")]` on the fields of the
+//! custom resources.
+
+use schemars::{
+    gen::SchemaGenerator,
+    schema::{ArrayValidation, InstanceType, Schema, SchemaObject, StringValidation},
+};
+
+use crate::v1alpha1::MAX_LABEL_LENGTH;
+
+/// Longest domain name in text form, including the trailing dot.
+///
+/// Domain names are limited to 255 octets on the wire, where each label
+/// is prefixed by its length, and the name is terminated by the empty root
+/// label, which leaves 254 characters for the text form.
+pub const MAX_DOMAIN_NAME_LENGTH: usize = 254;
+
+/// Longest delegation pattern, glob or regular expression.
+pub const MAX_PATTERN_LENGTH: usize = 255;
+
+/// Longest network in CIDR notation, an IPv6 address with a prefix length.
+const MAX_NETWORK_LENGTH: usize = 43;
+
+/// A single label, which is either a standalone wildcard, or up to
+/// [`MAX_LABEL_LENGTH`] characters not starting or ending with a hyphen.
+fn label() -> String {
+    format!(
+        r"(\*|[A-Za-z0-9_]([A-Za-z0-9_-]{{0,{}}}[A-Za-z0-9_])?)",
+        MAX_LABEL_LENGTH - 2
+    )
+}
+
+/// Regular expression matching fully or partially qualified domain names.
+pub fn domain_name_pattern() -> String {
+    let label = label();
+    format!(r"^({label}\.)*{label}\.?$")
+}
+
+/// Regular expression matching only fully qualified domain names.
+pub fn fully_qualified_domain_name_pattern() -> String {
+    format!(r"^({}\.)+$", label())
+}
+
+fn string(pattern: String, max_length: usize) -> SchemaObject {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            max_length: Some(max_length as u32),
+            min_length: Some(1),
+            pattern: Some(pattern),
+        })),
+        ..Default::default()
+    }
+}
+
+fn array(items: Schema) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::Array.into()),
+        array: Some(Box::new(ArrayValidation {
+            items: Some(items.into()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+fn nullable(mut schema: SchemaObject) -> Schema {
+    schema
+        .extensions
+        .insert(String::from("nullable"), serde_json::Value::Bool(true));
+    schema.into()
+}
+
+/// Schema of a fully or partially qualified domain name.
+pub(crate) fn domain_name(_: &mut SchemaGenerator) -> Schema {
+    string(domain_name_pattern(), MAX_DOMAIN_NAME_LENGTH).into()
+}
+
+/// Schema of a fully qualified domain name, such as `ZoneEntry.fqdn`.
+pub(crate) fn fully_qualified_domain_name(_: &mut SchemaGenerator) -> Schema {
+    string(
+        fully_qualified_domain_name_pattern(),
+        MAX_DOMAIN_NAME_LENGTH,
+    )
+    .into()
+}
+
+/// Schema of a list of fully qualified domain names, such as `nameservers`.
+pub(crate) fn fully_qualified_domain_names(gen: &mut SchemaGenerator) -> Schema {
+    array(fully_qualified_domain_name(gen))
+}
+
+/// Schema of an optional fully qualified domain name, such as `status.fqdn`.
+pub(crate) fn optional_fully_qualified_domain_name(_: &mut SchemaGenerator) -> Schema {
+    nullable(string(
+        fully_qualified_domain_name_pattern(),
+        MAX_DOMAIN_NAME_LENGTH,
+    ))
+}
+
+/// Schema of a delegation pattern.
+///
+/// Patterns may be regular expressions, depending on the `patternType` next
+/// to them, so only their length and lack of whitespace is checked here.
+pub(crate) fn pattern(_: &mut SchemaGenerator) -> Schema {
+    string(String::from(r"^\S+$"), MAX_PATTERN_LENGTH).into()
+}
+
+/// Schema of a list of delegation patterns, see [`pattern`].
+pub(crate) fn patterns(gen: &mut SchemaGenerator) -> Schema {
+    array(pattern(gen))
+}
+
+/// Schema of a list of networks in CIDR notation, such as `matchClients`.
+pub(crate) fn networks(_: &mut SchemaGenerator) -> Schema {
+    array(
+        string(
+            String::from(r"^[0-9a-fA-F:.]+/[0-9]{1,3}$"),
+            MAX_NETWORK_LENGTH,
+        )
+        .into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use regex::Regex;
+
+    use super::{domain_name_pattern, fully_qualified_domain_name_pattern};
+
+    #[test]
+    fn test_domain_name_patterns() {
+        let domain_name = Regex::new(&domain_name_pattern()).unwrap();
+        let fqdn = Regex::new(&fully_qualified_domain_name_pattern()).unwrap();
+
+        for valid in ["www", "*.example.org.", "_dmarc.Example.org.", "a-b.c"] {
+            assert!(domain_name.is_match(valid), "{valid}");
+        }
+
+        for invalid in ["", ".", "-www", "www-.", "a..b", "www example", "ww*w"] {
+            assert!(!domain_name.is_match(invalid), "{invalid}");
+        }
+
+        assert!(!domain_name.is_match(&format!("{}.", "a".repeat(64))));
+        assert!(fqdn.is_match("example.org."));
+        assert!(!fqdn.is_match("example.org"));
+    }
+}
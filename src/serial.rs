@@ -0,0 +1,194 @@
+//! Zone serial number computation.
+//!
+//! Secondary name servers only transfer a zone when its serial increases,
+//! so every change to a zone must produce a serial which is greater than
+//! the previous one. How the next serial is picked is controlled by the
+//! zone's [`SerialStrategy`].
+//!
+//! Since serials are 32-bit and eventually wrap around, "greater" is
+//! defined by the serial number arithmetic of
+//! [RFC 1982](https://datatracker.ietf.org/doc/html/rfc1982) rather than
+//! plain integer comparison. Use [`serial_cmp`] and friends instead of
+//! comparing serials directly.
+
+use std::cmp::Ordering;
+
+use chrono::{DateTime, Datelike, Utc};
+
+use crate::v1alpha1::SerialStrategy;
+
+/// Largest value which may be added to a serial in a single step.
+pub const SERIAL_MAX_INCREMENT: u32 = (1 << 31) - 1;
+
+/// Add `n` to `serial`, wrapping around as defined by RFC 1982.
+///
+/// Returns `None` if `n` is larger than [`SERIAL_MAX_INCREMENT`], since
+/// the result would no longer be greater than `serial`.
+pub fn serial_add(serial: u32, n: u32) -> Option<u32> {
+    (n <= SERIAL_MAX_INCREMENT).then(|| serial.wrapping_add(n))
+}
+
+/// Compare two serials according to RFC 1982.
+///
+/// Returns `None` for pairs exactly 2^31 apart, for which the
+/// comparison is undefined.
+pub fn serial_cmp(a: u32, b: u32) -> Option<Ordering> {
+    match a.wrapping_sub(b) {
+        0 => Some(Ordering::Equal),
+        distance if distance < 1 << 31 => Some(Ordering::Greater),
+        distance if distance > 1 << 31 => Some(Ordering::Less),
+        _ => None,
+    }
+}
+
+/// Check if serial `a` is greater than `b`, according to RFC 1982.
+pub fn serial_gt(a: u32, b: u32) -> bool {
+    serial_cmp(a, b) == Some(Ordering::Greater)
+}
+
+/// Check if serial `a` is less than `b`, according to RFC 1982.
+pub fn serial_lt(a: u32, b: u32) -> bool {
+    serial_cmp(a, b) == Some(Ordering::Less)
+}
+
+/// Compute the serial following `current`, according to `strategy`.
+///
+/// Zones without a current serial start at `1` for
+/// [`SerialStrategy::Incrementing`], and at revision `00` of the current
+/// day for [`SerialStrategy::DateBased`].
+///
+/// Date based serials follow the `YYYYMMDDnn` convention of
+/// [RFC 1912 section 2.2](https://datatracker.ietf.org/doc/html/rfc1912#section-2.2).
+/// Changes on the same day increment the revision `nn`. Once the revision
+/// is exhausted, or if the current serial is already ahead of today's date,
+/// the serial is simply incremented, borrowing from the following day, since
+/// the serial must never decrease. Whether the current serial is ahead is
+/// decided by [`serial_gt`], so serials which wrapped around are handled too.
+pub fn next_serial(current: Option<u32>, strategy: SerialStrategy, now: DateTime<Utc>) -> u32 {
+    match strategy {
+        SerialStrategy::Incrementing => current.map_or(1, |serial| serial.wrapping_add(1)),
+        SerialStrategy::DateBased => {
+            let today = date_serial(now);
+
+            match current {
+                Some(serial) if !serial_gt(today, serial) => serial.wrapping_add(1),
+                _ => today,
+            }
+        }
+    }
+}
+
+/// First `YYYYMMDDnn` serial of the day.
+fn date_serial(now: DateTime<Utc>) -> u32 {
+    // Years beyond 4294 do not fit, at which point the date is clamped.
+    let year = u32::try_from(now.year()).unwrap_or_default().min(4294);
+
+    year.saturating_mul(1_000_000)
+        .saturating_add(now.month() * 10_000)
+        .saturating_add(now.day() * 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use chrono::{DateTime, Utc};
+
+    use crate::v1alpha1::SerialStrategy;
+
+    use super::{next_serial, serial_add, serial_cmp, serial_gt, serial_lt, SERIAL_MAX_INCREMENT};
+
+    fn time(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    #[test]
+    fn test_incrementing() {
+        let now = time("2024-03-05T12:00:00Z");
+
+        assert_eq!(next_serial(None, SerialStrategy::Incrementing, now), 1);
+        assert_eq!(next_serial(Some(41), SerialStrategy::Incrementing, now), 42);
+        assert_eq!(
+            next_serial(Some(u32::MAX), SerialStrategy::Incrementing, now),
+            0
+        );
+    }
+
+    #[test]
+    fn test_date_based() {
+        let now = time("2024-03-05T12:00:00Z");
+
+        // Fresh zones and serials from a previous day start at today's first revision.
+        assert_eq!(
+            next_serial(None, SerialStrategy::DateBased, now),
+            2024030500
+        );
+        assert_eq!(
+            next_serial(Some(2024030417), SerialStrategy::DateBased, now),
+            2024030500
+        );
+        assert_eq!(
+            next_serial(Some(12), SerialStrategy::DateBased, now),
+            2024030500
+        );
+
+        // Same-day changes bump the revision.
+        assert_eq!(
+            next_serial(Some(2024030500), SerialStrategy::DateBased, now),
+            2024030501
+        );
+
+        // Exhausted revisions borrow from the next day.
+        assert_eq!(
+            next_serial(Some(2024030599), SerialStrategy::DateBased, now),
+            2024030600
+        );
+        assert_eq!(
+            next_serial(Some(2024030600), SerialStrategy::DateBased, now),
+            2024030601
+        );
+
+        // Serials which are numerically larger, but behind today's date
+        // according to RFC 1982, are replaced by today's first revision.
+        assert_eq!(
+            next_serial(Some(u32::MAX - 5), SerialStrategy::DateBased, now),
+            2024030500
+        );
+
+        // Serials less than half the serial space ahead are incremented.
+        assert_eq!(
+            next_serial(Some(4000000000), SerialStrategy::DateBased, now),
+            4000000001
+        );
+    }
+
+    #[test]
+    fn test_serial_arithmetic() {
+        assert_eq!(serial_add(1, 1), Some(2));
+        assert_eq!(serial_add(u32::MAX, 1), Some(0));
+        assert_eq!(
+            serial_add(0, SERIAL_MAX_INCREMENT),
+            Some(SERIAL_MAX_INCREMENT)
+        );
+        assert_eq!(serial_add(0, SERIAL_MAX_INCREMENT + 1), None);
+
+        assert_eq!(serial_cmp(5, 5), Some(Ordering::Equal));
+        assert!(serial_gt(2, 1));
+        assert!(serial_lt(1, 2));
+
+        // Wrapped serials are greater than the ones they wrapped from.
+        assert!(serial_gt(0, u32::MAX));
+        assert!(serial_gt(10, u32::MAX - 10));
+        assert!(serial_lt(u32::MAX - 10, 10));
+
+        // Every sum within the allowed increment is greater.
+        for n in [1, 1000, SERIAL_MAX_INCREMENT] {
+            let serial = u32::MAX - 3;
+            assert!(serial_gt(serial_add(serial, n).unwrap(), serial));
+        }
+
+        // Serials exactly half the space apart are incomparable.
+        assert_eq!(serial_cmp(0, 1 << 31), None);
+        assert!(!serial_gt(0, 1 << 31) && !serial_lt(0, 1 << 31));
+    }
+}
@@ -0,0 +1,99 @@
+//! Substitution of `${variable}` placeholders.
+//!
+//! Used by [`RecordTemplate`](crate::v1alpha1::RecordTemplate)s to stamp out
+//! records for many namespaces from a single template, and by zones to share
+//! their [`variables`](crate::v1alpha1::ZoneSpec::variables), if they define
+//! any, with the data of their records.
+//!
+//! Placeholders consist of the variable name enclosed in `${` and `}`, and
+//! `$$` produces a literal `$`. Any other `$` is kept as is.
+//!
+//! ```rust
+//! # use std::collections::BTreeMap;
+//! # use kubizone_crds::template::substitute;
+//! let variables = BTreeMap::from([(String::from("namespace"), String::from("web"))]);
+//!
+//! assert_eq!(
+//!     substitute("${namespace}.example.org.", &variables).unwrap(),
+//!     "web.example.org."
+//! );
+//! assert!(substitute("${cluster}.example.org.", &variables).is_err());
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::errors::SubstitutionError;
+
+/// Replace every `${variable}` placeholder in `input` with its value from `variables`.
+///
+/// Fails if `input` references a variable which is not defined, or contains
+/// a placeholder without a closing `}`.
+pub fn substitute(
+    input: &str,
+    variables: &BTreeMap<String, String>,
+) -> Result<String, SubstitutionError> {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(position) = rest.find('$') {
+        output.push_str(&rest[..position]);
+        rest = &rest[position..];
+
+        if let Some(escaped) = rest.strip_prefix("$$") {
+            output.push('$');
+            rest = escaped;
+        } else if let Some(placeholder) = rest.strip_prefix("${") {
+            let Some(end) = placeholder.find('}') else {
+                return Err(SubstitutionError::Unterminated(input.to_string()));
+            };
+
+            let name = &placeholder[..end];
+            let Some(value) = variables.get(name) else {
+                return Err(SubstitutionError::Undefined(name.to_string()));
+            };
+
+            output.push_str(value);
+            rest = &placeholder[end + 1..];
+        } else {
+            output.push('$');
+            rest = &rest[1..];
+        }
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::errors::SubstitutionError;
+
+    use super::substitute;
+
+    #[test]
+    fn test_substitute() {
+        let variables = BTreeMap::from([
+            (String::from("name"), String::from("api")),
+            (String::from("namespace"), String::from("web")),
+        ]);
+
+        assert_eq!(
+            substitute("${name}-${namespace}.example.org.", &variables),
+            Ok(String::from("api-web.example.org."))
+        );
+        assert_eq!(
+            substitute("v=spf1 $${name} $5 ${name}$", &variables),
+            Ok(String::from("v=spf1 ${name} $5 api$"))
+        );
+        assert_eq!(
+            substitute("${cluster}", &variables),
+            Err(SubstitutionError::Undefined(String::from("cluster")))
+        );
+        assert_eq!(
+            substitute("www.${name", &variables),
+            Err(SubstitutionError::Unterminated(String::from("www.${name")))
+        );
+    }
+}
@@ -0,0 +1,234 @@
+//! Ready-made resources and assertions for testing controllers built on
+//! kubizone resources.
+//!
+//! The fixtures form a small hierarchy around the `example.org.` zone in
+//! the [`ZONE_NAMESPACE`] namespace, which delegates everything beneath it
+//! to the [`DELEGATED_NAMESPACE`] namespace. All fixtures have distinct
+//! UIDs, as if created through the API server, and their fully qualified
+//! domain names resolved in their status and their parent zone labels set,
+//! as the controller would have done.
+//!
+//! Delegations are evaluated with the labels of the child's namespace,
+//! which are empty unless given as a third argument to the assertions.
+//!
+//! ```rust
+//! use kubizone_crds::{assert_delegated, assert_not_delegated, test_util};
+//!
+//! let zone = test_util::delegated_zone();
+//!
+//! assert_delegated!(zone, test_util::delegated_record());
+//! assert_delegated!(zone, test_util::nested_sub_zone());
+//! assert_not_delegated!(zone, test_util::orphan_record());
+//! ```
+
+use std::collections::BTreeMap;
+
+use kubizone_common::{FullyQualifiedDomainName, Pattern, Type};
+
+use crate::v1alpha1::{
+    Delegation, DelegationDenied, Record, RecordDelegation, RecordStatus, Zone, ZonePatterns,
+    ZoneStatus,
+};
+
+/// Namespace of [`delegated_zone`].
+pub const ZONE_NAMESPACE: &str = "dns";
+
+/// Namespace which [`delegated_zone`] delegates to.
+pub const DELEGATED_NAMESPACE: &str = "default";
+
+fn fqdn(name: &str) -> FullyQualifiedDomainName {
+    // Unwrap safety: Only used with the constant names below.
+    FullyQualifiedDomainName::try_from(name).unwrap()
+}
+
+/// The `example.org.` zone, which delegates any record and sub-zone to
+/// the [`DELEGATED_NAMESPACE`].
+pub fn delegated_zone() -> Zone {
+    let wildcard = || Pattern::try_from("*").unwrap();
+
+    let mut zone = Zone::builder("example.org.")
+        .namespace(ZONE_NAMESPACE)
+        .delegate(Delegation {
+            namespaces: vec![String::from(DELEGATED_NAMESPACE)],
+            namespace_selector: None,
+            zones: ZonePatterns::Glob {
+                allow: vec![wildcard()],
+                deny: vec![],
+            },
+            records: vec![RecordDelegation {
+                pattern: wildcard().into(),
+                types: vec![],
+                min_ttl: None,
+                max_ttl: None,
+            }],
+            deny_records: vec![],
+            require_approval: false,
+        })
+        .build()
+        .unwrap();
+
+    zone.metadata.uid = Some(String::from("00000000-0000-0000-0000-000000000001"));
+    zone.status = Some(ZoneStatus {
+        fqdn: Some(fqdn("example.org.")),
+        ..Default::default()
+    });
+
+    zone
+}
+
+/// `sub.example.org.` zone in the [`DELEGATED_NAMESPACE`], referencing
+/// [`delegated_zone`] as its parent.
+pub fn nested_sub_zone() -> Zone {
+    let mut zone = Zone::builder("sub")
+        .namespace(DELEGATED_NAMESPACE)
+        .zone_ref(delegated_zone().zone_ref())
+        .parent(delegated_zone().zone_ref())
+        .build()
+        .unwrap();
+
+    zone.metadata.uid = Some(String::from("00000000-0000-0000-0000-000000000002"));
+    zone.status = Some(ZoneStatus {
+        fqdn: Some(fqdn("sub.example.org.")),
+        ..Default::default()
+    });
+
+    zone
+}
+
+/// `www.example.org.` A record in the [`DELEGATED_NAMESPACE`].
+pub fn delegated_record() -> Record {
+    let mut record = Record::builder("www.example.org.", Type::A, "192.0.2.1")
+        .namespace(DELEGATED_NAMESPACE)
+        .parent(delegated_zone().zone_ref())
+        .build()
+        .unwrap();
+
+    record.metadata.uid = Some(String::from("00000000-0000-0000-0000-000000000003"));
+    record.status = Some(RecordStatus {
+        fqdn: Some(fqdn("www.example.org.")),
+        ..Default::default()
+    });
+
+    record
+}
+
+/// `www.example.com.` A record in the [`DELEGATED_NAMESPACE`], which no
+/// fixture zone is authoritative for.
+pub fn orphan_record() -> Record {
+    let mut record = Record::builder("www.example.com.", Type::A, "192.0.2.2")
+        .namespace(DELEGATED_NAMESPACE)
+        .build()
+        .unwrap();
+
+    record.metadata.uid = Some(String::from("00000000-0000-0000-0000-000000000004"));
+    record.status = Some(RecordStatus {
+        fqdn: Some(fqdn("www.example.com.")),
+        ..Default::default()
+    });
+
+    record
+}
+
+/// Resources which can be delegated to a zone, for use by [`assert_delegated`](crate::assert_delegated).
+pub trait Delegated {
+    /// Check if `zone` accepts this resource, evaluating `namespaceSelector`s
+    /// against `namespace_labels`.
+    fn delegated_by(
+        &self,
+        zone: &Zone,
+        namespace_labels: &BTreeMap<String, String>,
+    ) -> Result<(), DelegationDenied>;
+}
+
+impl Delegated for Record {
+    fn delegated_by(
+        &self,
+        zone: &Zone,
+        namespace_labels: &BTreeMap<String, String>,
+    ) -> Result<(), DelegationDenied> {
+        zone.validate_record_with_labels(self, namespace_labels)
+    }
+}
+
+impl Delegated for Zone {
+    fn delegated_by(
+        &self,
+        zone: &Zone,
+        namespace_labels: &BTreeMap<String, String>,
+    ) -> Result<(), DelegationDenied> {
+        zone.validate_zone_with_labels(self, namespace_labels)
+    }
+}
+
+/// Assert that a zone accepts a record or sub-zone, panicking with the
+/// reason it was denied otherwise.
+///
+/// Takes the labels of the child's namespace as an optional third argument.
+#[macro_export]
+macro_rules! assert_delegated {
+    ($zone:expr, $child:expr $(,)?) => {
+        $crate::assert_delegated!($zone, $child, ::std::collections::BTreeMap::new())
+    };
+    ($zone:expr, $child:expr, $labels:expr $(,)?) => {{
+        let (zone, child, labels) = (&$zone, &$child, &$labels);
+        if let Err(reason) = $crate::test_util::Delegated::delegated_by(child, zone, labels) {
+            panic!("expected {zone} to accept {child}, but it was denied: {reason}");
+        }
+    }};
+}
+
+/// Assert that a zone refuses a record or sub-zone.
+///
+/// Takes the labels of the child's namespace as an optional third argument.
+#[macro_export]
+macro_rules! assert_not_delegated {
+    ($zone:expr, $child:expr $(,)?) => {
+        $crate::assert_not_delegated!($zone, $child, ::std::collections::BTreeMap::new())
+    };
+    ($zone:expr, $child:expr, $labels:expr $(,)?) => {{
+        let (zone, child, labels) = (&$zone, &$child, &$labels);
+        if $crate::test_util::Delegated::delegated_by(child, zone, labels).is_ok() {
+            panic!("expected {zone} to refuse {child}, but it was accepted");
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::v1alpha1::DomainExt;
+
+    use super::{delegated_record, delegated_zone, nested_sub_zone, orphan_record};
+
+    #[test]
+    fn test_fixtures() {
+        let zone = delegated_zone();
+
+        assert_delegated!(zone, delegated_record());
+        assert_delegated!(zone, nested_sub_zone());
+        assert_not_delegated!(zone, orphan_record());
+        assert_not_delegated!(nested_sub_zone(), delegated_record());
+
+        assert_eq!(delegated_record().parent(), Some(zone.zone_ref()));
+        assert_eq!(nested_sub_zone().parent(), Some(zone.zone_ref()));
+    }
+
+    #[test]
+    fn test_namespace_selector() {
+        let mut zone = delegated_zone();
+        zone.spec.delegations[0].namespaces.clear();
+        zone.spec.delegations[0].namespace_selector =
+            Some(serde_json::from_str(r#"{"matchLabels": {"team": "web"}}"#).unwrap());
+
+        let labels = BTreeMap::from([(String::from("team"), String::from("web"))]);
+        assert_delegated!(zone, delegated_record(), labels);
+        assert_not_delegated!(zone, delegated_record());
+    }
+
+    #[test]
+    #[should_panic(expected = "to accept default/www-example-com-a")]
+    fn test_assert_delegated_panics() {
+        assert_delegated!(delegated_zone(), orphan_record());
+    }
+}
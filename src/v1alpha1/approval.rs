@@ -0,0 +1,163 @@
+use kube::ResourceExt;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{Condition, ConditionReason, Record, RecordConditionType, RecordStatus, ZoneStatus};
+
+/// Sign-off on a record by the owner of a zone, as required by delegations
+/// with `requireApproval` set. See [`ZoneStatus::approve`].
+///
+/// Approvals are kept in the status of the zone rather than on the record
+/// itself, so that they can only be granted by those allowed to update the
+/// zone's status, and never by the author of the record.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordApproval {
+    /// Namespace of the approved record.
+    pub namespace: String,
+
+    /// Name of the approved record.
+    pub name: String,
+
+    /// `.metadata.uid` of the record, so that approvals don't carry over
+    /// to a different record created under the same name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<String>,
+
+    /// `.metadata.generation` of the record when it was approved. Any later
+    /// change to the spec of the record bumps its generation, and
+    /// invalidates the approval.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generation: Option<i64>,
+
+    /// Person or automation which approved the record.
+    pub approved_by: String,
+}
+
+impl RecordApproval {
+    /// Approve the current generation of `record` on behalf of `approved_by`.
+    pub fn new(record: &Record, approved_by: &str) -> Self {
+        RecordApproval {
+            namespace: record.namespace().unwrap_or_default(),
+            name: record.name_any(),
+            uid: record.uid(),
+            generation: record.metadata.generation,
+            approved_by: approved_by.to_string(),
+        }
+    }
+
+    /// Check if this approval refers to `record`, regardless of its generation.
+    fn refers_to(&self, record: &Record) -> bool {
+        self.namespace == record.namespace().unwrap_or_default()
+            && self.name == record.name_any()
+            && self.uid == record.uid()
+    }
+
+    /// Check if this approval applies to the current generation of `record`.
+    pub fn applies_to(&self, record: &Record) -> bool {
+        self.refers_to(record) && self.generation == record.metadata.generation
+    }
+}
+
+impl ZoneStatus {
+    /// Approve the current generation of `record` on behalf of `approved_by`,
+    /// replacing any previous approval of it.
+    pub fn approve(&mut self, record: &Record, approved_by: &str) {
+        self.revoke_approval(record);
+        self.approvals
+            .push(RecordApproval::new(record, approved_by));
+    }
+
+    /// Remove any approval of `record`.
+    pub fn revoke_approval(&mut self, record: &Record) {
+        self.approvals
+            .retain(|approval| !approval.refers_to(record));
+    }
+
+    /// Check if the current generation of `record` has been approved.
+    pub fn is_approved(&self, record: &Record) -> bool {
+        self.approvals
+            .iter()
+            .any(|approval| approval.applies_to(record))
+    }
+}
+
+impl RecordStatus {
+    /// Set the [`Approved`](RecordConditionType::Approved) condition of a
+    /// record delegated by a delegation with `requireApproval` set, at the
+    /// given generation of the record.
+    pub fn set_approved(&mut self, approved: bool, generation: Option<i64>) {
+        let (reason, message) = if approved {
+            (
+                ConditionReason::ApprovalGranted,
+                "record has been approved by the owner of its zone",
+            )
+        } else {
+            (
+                ConditionReason::AwaitingApproval,
+                "record is awaiting approval by the owner of its zone",
+            )
+        };
+
+        self.set_condition(
+            Condition::new(RecordConditionType::Approved, approved, reason, message)
+                .with_observed_generation(generation),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_util::delegated_record,
+        v1alpha1::{ConditionReason, RecordConditionType, RecordStatus, ZoneStatus},
+    };
+
+    #[test]
+    fn test_approval() {
+        let mut status = ZoneStatus::default();
+        let mut record = delegated_record();
+        record.metadata.generation = Some(1);
+        record.metadata.uid = Some(String::from("a"));
+        assert!(!status.is_approved(&record));
+
+        status.approve(&record, "alice");
+        assert!(status.is_approved(&record));
+
+        // Changing the spec bumps the generation, and requires a new approval.
+        record.metadata.generation = Some(2);
+        assert!(!status.is_approved(&record));
+
+        status.approve(&record, "bob");
+        assert!(status.is_approved(&record));
+        assert_eq!(status.approvals.len(), 1);
+        assert_eq!(status.approvals[0].approved_by, "bob");
+
+        // Records recreated under the same name are not approved.
+        let mut recreated = record.clone();
+        recreated.metadata.uid = Some(String::from("b"));
+        assert!(!status.is_approved(&recreated));
+
+        status.revoke_approval(&record);
+        assert!(!status.is_approved(&record));
+        assert!(status.approvals.is_empty());
+    }
+
+    #[test]
+    fn test_approved_condition() {
+        let mut status = RecordStatus::default();
+
+        status.set_approved(false, Some(1));
+        let condition = status.get_condition(RecordConditionType::Approved).unwrap();
+        assert!(!condition.is_true());
+        assert_eq!(
+            condition.parsed_reason(),
+            Ok(ConditionReason::AwaitingApproval)
+        );
+
+        status.set_approved(true, Some(2));
+        let condition = status.get_condition(RecordConditionType::Approved).unwrap();
+        assert!(condition.is_true());
+        assert_eq!(condition.observed_generation, Some(2));
+    }
+}
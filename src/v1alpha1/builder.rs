@@ -0,0 +1,446 @@
+use std::collections::BTreeMap;
+
+use kube::core::ObjectMeta;
+use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, Type};
+use thiserror::Error;
+
+use crate::errors::{RecordValidationError, ValidationError};
+
+use super::{
+    defaults, validate_parent, Delegation, DomainExt, HashAlgorithm, Record, RecordSpec, ViewRef,
+    Zone, ZoneRef, ZoneSpec, MAX_LABEL_LENGTH,
+};
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum BuilderError {
+    #[error("invalid domain name {name}: {reason}")]
+    InvalidDomainName { name: String, reason: String },
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    #[error(transparent)]
+    Record(#[from] RecordValidationError),
+}
+
+/// Builder for [`Zone`]s, see [`Zone::builder`].
+#[derive(Clone, Debug)]
+pub struct ZoneBuilder {
+    domain_name: String,
+    metadata: ObjectMeta,
+    spec: ZoneSpec,
+    parent: Option<ZoneRef>,
+}
+
+/// Builder for [`Record`]s, see [`Record::builder`].
+#[derive(Clone, Debug)]
+pub struct RecordBuilder {
+    domain_name: String,
+    metadata: ObjectMeta,
+    spec: RecordSpec,
+    parent: Option<ZoneRef>,
+}
+
+/// Derive a resource name from a domain name, such as `www-example-org`
+/// for `www.example.org.`, and `wildcard-example-org` for `*.example.org.`.
+fn resource_name(domain_name: &str) -> String {
+    domain_name
+        .to_ascii_lowercase()
+        .replace('*', "wildcard")
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Number of hex digits of the hash suffix of [`derived_name`]s.
+const DERIVED_NAME_HASH_LENGTH: usize = 10;
+
+/// Derive a stable resource name for a record of `type_` at `fqdn`.
+///
+/// Meant for tools generating Records from zonefiles, Services or Ingresses,
+/// which must find the same Record again on their next run. The
+/// `discriminator`, such as the record data or the uid of the originating
+/// object, tells apart records sharing a name and type.
+///
+/// The name ends in a hash of all three, so it stays unique even though
+/// long domain names are truncated, and is at most 63 characters long,
+/// making it valid both as a resource name and as a label value.
+///
+/// ```rust
+/// # use kubizone_common::{FullyQualifiedDomainName, Type};
+/// # use kubizone_crds::v1alpha1::derived_name;
+/// let fqdn = FullyQualifiedDomainName::try_from("www.example.org.").unwrap();
+/// let name = derived_name(&fqdn, Type::A, "192.0.2.1");
+///
+/// assert_eq!(name, "www-example-org-a-c64dc2df24");
+/// assert_ne!(name, derived_name(&fqdn, Type::A, "192.0.2.2"));
+/// ```
+pub fn derived_name(fqdn: &FullyQualifiedDomainName, type_: Type, discriminator: &str) -> String {
+    let fqdn = fqdn.to_string().to_ascii_lowercase();
+    let hash =
+        HashAlgorithm::XxHash64.digest(format!("{fqdn}\t{type_}\t{discriminator}").as_bytes());
+
+    let mut prefix = resource_name(&format!("{fqdn}-{type_}"));
+    prefix.truncate(MAX_LABEL_LENGTH - DERIVED_NAME_HASH_LENGTH - 1);
+
+    format!(
+        "{}-{}",
+        prefix.trim_end_matches('-'),
+        &hash[..DERIVED_NAME_HASH_LENGTH]
+    )
+}
+
+fn parse_domain_name(domain_name: &str) -> Result<DomainName, BuilderError> {
+    crate::domain_name::parse_domain_name(domain_name).map_err(|err| {
+        BuilderError::InvalidDomainName {
+            name: domain_name.to_string(),
+            reason: err.to_string(),
+        }
+    })
+}
+
+impl Zone {
+    /// Start building a zone for `domain_name`.
+    ///
+    /// The zone is named after its domain name unless given a name, and
+    /// uses the default SOA timers and TTL.
+    ///
+    /// ```rust
+    /// # use kubizone_crds::v1alpha1::Zone;
+    /// let zone = Zone::builder("example.org.")
+    ///     .namespace("dns")
+    ///     .ttl(300)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(zone.to_string(), "dns/example-org");
+    /// ```
+    pub fn builder(domain_name: &str) -> ZoneBuilder {
+        ZoneBuilder {
+            domain_name: domain_name.to_string(),
+            metadata: ObjectMeta::default(),
+            parent: None,
+            spec: ZoneSpec {
+                ttl: defaults::TTL,
+                refresh: defaults::REFRESH,
+                retry: defaults::RETRY,
+                expire: defaults::EXPIRE,
+                negative_response_cache: defaults::NEGATIVE_RESPONSE_CACHE,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl ZoneBuilder {
+    pub fn name(mut self, name: &str) -> Self {
+        self.metadata.name = Some(name.to_string());
+        self
+    }
+
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.metadata.namespace = Some(namespace.to_string());
+        self
+    }
+
+    pub fn label(mut self, key: &str, value: &str) -> Self {
+        self.metadata
+            .labels
+            .get_or_insert_with(BTreeMap::new)
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Make the zone a sub-zone of the referenced zone. Required for zones
+    /// whose domain name is not fully qualified.
+    pub fn zone_ref(mut self, zone_ref: ZoneRef) -> Self {
+        self.spec.zone_ref = Some(zone_ref);
+        self
+    }
+
+    /// Mark the zone as adopted by `parent`, as the controller does once
+    /// the zone has been accepted, see [`DomainExt::set_parent`].
+    pub fn parent(mut self, parent: ZoneRef) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.spec.ttl = ttl;
+        self
+    }
+
+    /// Allow records and zones to insert themselves into the zone.
+    pub fn delegate(mut self, delegation: Delegation) -> Self {
+        self.spec.delegations.push(delegation);
+        self
+    }
+
+    pub fn nameserver(mut self, nameserver: FullyQualifiedDomainName) -> Self {
+        self.spec.nameservers.push(nameserver);
+        self
+    }
+
+    pub fn build(self) -> Result<Zone, BuilderError> {
+        let ZoneBuilder {
+            domain_name,
+            mut metadata,
+            mut spec,
+            parent,
+        } = self;
+
+        spec.domain_name = parse_domain_name(&domain_name)?;
+        spec.validate()?;
+
+        metadata
+            .name
+            .get_or_insert_with(|| resource_name(&domain_name));
+
+        let mut zone = Zone {
+            metadata,
+            spec,
+            status: None,
+        };
+
+        if let Some(parent) = parent {
+            zone.set_parent(parent);
+        }
+
+        Ok(zone)
+    }
+}
+
+impl Record {
+    /// Start building a record of the given type and data for `domain_name`.
+    ///
+    /// The record is named after its domain name and type unless given a name.
+    ///
+    /// ```rust
+    /// # use kubizone_crds::v1alpha1::Record;
+    /// # use kubizone_common::Type;
+    /// let record = Record::builder("www", Type::A, "192.0.2.1")
+    ///     .namespace("web")
+    ///     .zone_ref("example-org.dns".parse().unwrap())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(record.to_string(), "web/www-a");
+    /// ```
+    pub fn builder(domain_name: &str, type_: Type, rdata: &str) -> RecordBuilder {
+        RecordBuilder {
+            domain_name: domain_name.to_string(),
+            metadata: ObjectMeta::default(),
+            parent: None,
+            spec: RecordSpec {
+                type_,
+                rdata: rdata.to_string(),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl RecordBuilder {
+    pub fn name(mut self, name: &str) -> Self {
+        self.metadata.name = Some(name.to_string());
+        self
+    }
+
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.metadata.namespace = Some(namespace.to_string());
+        self
+    }
+
+    pub fn label(mut self, key: &str, value: &str) -> Self {
+        self.metadata
+            .labels
+            .get_or_insert_with(BTreeMap::new)
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Place the record in the referenced zone. Required for records
+    /// whose domain name is not fully qualified.
+    pub fn zone_ref(mut self, zone_ref: ZoneRef) -> Self {
+        self.spec.zone_ref = Some(zone_ref);
+        self
+    }
+
+    /// Mark the record as adopted by `parent`, as the controller does once
+    /// the record has been accepted, see [`DomainExt::set_parent`].
+    pub fn parent(mut self, parent: ZoneRef) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.spec.ttl = Some(ttl);
+        self
+    }
+
+    pub fn class(mut self, class: Class) -> Self {
+        self.spec.class = class;
+        self
+    }
+
+    /// Let the record expire `seconds` after its creation, see [`RecordSpec::expires_after`].
+    pub fn expires_after(mut self, seconds: u32) -> Self {
+        self.spec.expires_after = Some(seconds);
+        self
+    }
+
+    /// Only publish the record in the given view, see [`RecordSpec::views`].
+    pub fn view(mut self, view: ViewRef) -> Self {
+        self.spec.views.push(view);
+        self
+    }
+
+    pub fn build(self) -> Result<Record, BuilderError> {
+        let RecordBuilder {
+            domain_name,
+            mut metadata,
+            mut spec,
+            parent,
+        } = self;
+
+        spec.domain_name = parse_domain_name(&domain_name)?;
+        validate_parent(&spec.domain_name, spec.zone_ref.as_ref())?;
+        spec.validate()?;
+
+        metadata
+            .name
+            .get_or_insert_with(|| resource_name(&format!("{domain_name}-{}", spec.type_)));
+
+        let mut record = Record {
+            metadata,
+            spec,
+            status: None,
+        };
+
+        if let Some(parent) = parent {
+            record.set_parent(parent);
+        }
+
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kube::ResourceExt;
+    use kubizone_common::{DomainName, FullyQualifiedDomainName, Type};
+
+    use crate::{
+        errors::{RecordValidationError, ValidationError},
+        v1alpha1::{defaults, DomainExt, Record, Zone},
+        PARENT_ZONE_LABEL,
+    };
+
+    use super::{derived_name, BuilderError};
+
+    #[test]
+    fn test_zone_builder() {
+        let zone = Zone::builder("Example.org.")
+            .namespace("dns")
+            .label("team", "platform")
+            .ttl(300)
+            .delegate(serde_json::from_str(r#"{"namespaces": ["web"]}"#).unwrap())
+            .build()
+            .unwrap();
+
+        assert_eq!(zone.to_string(), "dns/example-org");
+        assert_eq!(zone.metadata.labels.unwrap()["team"], "platform");
+        assert_eq!(zone.spec.ttl, 300);
+        assert_eq!(zone.spec.delegations.len(), 1);
+
+        let zone = Zone::builder("example.org.").build().unwrap();
+        assert_eq!(zone.spec.ttl, defaults::TTL);
+        assert_eq!(zone.spec.expire, defaults::EXPIRE);
+
+        assert_eq!(
+            Zone::builder("example.org.").ttl(10).build().unwrap_err(),
+            BuilderError::Validation(ValidationError::TtlTooLow {
+                ttl: 10,
+                minimum: 30
+            })
+        );
+        assert_eq!(
+            Zone::builder("sub").build().unwrap_err(),
+            BuilderError::Validation(ValidationError::MissingParent(
+                DomainName::try_from("sub").unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_record_builder() {
+        let record = Record::builder("*.example.org.", Type::CNAME, "www.example.org.")
+            .namespace("web")
+            .ttl(60)
+            .build()
+            .unwrap();
+
+        assert_eq!(record.to_string(), "web/wildcard-example-org-cname");
+        assert_eq!(record.spec.ttl, Some(60));
+        assert!(record.spec.enabled);
+
+        let record = Record::builder("www.example.org.", Type::A, "192.0.2.1")
+            .namespace("web")
+            .parent("example-org.dns".parse().unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(
+            record.labels().get(PARENT_ZONE_LABEL).map(String::as_str),
+            Some("dns_example-org")
+        );
+        assert_eq!(record.parent(), Some("example-org.dns".parse().unwrap()));
+
+        assert!(matches!(
+            Record::builder("www.example.org.", Type::A, "not an address").build(),
+            Err(BuilderError::Record(RecordValidationError::RData(_)))
+        ));
+        assert!(matches!(
+            Record::builder("bad name", Type::A, "192.0.2.1").build(),
+            Err(BuilderError::InvalidDomainName { name, .. }) if name == "bad name"
+        ));
+        assert!(matches!(
+            Record::builder("www.example.org.", Type::A, "192.0.2.1")
+                .zone_ref("example-org".parse().unwrap())
+                .build(),
+            Err(BuilderError::Validation(
+                ValidationError::ConflictingParent(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn test_derived_name() {
+        let fqdn = |name: &str| FullyQualifiedDomainName::try_from(name).unwrap();
+
+        let name = derived_name(&fqdn("*.Example.org."), Type::TXT, "");
+        assert!(name.starts_with("wildcard-example-org-txt-"));
+        assert_eq!(name.len(), "wildcard-example-org-txt-".len() + 10);
+
+        // Case differences in the domain name do not change the name.
+        assert_eq!(name, derived_name(&fqdn("*.example.org."), Type::TXT, ""));
+        assert_ne!(name, derived_name(&fqdn("*.example.org."), Type::A, ""));
+
+        let long = fqdn(&format!(
+            "{}.{}.example.org.",
+            "a".repeat(63),
+            "b".repeat(63)
+        ));
+        let names = [
+            derived_name(&long, Type::A, "192.0.2.1"),
+            derived_name(&long, Type::A, "192.0.2.2"),
+        ];
+        assert_ne!(names[0], names[1]);
+
+        for name in names {
+            assert_eq!(name.len(), 63);
+            assert!(name.starts_with(&"a".repeat(52)));
+            assert!(name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'));
+        }
+    }
+}
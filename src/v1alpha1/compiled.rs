@@ -0,0 +1,685 @@
+use std::collections::{BTreeMap, HashMap};
+
+use kube::ResourceExt;
+use kubizone_common::{FullyQualifiedDomainName, Type};
+use tracing::*;
+
+use crate::{audit::MatchedRule, otel};
+
+use super::{
+    Delegation, DelegationDenied, DelegationPattern, DelegationPolicy, DomainExt, Record,
+    RecordApproval, RecordDelegation, Zone,
+};
+
+/// Delegations of a Zone, prepared for repeated evaluation.
+///
+/// Patterns are anchored to the zone's fully qualified domain name once,
+/// and record delegations are indexed by type, so validating many records
+/// against a zone with many delegations only considers the relevant rules.
+///
+/// Produced by [`Zone::compile_delegations`]. The compiled delegations do not
+/// track changes to the zone, and must be recompiled whenever it changes.
+/// [`Zone::validate_record`] and friends compile the delegations on every
+/// call, so callers evaluating many records should hold on to them instead.
+#[derive(Clone, Debug)]
+pub struct CompiledDelegations {
+    fqdn: FullyQualifiedDomainName,
+    uid: Option<String>,
+    namespace: Option<String>,
+    name: Option<String>,
+    ttl: u32,
+    max_child_depth: Option<u32>,
+    policy: DelegationPolicy,
+    delegations: Vec<CompiledDelegation>,
+    approvals: Vec<RecordApproval>,
+}
+
+#[derive(Clone, Debug)]
+struct CompiledDelegation {
+    /// Index of the delegation within `.spec.delegations`.
+    index: usize,
+    /// Delegation as written, for deciding which namespaces it covers.
+    source: Delegation,
+    zones: Vec<DelegationPattern>,
+    deny_zones: Vec<CompiledPattern>,
+    records: RecordRules,
+    deny_records: RecordRules,
+}
+
+#[derive(Clone, Debug)]
+struct CompiledPattern {
+    /// Pattern as written in the delegation, relative to the zone.
+    source: DelegationPattern,
+    anchored: DelegationPattern,
+}
+
+#[derive(Clone, Debug)]
+struct CompiledRecordRule {
+    /// Index of the rule within `records` or `denyRecords`.
+    index: usize,
+    pattern: CompiledPattern,
+    rule: RecordDelegation,
+}
+
+#[derive(Clone, Debug, Default)]
+struct RecordRules {
+    rules: Vec<CompiledRecordRule>,
+    /// Indices of rules which apply to any type.
+    any_type: Vec<usize>,
+    /// Indices of rules which apply to specific types.
+    by_type: HashMap<Type, Vec<usize>>,
+}
+
+impl CompiledPattern {
+    fn new(source: &DelegationPattern, origin: &FullyQualifiedDomainName) -> Self {
+        CompiledPattern {
+            source: source.clone(),
+            anchored: source.with_origin(origin),
+        }
+    }
+}
+
+impl RecordRules {
+    fn new(rules: &[RecordDelegation], origin: &FullyQualifiedDomainName) -> Self {
+        let mut compiled = RecordRules::default();
+
+        for (index, rule) in rules.iter().enumerate() {
+            if rule.types.is_empty() {
+                compiled.any_type.push(index);
+            }
+
+            for type_ in &rule.types {
+                compiled.by_type.entry(*type_).or_default().push(index);
+            }
+
+            compiled.rules.push(CompiledRecordRule {
+                index,
+                pattern: CompiledPattern::new(&rule.pattern, origin),
+                rule: rule.clone(),
+            });
+        }
+
+        compiled
+    }
+
+    /// Rules applicable to records of the given type.
+    fn candidates(&self, type_: Type) -> impl Iterator<Item = &CompiledRecordRule> + '_ {
+        self.any_type
+            .iter()
+            .chain(self.by_type.get(&type_).into_iter().flatten())
+            .map(|index| &self.rules[*index])
+    }
+}
+
+impl CompiledDelegation {
+    fn new(index: usize, delegation: &Delegation, origin: &FullyQualifiedDomainName) -> Self {
+        CompiledDelegation {
+            index,
+            source: delegation.clone(),
+            zones: delegation
+                .zones
+                .allowed()
+                .iter()
+                .map(|pattern| pattern.with_origin(origin))
+                .collect(),
+            deny_zones: delegation
+                .zones
+                .denied()
+                .iter()
+                .map(|pattern| CompiledPattern::new(pattern, origin))
+                .collect(),
+            records: RecordRules::new(&delegation.records, origin),
+            deny_records: RecordRules::new(&delegation.deny_records, origin),
+        }
+    }
+}
+
+impl CompiledDelegations {
+    /// Compile the delegations of `zone`, returning `None` if the
+    /// zone has not been assigned a fully qualified domain name yet.
+    pub fn new(zone: &Zone) -> Option<Self> {
+        let fqdn = zone.fqdn()?.clone();
+
+        Some(CompiledDelegations {
+            uid: zone.uid(),
+            namespace: zone.namespace(),
+            name: zone.metadata.name.clone(),
+            ttl: zone.spec.ttl,
+            max_child_depth: zone.spec.max_child_depth,
+            policy: zone.spec.delegation_policy,
+            delegations: zone
+                .spec
+                .delegations
+                .iter()
+                .enumerate()
+                .map(|(index, delegation)| CompiledDelegation::new(index, delegation, &fqdn))
+                .collect(),
+            approvals: zone
+                .status
+                .as_ref()
+                .map(|status| status.approvals.clone())
+                .unwrap_or_default(),
+            fqdn,
+        })
+    }
+
+    /// Check if `zone` is the zone these delegations were compiled from,
+    /// by UID if both have one, and by namespace and name otherwise.
+    fn is_same_zone(&self, zone: &Zone) -> bool {
+        match (&self.uid, zone.uid()) {
+            (Some(uid), Some(other)) => *uid == other,
+            _ => {
+                self.name.is_some()
+                    && self.name == zone.metadata.name
+                    && self.namespace == zone.namespace()
+            }
+        }
+    }
+
+    /// Fully qualified domain name of the zone these delegations were compiled from.
+    pub fn fqdn(&self) -> &FullyQualifiedDomainName {
+        &self.fqdn
+    }
+
+    /// Validate that the given Record is allowed by these delegations.
+    ///
+    /// Equivalent to [`Zone::validate_record`].
+    #[cfg_attr(
+        feature = "otel",
+        instrument(
+            level = "debug",
+            skip_all,
+            fields(kubizone.zone.fqdn, kubizone.record.fqdn, kubizone.outcome)
+        )
+    )]
+    pub fn validate_record(&self, record: &Record) -> Result<(), DelegationDenied> {
+        otel::record_result(self.evaluate_record(record, None)).map(drop)
+    }
+
+    /// Validate that the given Record is allowed by these delegations,
+    /// taking the labels of the record's namespace into account.
+    ///
+    /// Equivalent to [`Zone::validate_record_with_labels`].
+    #[cfg_attr(
+        feature = "otel",
+        instrument(
+            level = "debug",
+            skip_all,
+            fields(kubizone.zone.fqdn, kubizone.record.fqdn, kubizone.outcome)
+        )
+    )]
+    pub fn validate_record_with_labels(
+        &self,
+        record: &Record,
+        namespace_labels: &BTreeMap<String, String>,
+    ) -> Result<(), DelegationDenied> {
+        otel::record_result(self.evaluate_record(record, Some(namespace_labels))).map(drop)
+    }
+
+    /// Validate that the given Zone is allowed by these delegations.
+    ///
+    /// Equivalent to [`Zone::validate_zone`].
+    #[cfg_attr(
+        feature = "otel",
+        instrument(
+            level = "debug",
+            skip_all,
+            fields(kubizone.zone.fqdn, kubizone.record.fqdn, kubizone.outcome)
+        )
+    )]
+    pub fn validate_zone(&self, zone: &Zone) -> Result<(), DelegationDenied> {
+        otel::record_result(self.evaluate_zone(zone, None)).map(drop)
+    }
+
+    /// Validate that the given Zone is allowed by these delegations,
+    /// taking the labels of the sub-zone's namespace into account.
+    ///
+    /// Equivalent to [`Zone::validate_zone_with_labels`].
+    #[cfg_attr(
+        feature = "otel",
+        instrument(
+            level = "debug",
+            skip_all,
+            fields(kubizone.zone.fqdn, kubizone.record.fqdn, kubizone.outcome)
+        )
+    )]
+    pub fn validate_zone_with_labels(
+        &self,
+        zone: &Zone,
+        namespace_labels: &BTreeMap<String, String>,
+    ) -> Result<(), DelegationDenied> {
+        otel::record_result(self.evaluate_zone(zone, Some(namespace_labels))).map(drop)
+    }
+
+    /// Check if the given Record is allowed by these delegations.
+    pub fn allows_record(&self, record: &Record) -> bool {
+        self.validate_record(record).is_ok()
+    }
+
+    /// Check if the given Zone is allowed by these delegations.
+    pub fn allows_zone(&self, zone: &Zone) -> bool {
+        self.validate_zone(zone).is_ok()
+    }
+
+    /// Delegations covering `namespace`, failing if there are none, or if
+    /// some might cover it by labels which were not provided.
+    fn covering(
+        &self,
+        namespace: &str,
+        labels: Option<&BTreeMap<String, String>>,
+    ) -> Result<Vec<&CompiledDelegation>, DelegationDenied> {
+        if labels.is_none()
+            && self
+                .delegations
+                .iter()
+                .any(|delegation| delegation.source.needs_labels(namespace))
+        {
+            trace!(
+                "zone {} selects namespaces by label, but no labels were given for {namespace}",
+                self.fqdn
+            );
+            return Err(DelegationDenied::NamespaceLabelsRequired {
+                namespace: namespace.to_string(),
+            });
+        }
+
+        let delegations: Vec<_> = self
+            .delegations
+            .iter()
+            .filter(|delegation| delegation.source.covers(namespace, labels))
+            .collect();
+
+        if delegations.is_empty() {
+            trace!(
+                "zone {} does not delegate to namespace {namespace}",
+                self.fqdn
+            );
+            return Err(DelegationDenied::NamespaceNotDelegated {
+                namespace: namespace.to_string(),
+            });
+        }
+
+        Ok(delegations)
+    }
+
+    /// Evaluate `record`, returning the rule which allowed it.
+    pub(crate) fn evaluate_record(
+        &self,
+        record: &Record,
+        namespace_labels: Option<&BTreeMap<String, String>>,
+    ) -> Result<MatchedRule, DelegationDenied> {
+        let approved = self
+            .approvals
+            .iter()
+            .any(|approval| approval.applies_to(record));
+
+        self.evaluate_record_approved(record, namespace_labels, approved)
+    }
+
+    /// Like [`CompiledDelegations::evaluate_record`], but with the approval
+    /// of the record given, rather than looked up in the zone's status.
+    pub(crate) fn evaluate_record_approved(
+        &self,
+        record: &Record,
+        namespace_labels: Option<&BTreeMap<String, String>>,
+        approved: bool,
+    ) -> Result<MatchedRule, DelegationDenied> {
+        let parent_fqdn = &self.fqdn;
+        otel::record_fqdn(otel::ZONE_FQDN, parent_fqdn);
+
+        let Some(record_fqdn) = record.fqdn() else {
+            trace!("record {record} has no fqdn, and can therefore not be validated");
+            return Err(DelegationDenied::FqdnMissing);
+        };
+        otel::record_fqdn(otel::RECORD_FQDN, record_fqdn);
+
+        if record_fqdn != parent_fqdn && !record_fqdn.is_subdomain_of(parent_fqdn) {
+            trace!("record {record_fqdn} is not a subdomain of {parent_fqdn}");
+            return Err(DelegationDenied::NotSubdomain {
+                fqdn: record_fqdn.clone(),
+                parent: parent_fqdn.clone(),
+            });
+        }
+
+        let namespace = record.namespace().unwrap_or_default();
+        let delegations = self.covering(&namespace, namespace_labels)?;
+
+        let type_ = record.spec.type_;
+
+        // Records without an explicit TTL inherit the zone's.
+        let ttl = record.spec.ttl.unwrap_or(self.ttl);
+
+        let decision = match self.policy {
+            DelegationPolicy::AnyMatch => {
+                decide_record(&delegations, record_fqdn, type_, ttl, approved)
+            }
+            DelegationPolicy::FirstMatch => delegations.iter().find_map(|delegation| {
+                decide_record(&[delegation], record_fqdn, type_, ttl, approved)
+            }),
+        };
+
+        if let Some(decision) = decision {
+            return decision;
+        }
+
+        trace!("zone {parent_fqdn} forbid delegation to record {record_fqdn}");
+
+        // Distinguish between records which match no pattern at
+        // all, and records which match only on the wrong type.
+        let mut allowed: Vec<Type> = delegations
+            .iter()
+            .flat_map(|delegation| &delegation.records.rules)
+            .filter(|rule| rule.pattern.anchored.matches(record_fqdn))
+            .flat_map(|rule| rule.rule.types.iter().copied())
+            .collect();
+
+        if allowed.is_empty() {
+            return Err(DelegationDenied::NoMatchingPattern {
+                fqdn: record_fqdn.clone(),
+            });
+        }
+
+        allowed.sort();
+        allowed.dedup();
+
+        Err(DelegationDenied::TypeNotAllowed { type_, allowed })
+    }
+
+    /// Evaluate the sub-zone `zone`, returning the pattern which allowed it.
+    pub(crate) fn evaluate_zone(
+        &self,
+        zone: &Zone,
+        namespace_labels: Option<&BTreeMap<String, String>>,
+    ) -> Result<MatchedRule, DelegationDenied> {
+        let parent_fqdn = &self.fqdn;
+        otel::record_fqdn(otel::ZONE_FQDN, parent_fqdn);
+
+        let Some(zone_fqdn) = zone.fqdn() else {
+            trace!("zone {zone}'s fqdn is not defined.");
+            return Err(DelegationDenied::FqdnMissing);
+        };
+        otel::record_fqdn(otel::RECORD_FQDN, zone_fqdn);
+
+        if !zone_fqdn.is_subdomain_of(parent_fqdn) {
+            trace!("zone {} is not a subdomain of {}", zone_fqdn, parent_fqdn);
+            return Err(DelegationDenied::NotSubdomain {
+                fqdn: zone_fqdn.clone(),
+                parent: parent_fqdn.clone(),
+            });
+        }
+
+        // Cannot be a subdomain of itself
+        if self.is_same_zone(zone) {
+            return Err(DelegationDenied::SelfDelegation);
+        }
+
+        check_depth(parent_fqdn, self.max_child_depth, zone_fqdn)?;
+
+        let namespace = zone.namespace().unwrap_or_default();
+        let delegations = self.covering(&namespace, namespace_labels)?;
+
+        let decision = match self.policy {
+            DelegationPolicy::AnyMatch => decide_zone(&delegations, zone_fqdn),
+            DelegationPolicy::FirstMatch => delegations
+                .iter()
+                .find_map(|delegation| decide_zone(&[delegation], zone_fqdn)),
+        };
+
+        decision.unwrap_or_else(|| {
+            Err(DelegationDenied::NoMatchingPattern {
+                fqdn: zone_fqdn.clone(),
+            })
+        })
+    }
+}
+
+/// Check that `fqdn`, a subdomain of `parent_fqdn`, adds at most `max` labels to it.
+pub(crate) fn check_depth(
+    parent_fqdn: &FullyQualifiedDomainName,
+    max: Option<u32>,
+    fqdn: &FullyQualifiedDomainName,
+) -> Result<(), DelegationDenied> {
+    let Some(max) = max else {
+        return Ok(());
+    };
+
+    let depth = fqdn.as_ref().len() - parent_fqdn.as_ref().len();
+    if depth > max as usize {
+        trace!("zone {fqdn} is nested {depth} labels beneath {parent_fqdn}, exceeding {max}");
+        return Err(DelegationDenied::TooDeep {
+            fqdn: fqdn.clone(),
+            depth,
+            max,
+        });
+    }
+
+    Ok(())
+}
+
+/// Decide on a record based on the deny and allow rules of `delegations`,
+/// returning the rule which allowed it, or `None` if no rule matches it.
+///
+/// Deny rules of any of the delegations take precedence over allow rules,
+/// and allow rules of delegations requiring approval only apply to
+/// `approved` records.
+fn decide_record(
+    delegations: &[&CompiledDelegation],
+    record_fqdn: &FullyQualifiedDomainName,
+    type_: Type,
+    ttl: u32,
+    approved: bool,
+) -> Option<Result<MatchedRule, DelegationDenied>> {
+    if let Some(rule) = delegations
+        .iter()
+        .flat_map(|delegation| delegation.deny_records.candidates(type_))
+        .find(|rule| rule.pattern.anchored.matches(record_fqdn))
+    {
+        trace!("record {record_fqdn} is explicitly denied");
+        return Some(Err(DelegationDenied::ExplicitlyDenied {
+            fqdn: record_fqdn.clone(),
+            pattern: rule.pattern.source.clone(),
+        }));
+    }
+
+    let mut candidates = delegations
+        .iter()
+        .flat_map(|delegation| {
+            delegation
+                .records
+                .candidates(type_)
+                .map(move |rule| (*delegation, rule))
+        })
+        .filter(|(_, rule)| rule.pattern.anchored.matches(record_fqdn))
+        .peekable();
+
+    candidates.peek()?;
+
+    let mut bounds = None;
+    let mut awaiting_approval = false;
+
+    for (delegation, rule) in candidates {
+        if !rule.rule.validate_ttl(ttl) {
+            bounds.get_or_insert((rule.rule.min_ttl, rule.rule.max_ttl));
+        } else if delegation.source.require_approval && !approved {
+            awaiting_approval = true;
+        } else {
+            debug!("delegation allowed for record {record_fqdn}");
+            return Some(Ok(MatchedRule {
+                delegation: delegation.index,
+                entry: rule.index,
+            }));
+        }
+    }
+
+    if awaiting_approval {
+        trace!("record {record_fqdn} is delegated, but not approved");
+        return Some(Err(DelegationDenied::ApprovalRequired {
+            fqdn: record_fqdn.clone(),
+        }));
+    }
+
+    trace!("delegation forbidden for record {record_fqdn}");
+
+    // Unwrap safety: At least one candidate was inspected.
+    let (min, max) = bounds.unwrap();
+    Some(Err(DelegationDenied::TtlOutOfRange { ttl, min, max }))
+}
+
+/// Decide on a sub-zone based on the deny and allow patterns of
+/// `delegations`, returning the pattern which allowed it, or `None` if no
+/// pattern matches it.
+fn decide_zone(
+    delegations: &[&CompiledDelegation],
+    zone_fqdn: &FullyQualifiedDomainName,
+) -> Option<Result<MatchedRule, DelegationDenied>> {
+    if let Some(pattern) = delegations
+        .iter()
+        .flat_map(|delegation| &delegation.deny_zones)
+        .find(|pattern| pattern.anchored.matches(zone_fqdn))
+    {
+        trace!("zone {zone_fqdn} is explicitly denied");
+        return Some(Err(DelegationDenied::ExplicitlyDenied {
+            fqdn: zone_fqdn.clone(),
+            pattern: pattern.source.clone(),
+        }));
+    }
+
+    delegations.iter().find_map(|delegation| {
+        let entry = delegation
+            .zones
+            .iter()
+            .position(|pattern| pattern.matches(zone_fqdn))?;
+
+        Some(Ok(MatchedRule {
+            delegation: delegation.index,
+            entry,
+        }))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use kube::core::ObjectMeta;
+    use kubizone_common::{DomainName, FullyQualifiedDomainName, Pattern, Type};
+
+    use crate::v1alpha1::{
+        Delegation, DelegationDenied, Record, RecordDelegation, RecordSpec, RecordStatus, Zone,
+        ZonePatterns, ZoneSpec, ZoneStatus,
+    };
+
+    use crate::errors::DelegationError;
+
+    #[test]
+    fn test_compiled_delegations() {
+        let mut zone = Zone {
+            spec: ZoneSpec {
+                domain_name: DomainName::try_from("example.org.").unwrap(),
+                delegations: vec![Delegation {
+                    namespaces: vec![],
+                    namespace_selector: None,
+                    zones: ZonePatterns::default(),
+                    records: vec![
+                        RecordDelegation {
+                            pattern: Pattern::try_from("mail").unwrap().into(),
+                            types: vec![Type::A, Type::AAAA],
+                            min_ttl: None,
+                            max_ttl: None,
+                        },
+                        RecordDelegation {
+                            pattern: Pattern::origin().into(),
+                            types: vec![Type::MX],
+                            min_ttl: None,
+                            max_ttl: None,
+                        },
+                    ],
+                    deny_records: vec![],
+                    require_approval: false,
+                }],
+                ..Default::default()
+            },
+            status: None,
+            metadata: ObjectMeta::default(),
+        };
+
+        assert_eq!(
+            zone.compile_delegations().unwrap_err(),
+            DelegationError::MissingFqdn
+        );
+
+        zone.status = Some(ZoneStatus {
+            fqdn: Some(FullyQualifiedDomainName::try_from("example.org.").unwrap()),
+            ..Default::default()
+        });
+        let compiled = zone.compile_delegations().unwrap();
+
+        let record = |name: &str, type_: Type| Record {
+            metadata: ObjectMeta::default(),
+            spec: RecordSpec {
+                domain_name: DomainName::try_from(name).unwrap(),
+                type_,
+                ..Default::default()
+            },
+            status: Some(RecordStatus {
+                fqdn: Some(FullyQualifiedDomainName::try_from(name).unwrap()),
+                ..Default::default()
+            }),
+        };
+
+        for (name, type_, allowed) in [
+            ("mail.example.org.", Type::A, true),
+            ("mail.example.org.", Type::AAAA, true),
+            ("mail.example.org.", Type::MX, false),
+            ("example.org.", Type::MX, true),
+            ("example.org.", Type::A, false),
+        ] {
+            let record = record(name, type_);
+            assert_eq!(compiled.allows_record(&record), allowed);
+            assert_eq!(
+                compiled.validate_record(&record),
+                zone.validate_record(&record)
+            );
+        }
+    }
+
+    #[test]
+    fn test_self_delegation() {
+        let with_fqdn = |mut zone: Zone, fqdn: &str| {
+            zone.status = Some(ZoneStatus {
+                fqdn: Some(FullyQualifiedDomainName::try_from(fqdn).unwrap()),
+                ..Default::default()
+            });
+            zone
+        };
+
+        let parent = Zone::builder("example.org.")
+            .delegate(serde_json::from_str(r#"{"zones": ["*"]}"#).unwrap())
+            .build()
+            .unwrap();
+        let parent = with_fqdn(parent, "example.org.");
+        let child = Zone::builder("sub.example.org.").build().unwrap();
+        let child = with_fqdn(child, "sub.example.org.");
+
+        // Neither zone has a UID, which must not make them the same zone.
+        assert_eq!(parent.validate_zone(&child), Ok(()));
+
+        let itself = with_fqdn(parent.clone(), "sub.example.org.");
+        assert_eq!(
+            parent.validate_zone(&itself),
+            Err(DelegationDenied::SelfDelegation)
+        );
+    }
+
+    #[test]
+    fn test_in_memory_changes() {
+        let mut zone = crate::test_util::delegated_zone();
+        let record = crate::test_util::delegated_record();
+        let compiled = zone.compile_delegations().unwrap();
+        assert!(zone.allows_record(&record));
+
+        // Edits which don't bump the generation still take effect
+        // immediately, while held compiled delegations are unaffected.
+        zone.spec.delegations.clear();
+        assert!(!zone.allows_record(&record));
+        assert!(compiled.allows_record(&record));
+    }
+}
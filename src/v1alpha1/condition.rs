@@ -0,0 +1,387 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ConditionError {
+    #[error("unknown condition type {0}")]
+    UnknownType(String),
+    #[error("unknown condition reason {0}")]
+    UnknownReason(String),
+}
+
+/// Status of a [`Condition`].
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, PartialEq, Eq, Hash)]
+pub enum ConditionStatus {
+    True,
+    False,
+    Unknown,
+}
+
+impl Display for ConditionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConditionStatus::True => f.write_str("True"),
+            ConditionStatus::False => f.write_str("False"),
+            ConditionStatus::Unknown => f.write_str("Unknown"),
+        }
+    }
+}
+
+impl From<bool> for ConditionStatus {
+    fn from(value: bool) -> Self {
+        if value {
+            ConditionStatus::True
+        } else {
+            ConditionStatus::False
+        }
+    }
+}
+
+/// Condition types set on [`Zone`](super::Zone)s by kubizone controllers.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, PartialEq, Eq, Hash)]
+pub enum ZoneConditionType {
+    /// The zone has been reconciled, and its entries are up to date.
+    Ready,
+    /// The zone has been accepted into its parent zone.
+    Delegated,
+    /// The zone is served, but some of its records or sub-zones were rejected.
+    Degraded,
+    /// The zone has been signed with its DNSSEC keys.
+    Signed,
+}
+
+impl ZoneConditionType {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            ZoneConditionType::Ready => "Ready",
+            ZoneConditionType::Delegated => "Delegated",
+            ZoneConditionType::Degraded => "Degraded",
+            ZoneConditionType::Signed => "Signed",
+        }
+    }
+}
+
+impl FromStr for ZoneConditionType {
+    type Err = ConditionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Ready" => Ok(ZoneConditionType::Ready),
+            "Delegated" => Ok(ZoneConditionType::Delegated),
+            "Degraded" => Ok(ZoneConditionType::Degraded),
+            "Signed" => Ok(ZoneConditionType::Signed),
+            _ => Err(ConditionError::UnknownType(s.to_string())),
+        }
+    }
+}
+
+/// Condition types set on [`Record`](super::Record)s by kubizone controllers.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, PartialEq, Eq, Hash)]
+pub enum RecordConditionType {
+    /// The record has been reconciled, and is published in its zone.
+    Ready,
+    /// The record has been accepted by the delegations of its zone.
+    Delegated,
+    /// The record has been approved, as required by a delegation with
+    /// `requireApproval` set.
+    Approved,
+}
+
+impl RecordConditionType {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            RecordConditionType::Ready => "Ready",
+            RecordConditionType::Delegated => "Delegated",
+            RecordConditionType::Approved => "Approved",
+        }
+    }
+}
+
+impl FromStr for RecordConditionType {
+    type Err = ConditionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Ready" => Ok(RecordConditionType::Ready),
+            "Delegated" => Ok(RecordConditionType::Delegated),
+            "Approved" => Ok(RecordConditionType::Approved),
+            _ => Err(ConditionError::UnknownType(s.to_string())),
+        }
+    }
+}
+
+/// Reasons for the last transition of a condition, shared between zones
+/// and records, since most of them apply to both.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, PartialEq, Eq, Hash)]
+pub enum ConditionReason {
+    /// The resource was reconciled successfully.
+    Reconciled,
+    /// The resource is paused, and is not being reconciled.
+    Paused,
+    /// The spec of the resource failed validation.
+    InvalidSpec,
+    /// The parent zone of the resource does not exist, or has not yet been
+    /// assigned a fully qualified domain name.
+    MissingParent,
+    /// The parent zone of the resource does not delegate it.
+    RejectedByDelegation,
+    /// The entries of the zone changed, and its serial was bumped.
+    SerialBumped,
+    /// The entries of the zone exceeded their size budget, and were truncated.
+    EntriesTruncated,
+    /// The record is delegated, but awaiting approval.
+    AwaitingApproval,
+    /// The record has been approved at its current generation.
+    ApprovalGranted,
+}
+
+impl ConditionReason {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            ConditionReason::Reconciled => "Reconciled",
+            ConditionReason::Paused => "Paused",
+            ConditionReason::InvalidSpec => "InvalidSpec",
+            ConditionReason::MissingParent => "MissingParent",
+            ConditionReason::RejectedByDelegation => "RejectedByDelegation",
+            ConditionReason::SerialBumped => "SerialBumped",
+            ConditionReason::EntriesTruncated => "EntriesTruncated",
+            ConditionReason::AwaitingApproval => "AwaitingApproval",
+            ConditionReason::ApprovalGranted => "ApprovalGranted",
+        }
+    }
+}
+
+impl FromStr for ConditionReason {
+    type Err = ConditionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Reconciled" => Ok(ConditionReason::Reconciled),
+            "Paused" => Ok(ConditionReason::Paused),
+            "InvalidSpec" => Ok(ConditionReason::InvalidSpec),
+            "MissingParent" => Ok(ConditionReason::MissingParent),
+            "RejectedByDelegation" => Ok(ConditionReason::RejectedByDelegation),
+            "SerialBumped" => Ok(ConditionReason::SerialBumped),
+            "EntriesTruncated" => Ok(ConditionReason::EntriesTruncated),
+            "AwaitingApproval" => Ok(ConditionReason::AwaitingApproval),
+            "ApprovalGranted" => Ok(ConditionReason::ApprovalGranted),
+            _ => Err(ConditionError::UnknownReason(s.to_string())),
+        }
+    }
+}
+
+impl Display for ZoneConditionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Display for RecordConditionType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl Display for ConditionReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl AsRef<str> for ZoneConditionType {
+    fn as_ref(&self) -> &str {
+        self.name()
+    }
+}
+
+impl AsRef<str> for RecordConditionType {
+    fn as_ref(&self) -> &str {
+        self.name()
+    }
+}
+
+impl From<ZoneConditionType> for String {
+    fn from(value: ZoneConditionType) -> Self {
+        value.name().to_string()
+    }
+}
+
+impl From<RecordConditionType> for String {
+    fn from(value: RecordConditionType) -> Self {
+        value.name().to_string()
+    }
+}
+
+impl From<ConditionReason> for String {
+    fn from(value: ConditionReason) -> Self {
+        value.name().to_string()
+    }
+}
+
+/// Observation of one aspect of a resource's state, following the
+/// conventions of the kubernetes `metav1.Condition` type, so they can be
+/// consumed by `kubectl wait --for=condition=<type>` and similar tools.
+///
+/// `metav1.Condition` itself is not used, since its timestamp type can
+/// be neither hashed nor used in a JSON schema without extra features.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+    /// Type of condition in CamelCase, such as `Ready`.
+    #[serde(rename = "type")]
+    pub type_: String,
+
+    pub status: ConditionStatus,
+
+    /// Programmatic identifier for the reason of the last transition, in CamelCase.
+    pub reason: String,
+
+    /// Human-readable details about the last transition.
+    #[serde(default)]
+    pub message: String,
+
+    /// Last time the condition transitioned from one status to another.
+    pub last_transition_time: DateTime<Utc>,
+
+    /// `.metadata.generation` of the resource this condition was set based on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observed_generation: Option<i64>,
+}
+
+impl Condition {
+    /// Construct a new condition, which transitioned just now.
+    pub fn new(
+        type_: impl Into<String>,
+        status: impl Into<ConditionStatus>,
+        reason: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        Condition {
+            type_: type_.into(),
+            status: status.into(),
+            reason: reason.into(),
+            message: message.into(),
+            last_transition_time: Utc::now(),
+            observed_generation: None,
+        }
+    }
+
+    /// Set the generation this condition was observed at.
+    pub fn with_observed_generation(mut self, generation: Option<i64>) -> Self {
+        self.observed_generation = generation;
+        self
+    }
+
+    pub fn is_true(&self) -> bool {
+        self.status == ConditionStatus::True
+    }
+
+    /// Parse the reason of the condition, if set by a kubizone controller.
+    pub fn parsed_reason(&self) -> Result<ConditionReason, ConditionError> {
+        self.reason.parse()
+    }
+}
+
+/// Insert `condition` into `conditions`, replacing any existing condition
+/// of the same type.
+///
+/// If the status of the condition did not change, the previous
+/// transition time is kept, as required by the API conventions.
+pub(crate) fn set_condition(conditions: &mut Vec<Condition>, mut condition: Condition) {
+    match conditions
+        .iter_mut()
+        .find(|existing| existing.type_ == condition.type_)
+    {
+        Some(existing) => {
+            if existing.status == condition.status {
+                condition.last_transition_time = existing.last_transition_time;
+            }
+
+            *existing = condition;
+        }
+        None => conditions.push(condition),
+    }
+}
+
+pub(crate) fn get_condition<'a>(conditions: &'a [Condition], type_: &str) -> Option<&'a Condition> {
+    conditions.iter().find(|condition| condition.type_ == type_)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{
+        get_condition, set_condition, Condition, ConditionError, ConditionReason, ConditionStatus,
+        RecordConditionType, ZoneConditionType,
+    };
+
+    #[test]
+    fn test_set_condition() {
+        let mut conditions = Vec::new();
+
+        let mut ready = Condition::new("Ready", true, "Reconciled", "");
+        ready.last_transition_time = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        set_condition(&mut conditions, ready.clone());
+
+        // Same status keeps the original transition time.
+        set_condition(
+            &mut conditions,
+            Condition::new("Ready", true, "Reconciled", "still ready"),
+        );
+        let current = get_condition(&conditions, "Ready").unwrap();
+        assert_eq!(current.last_transition_time, ready.last_transition_time);
+        assert_eq!(current.message, "still ready");
+
+        // Changed status updates it.
+        set_condition(
+            &mut conditions,
+            Condition::new("Ready", false, "ParentMissing", ""),
+        );
+        let current = get_condition(&conditions, "Ready").unwrap();
+        assert_ne!(current.last_transition_time, ready.last_transition_time);
+        assert_eq!(current.status, ConditionStatus::False);
+
+        set_condition(
+            &mut conditions,
+            Condition::new("Degraded", false, "Reconciled", ""),
+        );
+        assert_eq!(conditions.len(), 2);
+        assert!(get_condition(&conditions, "Missing").is_none());
+    }
+
+    #[test]
+    fn test_typed_conditions() {
+        let condition = Condition::new(
+            ZoneConditionType::Ready,
+            false,
+            ConditionReason::RejectedByDelegation,
+            "",
+        );
+        assert_eq!(condition.type_, "Ready");
+        assert_eq!(
+            condition.parsed_reason(),
+            Ok(ConditionReason::RejectedByDelegation)
+        );
+
+        assert_eq!(
+            "Delegated".parse::<RecordConditionType>(),
+            Ok(RecordConditionType::Delegated)
+        );
+        assert_eq!(
+            "Signed".parse::<RecordConditionType>(),
+            Err(ConditionError::UnknownType(String::from("Signed")))
+        );
+
+        // Serialized and displayed forms must agree, since controllers
+        // compare conditions by their string representation.
+        assert_eq!(
+            serde_json::to_value(ConditionReason::SerialBumped).unwrap(),
+            serde_json::Value::String(ConditionReason::SerialBumped.to_string())
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use std::{fmt::Display, net::IpAddr};
+
+use ipnet::IpNet;
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::FullyQualifiedDomainName;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{Zone, ZoneEntry, ZoneRef};
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum DNSViewError {
+    #[error("network {0} has host bits set, did you mean {1}?")]
+    HostBitsSet(IpNet, IpNet),
+}
+
+/// Named view of a set of zones, for split-horizon DNS.
+///
+/// Clients are served the view with the highest priority whose match
+/// criteria they satisfy, see [`select_view`]. Within a view, zones only
+/// publish the entries of records which are part of the view, see
+/// [`ZoneEntry::is_in_view`]. Records reference views by [`ViewRef`].
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "DNSView",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "DNSView",
+        namespaced
+    )
+)]
+#[kube(status = "DNSViewStatus")]
+#[kube(printcolumn = r#"{"name":"priority", "jsonPath": ".spec.priority", "type": "integer"}"#)]
+#[serde(rename_all = "camelCase")]
+pub struct DNSViewSpec {
+    /// Zones served in this view. Zone references without a namespace
+    /// refer to zones in the namespace of the view.
+    pub zones: Vec<ZoneRef>,
+
+    /// Client networks in CIDR notation served by this view, such as
+    /// `10.0.0.0/8`. Matches all clients if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(schema_with = "crate::schema::networks")]
+    pub match_clients: Vec<IpNet>,
+
+    /// Server addresses in CIDR notation which queries for this view must
+    /// be sent to. Matches all destinations if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(schema_with = "crate::schema::networks")]
+    pub match_destinations: Vec<IpNet>,
+
+    /// Views with a higher priority are matched first.
+    #[serde(default)]
+    pub priority: i32,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DNSViewStatus {
+    /// Fully qualified domain names of the zones in this view.
+    #[serde(default)]
+    pub zones: Vec<FullyQualifiedDomainName>,
+}
+
+/// Reference to a [`DNSView`], optionally in a specific namespace.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct ViewRef {
+    pub name: String,
+
+    /// Namespace of the view. References without a namespace refer to the
+    /// namespace of the referencing resource, see [`ViewRef::within`].
+    pub namespace: Option<String>,
+}
+
+impl ViewRef {
+    /// Default the namespace of the reference to `namespace`, which should
+    /// be the namespace of the referencing resource.
+    pub fn within(&self, namespace: Option<&str>) -> ViewRef {
+        ViewRef {
+            name: self.name.clone(),
+            namespace: self.namespace.as_deref().or(namespace).map(String::from),
+        }
+    }
+
+    /// Check if this reference refers to `view`. References without a
+    /// namespace must be resolved with [`ViewRef::within`] first.
+    pub fn refers_to(&self, view: &DNSView) -> bool {
+        self.name == view.name_any() && self.namespace == view.namespace()
+    }
+}
+
+impl Display for ViewRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(namespace) = &self.namespace {
+            write!(f, "{namespace}/{}", self.name)
+        } else {
+            f.write_str(&self.name)
+        }
+    }
+}
+
+fn validate_networks(networks: &[IpNet]) -> Result<(), DNSViewError> {
+    match networks
+        .iter()
+        .find(|network| network.addr() != network.network())
+    {
+        Some(network) => Err(DNSViewError::HostBitsSet(*network, network.trunc())),
+        None => Ok(()),
+    }
+}
+
+fn matches(networks: &[IpNet], address: &IpAddr) -> bool {
+    networks.is_empty() || networks.iter().any(|network| network.contains(address))
+}
+
+impl DNSViewSpec {
+    /// Check the match criteria for networks with host bits set, which are
+    /// likely typos.
+    pub fn validate(&self) -> Result<(), DNSViewError> {
+        validate_networks(&self.match_clients)?;
+        validate_networks(&self.match_destinations)
+    }
+
+    /// Check if a query from `client` to `destination` is served by this view.
+    pub fn matches(&self, client: &IpAddr, destination: &IpAddr) -> bool {
+        matches(&self.match_clients, client) && matches(&self.match_destinations, destination)
+    }
+}
+
+impl DNSView {
+    /// Check if `zone` is served in this view.
+    pub fn includes_zone(&self, zone: &Zone) -> bool {
+        self.spec.zones.iter().any(|zone_ref| {
+            zone_ref.name == zone.name_any()
+                && zone_ref
+                    .namespace
+                    .as_ref()
+                    .or(self.metadata.namespace.as_ref())
+                    == zone.metadata.namespace.as_ref()
+        })
+    }
+
+    /// Entries of a zone which are published in this view.
+    pub fn entries(&self, entries: &[ZoneEntry]) -> Vec<ZoneEntry> {
+        entries
+            .iter()
+            .filter(|entry| entry.is_in_view(self))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Select the view serving a query from `client` to `destination`.
+///
+/// The matching view with the highest priority wins, with ties broken by
+/// name, so that the selection does not depend on the order of `views`.
+pub fn select_view<'a>(
+    views: &'a [DNSView],
+    client: &IpAddr,
+    destination: &IpAddr,
+) -> Option<&'a DNSView> {
+    views
+        .iter()
+        .filter(|view| view.spec.matches(client, destination))
+        .min_by(|a, b| {
+            b.spec
+                .priority
+                .cmp(&a.spec.priority)
+                .then_with(|| a.name_any().cmp(&b.name_any()))
+        })
+}
+
+impl Display for DNSView {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: Views are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use kube::core::ObjectMeta;
+
+    use crate::v1alpha1::{ZoneEntry, ZoneRef};
+
+    use super::{select_view, DNSView, DNSViewError, DNSViewSpec, ViewRef};
+
+    fn view(name: &str, clients: &[&str], priority: i32) -> DNSView {
+        DNSView {
+            metadata: ObjectMeta {
+                name: Some(String::from(name)),
+                namespace: Some(String::from("default")),
+                ..Default::default()
+            },
+            spec: DNSViewSpec {
+                zones: vec![ZoneRef {
+                    name: String::from("example-org"),
+                    namespace: None,
+                }],
+                match_clients: clients
+                    .iter()
+                    .map(|client| client.parse().unwrap())
+                    .collect(),
+                priority,
+                ..Default::default()
+            },
+            status: None,
+        }
+    }
+
+    #[test]
+    fn test_view_selection() {
+        let views = vec![
+            view("external", &[], 0),
+            view("internal", &["10.0.0.0/8", "fd00::/8"], 10),
+        ];
+
+        let server: IpAddr = "192.0.2.53".parse().unwrap();
+        let select = |client: &str| {
+            select_view(&views, &client.parse().unwrap(), &server).map(|view| view.spec.priority)
+        };
+
+        assert_eq!(select("10.1.2.3"), Some(10));
+        assert_eq!(select("fd00::1"), Some(10));
+        assert_eq!(select("198.51.100.1"), Some(0));
+
+        let mut invalid = view("invalid", &["10.0.0.1/8"], 0);
+        assert_eq!(
+            invalid.spec.validate(),
+            Err(DNSViewError::HostBitsSet(
+                "10.0.0.1/8".parse().unwrap(),
+                "10.0.0.0/8".parse().unwrap()
+            ))
+        );
+        invalid.spec.match_clients.clear();
+        assert_eq!(invalid.spec.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_view_entries() {
+        let mut internal: ZoneEntry = "www.example.org. 300 IN A 10.0.0.1".parse().unwrap();
+        internal.views = vec![ViewRef {
+            name: String::from("internal"),
+            namespace: Some(String::from("default")),
+        }];
+        let shared: ZoneEntry = "mail.example.org. 300 IN A 192.0.2.25".parse().unwrap();
+        let entries = vec![internal, shared];
+
+        assert_eq!(view("internal", &[], 0).entries(&entries).len(), 2);
+
+        // Views of the same name in other namespaces are different views.
+        let mut elsewhere = view("internal", &[], 0);
+        elsewhere.metadata.namespace = Some(String::from("other"));
+        assert_eq!(elsewhere.entries(&entries).len(), 1);
+
+        let external = view("external", &[], 0).entries(&entries);
+        assert_eq!(external.len(), 1);
+        assert_eq!(external[0].rdata, "192.0.2.25");
+    }
+}
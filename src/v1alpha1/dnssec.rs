@@ -0,0 +1,298 @@
+use std::fmt::Display;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Highest NSEC3 iteration count accepted. Validators may treat zones with
+/// more iterations as insecure, see
+/// [RFC 9276 section 3.2](https://datatracker.ietf.org/doc/html/rfc9276#section-3.2).
+pub const MAX_NSEC3_ITERATIONS: u16 = 100;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum DnssecError {
+    /// Keys must outlive cached signatures made with them, see
+    /// [RFC 7583](https://datatracker.ietf.org/doc/html/rfc7583).
+    #[error(
+        "key rotation interval of {interval}s must be at least {minimum}s, twice the zone's TTL"
+    )]
+    RotationTooFrequent { interval: u32, minimum: u32 },
+    #[error("{0} NSEC3 iterations exceeds the maximum of {MAX_NSEC3_ITERATIONS}")]
+    TooManyNsec3Iterations(u16),
+    #[error("NSEC3 parameters are set, but denial of existence uses NSEC")]
+    UnusedNsec3Parameters,
+}
+
+/// DNSSEC signing configuration of a zone.
+///
+/// Signing itself is performed by downstream signers, this only declares
+/// how the zone should be signed.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Dnssec {
+    #[serde(default)]
+    pub algorithm: DnssecAlgorithm,
+
+    #[serde(default)]
+    pub key_strategy: KeyStrategy,
+
+    /// Seconds between rollovers of the signing key, which is the CSK, or
+    /// the ZSK when using separate keys. Keys are not rotated automatically
+    /// if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 3600))]
+    pub key_rotation_interval: Option<u32>,
+
+    /// Mechanism for authenticated denial of existence.
+    #[serde(default)]
+    pub denial_of_existence: DenialOfExistence,
+
+    /// Parameters of NSEC3 chains, only valid when using NSEC3 for denial
+    /// of existence. The recommended values of [`Nsec3::default`] are used
+    /// if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nsec3: Option<Nsec3>,
+
+    /// Names of the [`DNSSECKey`](super::DNSSECKey)s in the zone's namespace
+    /// to sign with. Keys are managed by the signer if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<String>,
+}
+
+/// Signing algorithms recommended for use by
+/// [RFC 8624](https://datatracker.ietf.org/doc/html/rfc8624#section-3.1),
+/// named by their IANA mnemonics.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum DnssecAlgorithm {
+    #[serde(rename = "RSASHA256")]
+    RsaSha256,
+    #[serde(rename = "RSASHA512")]
+    RsaSha512,
+    #[default]
+    #[serde(rename = "ECDSAP256SHA256")]
+    EcdsaP256Sha256,
+    #[serde(rename = "ECDSAP384SHA384")]
+    EcdsaP384Sha384,
+    #[serde(rename = "ED25519")]
+    Ed25519,
+    #[serde(rename = "ED448")]
+    Ed448,
+}
+
+impl DnssecAlgorithm {
+    /// Algorithm number, as used in DNSKEY, RRSIG and DS records.
+    pub const fn number(&self) -> u8 {
+        match self {
+            DnssecAlgorithm::RsaSha256 => 8,
+            DnssecAlgorithm::RsaSha512 => 10,
+            DnssecAlgorithm::EcdsaP256Sha256 => 13,
+            DnssecAlgorithm::EcdsaP384Sha384 => 14,
+            DnssecAlgorithm::Ed25519 => 15,
+            DnssecAlgorithm::Ed448 => 16,
+        }
+    }
+}
+
+impl Display for DnssecAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            DnssecAlgorithm::RsaSha256 => "RSASHA256",
+            DnssecAlgorithm::RsaSha512 => "RSASHA512",
+            DnssecAlgorithm::EcdsaP256Sha256 => "ECDSAP256SHA256",
+            DnssecAlgorithm::EcdsaP384Sha384 => "ECDSAP384SHA384",
+            DnssecAlgorithm::Ed25519 => "ED25519",
+            DnssecAlgorithm::Ed448 => "ED448",
+        })
+    }
+}
+
+/// Whether a zone is signed with a single key, or separate keys for
+/// signing the DNSKEY set and the rest of the zone.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum KeyStrategy {
+    /// Combined signing key.
+    #[default]
+    #[serde(rename = "CSK")]
+    Csk,
+    /// Key signing key and zone signing key.
+    #[serde(rename = "KSK+ZSK")]
+    KskZsk,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum DenialOfExistence {
+    #[default]
+    #[serde(rename = "NSEC")]
+    Nsec,
+    #[serde(rename = "NSEC3")]
+    Nsec3,
+}
+
+/// NSEC3 hashing parameters.
+///
+/// The defaults follow the recommendations of
+/// [RFC 9276](https://datatracker.ietf.org/doc/html/rfc9276#section-3.1):
+/// no additional iterations, no salt, and no opt-out.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Nsec3 {
+    /// Length in bytes of the salt, which is generated by the signer.
+    #[serde(default)]
+    pub salt_length: u8,
+
+    /// Additional hash iterations.
+    #[serde(default)]
+    #[schemars(range(max = 100))]
+    pub iterations: u16,
+
+    /// Leave insecure delegations out of the NSEC3 chain. Only useful for
+    /// large zones consisting mostly of delegations.
+    #[serde(default)]
+    pub opt_out: bool,
+}
+
+/// DNSSEC state of a zone, as reported by its signer.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DnssecStatus {
+    /// Key tags of the keys currently signing the zone.
+    #[serde(default)]
+    pub key_tags: Vec<u16>,
+
+    /// DS records to publish in the parent zone, in presentation format.
+    #[serde(default)]
+    pub ds_records: Vec<String>,
+}
+
+impl Dnssec {
+    /// Validate the configuration for a zone with the given TTL.
+    pub fn validate(&self, ttl: u32) -> Result<(), DnssecError> {
+        let minimum = ttl.saturating_mul(2);
+
+        if let Some(interval) = self.key_rotation_interval {
+            if interval < minimum {
+                return Err(DnssecError::RotationTooFrequent { interval, minimum });
+            }
+        }
+
+        if let Some(nsec3) = &self.nsec3 {
+            if self.denial_of_existence != DenialOfExistence::Nsec3 {
+                return Err(DnssecError::UnusedNsec3Parameters);
+            }
+
+            if nsec3.iterations > MAX_NSEC3_ITERATIONS {
+                return Err(DnssecError::TooManyNsec3Iterations(nsec3.iterations));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// NSEC3 parameters to sign the zone with, or `None` if the zone uses NSEC.
+    pub fn nsec3_parameters(&self) -> Option<Nsec3> {
+        match self.denial_of_existence {
+            DenialOfExistence::Nsec => None,
+            DenialOfExistence::Nsec3 => Some(self.nsec3.clone().unwrap_or_default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DenialOfExistence, Dnssec, DnssecAlgorithm, DnssecError, KeyStrategy, Nsec3};
+
+    #[test]
+    fn test_dnssec_config() {
+        let dnssec: Dnssec = serde_json::from_str(
+            r#"{"algorithm": "ED25519", "keyStrategy": "KSK+ZSK", "keyRotationInterval": 3600, "denialOfExistence": "NSEC3"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(dnssec.algorithm, DnssecAlgorithm::Ed25519);
+        assert_eq!(dnssec.algorithm.number(), 15);
+        assert_eq!(dnssec.key_strategy, KeyStrategy::KskZsk);
+        assert_eq!(dnssec.denial_of_existence, DenialOfExistence::Nsec3);
+
+        assert_eq!(dnssec.validate(360), Ok(()));
+        assert_eq!(
+            dnssec.validate(3600),
+            Err(DnssecError::RotationTooFrequent {
+                interval: 3600,
+                minimum: 7200
+            })
+        );
+
+        let defaults: Dnssec = serde_json::from_str("{}").unwrap();
+        assert_eq!(defaults.algorithm, DnssecAlgorithm::EcdsaP256Sha256);
+        assert_eq!(defaults.key_strategy, KeyStrategy::Csk);
+        assert_eq!(defaults.validate(u32::MAX), Ok(()));
+        assert_eq!(defaults.nsec3_parameters(), None);
+    }
+
+    #[test]
+    fn test_nsec3_parameters() {
+        let mut dnssec: Dnssec = serde_json::from_str(
+            r#"{"denialOfExistence": "NSEC3", "nsec3": {"iterations": 150, "optOut": true}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            dnssec.validate(300),
+            Err(DnssecError::TooManyNsec3Iterations(150))
+        );
+
+        dnssec.nsec3 = None;
+        assert_eq!(dnssec.validate(300), Ok(()));
+        assert_eq!(dnssec.nsec3_parameters(), Some(Nsec3::default()));
+
+        dnssec.nsec3 = Some(Nsec3::default());
+        dnssec.denial_of_existence = DenialOfExistence::Nsec;
+        assert_eq!(
+            dnssec.validate(300),
+            Err(DnssecError::UnusedNsec3Parameters)
+        );
+    }
+}
@@ -0,0 +1,298 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::FullyQualifiedDomainName;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use super::{zone::hex, DnssecAlgorithm};
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum DNSSECKeyError {
+    #[error("retirement time {retirement} must be after activation time {activation}")]
+    RetiredBeforeActivation {
+        activation: DateTime<Utc>,
+        retirement: DateTime<Utc>,
+    },
+}
+
+/// DNSSEC signing key, whose key material is stored in a Secret.
+///
+/// Zones reference keys by name through [`Dnssec::keys`](super::Dnssec::keys).
+/// The key signs the zone between its activation and retirement time, see
+/// [`DNSSECKeySpec::is_active`].
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "DNSSECKey",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "DNSSECKey",
+        namespaced
+    )
+)]
+#[kube(status = "DNSSECKeyStatus")]
+#[kube(printcolumn = r#"{"name":"type", "jsonPath": ".spec.keyType", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"algorithm", "jsonPath": ".spec.algorithm", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"key tag", "jsonPath": ".status.keyTag", "type": "integer"}"#)]
+#[serde(rename_all = "camelCase")]
+pub struct DNSSECKeySpec {
+    pub key_type: KeyType,
+
+    #[serde(default)]
+    pub algorithm: DnssecAlgorithm,
+
+    /// Secret holding the key material.
+    pub secret_ref: SecretRef,
+
+    /// Time from which the key signs the zone. Keys without an activation
+    /// time are active immediately.
+    pub activation_time: Option<DateTime<Utc>>,
+
+    /// Time from which the key no longer signs the zone.
+    pub retirement_time: Option<DateTime<Utc>>,
+}
+
+/// Role of a [`DNSSECKey`].
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum KeyType {
+    /// Key signing key, which signs the DNSKEY set.
+    #[serde(rename = "KSK")]
+    Ksk,
+    /// Zone signing key, which signs all other record sets.
+    #[serde(rename = "ZSK")]
+    Zsk,
+    /// Combined signing key, acting as both KSK and ZSK.
+    #[default]
+    #[serde(rename = "CSK")]
+    Csk,
+}
+
+impl KeyType {
+    /// Flags field of the key's DNSKEY record. Keys which sign the DNSKEY
+    /// set have the Secure Entry Point flag set.
+    pub const fn flags(&self) -> u16 {
+        match self {
+            KeyType::Zsk => 256,
+            KeyType::Ksk | KeyType::Csk => 257,
+        }
+    }
+}
+
+/// Reference to a Secret in the namespace of the referencing resource.
+///
+/// Secrets in other namespaces can't be referenced, since that would let
+/// anyone allowed to create a DNSSECKey use the key material of others.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct SecretRef {
+    pub name: String,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DNSSECKeyStatus {
+    /// Key tag of the key, see [`key_tag`].
+    pub key_tag: Option<u16>,
+
+    /// SHA-256 digest of the key, as published in DS records, see [`ds_digest`].
+    pub ds_digest: Option<String>,
+}
+
+impl DNSSECKeySpec {
+    /// Check that the key is activated before it is retired.
+    pub fn validate(&self) -> Result<(), DNSSECKeyError> {
+        if let (Some(activation), Some(retirement)) = (self.activation_time, self.retirement_time) {
+            if retirement <= activation {
+                return Err(DNSSECKeyError::RetiredBeforeActivation {
+                    activation,
+                    retirement,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check if the key should be signing the zone at `now`.
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.activation_time.is_none_or(|time| time <= now)
+            && self.retirement_time.is_none_or(|time| now < time)
+    }
+
+    /// Wire format RDATA of the DNSKEY record for `public_key`.
+    pub fn dnskey_rdata(&self, public_key: &[u8]) -> Vec<u8> {
+        let mut rdata = Vec::with_capacity(4 + public_key.len());
+        rdata.extend_from_slice(&self.key_type.flags().to_be_bytes());
+        // Protocol, which must always be 3.
+        rdata.push(3);
+        rdata.push(self.algorithm.number());
+        rdata.extend_from_slice(public_key);
+        rdata
+    }
+
+    /// DS record for `public_key` in presentation format, as published in
+    /// the parent of zone `owner`.
+    pub fn ds_record(&self, owner: &FullyQualifiedDomainName, public_key: &[u8]) -> String {
+        let rdata = self.dnskey_rdata(public_key);
+
+        format!(
+            "{} {} 2 {}",
+            key_tag(&rdata),
+            self.algorithm.number(),
+            ds_digest(owner, &rdata)
+        )
+    }
+}
+
+/// Key tag of a DNSKEY record, as specified in
+/// [RFC 4034 Appendix B](https://datatracker.ietf.org/doc/html/rfc4034#appendix-B).
+pub fn key_tag(dnskey_rdata: &[u8]) -> u16 {
+    let mut accumulator: u32 = 0;
+
+    for (i, byte) in dnskey_rdata.iter().enumerate() {
+        accumulator += if i & 1 == 0 {
+            u32::from(*byte) << 8
+        } else {
+            u32::from(*byte)
+        };
+    }
+
+    accumulator += (accumulator >> 16) & 0xFFFF;
+    (accumulator & 0xFFFF) as u16
+}
+
+/// SHA-256 DS digest of a DNSKEY record owned by `owner`, as lowercase hex
+/// digits, see [RFC 4509](https://datatracker.ietf.org/doc/html/rfc4509).
+pub fn ds_digest(owner: &FullyQualifiedDomainName, dnskey_rdata: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+
+    // Owner name in canonical wire format: length-prefixed lowercase labels.
+    for label in owner.to_string().to_lowercase().split('.') {
+        hasher.update([label.len() as u8]);
+        hasher.update(label.as_bytes());
+    }
+
+    hasher.update(dnskey_rdata);
+    hex(&hasher.finalize())
+}
+
+impl Display for DNSSECKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: Keys are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use kubizone_common::FullyQualifiedDomainName;
+
+    use super::{ds_digest, key_tag, DNSSECKeyError, DNSSECKeySpec, KeyType};
+
+    // Example key of RFC 4034 section 5.4 and RFC 4509 section 2.3.
+    const PUBLIC_KEY: &str = "01039e8a247418e318903b215a848acfd5f37f026bd4062db26c774c690968d5\
+        d56df8bfda91e6f36d9a279888f41333357c5e6029990d10fdf5663062a51276\
+        3326980a615ddbf17a05ddfcce7e5fb3abcca05a31b0957452d4521e83870789\
+        063115bf97f6c308ccf57cdc9ce7fe10f6ed1bd0cc0660038c50dcdb0feb963c\
+        2f17";
+
+    #[test]
+    fn test_key_tag_and_ds_digest() {
+        let public_key: Vec<u8> = (0..PUBLIC_KEY.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&PUBLIC_KEY[i..i + 2], 16).unwrap())
+            .collect();
+
+        // Algorithm 5 (RSASHA1) is not supported for signing, so build the
+        // RDATA of the example by hand.
+        let mut rdata = vec![1, 0, 3, 5];
+        rdata.extend_from_slice(&public_key);
+
+        assert_eq!(key_tag(&rdata), 60485);
+        assert_eq!(
+            ds_digest(
+                &FullyQualifiedDomainName::try_from("dskey.example.com.").unwrap(),
+                &rdata
+            ),
+            "d4b7d520e7bb5f0f67674a0cceb1e3e0614b93c4f9e99b8383f6a1e4469da50a"
+        );
+
+        let spec = DNSSECKeySpec {
+            key_type: KeyType::Zsk,
+            ..Default::default()
+        };
+        assert_eq!(&spec.dnskey_rdata(&public_key)[..4], &[1, 0, 3, 13]);
+    }
+
+    #[test]
+    fn test_key_lifecycle() {
+        let now = Utc::now();
+
+        let mut spec = DNSSECKeySpec::default();
+        assert!(spec.is_active(now));
+
+        spec.activation_time = Some(now + Duration::hours(1));
+        assert!(!spec.is_active(now));
+
+        spec.activation_time = Some(now - Duration::hours(1));
+        spec.retirement_time = Some(now);
+        assert!(!spec.is_active(now));
+        assert!(spec.is_active(now - Duration::minutes(1)));
+        assert_eq!(spec.validate(), Ok(()));
+
+        spec.retirement_time = spec.activation_time;
+        assert!(matches!(
+            spec.validate(),
+            Err(DNSSECKeyError::RetiredBeforeActivation { .. })
+        ));
+    }
+}
@@ -0,0 +1,360 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use kube::core::ObjectMeta;
+use kubizone_common::{DomainName, FullyQualifiedDomainName};
+
+use super::{
+    DelegationDenied, DelegationPattern, DomainExt, Record, RecordDelegation, RecordSpec,
+    RecordStatus, Zone,
+};
+
+/// Structured account of how a zone decided whether to accept a record.
+///
+/// Produced by [`Zone::explain`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    /// Fully qualified domain name the record would have in this zone.
+    pub fqdn: Option<FullyQualifiedDomainName>,
+
+    /// Every delegation of the zone, in order.
+    pub delegations: Vec<DelegationTrace>,
+
+    /// Final decision, identical to the one made by [`Zone::validate_record`]
+    /// once the record has been approved.
+    pub decision: Result<(), DelegationDenied>,
+
+    /// Whether the record would only be published once approved by the
+    /// owner of the zone, as required by delegations with `requireApproval`
+    /// set. See [`ZoneStatus::approve`](super::ZoneStatus::approve).
+    pub requires_approval: bool,
+}
+
+/// Evaluation of a single delegation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DelegationTrace {
+    /// Index of the delegation within `.spec.delegations`.
+    pub index: usize,
+
+    /// Whether the delegation covers the record's namespace, by name or, if
+    /// labels were provided, by its `namespaceSelector`. Rules of delegations
+    /// which don't are not evaluated, and left empty.
+    pub covers_namespace: bool,
+
+    pub records: Vec<RuleTrace>,
+    pub deny_records: Vec<RuleTrace>,
+}
+
+/// Evaluation of a single record delegation rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleTrace {
+    /// Index of the rule within `records` or `denyRecords`.
+    pub index: usize,
+    pub pattern: DelegationPattern,
+    pub pattern_matches: bool,
+    pub type_matches: bool,
+    pub ttl_matches: bool,
+}
+
+impl RuleTrace {
+    /// Whether the rule matched the record in every respect.
+    pub fn matches(&self) -> bool {
+        self.pattern_matches && self.type_matches && self.ttl_matches
+    }
+}
+
+impl Zone {
+    /// Explain whether a record with the given spec, in the given namespace,
+    /// would be accepted by this zone, and why.
+    ///
+    /// Partially qualified domain names are assumed to be relative to this zone.
+    ///
+    /// If any delegation of this zone might cover the namespace by its
+    /// `namespaceSelector`, use [`Zone::explain_with_labels`] instead.
+    pub fn explain(&self, spec: &RecordSpec, namespace: &str) -> Explanation {
+        self.explain_inner(spec, namespace, None)
+    }
+
+    /// Like [`Zone::explain`], but also evaluates the `namespaceSelector`
+    /// of delegations against the labels of the namespace.
+    pub fn explain_with_labels(
+        &self,
+        spec: &RecordSpec,
+        namespace: &str,
+        namespace_labels: &BTreeMap<String, String>,
+    ) -> Explanation {
+        self.explain_inner(spec, namespace, Some(namespace_labels))
+    }
+
+    fn explain_inner(
+        &self,
+        spec: &RecordSpec,
+        namespace: &str,
+        namespace_labels: Option<&BTreeMap<String, String>>,
+    ) -> Explanation {
+        let fqdn = match (&spec.domain_name, self.fqdn()) {
+            (DomainName::Full(full), _) => Some(full.clone()),
+            (DomainName::Partial(partial), Some(origin)) => Some(partial.with_origin(origin)),
+            (DomainName::Partial(_), None) => None,
+        };
+
+        let record = Record {
+            metadata: ObjectMeta {
+                namespace: Some(namespace.to_string()),
+                ..Default::default()
+            },
+            spec: spec.clone(),
+            status: Some(RecordStatus {
+                fqdn: fqdn.clone(),
+                ..Default::default()
+            }),
+        };
+
+        // Dry-runs have no record to approve, so approval is assumed, and
+        // reported separately.
+        let (decision, requires_approval) = match self.compile_delegations() {
+            Ok(delegations) => {
+                let decision = delegations
+                    .evaluate_record_approved(&record, namespace_labels, true)
+                    .map(drop);
+                let requires_approval = decision.is_ok()
+                    && matches!(
+                        delegations.evaluate_record_approved(&record, namespace_labels, false),
+                        Err(DelegationDenied::ApprovalRequired { .. })
+                    );
+
+                (decision, requires_approval)
+            }
+            Err(_) => (Err(DelegationDenied::ParentFqdnMissing), false),
+        };
+
+        let delegations = match (self.fqdn(), &fqdn) {
+            (Some(origin), Some(fqdn)) => {
+                let ttl = spec.ttl.unwrap_or(self.spec.ttl);
+                let trace = |rules: &[RecordDelegation]| {
+                    rules
+                        .iter()
+                        .enumerate()
+                        .map(|(index, rule)| RuleTrace {
+                            index,
+                            pattern: rule.pattern.clone(),
+                            pattern_matches: rule.pattern.with_origin(origin).matches(fqdn),
+                            type_matches: rule.types.is_empty() || rule.types.contains(&spec.type_),
+                            ttl_matches: rule.validate_ttl(ttl),
+                        })
+                        .collect()
+                };
+
+                self.spec
+                    .delegations
+                    .iter()
+                    .enumerate()
+                    .map(|(index, delegation)| {
+                        let covers_namespace = match namespace_labels {
+                            Some(labels) => delegation.covers_labelled_namespace(namespace, labels),
+                            None => delegation.covers_namespace(namespace),
+                        };
+
+                        DelegationTrace {
+                            index,
+                            covers_namespace,
+                            records: if covers_namespace {
+                                trace(&delegation.records)
+                            } else {
+                                Vec::new()
+                            },
+                            deny_records: if covers_namespace {
+                                trace(&delegation.deny_records)
+                            } else {
+                                Vec::new()
+                            },
+                        }
+                    })
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+
+        Explanation {
+            fqdn,
+            delegations,
+            decision,
+            requires_approval,
+        }
+    }
+}
+
+/// Formats the explanation as a human-readable, multi-line report.
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.decision {
+            Ok(()) if self.requires_approval => writeln!(f, "allowed, once approved")?,
+            Ok(()) => writeln!(f, "allowed")?,
+            Err(denied) => writeln!(f, "denied: {denied}")?,
+        }
+
+        for delegation in &self.delegations {
+            if !delegation.covers_namespace {
+                writeln!(
+                    f,
+                    "delegation {}: does not cover namespace",
+                    delegation.index
+                )?;
+                continue;
+            }
+
+            writeln!(f, "delegation {}:", delegation.index)?;
+            for (kind, rules) in [
+                ("allow", &delegation.records),
+                ("deny", &delegation.deny_records),
+            ] {
+                for rule in rules {
+                    writeln!(
+                        f,
+                        "  {kind} {} {}: pattern {}, type {}, ttl {}",
+                        rule.index,
+                        rule.pattern,
+                        verdict(rule.pattern_matches),
+                        verdict(rule.type_matches),
+                        verdict(rule.ttl_matches),
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn verdict(matches: bool) -> &'static str {
+    if matches {
+        "matches"
+    } else {
+        "does not match"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use kube::core::ObjectMeta;
+    use kubizone_common::{DomainName, FullyQualifiedDomainName, Pattern, Type};
+
+    use crate::v1alpha1::{
+        Delegation, DelegationDenied, LabelSelector, RecordDelegation, RecordSpec, Zone,
+        ZonePatterns, ZoneSpec, ZoneStatus,
+    };
+
+    #[test]
+    fn test_explain() {
+        let zone = Zone {
+            spec: ZoneSpec {
+                domain_name: DomainName::try_from("example.org.").unwrap(),
+                delegations: vec![
+                    Delegation {
+                        namespaces: vec![String::from("other")],
+                        namespace_selector: None,
+                        zones: ZonePatterns::default(),
+                        records: vec![],
+                        deny_records: vec![],
+                        require_approval: false,
+                    },
+                    Delegation {
+                        namespaces: vec![String::from("default")],
+                        namespace_selector: None,
+                        zones: ZonePatterns::default(),
+                        records: vec![RecordDelegation {
+                            pattern: Pattern::try_from("www").unwrap().into(),
+                            types: vec![Type::CNAME],
+                            min_ttl: None,
+                            max_ttl: None,
+                        }],
+                        deny_records: vec![],
+                        require_approval: false,
+                    },
+                ],
+                ..Default::default()
+            },
+            status: Some(ZoneStatus {
+                fqdn: Some(FullyQualifiedDomainName::try_from("example.org.").unwrap()),
+                ..Default::default()
+            }),
+            metadata: ObjectMeta::default(),
+        };
+
+        let explanation = zone.explain(
+            &RecordSpec {
+                domain_name: DomainName::try_from("www").unwrap(),
+                type_: Type::A,
+                ..Default::default()
+            },
+            "default",
+        );
+
+        assert_eq!(
+            explanation.fqdn,
+            Some(FullyQualifiedDomainName::try_from("www.example.org.").unwrap())
+        );
+        assert_eq!(
+            explanation.decision,
+            Err(DelegationDenied::TypeNotAllowed {
+                type_: Type::A,
+                allowed: vec![Type::CNAME]
+            })
+        );
+
+        assert!(!explanation.delegations[0].covers_namespace);
+        assert!(explanation.delegations[0].records.is_empty());
+
+        let rule = &explanation.delegations[1].records[0];
+        assert!(rule.pattern_matches && !rule.type_matches && rule.ttl_matches);
+        assert!(!rule.matches());
+        assert!(!explanation.requires_approval);
+    }
+
+    #[test]
+    fn test_explain_approval() {
+        let mut zone = crate::test_util::delegated_zone();
+        zone.spec.delegations[0].require_approval = true;
+
+        let spec = crate::test_util::delegated_record().spec;
+        let explanation = zone.explain(&spec, "default");
+        assert_eq!(explanation.decision, Ok(()));
+        assert!(explanation.requires_approval);
+        assert!(explanation
+            .to_string()
+            .starts_with("allowed, once approved\n"));
+
+        zone.spec.delegations[0].require_approval = false;
+        assert!(!zone.explain(&spec, "default").requires_approval);
+    }
+
+    #[test]
+    fn test_explain_namespace_selector() {
+        let mut zone = crate::test_util::delegated_zone();
+        zone.spec.delegations[0].namespaces = vec![];
+        zone.spec.delegations[0].namespace_selector = Some(LabelSelector {
+            match_labels: BTreeMap::from([(String::from("tenant"), String::from("blue"))]),
+            match_expressions: vec![],
+        });
+
+        let spec = crate::test_util::delegated_record().spec;
+        assert_eq!(
+            zone.explain(&spec, "default").decision,
+            Err(DelegationDenied::NamespaceLabelsRequired {
+                namespace: String::from("default")
+            })
+        );
+
+        let blue = BTreeMap::from([(String::from("tenant"), String::from("blue"))]);
+        let explanation = zone.explain_with_labels(&spec, "default", &blue);
+        assert_eq!(explanation.decision, Ok(()));
+        assert!(explanation.delegations[0].covers_namespace);
+        assert!(explanation.delegations[0].records[0].matches());
+
+        let red = BTreeMap::from([(String::from("tenant"), String::from("red"))]);
+        let explanation = zone.explain_with_labels(&spec, "default", &red);
+        assert!(explanation.decision.is_err());
+        assert!(!explanation.delegations[0].covers_namespace);
+    }
+}
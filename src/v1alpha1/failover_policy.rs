@@ -0,0 +1,251 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Utc};
+use kube::{CustomResource, ResourceExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{FailoverSet, HealthCheckRef, ZoneEntry};
+
+/// Active/passive failover between two sets of existing Records.
+///
+/// Where a [`DnsFailoverPool`](super::DnsFailoverPool) owns its targets, a
+/// failover policy switches between Records which are managed as usual.
+/// While the primary set is healthy, entries of the secondary records are
+/// withheld from the zone, and vice versa. Like pools, policies fail open
+/// to the primary set when neither set is healthy.
+///
+/// Records are referenced by name, in the namespace of the policy.
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "FailoverPolicy",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "FailoverPolicy",
+        namespaced
+    )
+)]
+#[kube(status = "FailoverPolicyStatus")]
+#[kube(printcolumn = r#"{"name":"active", "jsonPath": ".status.activeSet", "type": "string"}"#)]
+#[kube(
+    printcolumn = r#"{"name":"last failover", "jsonPath": ".status.lastTransitionTime", "type": "date"}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverPolicySpec {
+    /// Records published while healthy.
+    pub primary: FailoverRecordSet,
+
+    /// Records published only while the primary set is unhealthy.
+    pub secondary: FailoverRecordSet,
+}
+
+/// Named Records whose health is determined by a single health check.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverRecordSet {
+    /// Names of the Records in the set.
+    pub records: Vec<String>,
+
+    /// Health check which determines whether the set can be published.
+    ///
+    /// Sets without a health check are always considered healthy.
+    pub health_check_ref: Option<HealthCheckRef>,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverPolicyStatus {
+    /// Set of records currently being published.
+    pub active_set: Option<FailoverSet>,
+
+    /// Time at which the active set last changed.
+    pub last_transition_time: Option<DateTime<Utc>>,
+}
+
+impl FailoverRecordSet {
+    fn is_healthy<F>(&self, is_healthy: F) -> bool
+    where
+        F: FnMut(&HealthCheckRef) -> bool,
+    {
+        self.health_check_ref.as_ref().is_none_or(is_healthy)
+    }
+}
+
+impl FailoverPolicySpec {
+    /// Select the set which should currently be published, given a
+    /// function which reports whether a referenced health check is passing.
+    pub fn select_set<F>(&self, mut is_healthy: F) -> FailoverSet
+    where
+        F: FnMut(&HealthCheckRef) -> bool,
+    {
+        if self.primary.is_healthy(&mut is_healthy) {
+            FailoverSet::Primary
+        } else if self.secondary.is_healthy(&mut is_healthy) {
+            FailoverSet::Secondary
+        } else {
+            // Nothing is healthy, fail open rather than removing the records entirely.
+            FailoverSet::Primary
+        }
+    }
+
+    /// Records of the given set.
+    pub fn records(&self, set: FailoverSet) -> &FailoverRecordSet {
+        match set {
+            FailoverSet::Primary => &self.primary,
+            FailoverSet::Secondary => &self.secondary,
+        }
+    }
+}
+
+impl FailoverPolicy {
+    /// Check if the entry originates from a Record of the given set.
+    pub fn is_in_set(&self, entry: &ZoneEntry, set: FailoverSet) -> bool {
+        entry.source.as_ref().is_some_and(|source| {
+            source.kind == "Record"
+                && source.namespace == self.namespace()
+                && self.spec.records(set).records.contains(&source.name)
+        })
+    }
+
+    /// Remove the entries of the inactive set from `entries`.
+    ///
+    /// Entries which do not originate from either set, including entries
+    /// without a known source, are left untouched.
+    pub fn active_entries(&self, entries: &[ZoneEntry], active: FailoverSet) -> Vec<ZoneEntry> {
+        let inactive = match active {
+            FailoverSet::Primary => FailoverSet::Secondary,
+            FailoverSet::Secondary => FailoverSet::Primary,
+        };
+
+        entries
+            .iter()
+            .filter(|entry| !self.is_in_set(entry, inactive) || self.is_in_set(entry, active))
+            .cloned()
+            .collect()
+    }
+}
+
+impl FailoverPolicyStatus {
+    /// Record the currently active set, updating the transition time if it changed.
+    pub fn set_active(&mut self, set: FailoverSet, now: DateTime<Utc>) {
+        if self.active_set != Some(set) {
+            self.active_set = Some(set);
+            self.last_transition_time = Some(now);
+        }
+    }
+}
+
+impl Display for FailoverPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: Policies are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use kube::core::ObjectMeta;
+
+    use crate::v1alpha1::{EntrySource, FailoverSet, HealthCheckRef, ZoneEntry};
+
+    use super::{FailoverPolicy, FailoverPolicySpec, FailoverPolicyStatus, FailoverRecordSet};
+
+    fn entry(line: &str, record: &str) -> ZoneEntry {
+        let mut entry: ZoneEntry = line.parse().unwrap();
+        entry.source = Some(EntrySource {
+            kind: String::from("Record"),
+            namespace: Some(String::from("default")),
+            name: String::from(record),
+            uid: None,
+        });
+        entry
+    }
+
+    fn set(records: &[&str], check: &str) -> FailoverRecordSet {
+        FailoverRecordSet {
+            records: records.iter().map(ToString::to_string).collect(),
+            health_check_ref: Some(HealthCheckRef {
+                name: String::from(check),
+                namespace: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_failover_policy() {
+        let policy = FailoverPolicy {
+            metadata: ObjectMeta {
+                name: Some(String::from("www")),
+                namespace: Some(String::from("default")),
+                ..Default::default()
+            },
+            spec: FailoverPolicySpec {
+                primary: set(&["www-primary"], "primary"),
+                secondary: set(&["www-secondary"], "secondary"),
+            },
+            status: None,
+        };
+
+        assert_eq!(policy.spec.select_set(|_| true), FailoverSet::Primary);
+        assert_eq!(
+            policy.spec.select_set(|check| check.name == "secondary"),
+            FailoverSet::Secondary
+        );
+        assert_eq!(policy.spec.select_set(|_| false), FailoverSet::Primary);
+
+        let entries = vec![
+            entry("www.example.org. 300 IN A 192.168.0.1", "www-primary"),
+            entry("www.example.org. 300 IN A 10.0.0.1", "www-secondary"),
+            entry("mail.example.org. 300 IN A 192.168.0.2", "mail"),
+        ];
+
+        let active = policy.active_entries(&entries, FailoverSet::Primary);
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[0].rdata, "192.168.0.1");
+        assert_eq!(active[1].rdata, "192.168.0.2");
+
+        let active = policy.active_entries(&entries, FailoverSet::Secondary);
+        assert_eq!(active.len(), 2);
+        assert_eq!(active[0].rdata, "10.0.0.1");
+
+        let mut status = FailoverPolicyStatus::default();
+        let now = Utc::now();
+        status.set_active(FailoverSet::Primary, now);
+        status.set_active(FailoverSet::Primary, now + chrono::Duration::seconds(60));
+        assert_eq!(status.last_transition_time, Some(now));
+    }
+}
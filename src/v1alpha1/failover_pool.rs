@@ -0,0 +1,240 @@
+use std::fmt::Display;
+
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, Type};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::ZoneRef;
+
+/// A pool of interchangeable targets for a single domain name, split into a
+/// primary and a secondary set.
+///
+/// The primary set is published for as long as at least one of its targets
+/// is healthy. Once all primaries are unhealthy, the healthy secondaries are
+/// published instead. If neither set has any healthy targets, the pool fails
+/// open and publishes the full primary set, since removing the name from the
+/// zone entirely is rarely what anyone wants.
+///
+/// Health is not evaluated by this crate. Each target may reference a health
+/// check by name, and it is up to the controller to resolve those references
+/// against whatever health checking mechanism it has available.
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "DnsFailoverPool",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "DnsFailoverPool",
+        namespaced
+    )
+)]
+#[kube(status = "DnsFailoverPoolStatus")]
+#[kube(printcolumn = r#"{"name":"domain name", "jsonPath": ".spec.domainName", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"type", "jsonPath": ".spec.type", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"active", "jsonPath": ".status.activeSet", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"fqdn", "jsonPath": ".status.fqdn", "type": "string"}"#)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsFailoverPoolSpec {
+    #[schemars(schema_with = "crate::schema::domain_name")]
+    pub domain_name: DomainName,
+    pub zone_ref: Option<ZoneRef>,
+    #[serde(rename = "type")]
+    pub type_: Type,
+    #[serde(default = "super::defaults::class")]
+    pub class: Class,
+    pub ttl: Option<u32>,
+
+    /// Targets which are published while at least one of them is healthy.
+    pub primary: Vec<FailoverTarget>,
+
+    /// Targets which are published only when all primary targets are unhealthy.
+    #[serde(default)]
+    pub secondary: Vec<FailoverTarget>,
+}
+
+/// A single answer in a failover pool.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverTarget {
+    pub rdata: String,
+
+    /// Health check which determines whether this target can be published.
+    ///
+    /// Targets without a health check are always considered healthy.
+    pub health_check_ref: Option<HealthCheckRef>,
+}
+
+/// Reference to a health check, optionally in a specific namespace.
+///
+/// What the name refers to is up to the controller evaluating the pool.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct HealthCheckRef {
+    pub name: String,
+    pub namespace: Option<String>,
+}
+
+/// Identifies which set of targets in a pool is currently published.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum FailoverSet {
+    #[default]
+    Primary,
+    Secondary,
+}
+
+impl Display for FailoverSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FailoverSet::Primary => f.write_str("Primary"),
+            FailoverSet::Secondary => f.write_str("Secondary"),
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsFailoverPoolStatus {
+    #[schemars(schema_with = "crate::schema::optional_fully_qualified_domain_name")]
+    pub fqdn: Option<FullyQualifiedDomainName>,
+
+    /// Set of targets currently being published.
+    pub active_set: Option<FailoverSet>,
+
+    /// Rdata of the targets currently being published.
+    #[serde(default)]
+    pub published: Vec<String>,
+}
+
+/// Result of evaluating the health of a [`DnsFailoverPool`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FailoverSelection<'a> {
+    pub set: FailoverSet,
+    pub targets: Vec<&'a FailoverTarget>,
+}
+
+impl DnsFailoverPoolSpec {
+    /// Select the targets which should currently be published, given a
+    /// function which reports whether a referenced health check is passing.
+    pub fn select_targets<F>(&self, mut is_healthy: F) -> FailoverSelection<'_>
+    where
+        F: FnMut(&HealthCheckRef) -> bool,
+    {
+        let mut healthy =
+            |target: &&FailoverTarget| target.health_check_ref.as_ref().is_none_or(&mut is_healthy);
+
+        let primaries: Vec<_> = self.primary.iter().filter(&mut healthy).collect();
+        if !primaries.is_empty() {
+            return FailoverSelection {
+                set: FailoverSet::Primary,
+                targets: primaries,
+            };
+        }
+
+        let secondaries: Vec<_> = self.secondary.iter().filter(&mut healthy).collect();
+        if !secondaries.is_empty() {
+            return FailoverSelection {
+                set: FailoverSet::Secondary,
+                targets: secondaries,
+            };
+        }
+
+        // Nothing is healthy, fail open rather than removing the name entirely.
+        FailoverSelection {
+            set: FailoverSet::Primary,
+            targets: self.primary.iter().collect(),
+        }
+    }
+}
+
+impl Display for DnsFailoverPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: Pools are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DnsFailoverPoolSpec, FailoverSet, FailoverTarget, HealthCheckRef};
+
+    fn target(rdata: &str, check: &str) -> FailoverTarget {
+        FailoverTarget {
+            rdata: String::from(rdata),
+            health_check_ref: Some(HealthCheckRef {
+                name: String::from(check),
+                namespace: None,
+            }),
+        }
+    }
+
+    #[test]
+    fn test_failover_selection() {
+        let pool = DnsFailoverPoolSpec {
+            primary: vec![target("192.168.0.1", "a"), target("192.168.0.2", "b")],
+            secondary: vec![target("10.0.0.1", "c")],
+            ..Default::default()
+        };
+
+        // One healthy primary is enough to keep the primary set published.
+        let selection = pool.select_targets(|check| check.name != "a");
+        assert_eq!(selection.set, FailoverSet::Primary);
+        assert_eq!(selection.targets, vec![&pool.primary[1]]);
+
+        // All primaries down, fail over to secondaries.
+        let selection = pool.select_targets(|check| check.name == "c");
+        assert_eq!(selection.set, FailoverSet::Secondary);
+        assert_eq!(selection.targets, vec![&pool.secondary[0]]);
+
+        // Nothing healthy, fail open with all primaries.
+        let selection = pool.select_targets(|_| false);
+        assert_eq!(selection.set, FailoverSet::Primary);
+        assert_eq!(selection.targets.len(), 2);
+    }
+}
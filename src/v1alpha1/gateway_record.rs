@@ -0,0 +1,223 @@
+use std::fmt::Display;
+
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::{Class, FullyQualifiedDomainName, Type};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    hostnames::{address_targets, host_record_specs, qualify_hostnames},
+    RecordSpec, ZoneRef,
+};
+
+/// Publishes the hostnames of a Gateway API Gateway or HTTPRoute into a zone.
+///
+/// The hostnames are taken from the listeners of a Gateway, or the `hostnames`
+/// of an HTTPRoute, and pointed at the addresses of the (parent) Gateway, see
+/// [`GatewayRecordSpec::record_specs`].
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "GatewayRecord",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "GatewayRecord",
+        namespaced
+    )
+)]
+#[kube(status = "GatewayRecordStatus")]
+#[kube(printcolumn = r#"{"name":"kind", "jsonPath": ".spec.targetRef.kind", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"target", "jsonPath": ".spec.targetRef.name", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"zone", "jsonPath": ".spec.zoneRef.name", "type": "string"}"#)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayRecordSpec {
+    /// Gateway or HTTPRoute whose hostnames are published.
+    pub target_ref: GatewayTargetRef,
+
+    /// Zone the hostnames are published into.
+    pub zone_ref: Option<ZoneRef>,
+    #[serde(default = "super::defaults::class")]
+    pub class: Class,
+    pub ttl: Option<u32>,
+}
+
+/// Reference to a Gateway API resource in the namespace of the referencing
+/// resource.
+///
+/// Resources in other namespaces can't be referenced, since that would let
+/// anyone allowed to create a GatewayRecord publish the hostnames of any
+/// Gateway or HTTPRoute in the cluster.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct GatewayTargetRef {
+    #[serde(default)]
+    pub kind: GatewayTargetKind,
+    pub name: String,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum GatewayTargetKind {
+    #[default]
+    Gateway,
+    #[serde(rename = "HTTPRoute")]
+    HttpRoute,
+}
+
+/// Address of a Gateway, as reported in its `status.addresses`.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq)]
+pub struct GatewayAddress {
+    /// `IPAddress`, `Hostname` or an implementation-specific type.
+    /// Addresses without a type are IP addresses.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    pub value: String,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct GatewayRecordStatus {
+    /// Hostnames currently being published.
+    #[serde(default)]
+    pub hostnames: Vec<FullyQualifiedDomainName>,
+}
+
+impl GatewayAddress {
+    fn is_hostname(&self) -> bool {
+        self.type_.as_deref() == Some("Hostname")
+    }
+}
+
+impl GatewayRecordSpec {
+    /// Type and record data for the addresses of a Gateway.
+    ///
+    /// IP addresses become A and AAAA records. Gateways which only have
+    /// hostnames are aliased with a CNAME to the first of them.
+    /// Implementation-specific addresses are ignored.
+    pub fn targets(&self, addresses: &[GatewayAddress]) -> Vec<(Type, String)> {
+        address_targets(
+            addresses
+                .iter()
+                .filter(|address| !address.is_hostname())
+                .map(|address| address.value.as_str()),
+            addresses
+                .iter()
+                .filter(|address| address.is_hostname())
+                .map(|address| address.value.as_str()),
+        )
+    }
+
+    /// Records publishing `hostnames` at the Gateway's `addresses`.
+    ///
+    /// `hostnames` are the listener hostnames of the Gateway, or the
+    /// `hostnames` of the HTTPRoute, depending on the kind of target.
+    /// Duplicates and invalid hostnames are skipped.
+    pub fn record_specs(
+        &self,
+        hostnames: &[String],
+        addresses: &[GatewayAddress],
+    ) -> Vec<RecordSpec> {
+        host_record_specs(
+            &qualify_hostnames(hostnames.iter().map(String::as_str)),
+            &self.targets(addresses),
+            self.zone_ref.as_ref(),
+            self.class,
+            self.ttl,
+        )
+    }
+}
+
+impl Display for GatewayRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: GatewayRecords are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kubizone_common::Type;
+
+    use super::{GatewayAddress, GatewayRecordSpec, GatewayTargetKind};
+
+    fn address(type_: Option<&str>, value: &str) -> GatewayAddress {
+        GatewayAddress {
+            type_: type_.map(String::from),
+            value: String::from(value),
+        }
+    }
+
+    #[test]
+    fn test_gateway_records() {
+        let spec: GatewayRecordSpec =
+            serde_json::from_str(r#"{"targetRef":{"kind":"HTTPRoute","name":"web"}}"#).unwrap();
+        assert_eq!(spec.target_ref.kind, GatewayTargetKind::HttpRoute);
+
+        let hostnames = vec![
+            String::from("www.example.org"),
+            String::from("*.example.org"),
+        ];
+
+        let records = spec.record_specs(
+            &hostnames,
+            &[
+                address(Some("IPAddress"), "203.0.113.1"),
+                address(None, "2001:db8::1"),
+                address(Some("Hostname"), "gw.example.net"),
+                address(Some("example.com/custom"), "opaque"),
+            ],
+        );
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].to_string(), "www.example.org. IN A 203.0.113.1");
+        assert_eq!(records[3].to_string(), "*.example.org. IN AAAA 2001:db8::1");
+
+        let records = spec.record_specs(&hostnames, &[address(Some("Hostname"), "gw.example.net")]);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].type_, Type::CNAME);
+        assert_eq!(records[1].rdata, "gw.example.net.");
+    }
+}
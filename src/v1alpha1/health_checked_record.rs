@@ -0,0 +1,427 @@
+use std::{
+    fmt::Display,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use chrono::{DateTime, Utc};
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, Type};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{EntrySource, ZoneEntry, ZoneRef};
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum HealthCheckError {
+    #[error("{0} must be at least 1")]
+    Zero(&'static str),
+    #[error("only A and AAAA records can be health checked, not {0}")]
+    UnsupportedType(Type),
+    #[error("{0:?} is not a valid address")]
+    InvalidAddress(String),
+}
+
+/// A record which is only published while its health check passes.
+///
+/// Unlike the health check references of a [`DnsFailoverPool`](super::DnsFailoverPool),
+/// the probe is embedded in the resource, and executed by the controller
+/// itself. Probe results are accumulated in the status with
+/// [`HealthCheckedRecordStatus::record_probe`], and the record is
+/// published only once the status reports it as healthy.
+///
+/// Probes only ever connect to the address published by the record itself,
+/// so the controller can't be pointed at arbitrary hosts on its network.
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "HealthCheckedRecord",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "HealthCheckedRecord",
+        namespaced
+    )
+)]
+#[kube(status = "HealthCheckedRecordStatus")]
+#[kube(printcolumn = r#"{"name":"domain name", "jsonPath": ".spec.domainName", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"type", "jsonPath": ".spec.type", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"data", "jsonPath": ".spec.rdata", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"healthy", "jsonPath": ".status.healthy", "type": "boolean"}"#)]
+#[kube(printcolumn = r#"{"name":"fqdn", "jsonPath": ".status.fqdn", "type": "string"}"#)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheckedRecordSpec {
+    #[schemars(schema_with = "crate::schema::domain_name")]
+    pub domain_name: DomainName,
+    pub zone_ref: Option<ZoneRef>,
+    #[serde(rename = "type")]
+    pub type_: Type,
+    #[serde(default = "super::defaults::class")]
+    pub class: Class,
+    pub ttl: Option<u32>,
+    pub rdata: String,
+
+    /// Probe deciding whether the record is published.
+    pub health_check: HealthCheck,
+}
+
+/// Periodic probe, with thresholds modelled after kubernetes container probes.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    pub probe: Probe,
+
+    /// Seconds between probes.
+    #[serde(default = "super::defaults::probe_period_seconds")]
+    #[schemars(range(min = 1))]
+    pub period_seconds: u32,
+
+    /// Seconds after which a probe is considered failed.
+    #[serde(default = "super::defaults::probe_timeout_seconds")]
+    #[schemars(range(min = 1))]
+    pub timeout_seconds: u32,
+
+    /// Consecutive successes required for an unhealthy record to become healthy.
+    #[serde(default = "super::defaults::probe_success_threshold")]
+    #[schemars(range(min = 1))]
+    pub success_threshold: u32,
+
+    /// Consecutive failures required for a healthy record to become unhealthy.
+    #[serde(default = "super::defaults::probe_failure_threshold")]
+    #[schemars(range(min = 1))]
+    pub failure_threshold: u32,
+}
+
+/// How the address of the record is probed.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum Probe {
+    /// Succeeds if the request returns one of the expected status codes.
+    Http(HttpProbe),
+    /// Succeeds if a TCP connection can be established.
+    Tcp(TcpProbe),
+}
+
+impl Default for Probe {
+    fn default() -> Self {
+        Probe::Tcp(TcpProbe::default())
+    }
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct HttpProbe {
+    pub port: u16,
+    #[serde(default = "default_path")]
+    pub path: String,
+    #[serde(default)]
+    pub https: bool,
+
+    /// Status codes considered healthy. Defaults to any 2xx or 3xx status.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expected_statuses: Vec<u16>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TcpProbe {
+    pub port: u16,
+}
+
+fn default_path() -> String {
+    String::from("/")
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheckedRecordStatus {
+    #[schemars(schema_with = "crate::schema::optional_fully_qualified_domain_name")]
+    pub fqdn: Option<FullyQualifiedDomainName>,
+
+    /// Whether the record is currently published. Unset until enough
+    /// probes have completed to cross either threshold.
+    pub healthy: Option<bool>,
+
+    #[serde(default)]
+    pub consecutive_successes: u32,
+    #[serde(default)]
+    pub consecutive_failures: u32,
+
+    pub last_probe_time: Option<DateTime<Utc>>,
+
+    /// Human readable outcome of the most recent probe.
+    pub last_probe_message: Option<String>,
+}
+
+impl HttpProbe {
+    /// Check if the returned status code indicates a healthy target.
+    pub fn is_expected_status(&self, status: u16) -> bool {
+        if self.expected_statuses.is_empty() {
+            (200..400).contains(&status)
+        } else {
+            self.expected_statuses.contains(&status)
+        }
+    }
+}
+
+impl HealthCheck {
+    /// Check that the interval, timeout and thresholds are all non-zero.
+    pub fn validate(&self) -> Result<(), HealthCheckError> {
+        for (field, value) in [
+            ("periodSeconds", self.period_seconds),
+            ("timeoutSeconds", self.timeout_seconds),
+            ("successThreshold", self.success_threshold),
+            ("failureThreshold", self.failure_threshold),
+        ] {
+            if value == 0 {
+                return Err(HealthCheckError::Zero(field));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl HealthCheckedRecordSpec {
+    /// Check that the health check is valid, and that the record has an
+    /// address to probe.
+    pub fn validate(&self) -> Result<(), HealthCheckError> {
+        self.health_check.validate()?;
+        self.probe_address()?;
+        Ok(())
+    }
+
+    /// Address the probe connects to, which is always the one published by
+    /// the record. Only `A` and `AAAA` records can be probed.
+    pub fn probe_address(&self) -> Result<IpAddr, HealthCheckError> {
+        let rdata = self.rdata.trim();
+        let invalid = |_| HealthCheckError::InvalidAddress(rdata.to_string());
+
+        match self.type_ {
+            Type::A => rdata.parse::<Ipv4Addr>().map(IpAddr::from).map_err(invalid),
+            Type::AAAA => rdata.parse::<Ipv6Addr>().map(IpAddr::from).map_err(invalid),
+            type_ => Err(HealthCheckError::UnsupportedType(type_)),
+        }
+    }
+}
+
+impl HealthCheckedRecordStatus {
+    /// Accumulate the result of a single probe, updating `healthy` once
+    /// the relevant threshold of `check` has been reached.
+    pub fn record_probe(
+        &mut self,
+        check: &HealthCheck,
+        success: bool,
+        message: Option<String>,
+        now: DateTime<Utc>,
+    ) {
+        if success {
+            self.consecutive_successes = self.consecutive_successes.saturating_add(1);
+            self.consecutive_failures = 0;
+
+            if self.consecutive_successes >= check.success_threshold {
+                self.healthy = Some(true);
+            }
+        } else {
+            self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+            self.consecutive_successes = 0;
+
+            if self.consecutive_failures >= check.failure_threshold {
+                self.healthy = Some(false);
+            }
+        }
+
+        self.last_probe_time = Some(now);
+        self.last_probe_message = message;
+    }
+}
+
+impl HealthCheckedRecord {
+    /// Check if the record should currently be published.
+    pub fn is_healthy(&self) -> bool {
+        self.status
+            .as_ref()
+            .is_some_and(|status| status.healthy == Some(true))
+    }
+
+    /// Entries to publish for this record, which is nothing while it is not
+    /// healthy or before its fully qualified domain name has been resolved.
+    ///
+    /// Records without a TTL inherit `default_ttl`, usually that of the zone.
+    pub fn zone_entries(&self, default_ttl: u32) -> Vec<ZoneEntry> {
+        let Some(fqdn) = self
+            .status
+            .as_ref()
+            .filter(|_| self.is_healthy())
+            .and_then(|status| status.fqdn.clone())
+        else {
+            return Vec::new();
+        };
+
+        vec![ZoneEntry {
+            fqdn,
+            type_: self.spec.type_,
+            class: self.spec.class,
+            ttl: self.spec.ttl.unwrap_or(default_ttl),
+            rdata: self.spec.rdata.clone(),
+            response_policy: None,
+            weight: None,
+            policy: None,
+            views: Vec::new(),
+            source: Some(EntrySource::new(self)),
+        }]
+    }
+}
+
+impl Display for HealthCheckedRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: HealthCheckedRecords are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use chrono::Utc;
+    use kubizone_common::Type;
+
+    use super::{
+        HealthCheckError, HealthCheckedRecord, HealthCheckedRecordSpec, HealthCheckedRecordStatus,
+        Probe,
+    };
+
+    #[test]
+    fn test_health_gating() {
+        let spec: HealthCheckedRecordSpec = serde_json::from_str(
+            r#"{
+                "domainName": "www.example.org.",
+                "type": "A",
+                "rdata": "192.168.0.1",
+                "healthCheck": {"probe": {"http": {"port": 8080, "path": "/healthz"}}}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            spec.probe_address(),
+            Ok(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)))
+        );
+        assert_eq!(spec.validate(), Ok(()));
+        assert_eq!(spec.health_check.failure_threshold, 3);
+        let Probe::Http(probe) = &spec.health_check.probe else {
+            panic!("expected an http probe");
+        };
+        assert!(probe.is_expected_status(204));
+        assert!(!probe.is_expected_status(503));
+
+        let mut record = HealthCheckedRecord::new("www", spec);
+        record.metadata.namespace = Some(String::from("default"));
+        record.status = Some(HealthCheckedRecordStatus {
+            fqdn: Some("www.example.org.".try_into().unwrap()),
+            ..Default::default()
+        });
+        assert!(record.zone_entries(300).is_empty());
+
+        let check = record.spec.health_check.clone();
+        let status = record.status.as_mut().unwrap();
+        status.record_probe(&check, true, None, Utc::now());
+        assert!(record.is_healthy());
+
+        let entries = record.zone_entries(300);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0].to_string(),
+            "www.example.org. 300 IN A 192.168.0.1"
+        );
+        let source = entries[0].source.as_ref().unwrap();
+        assert_eq!(source.kind, "HealthCheckedRecord");
+        assert_eq!(source.name, "www");
+
+        // A single failure is tolerated, the third consecutive one is not.
+        let status = record.status.as_mut().unwrap();
+        for _ in 0..2 {
+            status.record_probe(&check, false, Some(String::from("timeout")), Utc::now());
+        }
+        assert!(record.is_healthy());
+
+        let status = record.status.as_mut().unwrap();
+        status.record_probe(&check, false, None, Utc::now());
+        assert!(!record.is_healthy());
+        assert!(record.zone_entries(300).is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut spec = HealthCheckedRecordSpec {
+            type_: Type::AAAA,
+            rdata: String::from("2001:db8::1"),
+            ..Default::default()
+        };
+        spec.health_check.period_seconds = 10;
+        spec.health_check.timeout_seconds = 1;
+        spec.health_check.success_threshold = 1;
+        spec.health_check.failure_threshold = 3;
+        assert_eq!(spec.validate(), Ok(()));
+
+        spec.health_check.timeout_seconds = 0;
+        assert_eq!(
+            spec.validate(),
+            Err(HealthCheckError::Zero("timeoutSeconds"))
+        );
+        spec.health_check.timeout_seconds = 1;
+
+        // Only the published address is ever probed.
+        spec.type_ = Type::CNAME;
+        spec.rdata = String::from("metadata.internal.");
+        assert_eq!(
+            spec.validate(),
+            Err(HealthCheckError::UnsupportedType(Type::CNAME))
+        );
+
+        spec.type_ = Type::A;
+        spec.rdata = String::from("2001:db8::1");
+        assert!(matches!(
+            spec.validate(),
+            Err(HealthCheckError::InvalidAddress(_))
+        ));
+    }
+}
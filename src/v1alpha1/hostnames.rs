@@ -0,0 +1,82 @@
+use std::net::IpAddr;
+
+use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, Type};
+
+use super::{RecordSpec, ZoneRef};
+
+/// Fully qualify hostnames as used by Ingresses and Gateways, dropping
+/// duplicates and hosts which are not valid domain names.
+pub(crate) fn qualify_hostnames<'a>(
+    hosts: impl IntoIterator<Item = &'a str>,
+) -> Vec<FullyQualifiedDomainName> {
+    let mut hostnames = Vec::new();
+
+    for host in hosts {
+        let Ok(fqdn) =
+            FullyQualifiedDomainName::try_from(format!("{}.", host.trim_end_matches('.')))
+        else {
+            continue;
+        };
+
+        if !hostnames.contains(&fqdn) {
+            hostnames.push(fqdn);
+        }
+    }
+
+    hostnames
+}
+
+/// IP addresses become A and AAAA records. If there are none, the first
+/// hostname is aliased with a CNAME, since a name can only hold a single CNAME.
+pub(crate) fn address_targets<'a>(
+    addresses: impl IntoIterator<Item = &'a str>,
+    hostnames: impl IntoIterator<Item = &'a str>,
+) -> Vec<(Type, String)> {
+    let addresses: Vec<(Type, String)> = addresses
+        .into_iter()
+        .filter_map(|address| address.parse::<IpAddr>().ok())
+        .map(|address| {
+            let type_ = if address.is_ipv4() {
+                Type::A
+            } else {
+                Type::AAAA
+            };
+
+            (type_, address.to_string())
+        })
+        .collect();
+
+    if !addresses.is_empty() {
+        return addresses;
+    }
+
+    hostnames
+        .into_iter()
+        .next()
+        .map(|hostname| vec![(Type::CNAME, format!("{}.", hostname.trim_end_matches('.')))])
+        .unwrap_or_default()
+}
+
+/// One record per hostname and target.
+pub(crate) fn host_record_specs(
+    hostnames: &[FullyQualifiedDomainName],
+    targets: &[(Type, String)],
+    zone_ref: Option<&ZoneRef>,
+    class: Class,
+    ttl: Option<u32>,
+) -> Vec<RecordSpec> {
+    hostnames
+        .iter()
+        .flat_map(|hostname| {
+            targets.iter().map(move |(type_, rdata)| RecordSpec {
+                domain_name: DomainName::Full(hostname.clone()),
+                zone_ref: zone_ref.cloned(),
+                type_: *type_,
+                class,
+                ttl,
+                rdata: rdata.clone(),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,222 @@
+use std::fmt::Display;
+
+use k8s_openapi::api::networking::v1::Ingress;
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::{Class, FullyQualifiedDomainName, Type};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    hostnames::{address_targets, host_record_specs, qualify_hostnames},
+    LabelSelector, RecordSpec, ZoneRef,
+};
+
+/// Publishes the hostnames of selected Ingresses into a zone.
+///
+/// Every host of a selected Ingress' rules is pointed at the addresses of its
+/// load balancer, see [`IngressRecordSpec::record_specs`].
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "IngressRecord",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "IngressRecord",
+        namespaced
+    )
+)]
+#[kube(status = "IngressRecordStatus")]
+#[kube(printcolumn = r#"{"name":"zone", "jsonPath": ".spec.zoneRef.name", "type": "string"}"#)]
+#[kube(
+    printcolumn = r#"{"name":"hostnames", "jsonPath": ".status.hostnames", "type": "string", "priority": 1}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct IngressRecordSpec {
+    /// Selects the Ingresses, in the namespace of the IngressRecord, whose
+    /// hostnames are published. An empty selector matches every Ingress.
+    #[serde(default)]
+    pub label_selector: LabelSelector,
+
+    /// Zone the hostnames are published into.
+    pub zone_ref: Option<ZoneRef>,
+    #[serde(default = "super::defaults::class")]
+    pub class: Class,
+    pub ttl: Option<u32>,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IngressRecordStatus {
+    /// Hostnames currently being published.
+    #[serde(default)]
+    pub hostnames: Vec<FullyQualifiedDomainName>,
+}
+
+impl IngressRecordSpec {
+    /// Check if the Ingress is selected by this IngressRecord.
+    pub fn selects(&self, ingress: &Ingress) -> bool {
+        self.label_selector.matches(ingress.labels())
+    }
+
+    /// Hostnames of the Ingress' rules, fully qualified and without duplicates.
+    ///
+    /// Hosts which are not valid domain names are skipped.
+    pub fn hostnames(&self, ingress: &Ingress) -> Vec<FullyQualifiedDomainName> {
+        qualify_hostnames(
+            ingress
+                .spec
+                .as_ref()
+                .and_then(|spec| spec.rules.as_ref())
+                .into_iter()
+                .flatten()
+                .filter_map(|rule| rule.host.as_deref()),
+        )
+    }
+
+    /// Type and record data for the addresses of the Ingress' load balancer.
+    ///
+    /// IP addresses become A and AAAA records. Load balancers which only
+    /// expose hostnames are aliased with a CNAME to the first of them, since
+    /// a name can only hold a single CNAME.
+    pub fn targets(&self, ingress: &Ingress) -> Vec<(Type, String)> {
+        let points: Vec<_> = ingress
+            .status
+            .as_ref()
+            .and_then(|status| status.load_balancer.as_ref())
+            .and_then(|load_balancer| load_balancer.ingress.as_ref())
+            .into_iter()
+            .flatten()
+            .collect();
+
+        address_targets(
+            points.iter().filter_map(|point| point.ip.as_deref()),
+            points.iter().filter_map(|point| point.hostname.as_deref()),
+        )
+    }
+
+    /// Records publishing every hostname of the Ingress.
+    pub fn record_specs(&self, ingress: &Ingress) -> Vec<RecordSpec> {
+        host_record_specs(
+            &self.hostnames(ingress),
+            &self.targets(ingress),
+            self.zone_ref.as_ref(),
+            self.class,
+            self.ttl,
+        )
+    }
+}
+
+impl Display for IngressRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: IngressRecords are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::api::networking::v1::{
+        Ingress, IngressLoadBalancerIngress, IngressLoadBalancerStatus, IngressRule, IngressSpec,
+        IngressStatus,
+    };
+    use kubizone_common::Type;
+
+    use super::IngressRecordSpec;
+
+    fn ingress(hosts: &[&str], points: Vec<IngressLoadBalancerIngress>) -> Ingress {
+        Ingress {
+            spec: Some(IngressSpec {
+                rules: Some(
+                    hosts
+                        .iter()
+                        .map(|host| IngressRule {
+                            host: Some(host.to_string()),
+                            ..Default::default()
+                        })
+                        .collect(),
+                ),
+                ..Default::default()
+            }),
+            status: Some(IngressStatus {
+                load_balancer: Some(IngressLoadBalancerStatus {
+                    ingress: Some(points),
+                }),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ingress_records() {
+        let spec = IngressRecordSpec::default();
+
+        let by_address = ingress(
+            &["www.example.org", "api.example.org", "www.example.org"],
+            vec![
+                IngressLoadBalancerIngress {
+                    ip: Some(String::from("203.0.113.1")),
+                    ..Default::default()
+                },
+                IngressLoadBalancerIngress {
+                    ip: Some(String::from("2001:db8::1")),
+                    ..Default::default()
+                },
+            ],
+        );
+
+        assert!(spec.selects(&by_address));
+
+        let records = spec.record_specs(&by_address);
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].to_string(), "www.example.org. IN A 203.0.113.1");
+        assert_eq!(records[1].type_, Type::AAAA);
+        assert_eq!(records[2].domain_name.to_string(), "api.example.org.");
+
+        let by_hostname = ingress(
+            &["*.example.org"],
+            vec![IngressLoadBalancerIngress {
+                hostname: Some(String::from("lb.example.net")),
+                ..Default::default()
+            }],
+        );
+
+        assert_eq!(
+            spec.targets(&by_hostname),
+            vec![(Type::CNAME, String::from("lb.example.net."))]
+        );
+        assert_eq!(
+            spec.record_specs(&by_hostname)[0].to_string(),
+            "*.example.org. IN CNAME lb.example.net."
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+
+use k8s_openapi::apimachinery::pkg::apis::meta::v1 as meta;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Label query over a set of objects, mirroring the kubernetes `LabelSelector`.
+///
+/// The upstream type does not implement `Hash` or `Ord`, which all spec types
+/// in this crate are expected to, hence the separate definition.
+///
+/// An empty selector matches every object.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelSelector {
+    /// Labels which must all be present with exactly these values.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub match_labels: BTreeMap<String, String>,
+
+    /// Requirements which must all be satisfied.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub match_expressions: Vec<LabelSelectorRequirement>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct LabelSelectorRequirement {
+    pub key: String,
+    pub operator: LabelSelectorOperator,
+
+    /// Must be non-empty for `In` and `NotIn`, and empty for `Exists` and `DoesNotExist`.
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum LabelSelectorOperator {
+    In,
+    NotIn,
+    Exists,
+    DoesNotExist,
+}
+
+impl LabelSelectorOperator {
+    /// Name of the operator, as used by kubernetes.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LabelSelectorOperator::In => "In",
+            LabelSelectorOperator::NotIn => "NotIn",
+            LabelSelectorOperator::Exists => "Exists",
+            LabelSelectorOperator::DoesNotExist => "DoesNotExist",
+        }
+    }
+}
+
+impl LabelSelector {
+    /// Check if an object with the given labels is selected.
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        self.match_labels
+            .iter()
+            .all(|(key, value)| labels.get(key) == Some(value))
+            && self
+                .match_expressions
+                .iter()
+                .all(|requirement| requirement.matches(labels))
+    }
+}
+
+impl LabelSelectorRequirement {
+    /// Check if the given labels satisfy this requirement.
+    pub fn matches(&self, labels: &BTreeMap<String, String>) -> bool {
+        let value = labels.get(&self.key);
+
+        match self.operator {
+            LabelSelectorOperator::In => value.is_some_and(|value| self.values.contains(value)),
+            LabelSelectorOperator::NotIn => value.is_none_or(|value| !self.values.contains(value)),
+            LabelSelectorOperator::Exists => value.is_some(),
+            LabelSelectorOperator::DoesNotExist => value.is_none(),
+        }
+    }
+}
+
+impl From<LabelSelector> for meta::LabelSelector {
+    fn from(value: LabelSelector) -> Self {
+        meta::LabelSelector {
+            match_labels: Some(value.match_labels).filter(|labels| !labels.is_empty()),
+            match_expressions: Some(
+                value
+                    .match_expressions
+                    .into_iter()
+                    .map(|requirement| meta::LabelSelectorRequirement {
+                        key: requirement.key,
+                        operator: requirement.operator.as_str().to_string(),
+                        values: Some(requirement.values).filter(|values| !values.is_empty()),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .filter(|expressions| !expressions.is_empty()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1 as meta;
+
+    use super::{LabelSelector, LabelSelectorOperator, LabelSelectorRequirement};
+
+    #[test]
+    fn test_label_selector() {
+        let labels = BTreeMap::from([
+            (String::from("tenant"), String::from("blue")),
+            (String::from("tier"), String::from("production")),
+        ]);
+
+        assert!(LabelSelector::default().matches(&labels));
+
+        let selector = LabelSelector {
+            match_labels: BTreeMap::from([(String::from("tenant"), String::from("blue"))]),
+            match_expressions: vec![LabelSelectorRequirement {
+                key: String::from("tier"),
+                operator: LabelSelectorOperator::In,
+                values: vec![String::from("production"), String::from("staging")],
+            }],
+        };
+        assert!(selector.matches(&labels));
+        assert!(!selector.matches(&BTreeMap::new()));
+
+        let selector = LabelSelector {
+            match_labels: BTreeMap::new(),
+            match_expressions: vec![LabelSelectorRequirement {
+                key: String::from("tenant"),
+                operator: LabelSelectorOperator::DoesNotExist,
+                values: vec![],
+            }],
+        };
+        assert!(!selector.matches(&labels));
+        assert!(selector.matches(&BTreeMap::new()));
+    }
+
+    #[test]
+    fn test_into_meta() {
+        let selector = LabelSelector {
+            match_labels: BTreeMap::new(),
+            match_expressions: vec![LabelSelectorRequirement {
+                key: String::from("tenant"),
+                operator: LabelSelectorOperator::NotIn,
+                values: vec![String::from("red")],
+            }],
+        };
+
+        let meta = meta::LabelSelector::from(selector);
+        assert_eq!(meta.match_labels, None);
+        assert_eq!(meta.match_expressions.unwrap()[0].operator, "NotIn");
+
+        for operator in [
+            LabelSelectorOperator::In,
+            LabelSelectorOperator::NotIn,
+            LabelSelectorOperator::Exists,
+            LabelSelectorOperator::DoesNotExist,
+        ] {
+            assert_eq!(
+                serde_json::to_value(operator).unwrap(),
+                serde_json::Value::from(operator.as_str())
+            );
+        }
+    }
+}
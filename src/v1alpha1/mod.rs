@@ -1,13 +1,69 @@
+mod approval;
+mod builder;
+mod compiled;
+mod condition;
+mod dns_view;
+mod dnssec;
+mod dnssec_key;
+mod explain;
+mod failover_policy;
+mod failover_pool;
+mod gateway_record;
+mod health_checked_record;
+mod hostnames;
+mod ingress_record;
+mod label_selector;
 mod record;
+mod record_pool;
+mod record_template;
+mod reverse_zone;
+mod secondary_zone;
+mod service_record;
+mod soa;
+mod transfer;
+mod tsig;
 mod zone;
+mod zone_data;
 
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use kubizone_common::FullyQualifiedDomainName;
+use crate::{
+    errors::{ObjectRefError, ValidationError, ZoneRefParseError},
+    PARENT_ZONE_ANNOTATION, PARENT_ZONE_LABEL,
+};
+
+pub use approval::*;
+pub use builder::*;
+pub use compiled::*;
+pub use condition::{
+    Condition, ConditionError, ConditionReason, ConditionStatus, RecordConditionType,
+    ZoneConditionType,
+};
+pub use dns_view::*;
+pub use dnssec::*;
+pub use dnssec_key::*;
+pub use explain::*;
+pub use failover_policy::*;
+pub use failover_pool::*;
+pub use gateway_record::*;
+pub use health_checked_record::*;
+pub use ingress_record::*;
+use kube::{runtime::reflector::ObjectRef, Resource, ResourceExt};
+use kubizone_common::{DomainName, FullyQualifiedDomainName};
+pub use label_selector::*;
 pub use record::*;
+pub use record_pool::*;
+pub use record_template::*;
+pub use reverse_zone::*;
 use schemars::JsonSchema;
+pub use secondary_zone::*;
 use serde::{Deserialize, Serialize};
+pub use service_record::*;
+pub use soa::*;
+pub use transfer::*;
+pub use tsig::*;
 pub use zone::*;
+pub use zone_data::*;
 
 /// Reference to a Zone, optionally in a specific namespace.
 #[derive(
@@ -15,56 +71,294 @@ pub use zone::*;
 )]
 pub struct ZoneRef {
     pub name: String,
+
+    /// Namespace of the zone. References without a namespace, whether in
+    /// specs, annotations or labels, refer to the namespace of the
+    /// referencing resource, see [`ZoneRef::namespace_or`].
     pub namespace: Option<String>,
 }
 
+/// Maximum length of a label value, and of a label of a domain name.
+pub(crate) const MAX_LABEL_LENGTH: usize = 63;
+
 /// Functionality common between Zones and Records, such as fetching the Fully Qualified Domain Name
 /// of the resource, or parsing the parent zone label.
-pub trait DomainExt {
+pub trait DomainExt: ResourceExt {
     /// Fetch the computed FQDN from this resource, if one has been set.
     fn fqdn(&self) -> Option<&FullyQualifiedDomainName>;
 
+    /// Store the computed FQDN in the status of this resource, creating the status if necessary.
+    fn set_fqdn(&mut self, fqdn: FullyQualifiedDomainName);
+
     /// Retrieve the kubi.zone/parent-zone label as a ZoneRef, if present.
     fn parent(&self) -> Option<ZoneRef>;
+
+    /// Record `parent` as the parent zone of this resource, in both the
+    /// [`PARENT_ZONE_ANNOTATION`] and the [`PARENT_ZONE_LABEL`].
+    ///
+    /// The label is removed instead if the reference does not fit into a
+    /// label value, or has no namespace and neither does this resource,
+    /// leaving only the annotation.
+    fn set_parent(&mut self, parent: ZoneRef) {
+        let label = parent
+            .within(self.namespace().as_deref())
+            .as_label()
+            .filter(|label| label.len() <= MAX_LABEL_LENGTH);
+
+        if let Some(label) = label {
+            self.labels_mut()
+                .insert(PARENT_ZONE_LABEL.to_string(), label);
+        } else {
+            self.labels_mut().remove(PARENT_ZONE_LABEL);
+        }
+
+        self.annotations_mut()
+            .insert(PARENT_ZONE_ANNOTATION.to_string(), parent.to_annotation());
+    }
+
+    /// Remove the parent zone of this resource, from the [`PARENT_ZONE_ANNOTATION`],
+    /// the [`PARENT_ZONE_LABEL`], and any Zone among its ownerReferences.
+    fn clear_parent(&mut self) {
+        self.labels_mut().remove(PARENT_ZONE_LABEL);
+        self.annotations_mut().remove(PARENT_ZONE_ANNOTATION);
+        self.owner_references_mut().retain(|owner| {
+            owner.api_version != Zone::api_version(&()) || owner.kind != Zone::kind(&())
+        });
+    }
+}
+
+/// Tracking of the `.metadata.generation` a status was computed from, so
+/// controllers can tell whether a status is stale relative to its spec.
+pub trait ObservedGeneration {
+    /// Generation the status was last computed from, if any.
+    fn observed_generation(&self) -> Option<i64>;
+
+    fn set_observed_generation(&mut self, generation: Option<i64>);
+
+    /// Record that the status was computed from the current generation of `resource`.
+    fn stamp<K: Resource>(&mut self, resource: &K) {
+        self.set_observed_generation(resource.meta().generation);
+    }
+
+    /// Check if the status was computed from the current generation of `resource`.
+    ///
+    /// Resources without a generation are never considered current.
+    fn is_current<K: Resource>(&self, resource: &K) -> bool {
+        resource
+            .meta()
+            .generation
+            .is_some_and(|generation| self.observed_generation() == Some(generation))
+    }
 }
 
+/// Separator between namespace and name in [`ZoneRef::as_label`].
+const LABEL_SEPARATOR: char = '_';
+
 impl ZoneRef {
-    /// Serialize the ZoneRef into a label-compatible format.
-    pub fn as_label(&self) -> String {
+    /// Namespace of the referenced zone, which defaults to the namespace
+    /// of the referencing resource.
+    pub fn namespace_or<'a>(&'a self, namespace: &'a str) -> &'a str {
+        self.namespace.as_deref().unwrap_or(namespace)
+    }
+
+    /// Default the namespace of the reference to `namespace`, which should
+    /// be the namespace of the referencing resource.
+    pub fn within(&self, namespace: Option<&str>) -> ZoneRef {
+        ZoneRef {
+            name: self.name.clone(),
+            namespace: self.namespace.as_deref().or(namespace).map(String::from),
+        }
+    }
+
+    /// Serialize the ZoneRef into a label-compatible format, used as the
+    /// value of the [`PARENT_ZONE_LABEL`](crate::PARENT_ZONE_LABEL).
+    ///
+    /// The namespace and name are separated by an underscore, which can
+    /// appear in neither, so zone names containing dots are unambiguous.
+    ///
+    /// References without a namespace have no label format, since a bare
+    /// name could not be told apart from the deprecated `name.namespace`
+    /// format. Resolve them with [`ZoneRef::within`] first.
+    ///
+    /// ```rust
+    /// # use kubizone_crds::v1alpha1::ZoneRef;
+    /// let zone_ref: ZoneRef = "example.org.dns".parse().unwrap();
+    /// assert_eq!(zone_ref.as_label().as_deref(), Some("dns_example.org"));
+    ///
+    /// let zone_ref: ZoneRef = "example-org".parse().unwrap();
+    /// assert_eq!(zone_ref.as_label(), None);
+    /// ```
+    pub fn as_label(&self) -> Option<String> {
+        let namespace = self.namespace.as_ref()?;
+
+        Some(format!("{namespace}{LABEL_SEPARATOR}{}", self.name))
+    }
+
+    /// Serialize the ZoneRef into the deprecated `name.namespace` label format.
+    #[cfg(any(test, feature = "client"))]
+    pub(crate) fn as_legacy_label(&self) -> String {
         if let Some(namespace) = &self.namespace {
             format!("{}.{namespace}", self.name)
         } else {
             self.name.clone()
         }
     }
-}
 
-impl From<&str> for ZoneRef {
-    fn from(s: &str) -> Self {
-        if let Some((name, namespace)) = s.split_once('.') {
-            ZoneRef {
+    /// Parse a ZoneRef from its label format, see [`ZoneRef::as_label`].
+    ///
+    /// Labels in the deprecated `name.namespace` format are still accepted.
+    /// Since namespaces cannot contain dots, they are split at the last dot,
+    /// and labels without any dot carry no namespace.
+    pub fn from_label(label: &str) -> Self {
+        if let Some((namespace, name)) = label.split_once(LABEL_SEPARATOR) {
+            return ZoneRef {
                 name: name.to_string(),
                 namespace: Some(namespace.to_string()),
-            }
-        } else {
-            // TODO: Might be valuable to do some validation here.
-            ZoneRef {
-                name: s.to_string(),
+            };
+        }
+
+        match label.rsplit_once('.') {
+            Some((name, namespace)) => ZoneRef {
+                name: name.to_string(),
+                namespace: Some(namespace.to_string()),
+            },
+            None => ZoneRef {
+                name: label.to_string(),
                 namespace: None,
-            }
+            },
         }
     }
 }
 
-impl From<&String> for ZoneRef {
-    fn from(value: &String) -> Self {
-        ZoneRef::from(value.as_str())
+impl ZoneRef {
+    /// Serialize the ZoneRef into the JSON format of the
+    /// [`PARENT_ZONE_ANNOTATION`](crate::PARENT_ZONE_ANNOTATION).
+    pub fn to_annotation(&self) -> String {
+        // Unwrap safety: ZoneRefs consist only of strings.
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Parse a ZoneRef from its annotation format, see [`ZoneRef::to_annotation`].
+    pub fn from_annotation(annotation: &str) -> Option<Self> {
+        serde_json::from_str(annotation).ok()
     }
 }
 
-impl From<String> for ZoneRef {
-    fn from(value: String) -> Self {
-        ZoneRef::from(value.as_str())
+impl ZoneRef {
+    /// Reference the zone as a `K`, for lookups in a reflector [`Store`](kube::runtime::reflector::Store).
+    ///
+    /// References without a namespace default to `namespace`, which should
+    /// be the namespace of the referencing resource.
+    ///
+    /// ```rust
+    /// # use kube::runtime::reflector::ObjectRef;
+    /// # use kubizone_crds::v1alpha1::{Zone, ZoneRef};
+    /// let zone_ref: ZoneRef = "example-org".parse().unwrap();
+    /// let object_ref = zone_ref.to_object_ref::<Zone>(Some("dns"));
+    ///
+    /// assert_eq!(object_ref, ObjectRef::new("example-org").within("dns"));
+    /// ```
+    pub fn to_object_ref<K>(&self, namespace: Option<&str>) -> ObjectRef<K>
+    where
+        K: Resource,
+        K::DynamicType: Default,
+    {
+        let object_ref = ObjectRef::new(&self.name);
+
+        match self.namespace.as_deref().or(namespace) {
+            Some(namespace) => object_ref.within(namespace),
+            None => object_ref,
+        }
+    }
+}
+
+/// Zones are namespaced, so references without a namespace are rejected.
+/// Use [`ZoneRef::to_object_ref`] with the namespace of the referencing
+/// resource for those.
+impl TryFrom<&ZoneRef> for ObjectRef<Zone> {
+    type Error = ObjectRefError;
+
+    fn try_from(zone_ref: &ZoneRef) -> Result<Self, Self::Error> {
+        match &zone_ref.namespace {
+            Some(namespace) => Ok(ObjectRef::new(&zone_ref.name).within(namespace)),
+            None => Err(ObjectRefError::MissingNamespace(zone_ref.name.clone())),
+        }
+    }
+}
+
+/// Check if `s` is an RFC 1123 label, as required of namespaces.
+fn is_dns_label(s: &str) -> bool {
+    !s.is_empty()
+        && s.len() <= MAX_LABEL_LENGTH
+        && s.bytes()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == b'-')
+        && !s.starts_with('-')
+        && !s.ends_with('-')
+}
+
+/// Check if `s` is an RFC 1123 subdomain, as required of resource names.
+fn is_dns_subdomain(s: &str) -> bool {
+    s.len() <= 253 && s.split('.').all(is_dns_label)
+}
+
+/// Parses the `name.namespace` shorthand, or just `name` for zones in the
+/// namespace of the referencing resource.
+///
+/// Since namespaces cannot contain dots, the namespace is everything after
+/// the last dot, and names containing dots must always be given with a namespace.
+///
+/// ```rust
+/// # use kubizone_crds::{errors::ZoneRefParseError, v1alpha1::ZoneRef};
+/// let zone_ref: ZoneRef = "example-org.dns".parse().unwrap();
+/// assert_eq!(zone_ref.namespace.as_deref(), Some("dns"));
+/// assert_eq!(
+///     "example-org.".parse::<ZoneRef>(),
+///     Err(ZoneRefParseError::EmptyNamespace(String::from("example-org.")))
+/// );
+/// ```
+impl FromStr for ZoneRef {
+    type Err = ZoneRefParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ZoneRefParseError::Empty);
+        }
+
+        let (name, namespace) = match s.rsplit_once('.') {
+            Some((name, namespace)) => (name, Some(namespace)),
+            None => (s, None),
+        };
+
+        if name.is_empty() {
+            return Err(ZoneRefParseError::EmptyName(s.to_string()));
+        }
+
+        if !is_dns_subdomain(name) {
+            return Err(ZoneRefParseError::InvalidName(name.to_string()));
+        }
+
+        match namespace {
+            Some("") => Err(ZoneRefParseError::EmptyNamespace(s.to_string())),
+            Some(namespace) if !is_dns_label(namespace) => {
+                Err(ZoneRefParseError::InvalidNamespace(namespace.to_string()))
+            }
+            _ => Ok(ZoneRef {
+                name: name.to_string(),
+                namespace: namespace.map(String::from),
+            }),
+        }
+    }
+}
+
+/// Resources must have *either* a zoneRef, or a fully qualified domain name.
+pub(crate) fn validate_parent(
+    domain_name: &DomainName,
+    zone_ref: Option<&ZoneRef>,
+) -> Result<(), ValidationError> {
+    match (domain_name.is_fully_qualified(), zone_ref.is_some()) {
+        (true, true) => Err(ValidationError::ConflictingParent(domain_name.clone())),
+        (false, false) => Err(ValidationError::MissingParent(domain_name.clone())),
+        _ => Ok(()),
     }
 }
 
@@ -82,7 +376,193 @@ pub mod defaults {
     use kubizone_common::Class;
 
     pub const CLASS: Class = Class::IN;
-    pub(super) fn class() -> Class {
+
+    pub const REFRESH: u32 = 86400;
+    /// Service addresses might change often, so we use a low
+    /// Time-to-Live to increase cache responsiveness.
+    pub const TTL: u32 = 360;
+
+    /// Recommendation for small and stable zones[^1]: 7200 seconds (2 hours).
+    ///
+    /// [^1]: <https://www.ripe.net/publications/docs/ripe-203>
+    pub const RETRY: u32 = 7200;
+
+    /// Recommendation for small and stable zones[^1]: 3600000 seconds (1000 hours).
+    ///
+    /// [^1]: <https://www.ripe.net/publications/docs/ripe-203>
+    pub const EXPIRE: u32 = 3600000;
+
+    /// Recommendation for small and stable zones[^1]: 172800 seconds (2 days),
+    /// but we select a much lower value to increase cache responsiveness
+    /// and reduce failed lookups to records still being provisioned.
+    ///
+    /// [^1]: <https://www.ripe.net/publications/docs/ripe-203>
+    pub const NEGATIVE_RESPONSE_CACHE: u32 = 360;
+
+    /// Records are published unless explicitly disabled.
+    pub const ENABLED: bool = true;
+
+    /// Probe defaults match those of kubernetes container probes, except
+    /// for the timeout, which leaves some room for network latency.
+    pub const PROBE_PERIOD_SECONDS: u32 = 10;
+    pub const PROBE_TIMEOUT_SECONDS: u32 = 5;
+    pub const PROBE_SUCCESS_THRESHOLD: u32 = 1;
+    pub const PROBE_FAILURE_THRESHOLD: u32 = 3;
+
+    /// Pool members share traffic equally unless weighted otherwise.
+    pub const POOL_WEIGHT: u32 = 1;
+
+    /// Zone transfers and notifications use the standard DNS port.
+    pub const DNS_PORT: u16 = 53;
+
+    /// etcd rejects objects larger than 1.5 MiB, so leave room for the
+    /// metadata, spec and remaining status of a zone.
+    pub const ENTRIES_SIZE_BUDGET: usize = 1024 * 1024;
+
+    // The functions below are only there for use with `serde(default)`.
+    pub(crate) const fn refresh() -> u32 {
+        REFRESH
+    }
+    pub(crate) const fn ttl() -> u32 {
+        TTL
+    }
+    pub(crate) const fn retry() -> u32 {
+        RETRY
+    }
+
+    pub(crate) const fn expire() -> u32 {
+        EXPIRE
+    }
+
+    pub(crate) const fn negative_response_cache() -> u32 {
+        NEGATIVE_RESPONSE_CACHE
+    }
+
+    pub(crate) const fn enabled() -> bool {
+        ENABLED
+    }
+
+    pub(crate) const fn probe_period_seconds() -> u32 {
+        PROBE_PERIOD_SECONDS
+    }
+
+    pub(crate) const fn probe_timeout_seconds() -> u32 {
+        PROBE_TIMEOUT_SECONDS
+    }
+
+    pub(crate) const fn probe_success_threshold() -> u32 {
+        PROBE_SUCCESS_THRESHOLD
+    }
+
+    pub(crate) const fn probe_failure_threshold() -> u32 {
+        PROBE_FAILURE_THRESHOLD
+    }
+
+    pub(crate) const fn pool_weight() -> u32 {
+        POOL_WEIGHT
+    }
+
+    pub(crate) const fn dns_port() -> u16 {
+        DNS_PORT
+    }
+
+    pub(crate) fn class() -> Class {
         CLASS
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use kube::runtime::reflector::ObjectRef;
+
+    use crate::errors::{ObjectRefError, ZoneRefParseError};
+
+    use super::{Zone, ZoneRef};
+
+    #[test]
+    fn test_label_roundtrip() {
+        for (name, namespace) in [
+            ("example-org", "dns"),
+            ("example.org", "dns"),
+            ("a.b.c", "team-1"),
+        ] {
+            let zone_ref = ZoneRef {
+                name: String::from(name),
+                namespace: Some(String::from(namespace)),
+            };
+
+            let label = zone_ref.as_label().unwrap();
+            assert_eq!(ZoneRef::from_label(&label), zone_ref, "{label}");
+        }
+
+        // Without a namespace, `example.org` could be mistaken for the legacy
+        // label of the `example` zone in the `org` namespace.
+        let unqualified = ZoneRef {
+            name: String::from("example.org"),
+            namespace: None,
+        };
+        assert_eq!(unqualified.as_label(), None);
+        assert_eq!(
+            unqualified.within(Some("dns")).as_label().as_deref(),
+            Some("dns_example.org")
+        );
+    }
+
+    #[test]
+    fn test_zone_ref_from_str() {
+        assert_eq!(
+            "example.org.dns".parse(),
+            Ok(ZoneRef {
+                name: String::from("example.org"),
+                namespace: Some(String::from("dns")),
+            })
+        );
+        assert_eq!(
+            "example-org".parse(),
+            Ok(ZoneRef {
+                name: String::from("example-org"),
+                namespace: None,
+            })
+        );
+
+        assert_eq!("".parse::<ZoneRef>(), Err(ZoneRefParseError::Empty));
+        assert_eq!(
+            ".dns".parse::<ZoneRef>(),
+            Err(ZoneRefParseError::EmptyName(String::from(".dns")))
+        );
+        assert_eq!(
+            "Example-Org.dns".parse::<ZoneRef>(),
+            Err(ZoneRefParseError::InvalidName(String::from("Example-Org")))
+        );
+        assert_eq!(
+            "example-org.-dns".parse::<ZoneRef>(),
+            Err(ZoneRefParseError::InvalidNamespace(String::from("-dns")))
+        );
+    }
+
+    #[test]
+    fn test_zone_ref_to_object_ref() {
+        let local: ZoneRef = "example-org".parse().unwrap();
+        let remote: ZoneRef = "example-org.dns".parse().unwrap();
+
+        assert_eq!(
+            ObjectRef::<Zone>::try_from(&local),
+            Err(ObjectRefError::MissingNamespace(String::from(
+                "example-org"
+            )))
+        );
+        assert_eq!(
+            ObjectRef::<Zone>::try_from(&remote),
+            Ok(ObjectRef::new("example-org").within("dns"))
+        );
+
+        assert_eq!(
+            local.to_object_ref::<Zone>(Some("web")),
+            ObjectRef::new("example-org").within("web")
+        );
+        assert_eq!(
+            remote.to_object_ref::<Zone>(Some("web")),
+            ObjectRef::new("example-org").within("dns")
+        );
+    }
+}
@@ -1,13 +1,19 @@
-use std::fmt::Display;
+use std::{fmt::Display, net::IpAddr};
 
-use kube::{CustomResource, Resource, ResourceExt};
+use chrono::{DateTime, Utc};
+use kube::{CustomResource, ResourceExt};
 use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, RecordIdent, Type};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use crate::PARENT_ZONE_LABEL;
+use crate::{
+    errors::RecordValidationError,
+    parent_reference,
+    rdata::{CaaData, RData, RDataError, SrvData},
+};
 
-use super::{DomainExt, ZoneRef};
+use super::{condition, Condition, DomainExt, ObservedGeneration, ViewRef, ZoneRef};
 
 #[derive(
     CustomResource,
@@ -15,7 +21,6 @@ use super::{DomainExt, ZoneRef};
     Serialize,
     Clone,
     Debug,
-    Default,
     JsonSchema,
     Hash,
     PartialEq,
@@ -45,11 +50,21 @@ use super::{DomainExt, ZoneRef};
 #[kube(printcolumn = r#"{"name":"type", "jsonPath": ".spec.type", "type": "string"}"#)]
 #[kube(printcolumn = r#"{"name":"data", "jsonPath": ".spec.rdata", "type": "string"}"#)]
 #[kube(printcolumn = r#"{"name":"fqdn", "jsonPath": ".status.fqdn", "type": "string"}"#)]
-#[kube(
-    printcolumn = r#"{"name":"parent", "jsonPath": ".metadata.labels.kubi\\.zone/parent-zone", "type": "string"}"#
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        printcolumn = r#"{"name":"parent", "jsonPath": ".metadata.labels.dev\\.kubi\\.zone/parent-zone", "type": "string"}"#
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        printcolumn = r#"{"name":"parent", "jsonPath": ".metadata.labels.kubi\\.zone/parent-zone", "type": "string"}"#
+    )
 )]
 #[serde(rename_all = "camelCase")]
 pub struct RecordSpec {
+    #[schemars(schema_with = "crate::schema::domain_name")]
     pub domain_name: DomainName,
     pub zone_ref: Option<ZoneRef>,
     #[serde(rename = "type")]
@@ -58,11 +73,298 @@ pub struct RecordSpec {
     pub class: Class,
     pub ttl: Option<u32>,
     pub rdata: String,
+
+    /// Order in which answers for this record's name and type should be
+    /// returned, for serializers which support answer rotation.
+    ///
+    /// Records sharing a name and type form a single record set, and should
+    /// agree on the policy. If unset, the serializer's own default applies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_policy: Option<ResponsePolicy>,
+
+    /// Restricts publication of the record to a (possibly recurring) window
+    /// of time. Records without a window are always active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_window: Option<ActiveWindow>,
+
+    /// Lease on the record in seconds, counted from its creation, after which
+    /// controllers should delete it. Intended for records created by
+    /// ephemeral workloads, such as preview environments, which may not
+    /// clean up after themselves. See [`Record::expires_at`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_after: Option<u32>,
+
+    /// Disabled records are left out of their zone, while keeping the
+    /// record object and its configuration around for re-enabling later.
+    #[serde(default = "super::defaults::enabled")]
+    pub enabled: bool,
+
+    /// Routing metadata for policy-aware serializers, which is passed
+    /// through to the record's zone entries unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<RoutingPolicy>,
+
+    /// Hint for controllers to maintain a matching PTR record in the reverse
+    /// zone of the record's address. Only meaningful for A and AAAA records,
+    /// see [`crate::reverse::ptr_record_for`].
+    #[serde(default)]
+    pub generate_ptr: bool,
+
+    /// [`DNSView`](super::DNSView)s the record is published in. References
+    /// without a namespace refer to views in the namespace of the record.
+    /// Records without views are published in every view.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub views: Vec<ViewRef>,
+}
+
+impl Default for RecordSpec {
+    fn default() -> Self {
+        RecordSpec {
+            domain_name: DomainName::default(),
+            zone_ref: None,
+            type_: Type::default(),
+            class: Class::default(),
+            ttl: None,
+            rdata: String::new(),
+            response_policy: None,
+            active_window: None,
+            expires_after: None,
+            enabled: super::defaults::ENABLED,
+            policy: None,
+            generate_ptr: false,
+            views: Vec::new(),
+        }
+    }
+}
+
+/// Window of time during which a record is published.
+///
+/// Either bound may be left out, so a window with only an `end` expires the
+/// record, while one with only a `start` publishes it from then on. Recurring
+/// windows require both bounds, and are treated as occurring once otherwise.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveWindow {
+    /// Start of the first occurrence of the window. Open-ended if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<DateTime<Utc>>,
+
+    /// End of the first occurrence of the window. Open-ended if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+
+    /// How often the window repeats, counted from `start`.
+    #[serde(default)]
+    pub recurrence: Recurrence,
+}
+
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum Recurrence {
+    /// The window occurs exactly once.
+    #[default]
+    Never,
+    /// The window repeats every 24 hours.
+    Daily,
+    /// The window repeats every 7 days.
+    Weekly,
+}
+
+impl ActiveWindow {
+    /// Check whether `now` falls within any occurrence of this window.
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        if self.start.is_some_and(|start| now < start) {
+            return false;
+        }
+
+        let (Some(start), Some(end)) = (self.start, self.end) else {
+            return self.end.is_none_or(|end| now < end);
+        };
+
+        let period = match self.recurrence {
+            Recurrence::Never => return now < end,
+            Recurrence::Daily => 86400,
+            Recurrence::Weekly => 7 * 86400,
+        };
+
+        let length = (end - start).num_seconds();
+
+        // Windows which are as long as their period never close.
+        if length >= period {
+            return true;
+        }
+
+        (now - start).num_seconds() % period < length
+    }
+}
+
+/// Ordering of answers within a record set.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum ResponsePolicy {
+    /// Answers are always returned in the same order.
+    #[default]
+    Fixed,
+    /// Answers are rotated between each response.
+    RoundRobin,
+    /// Answers are shuffled for each response.
+    Random,
+}
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum RoutingPolicyError {
+    #[error("invalid region {0}, expected an ISO 3166 country or subdivision code")]
+    InvalidRegion(String),
+    #[error("invalid subnet {0}, expected an address and prefix length")]
+    InvalidSubnet(String),
+}
+
+/// Geo and latency routing metadata of a record.
+///
+/// Plain DNS serializers ignore the policy entirely. Serializers which
+/// support policy-based answers should only return the record to clients
+/// matching it, where an empty list matches every client.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutingPolicy {
+    /// ISO 3166-1 alpha-2 country codes, optionally with an ISO 3166-2
+    /// subdivision, such as `DK` or `US-CA`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(inner(regex(pattern = r"^[A-Z]{2}(-[A-Z0-9]{1,3})?$")))]
+    pub regions: Vec<String>,
+
+    /// Relative latency of the answer, lower tiers are preferred.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_tier: Option<u8>,
+
+    /// Client subnets in CIDR notation, such as `192.0.2.0/24`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(inner(regex(pattern = r"^[0-9a-fA-F:.]+/[0-9]{1,3}$")))]
+    pub subnets: Vec<String>,
+}
+
+impl RoutingPolicy {
+    /// Check the policy for malformed regions and subnets, beyond what the
+    /// schema is able to express.
+    pub fn validate(&self) -> Result<(), RoutingPolicyError> {
+        for region in &self.regions {
+            let (country, subdivision) = match region.split_once('-') {
+                Some((country, subdivision)) => (country, Some(subdivision)),
+                None => (region.as_str(), None),
+            };
+
+            let valid = country.len() == 2
+                && country.bytes().all(|c| c.is_ascii_uppercase())
+                && subdivision.is_none_or(|subdivision| {
+                    (1..=3).contains(&subdivision.len())
+                        && subdivision
+                            .bytes()
+                            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+                });
+
+            if !valid {
+                return Err(RoutingPolicyError::InvalidRegion(region.clone()));
+            }
+        }
+
+        for subnet in &self.subnets {
+            let valid = subnet.split_once('/').is_some_and(|(address, prefix)| {
+                let max_prefix = match address.parse::<IpAddr>() {
+                    Ok(IpAddr::V4(_)) => 32,
+                    Ok(IpAddr::V6(_)) => 128,
+                    Err(_) => return false,
+                };
+
+                prefix
+                    .parse::<u8>()
+                    .is_ok_and(|prefix| prefix <= max_prefix)
+            });
+
+            if !valid {
+                return Err(RoutingPolicyError::InvalidSubnet(subnet.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for ResponsePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResponsePolicy::Fixed => f.write_str("Fixed"),
+            ResponsePolicy::RoundRobin => f.write_str("RoundRobin"),
+            ResponsePolicy::Random => f.write_str("Random"),
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct RecordStatus {
+    #[schemars(schema_with = "crate::schema::optional_fully_qualified_domain_name")]
     pub fqdn: Option<FullyQualifiedDomainName>,
+
+    /// `.metadata.generation` of the record this status was computed from.
+    #[serde(default)]
+    pub observed_generation: Option<i64>,
+
+    /// Latest observations of the record's state, such as whether
+    /// it was accepted into a zone, or rejected by its delegations.
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+}
+
+impl ObservedGeneration for RecordStatus {
+    fn observed_generation(&self) -> Option<i64> {
+        self.observed_generation
+    }
+
+    fn set_observed_generation(&mut self, generation: Option<i64>) {
+        self.observed_generation = generation;
+    }
+}
+
+impl RecordStatus {
+    /// Set a condition, replacing any existing condition of the same type.
+    ///
+    /// The transition time of the existing condition is kept if its status is unchanged.
+    pub fn set_condition(&mut self, condition: Condition) {
+        condition::set_condition(&mut self.conditions, condition)
+    }
+
+    /// Retrieve the condition of the given type, if present.
+    pub fn get_condition(&self, type_: impl AsRef<str>) -> Option<&Condition> {
+        condition::get_condition(&self.conditions, type_.as_ref())
+    }
 }
 
 impl DomainExt for Record {
@@ -70,16 +372,93 @@ impl DomainExt for Record {
         self.status.as_ref().and_then(|status| status.fqdn.as_ref())
     }
 
+    fn set_fqdn(&mut self, fqdn: FullyQualifiedDomainName) {
+        self.status.get_or_insert_with(RecordStatus::default).fqdn = Some(fqdn);
+    }
+
+    #[cfg_attr(
+        feature = "otel",
+        tracing::instrument(
+            level = "trace",
+            skip_all,
+            fields(kubizone.name = %self.name_any(), kubizone.namespace = self.namespace().as_deref()),
+            ret
+        )
+    )]
     fn parent(&self) -> Option<ZoneRef> {
-        self.meta()
-            .labels
-            .as_ref()?
-            .get(PARENT_ZONE_LABEL)
-            .map(ZoneRef::from)
+        parent_reference(self)
+    }
+}
+
+impl Record {
+    /// Time at which the record's lease runs out, according to its
+    /// `expiresAfter` and `metadata.creationTimestamp`.
+    ///
+    /// Returns `None` for records without a lease, and records which have
+    /// not been created yet.
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        let lease = self.spec.expires_after?;
+        let created = self.metadata.creation_timestamp.as_ref()?;
+
+        Some(created.0 + chrono::Duration::seconds(i64::from(lease)))
+    }
+
+    /// Check whether the record's lease has run out at the given time.
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at().is_some_and(|expiry| expiry <= now)
     }
 }
 
 impl RecordSpec {
+    /// Parse the record's rdata according to its type.
+    pub fn parse_rdata(&self) -> Result<RData, RDataError> {
+        RData::parse(self.type_, &self.rdata)
+    }
+
+    /// Check the syntax of the record's rdata against its type, see
+    /// [`RData::validate`], and its routing policy, if any, see
+    /// [`RoutingPolicy::validate`].
+    ///
+    /// Shared by controllers and the admission webhook, so both agree on
+    /// which records are well-formed.
+    pub fn validate(&self) -> Result<(), RecordValidationError> {
+        RData::validate(self.type_, &self.rdata)?;
+
+        if let Some(policy) = &self.policy {
+            policy.validate()?;
+        }
+
+        Ok(())
+    }
+
+    /// Preference and exchange of an MX record.
+    ///
+    /// Like [`RecordSpec::srv`] and [`RecordSpec::caa`], this is `None` if the
+    /// record is of another type, or its rdata is malformed.
+    pub fn mx(&self) -> Option<(u16, DomainName)> {
+        self.parse_rdata().ok()?.mx()
+    }
+
+    /// Priority, weight, port and target of an SRV record.
+    pub fn srv(&self) -> Option<SrvData> {
+        self.parse_rdata().ok()?.srv()
+    }
+
+    /// Flags, tag and value of a CAA record.
+    pub fn caa(&self) -> Option<CaaData> {
+        self.parse_rdata().ok()?.caa()
+    }
+
+    /// Check whether the record should be published at the given time,
+    /// according to its active window. Disabled records are never active.
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.enabled
+            && self
+                .active_window
+                .as_ref()
+                .is_none_or(|window| window.is_active(now))
+    }
+
     pub fn is_internet(&self) -> bool {
         self.class == Class::IN
     }
@@ -313,3 +692,198 @@ impl TryFrom<&Record> for RecordIdent {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Utc};
+    use k8s_openapi::apimachinery::pkg::apis::meta::v1::Time;
+    use kube::core::ObjectMeta;
+    use kubizone_common::{DomainName, Type};
+
+    use crate::{errors::RecordValidationError, rdata::SrvData, v1alpha1::ObservedGeneration};
+
+    use super::{
+        ActiveWindow, Record, RecordSpec, RecordStatus, Recurrence, RoutingPolicy,
+        RoutingPolicyError,
+    };
+
+    fn time(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    #[test]
+    fn test_active_window() {
+        let mut window = ActiveWindow {
+            start: Some(time("2024-01-01T22:00:00Z")),
+            end: Some(time("2024-01-02T02:00:00Z")),
+            recurrence: Recurrence::Never,
+        };
+
+        assert!(!window.is_active(time("2024-01-01T21:59:59Z")));
+        assert!(window.is_active(time("2024-01-01T22:00:00Z")));
+        assert!(window.is_active(time("2024-01-02T01:59:59Z")));
+        assert!(!window.is_active(time("2024-01-02T02:00:00Z")));
+        assert!(!window.is_active(time("2024-01-05T23:00:00Z")));
+
+        window.recurrence = Recurrence::Daily;
+        assert!(window.is_active(time("2024-01-05T23:00:00Z")));
+        assert!(!window.is_active(time("2024-01-05T12:00:00Z")));
+
+        window.recurrence = Recurrence::Weekly;
+        assert!(!window.is_active(time("2024-01-05T23:00:00Z")));
+        assert!(window.is_active(time("2024-01-08T23:00:00Z")));
+
+        // Without a start, the window only expires the record.
+        window.start = None;
+        assert!(window.is_active(time("2000-01-01T00:00:00Z")));
+        assert!(!window.is_active(time("2024-01-02T02:00:00Z")));
+        assert!(!window.is_active(time("2024-01-08T23:00:00Z")));
+
+        let window = ActiveWindow {
+            start: Some(time("2024-01-01T22:00:00Z")),
+            end: None,
+            recurrence: Recurrence::Never,
+        };
+        assert!(!window.is_active(time("2024-01-01T21:59:59Z")));
+        assert!(window.is_active(time("2100-01-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_expiry() {
+        let mut record = Record {
+            metadata: ObjectMeta {
+                creation_timestamp: Some(Time(time("2024-01-01T00:00:00Z"))),
+                ..Default::default()
+            },
+            spec: Default::default(),
+            status: None,
+        };
+
+        assert_eq!(record.expires_at(), None);
+        assert!(!record.is_expired(time("2100-01-01T00:00:00Z")));
+
+        record.spec.expires_after = Some(3600);
+        assert_eq!(record.expires_at(), Some(time("2024-01-01T01:00:00Z")));
+        assert!(!record.is_expired(time("2024-01-01T00:59:59Z")));
+        assert!(record.is_expired(time("2024-01-01T01:00:00Z")));
+
+        // Records which don't exist yet can't expire.
+        record.metadata.creation_timestamp = None;
+        assert!(!record.is_expired(time("2100-01-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn test_observed_generation() {
+        let mut record = Record {
+            metadata: ObjectMeta {
+                generation: Some(2),
+                ..Default::default()
+            },
+            spec: Default::default(),
+            status: None,
+        };
+
+        let mut status = RecordStatus::default();
+        assert!(!status.is_current(&record));
+
+        status.stamp(&record);
+        assert_eq!(status.observed_generation(), Some(2));
+        assert!(status.is_current(&record));
+
+        record.metadata.generation = Some(3);
+        assert!(!status.is_current(&record));
+    }
+
+    #[test]
+    fn test_enabled_by_default() {
+        let spec: RecordSpec = serde_json::from_value(serde_json::json!({
+            "domainName": "www",
+            "type": "A",
+            "rdata": "192.168.0.1",
+        }))
+        .unwrap();
+
+        assert!(spec.enabled);
+        assert!(RecordSpec::default().enabled);
+
+        let now = time("2024-01-01T00:00:00Z");
+        assert!(spec.is_active(now));
+        assert!(!RecordSpec {
+            enabled: false,
+            ..spec
+        }
+        .is_active(now));
+    }
+
+    #[test]
+    fn test_routing_policy() {
+        let mut policy = RoutingPolicy {
+            regions: vec![String::from("DK"), String::from("US-CA")],
+            latency_tier: Some(1),
+            subnets: vec![String::from("192.0.2.0/24"), String::from("2001:db8::/32")],
+        };
+        assert_eq!(policy.validate(), Ok(()));
+
+        policy.subnets.push(String::from("192.0.2.0/33"));
+        assert_eq!(
+            policy.validate(),
+            Err(RoutingPolicyError::InvalidSubnet(String::from(
+                "192.0.2.0/33"
+            )))
+        );
+
+        policy.subnets.clear();
+        policy.regions.push(String::from("dk"));
+        assert_eq!(
+            policy.validate(),
+            Err(RoutingPolicyError::InvalidRegion(String::from("dk")))
+        );
+
+        // Records check their routing policy along with their rdata.
+        let spec = RecordSpec {
+            type_: Type::A,
+            rdata: String::from("192.0.2.1"),
+            policy: Some(policy),
+            ..Default::default()
+        };
+        assert_eq!(
+            spec.validate(),
+            Err(RecordValidationError::Policy(
+                RoutingPolicyError::InvalidRegion(String::from("dk"))
+            ))
+        );
+    }
+
+    #[test]
+    fn test_typed_accessors() {
+        let mut spec = RecordSpec {
+            type_: Type::MX,
+            rdata: String::from("10 mail.example.org."),
+            ..Default::default()
+        };
+        let mail = DomainName::try_from("mail.example.org.").unwrap();
+        assert_eq!(spec.mx(), Some((10, mail.clone())));
+        assert_eq!(spec.srv(), None);
+
+        spec.type_ = Type::SRV;
+        assert_eq!(spec.srv(), None);
+        spec.rdata = String::from("0 5 25 mail.example.org.");
+        assert_eq!(
+            spec.srv(),
+            Some(SrvData {
+                priority: 0,
+                weight: 5,
+                port: 25,
+                target: mail
+            })
+        );
+        assert_eq!(spec.mx(), None);
+
+        spec.type_ = Type::CAA;
+        spec.rdata = String::from(r#"128 Issue "letsencrypt.org""#);
+        let caa = spec.caa().unwrap();
+        assert!(caa.is_critical());
+        assert_eq!(caa.tag, "issue");
+        assert_eq!(caa.value, "letsencrypt.org");
+    }
+}
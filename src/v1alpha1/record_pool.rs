@@ -0,0 +1,286 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, Type};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{ResponsePolicy, ZoneEntry, ZoneRef};
+
+/// Largest weight a single pool member may have.
+pub const MAX_POOL_WEIGHT: u32 = 255;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum RecordPoolError {
+    #[error("pool has no members")]
+    NoMembers,
+    #[error("all pool members have a weight of zero")]
+    NoWeight,
+    #[error("pool member {0} is listed more than once")]
+    DuplicateMember(String),
+    #[error("weight {weight} of pool member {rdata} exceeds the maximum of {MAX_POOL_WEIGHT}")]
+    WeightOutOfRange { rdata: String, weight: u32 },
+}
+
+/// Multiple weighted answers for a single domain name.
+///
+/// Plain DNS has no notion of weights, so a pool expands into one entry per
+/// member carrying its weight, see [`RecordPoolSpec::expand`]. Serializers
+/// which support weighted answers use the weights, all others publish the
+/// members as a regular record set, ordered by the pool's `responsePolicy`.
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "RecordPool",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "RecordPool",
+        namespaced
+    )
+)]
+#[kube(status = "RecordPoolStatus")]
+#[kube(printcolumn = r#"{"name":"domain name", "jsonPath": ".spec.domainName", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"type", "jsonPath": ".spec.type", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"fqdn", "jsonPath": ".status.fqdn", "type": "string"}"#)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordPoolSpec {
+    #[schemars(schema_with = "crate::schema::domain_name")]
+    pub domain_name: DomainName,
+    pub zone_ref: Option<ZoneRef>,
+    #[serde(rename = "type")]
+    pub type_: Type,
+    #[serde(default = "super::defaults::class")]
+    pub class: Class,
+    pub ttl: Option<u32>,
+
+    /// Order in which the members should be returned, for serializers which
+    /// support answer rotation but not weights. If unset, the serializer's
+    /// own default applies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_policy: Option<ResponsePolicy>,
+
+    /// Answers and their relative weights.
+    pub members: Vec<PoolMember>,
+}
+
+/// A single weighted answer in a [`RecordPool`].
+#[derive(
+    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolMember {
+    pub rdata: String,
+
+    /// Relative weight of this answer. Members with a weight of zero are
+    /// kept in the pool, but not published, which allows draining them.
+    #[serde(default = "super::defaults::pool_weight")]
+    #[schemars(range(max = 255))]
+    pub weight: u32,
+}
+
+impl PoolMember {
+    /// Record data identifying this member, shared by duplicate detection
+    /// and the ordering of expanded entries.
+    fn key(&self) -> &str {
+        self.rdata.trim()
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordPoolStatus {
+    #[schemars(schema_with = "crate::schema::optional_fully_qualified_domain_name")]
+    pub fqdn: Option<FullyQualifiedDomainName>,
+}
+
+impl RecordPoolSpec {
+    /// Check that the pool has members, that their weights are within range,
+    /// and that at least one of them has a non-zero weight.
+    pub fn validate(&self) -> Result<(), RecordPoolError> {
+        if self.members.is_empty() {
+            return Err(RecordPoolError::NoMembers);
+        }
+
+        let mut seen = BTreeSet::new();
+        for member in &self.members {
+            if !seen.insert(member.key()) {
+                return Err(RecordPoolError::DuplicateMember(member.rdata.clone()));
+            }
+
+            if member.weight > MAX_POOL_WEIGHT {
+                return Err(RecordPoolError::WeightOutOfRange {
+                    rdata: member.rdata.clone(),
+                    weight: member.weight,
+                });
+            }
+        }
+
+        if self.members.iter().all(|member| member.weight == 0) {
+            return Err(RecordPoolError::NoWeight);
+        }
+
+        Ok(())
+    }
+
+    /// Expand the pool into zone entries for `fqdn`.
+    ///
+    /// Members with a weight of zero are left out. Weights of the remaining
+    /// members are reduced by their greatest common divisor, so equivalent
+    /// pools produce identical entries, and the entries are ordered by
+    /// descending weight, then by rdata. Records without a TTL inherit
+    /// `default_ttl`, usually that of the zone.
+    pub fn expand(
+        &self,
+        fqdn: &FullyQualifiedDomainName,
+        default_ttl: u32,
+    ) -> Result<Vec<ZoneEntry>, RecordPoolError> {
+        self.validate()?;
+
+        let divisor = self.members.iter().map(|member| member.weight).fold(0, gcd);
+
+        let mut members: Vec<_> = self
+            .members
+            .iter()
+            .filter(|member| member.weight > 0)
+            .collect();
+
+        members.sort_by(|a, b| b.weight.cmp(&a.weight).then_with(|| a.key().cmp(b.key())));
+
+        Ok(members
+            .into_iter()
+            .map(|member| ZoneEntry {
+                fqdn: fqdn.clone(),
+                type_: self.type_,
+                class: self.class,
+                ttl: self.ttl.unwrap_or(default_ttl),
+                rdata: member.key().to_string(),
+                response_policy: self.response_policy,
+                weight: Some(member.weight / divisor),
+                policy: None,
+                views: Vec::new(),
+                source: None,
+            })
+            .collect())
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Display for RecordPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: Pools are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kubizone_common::{FullyQualifiedDomainName, Type};
+
+    use crate::v1alpha1::ResponsePolicy;
+
+    use super::{PoolMember, RecordPoolError, RecordPoolSpec};
+
+    fn member(rdata: &str, weight: u32) -> PoolMember {
+        PoolMember {
+            rdata: String::from(rdata),
+            weight,
+        }
+    }
+
+    #[test]
+    fn test_pool_expansion() {
+        let fqdn = FullyQualifiedDomainName::try_from("www.example.org.").unwrap();
+
+        let mut pool = RecordPoolSpec {
+            type_: Type::A,
+            members: vec![
+                member("192.168.0.2", 20),
+                member("192.168.0.3", 0),
+                member("192.168.0.1", 60),
+                member("192.168.0.4", 20),
+            ],
+            ..Default::default()
+        };
+
+        let entries = pool.expand(&fqdn, 300).unwrap();
+        let weights: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.rdata.as_str(), entry.weight.unwrap()))
+            .collect();
+
+        assert_eq!(
+            weights,
+            vec![("192.168.0.1", 3), ("192.168.0.2", 1), ("192.168.0.4", 1)]
+        );
+        assert_eq!(entries[0].ttl, 300);
+        assert_eq!(entries[0].response_policy, None);
+
+        // Surrounding whitespace neither affects the order, nor hides duplicates.
+        pool.members = vec![member("192.168.0.2", 1), member(" 192.168.0.1", 1)];
+        pool.response_policy = Some(ResponsePolicy::RoundRobin);
+        let entries = pool.expand(&fqdn, 300).unwrap();
+        assert_eq!(entries[0].rdata, "192.168.0.1");
+        assert_eq!(entries[0].response_policy, Some(ResponsePolicy::RoundRobin));
+
+        pool.members.push(member("192.168.0.1", 1));
+        assert_eq!(
+            pool.validate(),
+            Err(RecordPoolError::DuplicateMember(String::from(
+                "192.168.0.1"
+            )))
+        );
+
+        pool.members = vec![member("192.168.0.1", 0)];
+        assert_eq!(
+            pool.expand(&fqdn, 300).unwrap_err(),
+            RecordPoolError::NoWeight
+        );
+
+        pool.members = vec![member("192.168.0.1", 1000)];
+        assert!(matches!(
+            pool.validate(),
+            Err(RecordPoolError::WeightOutOfRange { weight: 1000, .. })
+        ));
+
+        pool.members.clear();
+        assert_eq!(pool.validate(), Err(RecordPoolError::NoMembers));
+    }
+}
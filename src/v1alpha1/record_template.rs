@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use kube::{CustomResource, Resource, ResourceExt};
+use kubizone_common::{Class, Type};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::RecordTemplateError, template::substitute};
+
+use super::{LabelSelector, Record, ZoneRef};
+
+/// Template stamping out one [`Record`] per namespace.
+///
+/// The `domainName` and `rdata` of the template may contain `${namespace}`,
+/// `${name}` and `${cluster}` placeholders, which are substituted for the
+/// namespace the record is rendered into, the name of the template, and
+/// the name of the cluster respectively, see [`RecordTemplate::render`].
+///
+/// Templates are cluster-scoped, since they render records into many
+/// namespaces. The rendered records are namespaced as usual, and owned by
+/// the template.
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(group = "dev.kubi.zone", version = "v1alpha1", kind = "RecordTemplate")
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(group = "kubi.zone", version = "v1alpha1", kind = "RecordTemplate")
+)]
+#[kube(printcolumn = r#"{"name":"domain name", "jsonPath": ".spec.domainName", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"type", "jsonPath": ".spec.type", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"data", "jsonPath": ".spec.rdata", "type": "string"}"#)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordTemplateSpec {
+    /// Domain name of the rendered records, such as `${namespace}.example.org.`
+    pub domain_name: String,
+
+    /// Zone of the rendered records. References without a namespace refer
+    /// to zones in the namespace each record is rendered into.
+    pub zone_ref: Option<ZoneRef>,
+    #[serde(rename = "type")]
+    pub type_: Type,
+    #[serde(default = "super::defaults::class")]
+    pub class: Class,
+    pub ttl: Option<u32>,
+
+    /// Record data of the rendered records, which may contain placeholders.
+    pub rdata: String,
+
+    /// Namespaces whose labels match this selector get a record rendered
+    /// into them. Every namespace does if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace_selector: Option<LabelSelector>,
+}
+
+impl RecordTemplateSpec {
+    /// Check if a record should be rendered into the namespace with the given labels.
+    pub fn covers_namespace(&self, labels: &BTreeMap<String, String>) -> bool {
+        self.namespace_selector
+            .as_ref()
+            .is_none_or(|selector| selector.matches(labels))
+    }
+}
+
+impl RecordTemplate {
+    /// Variables available to the placeholders of this template, when
+    /// rendered into `namespace` of `cluster`.
+    pub fn variables(&self, namespace: &str, cluster: &str) -> BTreeMap<String, String> {
+        BTreeMap::from([
+            (String::from("namespace"), namespace.to_string()),
+            (String::from("name"), self.name_any()),
+            (String::from("cluster"), cluster.to_string()),
+        ])
+    }
+
+    /// Render the record for `namespace` of `cluster`.
+    ///
+    /// The record is named after the template, and owned by it, so that
+    /// kubernetes garbage collects it when the template is deleted.
+    pub fn render(&self, namespace: &str, cluster: &str) -> Result<Record, RecordTemplateError> {
+        self.render_with(namespace, cluster, &BTreeMap::new())
+    }
+
+    /// Like [`RecordTemplate::render`], but also makes `variables`, such as
+    /// the [`ZoneSpec::variables`](super::ZoneSpec::variables) of the zone
+    /// the record is placed in, available to the placeholders. The
+    /// `namespace`, `name` and `cluster` variables take precedence.
+    pub fn render_with(
+        &self,
+        namespace: &str,
+        cluster: &str,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<Record, RecordTemplateError> {
+        let mut variables = variables.clone();
+        variables.extend(self.variables(namespace, cluster));
+
+        let mut builder = Record::builder(
+            &substitute(&self.spec.domain_name, &variables)?,
+            self.spec.type_,
+            &substitute(&self.spec.rdata, &variables)?,
+        )
+        .name(&self.name_any())
+        .namespace(namespace)
+        .class(self.spec.class);
+
+        if let Some(zone_ref) = &self.spec.zone_ref {
+            builder = builder.zone_ref(zone_ref.clone());
+        }
+
+        if let Some(ttl) = self.spec.ttl {
+            builder = builder.ttl(ttl);
+        }
+
+        let mut record = builder.build()?;
+        record
+            .owner_references_mut()
+            .extend(self.controller_owner_ref(&()));
+
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kube::{core::ObjectMeta, ResourceExt};
+    use kubizone_common::{DomainName, Type};
+
+    use crate::errors::{RecordTemplateError, SubstitutionError};
+
+    use super::{RecordTemplate, RecordTemplateSpec};
+
+    #[test]
+    fn test_render_template() {
+        let mut template = RecordTemplate {
+            metadata: ObjectMeta {
+                name: Some(String::from("preview")),
+                uid: Some(String::from("00000000-0000-0000-0000-000000000002")),
+                ..Default::default()
+            },
+            spec: RecordTemplateSpec {
+                domain_name: String::from("${name}.${namespace}.${cluster}.example.org."),
+                type_: Type::TXT,
+                ttl: Some(60),
+                rdata: String::from("owner=${namespace}"),
+                ..Default::default()
+            },
+        };
+
+        let record = template.render("web", "eu-1").unwrap();
+        assert_eq!(record.to_string(), "web/preview");
+        assert_eq!(
+            record.spec.domain_name,
+            DomainName::try_from("preview.web.eu-1.example.org.").unwrap()
+        );
+        assert_eq!(record.spec.rdata, "owner=web");
+        assert_eq!(record.spec.ttl, Some(60));
+        assert_eq!(record.owner_references()[0].name, "preview");
+
+        template.spec.rdata = String::from("${team}");
+        assert_eq!(
+            template.render("web", "eu-1").unwrap_err(),
+            RecordTemplateError::Substitution(SubstitutionError::Undefined(String::from("team")))
+        );
+
+        let zone_variables = [(String::from("team"), String::from("platform"))].into();
+        let record = template
+            .render_with("web", "eu-1", &zone_variables)
+            .unwrap();
+        assert_eq!(record.spec.rdata, "platform");
+
+        // Partially qualified names need a zone to be placed in.
+        template.spec.rdata = String::from("owner=${namespace}");
+        template.spec.domain_name = String::from("${namespace}");
+        assert!(matches!(
+            template.render("web", "eu-1"),
+            Err(RecordTemplateError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt::Display;
+
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::FullyQualifiedDomainName;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::reverse::{self, ReverseError};
+
+use super::ZoneRef;
+
+/// Reverse DNS zone for a network, whose name is derived from its CIDR.
+///
+/// The controller materializes a Zone named after the network, see
+/// [`ReverseZoneSpec::zone_name`], and records its name in the status.
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "ReverseZone",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "ReverseZone",
+        namespaced
+    )
+)]
+#[kube(status = "ReverseZoneStatus")]
+#[kube(printcolumn = r#"{"name":"cidr", "jsonPath": ".spec.cidr", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"fqdn", "jsonPath": ".status.fqdn", "type": "string"}"#)]
+#[serde(rename_all = "camelCase")]
+pub struct ReverseZoneSpec {
+    /// IPv4 or IPv6 network in CIDR notation, such as `192.0.2.0/24`.
+    ///
+    /// IPv4 prefixes must be a multiple of 8, or longer than 24 for classless
+    /// delegation. IPv6 prefixes must be a multiple of 4.
+    #[schemars(regex(pattern = r"^[0-9a-fA-F:.]+/[0-9]{1,3}$"))]
+    pub cidr: String,
+
+    /// Naming of classless IPv4 zones.
+    #[serde(default)]
+    pub classless_style: ClasslessStyle,
+
+    /// Parent zone the reverse zone is delegated from, if managed by kubizone.
+    pub zone_ref: Option<ZoneRef>,
+}
+
+/// Naming of [RFC 2317](https://datatracker.ietf.org/doc/html/rfc2317)
+/// classless reverse zones. RFC 2317 does not mandate a format, but uses
+/// a `/` separator, which is not a valid hostname character.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum ClasslessStyle {
+    /// First address and prefix length, such as `64-26.2.0.192.in-addr.arpa.`.
+    #[default]
+    Prefix,
+    /// First and last address, such as `64-127.2.0.192.in-addr.arpa.`.
+    Range,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReverseZoneStatus {
+    #[schemars(schema_with = "crate::schema::optional_fully_qualified_domain_name")]
+    pub fqdn: Option<FullyQualifiedDomainName>,
+}
+
+impl ReverseZoneSpec {
+    /// Name of the reverse zone for [`ReverseZoneSpec::cidr`].
+    pub fn zone_name(&self) -> Result<FullyQualifiedDomainName, ReverseError> {
+        reverse::reverse_zone_name(&reverse::parse_cidr(&self.cidr)?, self.classless_style)
+    }
+}
+
+impl Display for ReverseZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: ReverseZones are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    fmt::Display,
+    net::{IpAddr, SocketAddr},
+};
+
+use chrono::{DateTime, Utc};
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::FullyQualifiedDomainName;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::serial::serial_lt;
+
+use super::{TsigSecretRef, ZoneEntry, ZoneStatus};
+
+/// Zone mastered by a nameserver outside of the cluster.
+///
+/// The controller transfers the zone from its primary, and mirrors its
+/// records into the status as [`ZoneEntry`]s, so that it can be consumed
+/// the same way as the entries of a [`Zone`](super::Zone).
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "SecondaryZone",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "SecondaryZone",
+        namespaced
+    )
+)]
+#[kube(status = "SecondaryZoneStatus")]
+#[kube(printcolumn = r#"{"name":"domain name", "jsonPath": ".spec.domainName", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"serial", "jsonPath": ".status.serial", "type": "string"}"#)]
+#[kube(
+    printcolumn = r#"{"name":"last transfer", "jsonPath": ".status.lastTransferTime", "type": "date"}"#
+)]
+#[serde(rename_all = "camelCase")]
+pub struct SecondaryZoneSpec {
+    #[schemars(schema_with = "crate::schema::fully_qualified_domain_name")]
+    pub domain_name: FullyQualifiedDomainName,
+
+    /// Nameserver the zone is transferred from.
+    pub primary: ExternalPrimary,
+}
+
+/// External nameserver serving zone transfers.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalPrimary {
+    /// Addresses of the primary, tried in order.
+    #[schemars(length(min = 1))]
+    pub addresses: Vec<IpAddr>,
+
+    #[serde(default = "super::defaults::dns_port")]
+    pub port: u16,
+
+    /// Secret holding the TSIG key used to authenticate transfers, if any.
+    pub tsig_secret_ref: Option<TsigSecretRef>,
+}
+
+impl Default for ExternalPrimary {
+    fn default() -> Self {
+        Self {
+            addresses: Vec::new(),
+            port: super::defaults::DNS_PORT,
+            tsig_secret_ref: None,
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SecondaryZoneStatus {
+    /// Records of the zone, as of the latest transfer.
+    #[serde(default)]
+    pub entries: Vec<ZoneEntry>,
+
+    /// Serial of the zone, as of the latest transfer.
+    pub serial: Option<u32>,
+
+    /// Hash of the entries, see [`ZoneStatus::compute_hash`].
+    pub hash: Option<String>,
+
+    /// Time of the latest successful transfer.
+    pub last_transfer_time: Option<DateTime<Utc>>,
+}
+
+impl ExternalPrimary {
+    /// Socket addresses of the primary, in order.
+    pub fn socket_addrs(&self) -> Vec<SocketAddr> {
+        self.addresses
+            .iter()
+            .map(|address| SocketAddr::new(*address, self.port))
+            .collect()
+    }
+}
+
+impl SecondaryZoneStatus {
+    /// Record the result of a transfer of the zone at `fqdn`.
+    ///
+    /// Entries outside of the zone are discarded, since a primary has no
+    /// authority over them.
+    pub fn set_transfer(
+        &mut self,
+        fqdn: &FullyQualifiedDomainName,
+        serial: u32,
+        entries: Vec<ZoneEntry>,
+        now: DateTime<Utc>,
+    ) {
+        self.entries = entries
+            .into_iter()
+            .filter(|entry| &entry.fqdn == fqdn || entry.fqdn.is_subdomain_of(fqdn))
+            .collect();
+
+        self.hash = Some(ZoneStatus::compute_hash(&self.entries));
+        self.serial = Some(serial);
+        self.last_transfer_time = Some(now);
+    }
+}
+
+impl SecondaryZone {
+    /// Serial of the zone as of the latest transfer, if any.
+    pub fn serial(&self) -> Option<u32> {
+        self.status.as_ref().and_then(|status| status.serial)
+    }
+
+    /// Check if a transfer of the zone at `serial` would bring in changes.
+    ///
+    /// Serial numbers are compared using
+    /// [RFC 1982](https://datatracker.ietf.org/doc/html/rfc1982) arithmetic.
+    pub fn is_outdated(&self, serial: u32) -> bool {
+        self.serial()
+            .is_none_or(|current| serial_lt(current, serial))
+    }
+}
+
+impl Display for SecondaryZone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: SecondaryZones are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use kubizone_common::FullyQualifiedDomainName;
+
+    use crate::v1alpha1::ZoneEntry;
+
+    use super::{SecondaryZone, SecondaryZoneSpec, SecondaryZoneStatus};
+
+    #[test]
+    fn test_secondary_zone_transfer() {
+        let fqdn = FullyQualifiedDomainName::try_from("example.org.").unwrap();
+
+        let spec: SecondaryZoneSpec = serde_json::from_str(
+            r#"{"domainName": "example.org.", "primary": {"addresses": ["192.0.2.1", "2001:db8::1"]}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            spec.primary
+                .socket_addrs()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["192.0.2.1:53", "[2001:db8::1]:53"]
+        );
+
+        let mut zone = SecondaryZone::new("example-org", spec);
+        assert!(zone.is_outdated(1));
+
+        let entries: Vec<ZoneEntry> = [
+            "example.org. 300 IN MX 10 mail.example.org.",
+            "www.example.org. 300 IN A 192.168.0.1",
+            "www.example.com. 300 IN A 192.168.0.2",
+        ]
+        .into_iter()
+        .map(|line| line.parse().unwrap())
+        .collect();
+
+        let mut status = SecondaryZoneStatus::default();
+        status.set_transfer(&fqdn, u32::MAX, entries, Utc::now());
+        assert_eq!(status.entries.len(), 2);
+        assert!(status.hash.is_some());
+
+        zone.status = Some(status);
+        assert!(!zone.is_outdated(u32::MAX));
+        assert!(!zone.is_outdated(u32::MAX - 1));
+        assert!(zone.is_outdated(0));
+    }
+}
@@ -0,0 +1,278 @@
+use std::{fmt::Display, net::IpAddr};
+
+use k8s_openapi::api::core::v1::Service;
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, Type};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{RecordSpec, ZoneRef};
+
+/// Publishes the addresses of a Kubernetes Service as A and AAAA records.
+///
+/// The controller watches the referenced Service, and maintains one record
+/// per address through [`ServiceRecordSpec::record_specs`].
+#[derive(
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "ServiceRecord",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "ServiceRecord",
+        namespaced
+    )
+)]
+#[kube(status = "ServiceRecordStatus")]
+#[kube(printcolumn = r#"{"name":"domain name", "jsonPath": ".spec.domainName", "type": "string"}"#)]
+#[kube(
+    printcolumn = r#"{"name":"service", "jsonPath": ".spec.serviceRef.name", "type": "string"}"#
+)]
+#[kube(printcolumn = r#"{"name":"fqdn", "jsonPath": ".status.fqdn", "type": "string"}"#)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceRecordSpec {
+    #[schemars(schema_with = "crate::schema::domain_name")]
+    pub domain_name: DomainName,
+    pub zone_ref: Option<ZoneRef>,
+    #[serde(default = "super::defaults::class")]
+    pub class: Class,
+    pub ttl: Option<u32>,
+
+    /// Service whose addresses are published.
+    pub service_ref: ServiceRef,
+
+    /// Which of the Service's addresses are published.
+    #[serde(default)]
+    pub address_type: ServiceAddressType,
+}
+
+/// Reference to a Service in the namespace of the referencing resource.
+///
+/// Services in other namespaces can't be referenced, since that would let
+/// anyone allowed to create a ServiceRecord publish the addresses of any
+/// Service in the cluster.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct ServiceRef {
+    pub name: String,
+}
+
+/// Source of the addresses published for a Service.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum ServiceAddressType {
+    /// Load balancer addresses if the Service has any, and cluster IPs otherwise.
+    #[default]
+    Auto,
+    /// Only addresses assigned to the Service's load balancer.
+    LoadBalancer,
+    /// Only the Service's cluster IPs.
+    #[serde(rename = "ClusterIP")]
+    ClusterIp,
+}
+
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceRecordStatus {
+    #[schemars(schema_with = "crate::schema::optional_fully_qualified_domain_name")]
+    pub fqdn: Option<FullyQualifiedDomainName>,
+
+    /// Addresses currently being published.
+    #[serde(default)]
+    pub addresses: Vec<IpAddr>,
+}
+
+impl ServiceRecordSpec {
+    /// Addresses of the Service which should be published.
+    ///
+    /// Load balancer ingress points which only have a hostname are skipped,
+    /// as are headless Services.
+    pub fn addresses(&self, service: &Service) -> Vec<IpAddr> {
+        let load_balancer = || -> Vec<IpAddr> {
+            service
+                .status
+                .as_ref()
+                .and_then(|status| status.load_balancer.as_ref())
+                .and_then(|load_balancer| load_balancer.ingress.as_ref())
+                .into_iter()
+                .flatten()
+                .filter_map(|ingress| ingress.ip.as_deref()?.parse().ok())
+                .collect()
+        };
+
+        let cluster_ip = || -> Vec<IpAddr> {
+            let Some(spec) = service.spec.as_ref() else {
+                return Vec::new();
+            };
+
+            // Dual-stack Services list every address in clusterIPs, of which
+            // clusterIP is always the first.
+            spec.cluster_ips
+                .iter()
+                .flatten()
+                .chain(spec.cluster_ip.as_ref())
+                .filter_map(|ip| ip.parse().ok())
+                .fold(Vec::new(), |mut addresses, ip| {
+                    if !addresses.contains(&ip) {
+                        addresses.push(ip);
+                    }
+                    addresses
+                })
+        };
+
+        match self.address_type {
+            ServiceAddressType::Auto => {
+                let addresses = load_balancer();
+                if addresses.is_empty() {
+                    cluster_ip()
+                } else {
+                    addresses
+                }
+            }
+            ServiceAddressType::LoadBalancer => load_balancer(),
+            ServiceAddressType::ClusterIp => cluster_ip(),
+        }
+    }
+
+    /// A and AAAA records publishing the addresses of the Service.
+    pub fn record_specs(&self, service: &Service) -> Vec<RecordSpec> {
+        self.addresses(service)
+            .into_iter()
+            .map(|address| RecordSpec {
+                domain_name: self.domain_name.clone(),
+                zone_ref: self.zone_ref.clone(),
+                type_: if address.is_ipv4() {
+                    Type::A
+                } else {
+                    Type::AAAA
+                },
+                class: self.class,
+                ttl: self.ttl,
+                rdata: address.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
+impl Display for ServiceRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: ServiceRecords are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use k8s_openapi::api::core::v1::{
+        LoadBalancerIngress, LoadBalancerStatus, Service, ServiceSpec, ServiceStatus,
+    };
+    use kubizone_common::Type;
+
+    use super::{ServiceAddressType, ServiceRecordSpec};
+
+    fn service(cluster_ips: &[&str], ingress: &[&str]) -> Service {
+        Service {
+            spec: Some(ServiceSpec {
+                cluster_ip: cluster_ips.first().map(ToString::to_string),
+                cluster_ips: Some(cluster_ips.iter().map(ToString::to_string).collect()),
+                ..Default::default()
+            }),
+            status: Some(ServiceStatus {
+                load_balancer: Some(LoadBalancerStatus {
+                    ingress: Some(
+                        ingress
+                            .iter()
+                            .map(|address| LoadBalancerIngress {
+                                ip: address
+                                    .parse::<std::net::IpAddr>()
+                                    .is_ok()
+                                    .then(|| address.to_string()),
+                                hostname: address
+                                    .parse::<std::net::IpAddr>()
+                                    .is_err()
+                                    .then(|| address.to_string()),
+                                ..Default::default()
+                            })
+                            .collect(),
+                    ),
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_service_addresses() {
+        let mut spec = ServiceRecordSpec::default();
+
+        let cluster_only = service(&["10.0.0.1", "fd00::1"], &[]);
+        let records = spec.record_specs(&cluster_only);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].type_, Type::A);
+        assert_eq!(records[1].type_, Type::AAAA);
+        assert_eq!(records[1].rdata, "fd00::1");
+
+        let load_balanced = service(&["10.0.0.1"], &["203.0.113.1", "lb.example.org"]);
+        assert_eq!(
+            spec.addresses(&load_balanced),
+            vec!["203.0.113.1".parse::<std::net::IpAddr>().unwrap()]
+        );
+
+        spec.address_type = ServiceAddressType::ClusterIp;
+        assert_eq!(
+            spec.addresses(&load_balanced),
+            vec!["10.0.0.1".parse::<std::net::IpAddr>().unwrap()]
+        );
+
+        spec.address_type = ServiceAddressType::LoadBalancer;
+        assert!(spec.addresses(&cluster_only).is_empty());
+
+        // Headless services have no addresses to publish.
+        spec.address_type = ServiceAddressType::Auto;
+        assert!(spec.addresses(&service(&["None"], &[])).is_empty());
+    }
+}
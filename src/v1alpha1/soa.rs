@@ -0,0 +1,158 @@
+use std::fmt::Display;
+
+use kubizone_common::{error::FullyQualifiedDomainNameError, FullyQualifiedDomainName};
+use schemars::JsonSchema;
+use serde::{de::Error as _, Deserialize, Serialize};
+use thiserror::Error;
+
+/// Produced when an email address or domain name cannot be used as
+/// the `RNAME` of a zone's SOA record.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum RnameError {
+    #[error("email address has no local part")]
+    EmptyLocalPart,
+    #[error("email address has no domain")]
+    EmptyDomain,
+    /// Dots in the local part would have to be escaped in the zone file,
+    /// which domain names of this crate cannot represent.
+    #[error("local part of email address cannot contain dots")]
+    DottedLocalPart,
+    #[error("{0}")]
+    Domain(#[from] FullyQualifiedDomainNameError),
+}
+
+/// Mailbox of the person responsible for a zone, as published in the
+/// `RNAME` field of its SOA record.
+///
+/// Can be constructed either from an email address such as
+/// `hostmaster@example.org`, or directly from its domain name form
+/// `hostmaster.example.org.`, where the first label is the local part.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Rname(FullyQualifiedDomainName);
+
+impl Rname {
+    /// Convert an email address into its `RNAME` form.
+    pub fn from_email(email: &str) -> Result<Self, RnameError> {
+        let (local, domain) = email.rsplit_once('@').ok_or(RnameError::EmptyDomain)?;
+
+        if local.is_empty() {
+            return Err(RnameError::EmptyLocalPart);
+        }
+
+        if local.contains('.') {
+            return Err(RnameError::DottedLocalPart);
+        }
+
+        let domain = domain.trim_end_matches('.');
+        if domain.is_empty() {
+            return Err(RnameError::EmptyDomain);
+        }
+
+        Ok(Rname(FullyQualifiedDomainName::try_from(format!(
+            "{local}.{domain}."
+        ))?))
+    }
+
+    /// Convert the `RNAME` back into an email address.
+    pub fn email(&self) -> String {
+        let dns = self.0.to_string();
+
+        // Unwrap safety: Construction guarantees at least two labels.
+        let (local, domain) = dns.split_once('.').unwrap();
+        format!("{local}@{}", domain.trim_end_matches('.'))
+    }
+
+    pub fn as_fqdn(&self) -> &FullyQualifiedDomainName {
+        &self.0
+    }
+}
+
+impl TryFrom<&str> for Rname {
+    type Error = RnameError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        if value.contains('@') {
+            return Rname::from_email(value);
+        }
+
+        let fqdn = FullyQualifiedDomainName::try_from(value)?;
+        if fqdn.iter().count() < 2 {
+            return Err(RnameError::EmptyDomain);
+        }
+
+        Ok(Rname(fqdn))
+    }
+}
+
+impl TryFrom<String> for Rname {
+    type Error = RnameError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Rname::try_from(value.as_str())
+    }
+}
+
+impl Display for Rname {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl JsonSchema for Rname {
+    fn schema_name() -> String {
+        <String as JsonSchema>::schema_name()
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        <String as JsonSchema>::json_schema(gen)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rname {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+
+        Self::try_from(value).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Rname {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Rname, RnameError};
+
+    #[test]
+    fn test_rname_conversion() {
+        let rname = Rname::try_from("hostmaster@example.org").unwrap();
+        assert_eq!(rname.to_string(), "hostmaster.example.org.");
+        assert_eq!(rname.email(), "hostmaster@example.org");
+
+        assert_eq!(Rname::try_from("hostmaster.example.org.").unwrap(), rname);
+
+        assert_eq!(
+            Rname::try_from("john.doe@example.org"),
+            Err(RnameError::DottedLocalPart)
+        );
+        assert_eq!(
+            Rname::try_from("@example.org"),
+            Err(RnameError::EmptyLocalPart)
+        );
+        assert_eq!(Rname::try_from("hostmaster@"), Err(RnameError::EmptyDomain));
+        assert_eq!(Rname::try_from("org."), Err(RnameError::EmptyDomain));
+        assert!(matches!(
+            Rname::try_from("host master@example.org"),
+            Err(RnameError::Domain(_))
+        ));
+    }
+}
@@ -0,0 +1,276 @@
+use std::{
+    fmt::Display,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+};
+
+use ipnet::IpNet;
+use kubizone_common::FullyQualifiedDomainName;
+use schemars::{
+    schema::{InstanceType, Schema, SchemaObject, StringValidation},
+    JsonSchema,
+};
+use serde::{de::Error as _, Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum TransferPeerError {
+    #[error("{0} is neither an address, a network, nor a fully qualified domain name")]
+    Invalid(String),
+    #[error("network {0} has host bits set, did you mean {1}?")]
+    HostBitsSet(IpNet, IpNet),
+}
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum NotifyTargetError {
+    #[error("{0} is neither an address nor a fully qualified domain name")]
+    Invalid(String),
+}
+
+/// Peer allowed to transfer a zone, see [`ZoneSpec::allow_transfer`](super::ZoneSpec::allow_transfer).
+///
+/// Written as an address such as `192.0.2.1`, a network such as
+/// `2001:db8::/32`, or a fully qualified domain name such as
+/// `ns2.example.org.`, which provisioners resolve themselves.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TransferPeer {
+    Network(IpNet),
+    Host(FullyQualifiedDomainName),
+}
+
+impl TransferPeer {
+    /// Check if `address` is covered by this peer. Always false for
+    /// domain names, which must be resolved first.
+    pub fn contains(&self, address: &IpAddr) -> bool {
+        match self {
+            TransferPeer::Network(net) => net.contains(address),
+            TransferPeer::Host(_) => false,
+        }
+    }
+}
+
+impl FromStr for TransferPeer {
+    type Err = TransferPeerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Ok(address) = s.parse::<IpAddr>() {
+            return Ok(TransferPeer::Network(IpNet::from(address)));
+        }
+
+        if let Ok(net) = s.parse::<IpNet>() {
+            if net.addr() != net.network() {
+                return Err(TransferPeerError::HostBitsSet(net, net.trunc()));
+            }
+
+            return Ok(TransferPeer::Network(net));
+        }
+
+        parse_host(s)
+            .map(TransferPeer::Host)
+            .ok_or_else(|| TransferPeerError::Invalid(s.to_string()))
+    }
+}
+
+/// Single addresses are displayed without a prefix length, as written.
+impl Display for TransferPeer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransferPeer::Network(net) if net.prefix_len() == net.max_prefix_len() => {
+                net.addr().fmt(f)
+            }
+            TransferPeer::Network(net) => net.fmt(f),
+            TransferPeer::Host(fqdn) => fqdn.fmt(f),
+        }
+    }
+}
+
+impl JsonSchema for TransferPeer {
+    fn schema_name() -> String {
+        String::from("TransferPeer")
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> Schema {
+        peer_schema(r"[0-9a-fA-F:.]+(/[0-9]{1,3})?")
+    }
+}
+
+impl<'de> Deserialize<'de> for TransferPeer {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_parsed(deserializer)
+    }
+}
+
+impl Serialize for TransferPeer {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// Nameserver to send DNS NOTIFY messages to when the serial of a zone
+/// changes, see [`ZoneSpec::notify_targets`](super::ZoneSpec::notify_targets).
+///
+/// Written as an address such as `192.0.2.1`, or a fully qualified domain
+/// name such as `ns2.example.org.`, which provisioners resolve themselves.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NotifyTarget {
+    Address(IpAddr),
+    Host(FullyQualifiedDomainName),
+}
+
+impl NotifyTarget {
+    /// Socket address to send notifications to, on the standard DNS port.
+    /// `None` for domain names, which must be resolved first.
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        match self {
+            NotifyTarget::Address(address) => {
+                Some(SocketAddr::new(*address, super::defaults::DNS_PORT))
+            }
+            NotifyTarget::Host(_) => None,
+        }
+    }
+}
+
+impl FromStr for NotifyTarget {
+    type Err = NotifyTargetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if let Ok(address) = s.parse::<IpAddr>() {
+            return Ok(NotifyTarget::Address(address));
+        }
+
+        parse_host(s)
+            .map(NotifyTarget::Host)
+            .ok_or_else(|| NotifyTargetError::Invalid(s.to_string()))
+    }
+}
+
+impl Display for NotifyTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotifyTarget::Address(address) => address.fmt(f),
+            NotifyTarget::Host(fqdn) => fqdn.fmt(f),
+        }
+    }
+}
+
+impl JsonSchema for NotifyTarget {
+    fn schema_name() -> String {
+        String::from("NotifyTarget")
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> Schema {
+        peer_schema(r"[0-9a-fA-F:.]+")
+    }
+}
+
+impl<'de> Deserialize<'de> for NotifyTarget {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_parsed(deserializer)
+    }
+}
+
+impl Serialize for NotifyTarget {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+/// Parse a fully qualified domain name, for peers given by name.
+fn parse_host(s: &str) -> Option<FullyQualifiedDomainName> {
+    FullyQualifiedDomainName::try_from(s).ok()
+}
+
+/// Schema of a peer, which is either matched by the `address` pattern,
+/// or a fully qualified domain name.
+fn peer_schema(address: &str) -> Schema {
+    SchemaObject {
+        instance_type: Some(InstanceType::String.into()),
+        string: Some(Box::new(StringValidation {
+            pattern: Some(format!(r"^({address}|([a-z0-9_-]+\.)+)$")),
+            ..Default::default()
+        })),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Deserialize a peer from its string form, through its [`FromStr`] implementation.
+fn deserialize_parsed<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(D::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::{NotifyTarget, NotifyTargetError, TransferPeer, TransferPeerError};
+
+    #[test]
+    fn test_transfer_peers() {
+        let peers: Vec<TransferPeer> =
+            serde_json::from_str(r#"["192.0.2.1", "2001:db8::/32", "ns2.example.org."]"#).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&peers).unwrap(),
+            r#"["192.0.2.1","2001:db8::/32","ns2.example.org."]"#
+        );
+
+        let address: IpAddr = "2001:db8::53".parse().unwrap();
+        assert!(!peers[0].contains(&address));
+        assert!(peers[1].contains(&address));
+        assert!(!peers[2].contains(&address));
+
+        assert!(matches!(
+            "192.0.2.1/24".parse::<TransferPeer>(),
+            Err(TransferPeerError::HostBitsSet(..))
+        ));
+        assert!(matches!(
+            "not a peer".parse::<TransferPeer>(),
+            Err(TransferPeerError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_notify_targets() {
+        let targets: Vec<NotifyTarget> =
+            serde_json::from_str(r#"["2001:db8::53", "ns2.example.org."]"#).unwrap();
+
+        assert_eq!(
+            targets[0].socket_addr().unwrap().to_string(),
+            "[2001:db8::53]:53"
+        );
+        assert_eq!(targets[1].socket_addr(), None);
+        assert_eq!(
+            serde_json::to_string(&targets).unwrap(),
+            r#"["2001:db8::53","ns2.example.org."]"#
+        );
+
+        assert!(matches!(
+            "192.0.2.0/24".parse::<NotifyTarget>(),
+            Err(NotifyTargetError::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+use std::{fmt::Display, str::FromStr};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum TsigError {
+    #[error("unknown or unsupported TSIG algorithm {0}")]
+    UnknownAlgorithm(String),
+}
+
+/// Security of zone transfers.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct TransferSecurity {
+    /// TSIG key which transfers must be signed with.
+    pub tsig_secret_ref: Option<TsigSecretRef>,
+}
+
+/// Reference to a Secret holding a TSIG key, in the namespace of the
+/// referencing resource.
+///
+/// Secrets in other namespaces can't be referenced, since that would let
+/// anyone allowed to create a zone sign transfers with the keys of others.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, Default, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct TsigSecretRef {
+    pub name: String,
+
+    #[serde(default)]
+    pub algorithm: TsigAlgorithm,
+}
+
+/// HMAC algorithms of [RFC 8945](https://datatracker.ietf.org/doc/html/rfc8945#section-6),
+/// except for the insecure HMAC-MD5 and truncated variants.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum TsigAlgorithm {
+    #[serde(rename = "hmac-sha1")]
+    HmacSha1,
+    #[serde(rename = "hmac-sha224")]
+    HmacSha224,
+    #[default]
+    #[serde(rename = "hmac-sha256")]
+    HmacSha256,
+    #[serde(rename = "hmac-sha384")]
+    HmacSha384,
+    #[serde(rename = "hmac-sha512")]
+    HmacSha512,
+}
+
+impl TsigAlgorithm {
+    /// Algorithm name, as used in TSIG records and nameserver configuration.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            TsigAlgorithm::HmacSha1 => "hmac-sha1",
+            TsigAlgorithm::HmacSha224 => "hmac-sha224",
+            TsigAlgorithm::HmacSha256 => "hmac-sha256",
+            TsigAlgorithm::HmacSha384 => "hmac-sha384",
+            TsigAlgorithm::HmacSha512 => "hmac-sha512",
+        }
+    }
+
+    /// Check if `name` is the name of a supported algorithm.
+    pub fn is_valid_name(name: &str) -> bool {
+        name.parse::<TsigAlgorithm>().is_ok()
+    }
+}
+
+impl Display for TsigAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses algorithm names case-insensitively, with or without a trailing
+/// dot, since names are domain names in TSIG records.
+impl FromStr for TsigAlgorithm {
+    type Err = TsigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s
+            .strip_suffix('.')
+            .unwrap_or(s)
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "hmac-sha1" => Ok(TsigAlgorithm::HmacSha1),
+            "hmac-sha224" => Ok(TsigAlgorithm::HmacSha224),
+            "hmac-sha256" => Ok(TsigAlgorithm::HmacSha256),
+            "hmac-sha384" => Ok(TsigAlgorithm::HmacSha384),
+            "hmac-sha512" => Ok(TsigAlgorithm::HmacSha512),
+            _ => Err(TsigError::UnknownAlgorithm(s.to_string())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TsigAlgorithm, TsigError, TsigSecretRef};
+
+    #[test]
+    fn test_tsig_algorithm_names() {
+        assert_eq!("HMAC-SHA512.".parse(), Ok(TsigAlgorithm::HmacSha512));
+        assert_eq!(
+            "hmac-md5.sig-alg.reg.int".parse::<TsigAlgorithm>(),
+            Err(TsigError::UnknownAlgorithm(String::from(
+                "hmac-md5.sig-alg.reg.int"
+            )))
+        );
+        assert!(!TsigAlgorithm::is_valid_name("sha256"));
+
+        let secret_ref: TsigSecretRef = serde_json::from_str(r#"{"name": "transfer"}"#).unwrap();
+        assert_eq!(secret_ref.algorithm.to_string(), "hmac-sha256");
+    }
+}
@@ -1,58 +1,40 @@
-use std::fmt::Display;
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    net::IpAddr,
+};
 
-use kube::{core::object::HasSpec, CustomResource, Resource as _, ResourceExt};
-use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, Pattern, RecordIdent, Type};
+use chrono::{DateTime, Utc};
+use kube::{CustomResource, Resource, ResourceExt};
+use kubizone_common::{
+    error::PatternSegmentError, Class, DomainName, FullyQualifiedDomainName, Pattern, RecordIdent,
+    Type,
+};
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
 use tracing::*;
+use twox_hash::XxHash64;
 
-use crate::PARENT_ZONE_LABEL;
+use crate::{
+    canonical::sort_key,
+    errors::{DelegationError, RecordValidationError, SubstitutionError, ValidationError},
+    otel, parent_reference,
+    serial::serial_gt,
+    template::substitute,
+    zonefile,
+};
 
-use super::{DomainExt, Record, ZoneRef};
+use crate::pattern::{namespace_matches_pattern, DomainRegex};
 
-pub mod defaults {
-
-    pub const REFRESH: u32 = 86400;
-    /// Service addresses might change often, so we use a low
-    /// Time-to-Live to increase cache responsiveness.
-    pub const TTL: u32 = 360;
-
-    /// Recommendation for small and stable zones[^1]: 7200 seconds (2 hours).
-    ///
-    /// [^1]: <https://www.ripe.net/publications/docs/ripe-203>
-    pub const RETRY: u32 = 7200;
-
-    /// Recommendation for small and stable zones[^1]: 3600000 seconds (1000 hours).
-    ///
-    /// [^1]: <https://www.ripe.net/publications/docs/ripe-203>
-    pub const EXPIRE: u32 = 3600000;
-
-    /// Recommendation for small and stable zones[^1]: 172800 seconds (2 days),
-    /// but we select a much lower value to increase cache responsiveness
-    /// and reduce failed lookups to records still being provisioned.
-    ///
-    /// [^1]: <https://www.ripe.net/publications/docs/ripe-203>
-    pub const NEGATIVE_RESPONSE_CACHE: u32 = 360;
-
-    // The functions below are only there for use with `serde(default)`.
-    pub(super) const fn refresh() -> u32 {
-        REFRESH
-    }
-    pub(super) const fn ttl() -> u32 {
-        TTL
-    }
-    pub(super) const fn retry() -> u32 {
-        RETRY
-    }
-
-    pub(super) const fn expire() -> u32 {
-        EXPIRE
-    }
-
-    pub(super) const fn negative_response_cache() -> u32 {
-        NEGATIVE_RESPONSE_CACHE
-    }
-}
+use super::{
+    check_depth, condition, validate_parent, CompiledDelegations, Condition, DNSView, Dnssec,
+    DnssecStatus, DomainExt, LabelSelector, NotifyTarget, ObservedGeneration, Record,
+    RecordApproval, RecordSpec, ResponsePolicy, Rname, RoutingPolicy, TransferPeer,
+    TransferSecurity, ViewRef, ZoneDataRef, ZoneRef,
+};
 
 #[derive(
     Default,
@@ -89,20 +71,27 @@ pub mod defaults {
 #[kube(printcolumn = r#"{"name":"fqdn", "jsonPath": ".status.fqdn", "type": "string"}"#)]
 #[kube(printcolumn = r#"{"name":"hash", "jsonPath": ".status.hash", "type": "string"}"#)]
 #[kube(printcolumn = r#"{"name":"serial", "jsonPath": ".status.serial", "type": "string"}"#)]
+#[kube(
+    printcolumn = r#"{"name":"entries", "jsonPath": ".status.stats.entries", "type": "integer"}"#
+)]
+#[kube(
+    printcolumn = r#"{"name":"last change", "jsonPath": ".status.stats.lastChange", "type": "date", "priority": 1}"#
+)]
 #[cfg_attr(
     feature = "dev",
     kube(
-        printcolumn = r#"{"name":"parent", "jsonPath": ".metadata.labels.kubi\\.zone/parent-zone", "type": "string"}"#
+        printcolumn = r#"{"name":"parent", "jsonPath": ".metadata.labels.dev\\.kubi\\.zone/parent-zone", "type": "string"}"#
     )
 )]
 #[cfg_attr(
     not(feature = "dev"),
     kube(
-        printcolumn = r#"{"name":"parent", "jsonPath": ".metadata.labels.dev\\.kubi\\.zone/parent-zone", "type": "string"}"#
+        printcolumn = r#"{"name":"parent", "jsonPath": ".metadata.labels.kubi\\.zone/parent-zone", "type": "string"}"#
     )
 )]
 #[serde(rename_all = "camelCase")]
 pub struct ZoneSpec {
+    #[schemars(schema_with = "crate::schema::domain_name")]
     pub domain_name: DomainName,
 
     /// Optional reference to a parent zone which this zone is a sub-zone of.
@@ -115,9 +104,53 @@ pub struct ZoneSpec {
     /// themselves into this zone. See the [`Delegation`] type for more information.
     pub delegations: Vec<Delegation>,
 
+    /// How `delegations` are combined, see [`DelegationPolicy`].
+    #[serde(default)]
+    pub delegation_policy: DelegationPolicy,
+
+    /// Freeze the zone, so its entries and serial are not recomputed until
+    /// it is unpaused. Records can still be edited in the meantime, and are
+    /// picked up once the zone resumes.
+    #[serde(default)]
+    pub paused: bool,
+
+    /// Maximum number of labels a sub-zone may add beneath this zone, such
+    /// as `1` for `dev.example.org.` beneath `example.org.`, which would
+    /// reject `api.dev.example.org.`. Sub-zones may be nested arbitrarily
+    /// deep if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_child_depth: Option<u32>,
+
+    /// Authoritative name servers of the zone.
+    #[serde(default)]
+    #[schemars(schema_with = "crate::schema::fully_qualified_domain_names")]
+    pub nameservers: Vec<FullyQualifiedDomainName>,
+
+    /// Publish an `NS` entry at the apex of the zone for each of its `nameservers`.
+    #[serde(default)]
+    pub generate_ns_records: bool,
+
+    /// How the serial of the zone is advanced when its entries change.
+    #[serde(default)]
+    pub serial_strategy: SerialStrategy,
+
+    /// Serial to adopt as the new baseline, if it is greater than the
+    /// computed one, see [`ZoneStatus::apply_serial_override`]. Lets zones
+    /// migrated from an external primary continue from its serial.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_override: Option<u32>,
+
+    /// Algorithm used for computing the zone's hash.
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+
     /// Time-to-Live. Represents how long (in seconds) recursive resolvers should
     /// keep this record in their cache.
-    #[serde(default = "defaults::ttl")]
+    ///
+    /// Must be at least 30 seconds, since many resolvers will
+    /// ignore or clamp lower values anyway.
+    #[serde(default = "super::defaults::ttl")]
+    #[schemars(range(min = 30))]
     pub ttl: u32,
 
     /// Number of seconds after which secondary name servers should
@@ -126,7 +159,7 @@ pub struct ZoneSpec {
     /// Recommendation for small and stable zones[^1]: 86400 seconds (24 hours).
     ///
     /// [^1]: <https://www.ripe.net/publications/docs/ripe-203>
-    #[serde(default = "defaults::refresh")]
+    #[serde(default = "super::defaults::refresh")]
     pub refresh: u32,
 
     /// Number of seconds after which secondary name servers should
@@ -138,7 +171,7 @@ pub struct ZoneSpec {
     /// Recommendation for small and stable zones[^1]: 7200 seconds (2 hours).
     ///
     /// [^1]: <https://www.ripe.net/publications/docs/ripe-203>
-    #[serde(default = "defaults::retry")]
+    #[serde(default = "super::defaults::retry")]
     pub retry: u32,
 
     /// Number of seconds after which secondary name servers should
@@ -149,7 +182,7 @@ pub struct ZoneSpec {
     /// Recommendation for small and stable zones[^1]: 3600000 seconds (1000 hours)
     ///
     /// [^1]: <https://www.ripe.net/publications/docs/ripe-203>
-    #[serde(default = "defaults::expire")]
+    #[serde(default = "super::defaults::expire")]
     pub expire: u32,
 
     /// Used in calculating the time to live for purposes of negative caching.
@@ -161,9 +194,94 @@ pub struct ZoneSpec {
     ///
     /// Recommendation for small and stable zones[^1] 172800 seconds (2 days)
     ///
+    /// Must be at least 30 seconds.
+    ///
     /// [^1]: <https://www.ripe.net/publications/docs/ripe-203>
-    #[serde(default = "defaults::negative_response_cache")]
+    #[serde(default = "super::defaults::negative_response_cache")]
+    #[schemars(range(min = 30))]
     pub negative_response_cache: u32,
+
+    /// Primary name server of the zone, published as the `MNAME` of its SOA record.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mname: Option<FullyQualifiedDomainName>,
+
+    /// Mailbox of the person responsible for the zone, published as the
+    /// `RNAME` of its SOA record.
+    ///
+    /// Either an email address such as `hostmaster@example.org`, or its
+    /// domain name form `hostmaster.example.org.`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rname: Option<Rname>,
+
+    /// Sign the zone with DNSSEC, as configured.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dnssec: Option<Dnssec>,
+
+    /// Credentials required for transferring the zone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_security: Option<TransferSecurity>,
+
+    /// Peers allowed to transfer the zone through AXFR or IXFR.
+    /// Transfers are denied to everyone if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_transfer: Vec<TransferPeer>,
+
+    /// Nameservers to send DNS NOTIFY messages to whenever the serial of
+    /// the zone changes, in addition to those listed in `nameservers`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notify_targets: Vec<NotifyTarget>,
+
+    /// Identifiers of the downstream provisioners, such as `coredns` or
+    /// `route53`, which should serve the zone. Served by all provisioners
+    /// if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<String>,
+
+    /// Values shared by the records of the zone, such as the address of a
+    /// load balancer, which records reference as `${name}` in their rdata.
+    /// See [`Zone::render_record`].
+    ///
+    /// Substitution is opt-in: the rdata of records is left untouched unless
+    /// at least one variable is defined. Once any is, rdata of the zone's
+    /// records referencing undefined variables is rejected, and a literal
+    /// `${` or `$$` must be escaped as `$${` and `$$$$` respectively.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+}
+
+/// Minimum Time-to-Live of zones, since many resolvers ignore or clamp
+/// lower values anyway.
+pub const MIN_TTL: u32 = 30;
+
+impl ZoneSpec {
+    /// Check the spec for values which the API server accepts, but which
+    /// cannot be served, such as an invalid DNSSEC configuration.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        validate_parent(&self.domain_name, self.zone_ref.as_ref())?;
+
+        if self.ttl < MIN_TTL {
+            return Err(ValidationError::TtlTooLow {
+                ttl: self.ttl,
+                minimum: MIN_TTL,
+            });
+        }
+
+        if let Some(dnssec) = &self.dnssec {
+            dnssec.validate(self.ttl)?;
+        }
+
+        for (delegation, rules) in self.delegations.iter().enumerate() {
+            if let Some(rule) = rules
+                .deny_records
+                .iter()
+                .position(|rule| rule.min_ttl.is_some() || rule.max_ttl.is_some())
+            {
+                return Err(ValidationError::DenyRuleTtl { delegation, rule });
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Zone {
@@ -187,78 +305,368 @@ impl Zone {
         self.status.as_ref().and_then(|status| status.serial)
     }
 
+    /// Check if `address` may transfer the zone, judging by the addresses
+    /// and networks in [`ZoneSpec::allow_transfer`].
+    pub fn allows_transfer(&self, address: &IpAddr) -> bool {
+        self.spec
+            .allow_transfer
+            .iter()
+            .any(|peer| peer.contains(address))
+    }
+
+    /// Check if the zone explicitly opted into `provider`, see [`ZoneSpec::providers`].
+    pub fn has_provider(&self, provider: &str) -> bool {
+        self.spec.providers.iter().any(|p| p == provider)
+    }
+
+    /// Check if `provider` should serve the zone, either because the zone
+    /// opted into it, or because it did not opt into any specific providers.
+    pub fn is_served_by(&self, provider: &str) -> bool {
+        self.spec.providers.is_empty() || self.has_provider(provider)
+    }
+
+    /// Check if the zone is paused, see [`ZoneSpec::paused`].
+    pub fn is_paused(&self) -> bool {
+        self.spec.paused
+    }
+
+    /// Substitute the zone's `variables` for their `${name}` placeholders in `input`.
+    pub fn substitute_variables(&self, input: &str) -> Result<String, SubstitutionError> {
+        substitute(input, &self.spec.variables)
+    }
+
+    /// Resolve the placeholders in the rdata of a record placed in this
+    /// zone, see [`ZoneSpec::variables`].
+    ///
+    /// Zones without variables return the record as is, so that rdata
+    /// containing `$` is unaffected unless the zone opts in.
+    pub fn render_record(&self, spec: &RecordSpec) -> Result<RecordSpec, SubstitutionError> {
+        if self.spec.variables.is_empty() {
+            return Ok(spec.clone());
+        }
+
+        Ok(RecordSpec {
+            rdata: self.substitute_variables(&spec.rdata)?,
+            ..spec.clone()
+        })
+    }
+
+    /// Hash the given entries with the zone's configured [`HashAlgorithm`].
+    pub fn compute_hash(&self, entries: &[ZoneEntry]) -> String {
+        ZoneStatus::compute_hash_with(entries, self.spec.hash_algorithm)
+    }
+
+    /// Produce the apex `NS` entries of this zone, if `generateNsRecords` is set
+    /// and the zone has a fully qualified domain name.
+    pub fn ns_entries(&self) -> Vec<ZoneEntry> {
+        let Some(fqdn) = self.fqdn().filter(|_| self.spec.generate_ns_records) else {
+            return Vec::new();
+        };
+
+        self.spec
+            .nameservers
+            .iter()
+            .map(|nameserver| ZoneEntry {
+                fqdn: fqdn.clone(),
+                type_: Type::NS,
+                class: Class::IN,
+                ttl: self.spec.ttl,
+                rdata: nameserver.to_string(),
+                response_policy: None,
+                weight: None,
+                policy: None,
+                views: Vec::new(),
+                source: Some(EntrySource::new(self)),
+            })
+            .collect()
+    }
+
     /// Validate that the given Record is allowed, given the delegations of this Zone.
-    pub fn validate_record(&self, record: &Record) -> bool {
+    ///
+    /// See [`Zone::allows_record`] if the reason for a denial is irrelevant.
+    ///
+    /// Fails with [`DelegationDenied::NamespaceLabelsRequired`] if any delegation
+    /// might cover the record's namespace by its `namespaceSelector`, use
+    /// [`Zone::validate_record_with_labels`] instead.
+    #[cfg_attr(
+        feature = "otel",
+        instrument(
+            level = "debug",
+            skip_all,
+            fields(kubizone.zone.fqdn, kubizone.record.fqdn, kubizone.outcome)
+        )
+    )]
+    pub fn validate_record(&self, record: &Record) -> Result<(), DelegationDenied> {
+        otel::record_result(self.evaluate_record(record, None))
+    }
+
+    /// Like [`Zone::validate_record`], but also evaluates the `namespaceSelector`
+    /// of delegations against the labels of the record's namespace.
+    #[cfg_attr(
+        feature = "otel",
+        instrument(
+            level = "debug",
+            skip_all,
+            fields(kubizone.zone.fqdn, kubizone.record.fqdn, kubizone.outcome)
+        )
+    )]
+    pub fn validate_record_with_labels(
+        &self,
+        record: &Record,
+        namespace_labels: &BTreeMap<String, String>,
+    ) -> Result<(), DelegationDenied> {
+        otel::record_result(self.evaluate_record(record, Some(namespace_labels)))
+    }
+
+    /// Validate that the given Zone is allowed by the delgations specified in this Zone.
+    ///
+    /// See [`Zone::allows_zone`] if the reason for a denial is irrelevant.
+    ///
+    /// Fails with [`DelegationDenied::NamespaceLabelsRequired`] if any delegation
+    /// might cover the sub-zone's namespace by its `namespaceSelector`, use
+    /// [`Zone::validate_zone_with_labels`] instead.
+    #[cfg_attr(
+        feature = "otel",
+        instrument(
+            level = "debug",
+            skip_all,
+            fields(kubizone.zone.fqdn, kubizone.record.fqdn, kubizone.outcome)
+        )
+    )]
+    pub fn validate_zone(&self, zone: &Zone) -> Result<(), DelegationDenied> {
+        otel::record_result(self.evaluate_zone(zone, None))
+    }
+
+    /// Like [`Zone::validate_zone`], but also evaluates the `namespaceSelector`
+    /// of delegations against the labels of the sub-zone's namespace.
+    #[cfg_attr(
+        feature = "otel",
+        instrument(
+            level = "debug",
+            skip_all,
+            fields(kubizone.zone.fqdn, kubizone.record.fqdn, kubizone.outcome)
+        )
+    )]
+    pub fn validate_zone_with_labels(
+        &self,
+        zone: &Zone,
+        namespace_labels: &BTreeMap<String, String>,
+    ) -> Result<(), DelegationDenied> {
+        otel::record_result(self.evaluate_zone(zone, Some(namespace_labels)))
+    }
+
+    /// Check that `descendant` is not nested deeper beneath this zone than
+    /// allowed by its [`ZoneSpec::max_child_depth`].
+    ///
+    /// Sub-zones are only validated against the delegations of their direct
+    /// parent, which includes its own depth limit. Use this to enforce the
+    /// limits of zones further up the hierarchy as well.
+    pub fn validate_depth(&self, descendant: &Zone) -> Result<(), DelegationDenied> {
         let Some(parent_fqdn) = self.fqdn() else {
-            trace!("parent zone {self} has no fqdn, and can therefore not validate record");
-            return false;
+            return Err(DelegationDenied::ParentFqdnMissing);
         };
 
-        let Some(record_fqdn) = record.fqdn() else {
-            trace!("record {record} has no fqdn, and can therefore not be validated");
-            return false;
+        let Some(fqdn) = descendant.fqdn() else {
+            return Err(DelegationDenied::FqdnMissing);
         };
 
-        if !record
-            .fqdn()
-            .is_some_and(|fqdn| fqdn == parent_fqdn || fqdn.is_subdomain_of(parent_fqdn))
-        {
-            trace!("record {record_fqdn} is not a subdomain of {parent_fqdn}");
-            return false;
+        if !fqdn.is_subdomain_of(parent_fqdn) {
+            return Err(DelegationDenied::NotSubdomain {
+                fqdn: fqdn.clone(),
+                parent: parent_fqdn.clone(),
+            });
         }
 
-        if self.spec().delegations.iter().any(|delegation| {
-            delegation.covers_namespace(&record.namespace().unwrap_or_default())
-                && delegation.validate_record(parent_fqdn, record.spec.type_, record_fqdn)
-        }) {
-            debug!("zone {parent_fqdn} allows delegation to record {record_fqdn}");
-            true
-        } else {
-            trace!("zone {parent_fqdn} forbid delegation to record {record_fqdn}");
-            false
+        check_depth(parent_fqdn, self.spec.max_child_depth, fqdn)
+    }
+
+    /// Check if the given Record is allowed, given the delegations of this Zone.
+    pub fn allows_record(&self, record: &Record) -> bool {
+        self.validate_record(record).is_ok()
+    }
+
+    /// Check if the given Zone is allowed by the delegations specified in this Zone.
+    pub fn allows_zone(&self, zone: &Zone) -> bool {
+        self.validate_zone(zone).is_ok()
+    }
+
+    /// Validate a whole set of records in one pass, compiling the
+    /// delegations of this zone only once.
+    ///
+    /// The `namespaceSelector` of delegations is evaluated against the labels
+    /// returned by `namespace_labels` for the namespace of each record. If it
+    /// returns `None`, the record is validated as by [`Zone::validate_record`].
+    ///
+    /// If the zone has no fully qualified domain name yet, every record is
+    /// rejected with [`DelegationDenied::ParentFqdnMissing`].
+    pub fn validate_records<'a, 'b>(
+        &self,
+        records: impl IntoIterator<Item = &'a Record>,
+        namespace_labels: impl Fn(&str) -> Option<&'b BTreeMap<String, String>>,
+    ) -> ZoneValidationReport<'a> {
+        let delegations = CompiledDelegations::new(self);
+        let mut report = ZoneValidationReport::default();
+
+        for record in records {
+            let result = match &delegations {
+                Some(delegations) => {
+                    match namespace_labels(&record.namespace().unwrap_or_default()) {
+                        Some(labels) => delegations.validate_record_with_labels(record, labels),
+                        None => delegations.validate_record(record),
+                    }
+                }
+                None => Err(DelegationDenied::ParentFqdnMissing),
+            };
+
+            match result {
+                Ok(()) => report.accepted.push(record),
+                Err(denial) => report.rejected.push((record, denial)),
+            }
         }
+
+        report
     }
 
-    /// Validate that the given Zone is allowed by the delgations specified in this Zone.
-    pub fn validate_zone(&self, zone: &Zone) -> bool {
-        let Some(parent_fqdn) = self.fqdn() else {
-            trace!("zone {self}'s fqdn is not defined.");
-            return false;
+    /// Prepare the delegations of this zone for evaluating many records or zones.
+    ///
+    /// Fails with [`DelegationError::MissingFqdn`] if the zone has not been
+    /// assigned a fully qualified domain name yet.
+    pub fn compile_delegations(&self) -> Result<CompiledDelegations, DelegationError> {
+        CompiledDelegations::new(self).ok_or(DelegationError::MissingFqdn)
+    }
+
+    fn evaluate_record(
+        &self,
+        record: &Record,
+        namespace_labels: Option<&BTreeMap<String, String>>,
+    ) -> Result<(), DelegationDenied> {
+        let Some(delegations) = CompiledDelegations::new(self) else {
+            trace!("parent zone {self} has no fqdn, and can therefore not validate record");
+            return Err(DelegationDenied::ParentFqdnMissing);
         };
 
-        let Some(zone_fqdn) = zone.fqdn() else {
+        delegations
+            .evaluate_record(record, namespace_labels)
+            .map(drop)
+    }
+
+    fn evaluate_zone(
+        &self,
+        zone: &Zone,
+        namespace_labels: Option<&BTreeMap<String, String>>,
+    ) -> Result<(), DelegationDenied> {
+        let Some(delegations) = CompiledDelegations::new(self) else {
             trace!("zone {self}'s fqdn is not defined.");
-            return false;
+            return Err(DelegationDenied::ParentFqdnMissing);
         };
 
-        if !zone_fqdn.is_subdomain_of(parent_fqdn) {
-            trace!("zone {} is not a subdomain of {}", zone_fqdn, parent_fqdn);
-            return false;
-        }
+        delegations.evaluate_zone(zone, namespace_labels).map(drop)
+    }
+}
 
-        // Cannot be a subdomain of itself
-        if self.uid() == zone.uid() {
-            return false;
-        }
+/// Outcome of validating a set of records against a zone, see [`Zone::validate_records`].
+#[derive(Clone, Debug, Default)]
+pub struct ZoneValidationReport<'a> {
+    /// Records allowed by the delegations of the zone, in their original order.
+    pub accepted: Vec<&'a Record>,
+    /// Records refused by the zone, along with the reason for each.
+    pub rejected: Vec<(&'a Record, DelegationDenied)>,
+}
 
-        self.spec().delegations.iter().any(|delegation| {
-            delegation.covers_namespace(&zone.namespace().unwrap_or_default())
-                && delegation.validate_zone(parent_fqdn, zone_fqdn)
-        })
+impl ZoneValidationReport<'_> {
+    /// Check if every record was accepted.
+    pub fn is_clean(&self) -> bool {
+        self.rejected.is_empty()
+    }
+}
+
+/// Reason a zone refused to accept a record or sub-zone.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum DelegationDenied {
+    #[error("parent zone has no fully qualified domain name")]
+    ParentFqdnMissing,
+    #[error("no fully qualified domain name")]
+    FqdnMissing,
+    #[error("{fqdn} is not a subdomain of {parent}")]
+    NotSubdomain {
+        fqdn: FullyQualifiedDomainName,
+        parent: FullyQualifiedDomainName,
+    },
+    #[error("zone cannot be a sub-zone of itself")]
+    SelfDelegation,
+    #[error("no delegations cover namespace {namespace}")]
+    NamespaceNotDelegated { namespace: String },
+    /// Delegations of the zone select namespaces by label, so the labels of
+    /// the namespace must be provided, using for example
+    /// [`Zone::validate_record_with_labels`].
+    #[error("labels of namespace {namespace} are required to evaluate namespaceSelector")]
+    NamespaceLabelsRequired { namespace: String },
+    #[error("type {type_} not allowed, expected one of {}", display_types(allowed))]
+    TypeNotAllowed { type_: Type, allowed: Vec<Type> },
+    #[error("{fqdn} does not match any delegated pattern")]
+    NoMatchingPattern { fqdn: FullyQualifiedDomainName },
+    #[error(
+        "ttl {ttl} is outside of the delegated range {}",
+        display_range(min, max)
+    )]
+    TtlOutOfRange {
+        ttl: u32,
+        min: Option<u32>,
+        max: Option<u32>,
+    },
+    #[error("{fqdn} is nested {depth} labels deep, exceeding the maximum of {max}")]
+    TooDeep {
+        fqdn: FullyQualifiedDomainName,
+        depth: usize,
+        max: u32,
+    },
+    #[error("{fqdn} is explicitly denied by pattern {pattern}")]
+    ExplicitlyDenied {
+        fqdn: FullyQualifiedDomainName,
+        pattern: DelegationPattern,
+    },
+    #[error("{fqdn} is delegated, but awaiting approval")]
+    ApprovalRequired { fqdn: FullyQualifiedDomainName },
+}
+
+// Both bounds are inclusive, and either may be absent.
+fn display_range(min: &Option<u32>, max: &Option<u32>) -> String {
+    match (min, max) {
+        (Some(min), Some(max)) => format!("{min}..={max}"),
+        (Some(min), None) => format!("{min}.."),
+        (None, Some(max)) => format!("..={max}"),
+        (None, None) => String::from(".."),
     }
 }
 
+fn display_types(types: &[Type]) -> String {
+    types
+        .iter()
+        .map(Type::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 impl DomainExt for Zone {
     fn fqdn(&self) -> Option<&FullyQualifiedDomainName> {
         self.status.as_ref().and_then(|status| status.fqdn.as_ref())
     }
 
+    fn set_fqdn(&mut self, fqdn: FullyQualifiedDomainName) {
+        self.status.get_or_insert_with(ZoneStatus::default).fqdn = Some(fqdn);
+    }
+
+    #[cfg_attr(
+        feature = "otel",
+        instrument(
+            level = "trace",
+            skip_all,
+            fields(kubizone.name = %self.name_any(), kubizone.namespace = self.namespace().as_deref()),
+            ret
+        )
+    )]
     fn parent(&self) -> Option<ZoneRef> {
-        self.meta()
-            .labels
-            .as_ref()?
-            .get(PARENT_ZONE_LABEL)
-            .map(ZoneRef::from)
+        parent_reference(self)
     }
 }
 
@@ -288,9 +696,10 @@ pub struct ZoneStatus {
     /// this will be the concatenated version of this zone's `.spec.domainName`
     /// and the parent's `.status.fqdn`
     #[serde(default)]
+    #[schemars(schema_with = "crate::schema::optional_fully_qualified_domain_name")]
     pub fqdn: Option<FullyQualifiedDomainName>,
 
-    /// Hash value of all relevant zone entries.
+    /// Hash value of all relevant zone entries, see [`ZoneStatus::compute_hash`].
     #[serde(default)]
     pub hash: Option<String>,
 
@@ -301,102 +710,1191 @@ pub struct ZoneStatus {
     /// [RFC 1912](https://datatracker.ietf.org/doc/html/rfc1912#section-2.2)
     #[serde(default)]
     pub serial: Option<u32>,
+
+    /// Last time a controller finished reconciling the zone, whether or
+    /// not anything changed, see [`ZoneStatus::mark_updated`]. Zones whose
+    /// timestamp falls behind have stopped converging.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<DateTime<Utc>>,
+
+    /// Last time the serial changed, see [`ZoneStatus::set_serial`].
+    ///
+    /// Changes to the zone's entries are tracked separately, by
+    /// [`ZoneStats::last_change`], which is also shown as a print column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_serial_change: Option<DateTime<Utc>>,
+
+    /// Last [`ZoneSpec::serial_override`] adopted as the serial, so that
+    /// it is only applied once.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_override: Option<u32>,
+
+    /// `.metadata.generation` of the zone this status was computed from.
+    #[serde(default)]
+    pub observed_generation: Option<i64>,
+
+    /// Latest observations of the zone's state, such as `Ready`.
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+
+    /// Composition of the zone, see [`ZoneStatus::recompute_stats`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<ZoneStats>,
+
+    /// Signing state of zones with DNSSEC enabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dnssec: Option<DnssecStatus>,
+
+    /// Message digest of the latest generated zonefile, which secondaries
+    /// can use to verify the zone contents they receive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zonemd: Option<Zonemd>,
+
+    /// Zone data holding the entries of the zone, in which case `entries`
+    /// is empty. See [`Zone::split_entries`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_ref: Option<ZoneDataRef>,
+
+    /// Set if `entries` was cut short to fit the status size budget,
+    /// see [`ZoneStatus::try_set_entries_with`].
+    #[serde(default)]
+    pub entries_truncated: bool,
+
+    /// Records approved by the owner of the zone, as required by delegations
+    /// with `requireApproval` set, see [`ZoneStatus::approve`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub approvals: Vec<RecordApproval>,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Hash)]
-#[serde(rename_all = "camelCase")]
-pub struct ZoneEntry {
-    pub fqdn: FullyQualifiedDomainName,
-    #[serde(rename = "type")]
-    pub type_: Type,
-    pub class: Class,
-    pub ttl: u32,
-    pub rdata: String,
+/// Size budget for the entries stored in a [`ZoneStatus`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EntriesBudget {
+    /// Maximum size of the JSON serialized entries, in bytes.
+    pub max_size: usize,
+
+    /// Truncate entries exceeding the budget, instead of failing.
+    pub truncate: bool,
 }
 
-impl From<&ZoneEntry> for RecordIdent {
-    fn from(value: &ZoneEntry) -> Self {
-        RecordIdent {
-            fqdn: value.fqdn.clone(),
-            r#type: value.type_,
-            rdata: value.rdata.clone(),
+impl Default for EntriesBudget {
+    fn default() -> Self {
+        EntriesBudget {
+            max_size: super::defaults::ENTRIES_SIZE_BUDGET,
+            truncate: false,
         }
     }
 }
 
-#[derive(
-    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
-)]
+/// Entries exceeding the size budget of a zone status.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("entries take up {size} bytes, exceeding the budget of {budget} bytes")]
+pub struct TooLarge {
+    pub size: usize,
+    pub budget: usize,
+}
+
+/// Produced by [`ZoneEntry::from_record`].
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ConversionError {
+    #[error("zone {0} has not been assigned a fully qualified domain name")]
+    MissingZoneFqdn(String),
+    #[error("{fqdn} is not within zone {zone}")]
+    OutsideZone {
+        fqdn: FullyQualifiedDomainName,
+        zone: FullyQualifiedDomainName,
+    },
+    #[error(transparent)]
+    Substitution(#[from] SubstitutionError),
+    #[error(transparent)]
+    InvalidRecord(#[from] RecordValidationError),
+}
+
+/// [RFC 8976](https://datatracker.ietf.org/doc/html/rfc8976) message digest
+/// of a zone, using the `SIMPLE` scheme.
+///
+/// Computed by `kubizone_crds::zonemd::zone_digest`, which requires the
+/// `hickory` feature. Displays as the data of the corresponding `ZONEMD` record.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
-pub struct RecordDelegation {
-    /// Pattern which delegated records must match.
-    pub pattern: Pattern,
+pub struct Zonemd {
+    /// Serial of the zone the digest was computed for.
+    pub serial: u32,
 
-    /// Type of record to allow. Empty list implies *any*.
-    #[serde(default)]
-    pub types: Vec<Type>,
+    pub hash_algorithm: ZonemdHashAlgorithm,
+
+    /// Digest as lowercase hex digits.
+    pub digest: String,
 }
 
-impl RecordDelegation {
-    pub fn validate(
-        &self,
-        zone_fqdn: &FullyQualifiedDomainName,
-        record_type: Type,
-        domain: &FullyQualifiedDomainName,
-    ) -> bool {
-        self.pattern.with_origin(zone_fqdn).matches(domain)
-            && (self.types.is_empty() || self.types.contains(&record_type))
+impl Display for Zonemd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.serial,
+            ZONEMD_SCHEME_SIMPLE,
+            self.hash_algorithm.number(),
+            self.digest
+        )
     }
 }
 
+/// Scheme number of the `SIMPLE` ZONEMD scheme.
+pub const ZONEMD_SCHEME_SIMPLE: u8 = 1;
+
+/// Hash algorithms defined for ZONEMD records.
 #[derive(
-    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
 )]
-pub struct Delegation {
+pub enum ZonemdHashAlgorithm {
+    #[default]
+    #[serde(rename = "SHA384")]
+    Sha384,
+    #[serde(rename = "SHA512")]
+    Sha512,
+}
+
+impl ZonemdHashAlgorithm {
+    /// Hash algorithm number, as used in ZONEMD records.
+    pub const fn number(&self) -> u8 {
+        match self {
+            ZonemdHashAlgorithm::Sha384 => 1,
+            ZonemdHashAlgorithm::Sha512 => 2,
+        }
+    }
+}
+
+/// Summary of a zone's entries.
+#[derive(Default, Serialize, Deserialize, Clone, Debug, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoneStats {
+    /// Total number of entries in the zone.
+    pub entries: usize,
+
+    /// Number of entries per record type.
     #[serde(default)]
-    pub namespaces: Vec<String>,
+    pub by_type: BTreeMap<String, usize>,
+
+    /// Number of entries per namespace of the originating object.
+    /// Entries without a known source are not counted.
     #[serde(default)]
-    pub zones: Vec<Pattern>,
+    pub by_namespace: BTreeMap<String, usize>,
+
+    /// Last time the zone's entries were seen to change.
     #[serde(default)]
-    pub records: Vec<RecordDelegation>,
+    pub last_change: Option<DateTime<Utc>>,
+
+    /// Zone hash at the time of the last change, used for detecting the next one.
+    #[serde(default)]
+    pub last_change_hash: Option<String>,
 }
 
-impl Delegation {
-    /// Check if the given namespace is covered by this Delegation.
-    pub fn covers_namespace(&self, namespace: &str) -> bool {
-        if self.namespaces.is_empty() {
-            return true;
-        }
+impl ObservedGeneration for ZoneStatus {
+    fn observed_generation(&self) -> Option<i64> {
+        self.observed_generation
+    }
 
-        if self
-            .namespaces
-            .iter()
-            .any(|delegated_namespace| delegated_namespace == namespace)
-        {
-            return true;
-        }
+    fn set_observed_generation(&mut self, generation: Option<i64>) {
+        self.observed_generation = generation;
+    }
+}
+
+impl ZoneStatus {
+    /// Set a condition, replacing any existing condition of the same type.
+    ///
+    /// The transition time of the existing condition is kept if its status is unchanged.
+    pub fn set_condition(&mut self, condition: Condition) {
+        condition::set_condition(&mut self.conditions, condition)
+    }
+
+    /// Retrieve the condition of the given type, if present.
+    pub fn get_condition(&self, type_: impl AsRef<str>) -> Option<&Condition> {
+        condition::get_condition(&self.conditions, type_.as_ref())
+    }
+
+    /// Compute the canonical hash of a set of zone entries, using the
+    /// default [`HashAlgorithm::XxHash64`].
+    ///
+    /// Each entry is reduced to a line of its domain name (lowercased),
+    /// class, type, TTL, trimmed record data, and response policy, followed
+    /// by the weight and JSON encoded routing policy, only if either is set,
+    /// and the sorted, comma-separated `namespace/name` views, only if there are any.
+    /// The lines are sorted, so the result does not depend on the order of entries,
+    /// and the digest of the result is returned as lowercase hex digits.
+    /// Bookkeeping such as [`ZoneEntry::source`] does not contribute to the hash.
+    ///
+    /// The output is stable across releases, so that controllers and other
+    /// tools computing it independently agree on whether a zone changed.
+    pub fn compute_hash(entries: &[ZoneEntry]) -> String {
+        Self::compute_hash_with(entries, HashAlgorithm::default())
+    }
+
+    /// Like [`ZoneStatus::compute_hash`], but using the given algorithm.
+    pub fn compute_hash_with(entries: &[ZoneEntry], algorithm: HashAlgorithm) -> String {
+        let mut lines: Vec<String> = entries
+            .iter()
+            .map(|entry| {
+                let mut line = format!(
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    entry.fqdn.to_string().to_ascii_lowercase(),
+                    entry.class,
+                    entry.type_,
+                    entry.ttl,
+                    entry.rdata.trim(),
+                    entry
+                        .response_policy
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                );
+
+                // Appended only when set, so hashes of zones without weights,
+                // routing policies or views are unaffected by their introduction.
+                if entry.weight.is_some() || entry.policy.is_some() || !entry.views.is_empty() {
+                    line.push_str(&format!(
+                        "\t{}\t{}",
+                        entry
+                            .weight
+                            .map(|weight| weight.to_string())
+                            .unwrap_or_default(),
+                        entry
+                            .policy
+                            .as_ref()
+                            .and_then(|policy| serde_json::to_string(policy).ok())
+                            .unwrap_or_default(),
+                    ));
+                }
+
+                if !entry.views.is_empty() {
+                    let mut views: Vec<String> =
+                        entry.views.iter().map(ToString::to_string).collect();
+                    views.sort_unstable();
+                    line.push_str(&format!("\t{}", views.join(",")));
+                }
+
+                line
+            })
+            .collect();
+
+        lines.sort_unstable();
+
+        algorithm.digest(lines.join("\n").as_bytes())
+    }
+
+    /// Set the entries of the status, failing if their serialized size
+    /// exceeds the default [`EntriesBudget`].
+    ///
+    /// The entries are stored in [canonical order](crate::canonical), so the
+    /// status does not change with the order in which they were gathered.
+    /// The status is left untouched on failure.
+    pub fn try_set_entries(&mut self, entries: Vec<ZoneEntry>) -> Result<(), TooLarge> {
+        self.try_set_entries_with(entries, EntriesBudget::default())
+    }
+
+    /// Like [`ZoneStatus::try_set_entries`], but using the given budget.
+    ///
+    /// If the budget allows truncation, entries which do not fit are dropped
+    /// from the end and [`ZoneStatus::entries_truncated`] is set instead.
+    pub fn try_set_entries_with(
+        &mut self,
+        mut entries: Vec<ZoneEntry>,
+        budget: EntriesBudget,
+    ) -> Result<(), TooLarge> {
+        entries.sort_by_cached_key(sort_key);
+
+        // Serialized as a JSON array: brackets, plus a comma between each entry.
+        let mut size = 2;
+        let mut fitting = 0;
+
+        for (index, entry) in entries.iter().enumerate() {
+            size += serialized_size(entry) + usize::from(index > 0);
+
+            if size <= budget.max_size {
+                fitting = index + 1;
+            }
+        }
+
+        let truncated = fitting < entries.len();
+        if truncated && !budget.truncate {
+            return Err(TooLarge {
+                size,
+                budget: budget.max_size,
+            });
+        }
+
+        entries.truncate(fitting);
+        self.entries = entries;
+        self.entries_truncated = truncated;
+
+        Ok(())
+    }
+
+    /// Record that the zone was reconciled at `now`.
+    pub fn mark_updated(&mut self, now: DateTime<Utc>) {
+        self.last_updated = Some(now);
+    }
+
+    /// Set the serial of the zone, recording `now` as the time of the last
+    /// serial change if it differs from the current one.
+    ///
+    /// Returns whether the serial changed.
+    pub fn set_serial(&mut self, serial: u32, now: DateTime<Utc>) -> bool {
+        if self.serial == Some(serial) {
+            return false;
+        }
+
+        self.serial = Some(serial);
+        self.last_serial_change = Some(now);
+        true
+    }
+
+    /// Adopt `serial_override` as the serial of the zone, if it is greater
+    /// than the current serial and has not been adopted before. Subsequent
+    /// serials are computed from it as usual.
+    ///
+    /// Overrides which are not greater are ignored, since the serial must
+    /// never decrease. Returns whether the serial changed.
+    pub fn apply_serial_override(
+        &mut self,
+        serial_override: Option<u32>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let Some(serial_override) = serial_override else {
+            return false;
+        };
+
+        if self.serial_override == Some(serial_override)
+            || self
+                .serial
+                .is_some_and(|serial| !serial_gt(serial_override, serial))
+        {
+            return false;
+        }
+
+        self.serial_override = Some(serial_override);
+        self.set_serial(serial_override, now)
+    }
+
+    /// Recompute [`ZoneStatus::stats`] from the current entries.
+    ///
+    /// The last change timestamp is set to `now` whenever [`ZoneStatus::hash`]
+    /// differs from the one seen by the previous call, so this should be
+    /// called after the entries and hash have been updated.
+    pub fn recompute_stats(&mut self, now: DateTime<Utc>) {
+        let previous = self.stats.take().unwrap_or_default();
+
+        let mut stats = ZoneStats {
+            entries: self.entries.len(),
+            ..previous
+        };
+
+        stats.by_type.clear();
+        stats.by_namespace.clear();
+
+        for entry in &self.entries {
+            *stats.by_type.entry(entry.type_.to_string()).or_default() += 1;
+
+            if let Some(namespace) = entry
+                .source
+                .as_ref()
+                .and_then(|source| source.namespace.as_ref())
+            {
+                *stats.by_namespace.entry(namespace.clone()).or_default() += 1;
+            }
+        }
+
+        if stats.last_change.is_none() || stats.last_change_hash != self.hash {
+            stats.last_change = Some(now);
+            stats.last_change_hash = self.hash.clone();
+        }
+
+        self.stats = Some(stats);
+    }
+}
+
+/// Size of the entry when serialized as JSON.
+pub(super) fn serialized_size(entry: &ZoneEntry) -> usize {
+    // Serializing entries cannot fail, since all maps within have string keys.
+    serde_json::to_vec(entry).map_or(0, |json| json.len())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoneEntry {
+    #[schemars(schema_with = "crate::schema::fully_qualified_domain_name")]
+    pub fqdn: FullyQualifiedDomainName,
+    #[serde(rename = "type", deserialize_with = "deserialize_type")]
+    pub type_: Type,
+    #[serde(deserialize_with = "deserialize_class")]
+    pub class: Class,
+    pub ttl: u32,
+    pub rdata: String,
+
+    /// Answer ordering requested by the originating record, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_policy: Option<ResponsePolicy>,
+
+    /// Relative weight of this answer among the entries of the same name and
+    /// type, for serializers which support weighted answers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
+
+    /// Routing metadata of the originating record, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<RoutingPolicy>,
+
+    /// Views the entry is published in, see [`ZoneEntry::is_in_view`].
+    /// The references of entries built from records always have a namespace.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub views: Vec<ViewRef>,
+
+    /// Record or child zone this entry originates from, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<EntrySource>,
+}
+
+// Entries written by older or third-party controllers may spell types and
+// classes in lowercase, or in the generic `TYPE<n>` notation of RFC 3597.
+fn deserialize_type<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Type, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    zonefile::parse_type(&value)
+        .ok_or_else(|| D::Error::custom(format!("unsupported record type {value}")))
+}
+
+fn deserialize_class<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Class, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    zonefile::parse_class(&value)
+        .ok_or_else(|| D::Error::custom(format!("unsupported record class {value}")))
+}
+
+// The source is bookkeeping rather than zone content, and is left out of
+// the hash so that recreating a record with identical data does not
+// count as a change to the zone.
+impl Hash for ZoneEntry {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fqdn.hash(state);
+        self.type_.hash(state);
+        self.class.hash(state);
+        self.ttl.hash(state);
+        self.rdata.hash(state);
+        self.response_policy.hash(state);
+        self.weight.hash(state);
+        self.policy.hash(state);
+        self.views.hash(state);
+    }
+}
+
+impl ZoneEntry {
+    /// Produce the entry for `record`, placed in `zone`.
+    ///
+    /// The fully qualified domain name is taken from the record's status if
+    /// resolved, and is otherwise derived from its domain name and the zone.
+    /// Records without a TTL inherit that of the zone. The zone's
+    /// [`variables`](ZoneSpec::variables) are substituted into the rdata,
+    /// which must then pass [`RecordSpec::validate`].
+    ///
+    /// The type and class are copied from the record as is. Both are
+    /// already parsed into [`Type`] and [`Class`] when the record is
+    /// deserialized, so there are no alternative spellings left to normalize.
+    ///
+    /// Whether the zone's delegations allow the record is not checked,
+    /// see [`Zone::validate_record`].
+    #[cfg_attr(
+        feature = "otel",
+        instrument(
+            level = "debug",
+            skip_all,
+            fields(kubizone.zone.fqdn, kubizone.record.fqdn, kubizone.name = %record.name_any(), kubizone.namespace = record.namespace().as_deref())
+        )
+    )]
+    pub fn from_record(record: &Record, zone: &Zone) -> Result<ZoneEntry, ConversionError> {
+        let zone_fqdn = zone
+            .fqdn()
+            .ok_or_else(|| ConversionError::MissingZoneFqdn(zone.to_string()))?;
+        otel::record_fqdn(otel::ZONE_FQDN, zone_fqdn);
+
+        let fqdn = match (record.fqdn(), &record.spec.domain_name) {
+            (Some(fqdn), _) => fqdn.clone(),
+            (None, DomainName::Full(fqdn)) => fqdn.clone(),
+            (None, DomainName::Partial(partial)) => partial.with_origin(zone_fqdn),
+        };
+        otel::record_fqdn(otel::RECORD_FQDN, &fqdn);
+
+        if fqdn != *zone_fqdn && !fqdn.is_subdomain_of(zone_fqdn) {
+            return Err(ConversionError::OutsideZone {
+                fqdn,
+                zone: zone_fqdn.clone(),
+            });
+        }
+
+        let spec = zone.render_record(&record.spec)?;
+        spec.validate()?;
+
+        Ok(ZoneEntry {
+            fqdn,
+            type_: spec.type_,
+            class: spec.class,
+            ttl: spec.ttl.unwrap_or(zone.spec.ttl),
+            rdata: spec.rdata.trim().to_string(),
+            response_policy: spec.response_policy,
+            weight: None,
+            policy: spec.policy,
+            views: spec
+                .views
+                .iter()
+                .map(|view| view.within(record.namespace().as_deref()))
+                .collect(),
+            source: Some(EntrySource::new(record)),
+        })
+    }
+
+    /// Check if the entry is published in `view`.
+    /// Entries without views are published in every view.
+    pub fn is_in_view(&self, view: &DNSView) -> bool {
+        self.views.is_empty() || self.views.iter().any(|view_ref| view_ref.refers_to(view))
+    }
+}
+
+/// Reference to the kubernetes object a [`ZoneEntry`] originates from.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EntrySource {
+    /// Either `Record`, or `Zone` for delegation entries of child zones.
+    pub kind: String,
+    pub namespace: Option<String>,
+    pub name: String,
+    pub uid: Option<String>,
+}
+
+impl EntrySource {
+    pub fn new<K: Resource<DynamicType = ()>>(object: &K) -> Self {
+        EntrySource {
+            kind: K::kind(&()).into_owned(),
+            namespace: object.namespace(),
+            name: object.name_any(),
+            uid: object.uid(),
+        }
+    }
+}
+
+impl From<&ZoneEntry> for RecordIdent {
+    fn from(value: &ZoneEntry) -> Self {
+        RecordIdent {
+            fqdn: value.fqdn.clone(),
+            r#type: value.type_,
+            rdata: value.rdata.clone(),
+        }
+    }
+}
+
+/// Algorithm used for computing the [`ZoneStatus::hash`] of a zone.
+///
+/// Changing the algorithm of an existing zone changes its hash, and is
+/// therefore treated as a change to the zone.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum HashAlgorithm {
+    /// SHA-256, for when change detection must withstand deliberate collisions.
+    Sha256,
+
+    /// Fast non-cryptographic 64-bit hash.
+    #[default]
+    XxHash64,
+
+    /// BLAKE3, a cryptographic hash which is considerably faster than SHA-256.
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// Hash `data`, returning the digest as lowercase hex digits.
+    pub fn digest(&self, data: &[u8]) -> String {
+        match self {
+            HashAlgorithm::Sha256 => hex(&Sha256::digest(data)),
+            HashAlgorithm::XxHash64 => format!("{:016x}", XxHash64::oneshot(0, data)),
+            HashAlgorithm::Blake3 => blake3::hash(data).to_hex().to_string(),
+        }
+    }
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Scheme used for advancing the serial of a zone. See [`crate::serial::next_serial`].
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum SerialStrategy {
+    /// Increment the serial by one for every change.
+    #[default]
+    Incrementing,
+
+    /// Encode the date of the change in the serial as `YYYYMMDDnn`,
+    /// where `nn` counts the changes made on that day.
+    DateBased,
+}
+
+/// How the delegations of a zone are combined into a decision.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+pub enum DelegationPolicy {
+    /// Deny rules of any delegation covering the namespace take
+    /// precedence, after which any matching allow rule accepts.
+    #[default]
+    AnyMatch,
+
+    /// Delegations are evaluated in order, and the first one with a
+    /// matching deny or allow rule decides. Within a delegation, deny
+    /// rules still take precedence over allow rules.
+    FirstMatch,
+}
+
+/// How the patterns of a delegation are interpreted.
+#[derive(
+    Serialize,
+    Deserialize,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum PatternType {
+    /// Wildcard pattern relative to the zone, such as `*.dev`.
+    #[default]
+    Glob,
+    /// Regular expression which must match the entire fully qualified
+    /// domain name, including the trailing dot. See [`DomainRegex`].
+    Regex,
+}
+
+#[derive(Error, Debug, Clone)]
+pub enum DelegationPatternError {
+    #[error("invalid glob pattern: {0}")]
+    Glob(#[from] PatternSegmentError),
+    #[error("invalid regular expression: {0}")]
+    Regex(#[from] regex::Error),
+}
+
+/// Pattern of a record or zone delegation.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DelegationPattern {
+    Glob(Pattern),
+    Regex(DomainRegex),
+}
+
+impl DelegationPattern {
+    /// Parse a pattern according to its type.
+    pub fn parse(pattern_type: PatternType, value: &str) -> Result<Self, DelegationPatternError> {
+        Ok(match pattern_type {
+            PatternType::Glob => DelegationPattern::Glob(Pattern::try_from(value)?),
+            PatternType::Regex => DelegationPattern::Regex(DomainRegex::try_from(value)?),
+        })
+    }
+
+    pub fn pattern_type(&self) -> PatternType {
+        match self {
+            DelegationPattern::Glob(_) => PatternType::Glob,
+            DelegationPattern::Regex(_) => PatternType::Regex,
+        }
+    }
+
+    /// Anchor the pattern to the given zone.
+    ///
+    /// Only glob patterns are relative to the zone. Regular expressions
+    /// already match against the full domain name, so they are returned
+    /// unchanged, and may match names outside of `origin`. Delegations only
+    /// consult patterns for names within the zone, so such matches never
+    /// delegate anything.
+    pub fn with_origin(&self, origin: &FullyQualifiedDomainName) -> DelegationPattern {
+        match self {
+            DelegationPattern::Glob(pattern) => {
+                DelegationPattern::Glob(pattern.with_origin(origin))
+            }
+            DelegationPattern::Regex(regex) => DelegationPattern::Regex(regex.clone()),
+        }
+    }
+
+    /// Returns true if the pattern matches the given domain.
+    ///
+    /// Glob patterns must be anchored using [`DelegationPattern::with_origin`] first.
+    pub fn matches(&self, domain: &FullyQualifiedDomainName) -> bool {
+        match self {
+            DelegationPattern::Glob(pattern) => pattern.matches(domain),
+            DelegationPattern::Regex(regex) => regex.matches(domain),
+        }
+    }
+}
+
+impl From<Pattern> for DelegationPattern {
+    fn from(value: Pattern) -> Self {
+        DelegationPattern::Glob(value)
+    }
+}
+
+impl From<DomainRegex> for DelegationPattern {
+    fn from(value: DomainRegex) -> Self {
+        DelegationPattern::Regex(value)
+    }
+}
+
+/// Zones allowed and denied by a [`Delegation`].
+///
+/// Both share a single [`PatternType`] in the serialized form, so globs and
+/// regular expressions can't be mixed within a delegation.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ZonePatterns {
+    Glob {
+        allow: Vec<Pattern>,
+        deny: Vec<Pattern>,
+    },
+    Regex {
+        allow: Vec<DomainRegex>,
+        deny: Vec<DomainRegex>,
+    },
+}
+
+impl Default for ZonePatterns {
+    fn default() -> Self {
+        ZonePatterns::Glob {
+            allow: vec![],
+            deny: vec![],
+        }
+    }
+}
+
+impl ZonePatterns {
+    /// Parse allowed and denied zone patterns of the given type.
+    pub fn parse(
+        pattern_type: PatternType,
+        allow: &[String],
+        deny: &[String],
+    ) -> Result<Self, DelegationPatternError> {
+        let glob = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| Pattern::try_from(pattern.as_str()))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        let regex = |patterns: &[String]| {
+            patterns
+                .iter()
+                .map(|pattern| DomainRegex::try_from(pattern.as_str()))
+                .collect::<Result<Vec<_>, _>>()
+        };
+
+        Ok(match pattern_type {
+            PatternType::Glob => ZonePatterns::Glob {
+                allow: glob(allow)?,
+                deny: glob(deny)?,
+            },
+            PatternType::Regex => ZonePatterns::Regex {
+                allow: regex(allow)?,
+                deny: regex(deny)?,
+            },
+        })
+    }
+
+    pub fn pattern_type(&self) -> PatternType {
+        match self {
+            ZonePatterns::Glob { .. } => PatternType::Glob,
+            ZonePatterns::Regex { .. } => PatternType::Regex,
+        }
+    }
+
+    /// Patterns of zones which may be delegated.
+    pub fn allowed(&self) -> Vec<DelegationPattern> {
+        match self {
+            ZonePatterns::Glob { allow, .. } => allow.iter().cloned().map(Into::into).collect(),
+            ZonePatterns::Regex { allow, .. } => allow.iter().cloned().map(Into::into).collect(),
+        }
+    }
+
+    /// Patterns of zones which must *not* be delegated, even if allowed.
+    pub fn denied(&self) -> Vec<DelegationPattern> {
+        match self {
+            ZonePatterns::Glob { deny, .. } => deny.iter().cloned().map(Into::into).collect(),
+            ZonePatterns::Regex { deny, .. } => deny.iter().cloned().map(Into::into).collect(),
+        }
+    }
+}
+
+impl Display for DelegationPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DelegationPattern::Glob(pattern) => pattern.fmt(f),
+            DelegationPattern::Regex(regex) => regex.fmt(f),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "RawRecordDelegation", into = "RawRecordDelegation")]
+pub struct RecordDelegation {
+    /// Pattern which delegated records must match.
+    pub pattern: DelegationPattern,
+
+    /// Type of record to allow. Empty list implies *any*.
+    pub types: Vec<Type>,
+
+    /// Lowest TTL delegated records may use. Unbounded if not set.
+    pub min_ttl: Option<u32>,
+
+    /// Highest TTL delegated records may use. Unbounded if not set.
+    pub max_ttl: Option<u32>,
+}
+
+// Serialized form of RecordDelegation, since interpreting
+// the pattern depends on the patternType next to it.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct RawRecordDelegation {
+    /// Pattern which delegated records must match.
+    #[schemars(schema_with = "crate::schema::pattern")]
+    pattern: String,
+
+    /// How `pattern` is interpreted, either `glob` (default) or `regex`.
+    #[serde(default)]
+    pattern_type: PatternType,
+
+    /// Type of record to allow. Empty list implies *any*.
+    #[serde(default)]
+    types: Vec<Type>,
+
+    /// Lowest TTL delegated records may use. Unbounded if not set.
+    ///
+    /// Records without a TTL of their own are checked using the zone's TTL.
+    #[serde(default)]
+    min_ttl: Option<u32>,
+
+    /// Highest TTL delegated records may use. Unbounded if not set.
+    ///
+    /// Records without a TTL of their own are checked using the zone's TTL.
+    #[serde(default)]
+    max_ttl: Option<u32>,
+}
+
+impl TryFrom<RawRecordDelegation> for RecordDelegation {
+    type Error = DelegationPatternError;
+
+    fn try_from(value: RawRecordDelegation) -> Result<Self, Self::Error> {
+        Ok(RecordDelegation {
+            pattern: DelegationPattern::parse(value.pattern_type, &value.pattern)?,
+            types: value.types,
+            min_ttl: value.min_ttl,
+            max_ttl: value.max_ttl,
+        })
+    }
+}
+
+impl From<RecordDelegation> for RawRecordDelegation {
+    fn from(value: RecordDelegation) -> Self {
+        RawRecordDelegation {
+            pattern_type: value.pattern.pattern_type(),
+            pattern: value.pattern.to_string(),
+            types: value.types,
+            min_ttl: value.min_ttl,
+            max_ttl: value.max_ttl,
+        }
+    }
+}
+
+impl JsonSchema for RecordDelegation {
+    fn schema_name() -> String {
+        String::from("RecordDelegation")
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        RawRecordDelegation::json_schema(gen)
+    }
+}
+
+impl RecordDelegation {
+    /// Check that a record matches both the pattern and types of
+    /// this delegation, and that its TTL is within bounds.
+    pub fn validate(
+        &self,
+        zone_fqdn: &FullyQualifiedDomainName,
+        record_type: Type,
+        domain: &FullyQualifiedDomainName,
+        ttl: u32,
+    ) -> bool {
+        self.matches(zone_fqdn, record_type, domain) && self.validate_ttl(ttl)
+    }
+
+    /// Check that a (record type, domain) pair matches the
+    /// pattern and types of this delegation, ignoring TTL bounds.
+    pub fn matches(
+        &self,
+        zone_fqdn: &FullyQualifiedDomainName,
+        record_type: Type,
+        domain: &FullyQualifiedDomainName,
+    ) -> bool {
+        self.pattern.with_origin(zone_fqdn).matches(domain)
+            && (self.types.is_empty() || self.types.contains(&record_type))
+    }
+
+    /// Check that `ttl` falls within the `minTtl` and `maxTtl` bounds, inclusive.
+    pub fn validate_ttl(&self, ttl: u32) -> bool {
+        self.min_ttl.is_none_or(|min| ttl >= min) && self.max_ttl.is_none_or(|max| ttl <= max)
+    }
+}
+
+/// Zone and record patterns which namespaces are allowed to use.
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "RawDelegation", into = "RawDelegation")]
+pub struct Delegation {
+    /// Namespaces covered by this Delegation, either by name or by a
+    /// pattern such as `team-*`, see [`namespace_matches_pattern`].
+    pub namespaces: Vec<String>,
+
+    /// Namespaces whose labels match this selector are covered by this
+    /// Delegation, in addition to the ones listed in `namespaces`.
+    pub namespace_selector: Option<LabelSelector>,
+
+    /// Zones which may, and which must *not*, be delegated.
+    pub zones: ZonePatterns,
+    pub records: Vec<RecordDelegation>,
+
+    /// Records which must *not* be delegated, even if allowed by `records`.
+    /// TTL bounds don't apply to deny rules, and are rejected by [`ZoneSpec::validate`].
+    pub deny_records: Vec<RecordDelegation>,
+
+    /// Records allowed only by this Delegation are not published until
+    /// approved by the owner of the zone, see [`ZoneStatus::approve`].
+    pub require_approval: bool,
+}
+
+// Serialized form of Delegation, since interpreting the zone
+// patterns depends on the zonePatternType next to them.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+struct RawDelegation {
+    /// Namespaces covered by this Delegation, either by name or by a
+    /// pattern in which `*` matches any sequence of characters, such as
+    /// `team-*`. Covers every namespace if both this and the
+    /// `namespaceSelector` are empty.
+    #[serde(default)]
+    namespaces: Vec<String>,
+
+    /// Namespaces whose labels match this selector are covered by this
+    /// Delegation, in addition to the ones listed in `namespaces`.
+    #[serde(default)]
+    namespace_selector: Option<LabelSelector>,
+
+    /// How the patterns in `zones` and `denyZones` are interpreted,
+    /// either `glob` (default) or `regex`.
+    #[serde(default)]
+    zone_pattern_type: PatternType,
+    #[serde(default)]
+    #[schemars(schema_with = "crate::schema::patterns")]
+    zones: Vec<String>,
+    #[serde(default)]
+    records: Vec<RecordDelegation>,
+
+    /// Zones which must *not* be delegated, even if allowed by `zones`.
+    ///
+    /// Deny rules take precedence over allow rules of every delegation
+    /// covering the same namespace, not just this one, unless the zone's
+    /// `delegationPolicy` is `FirstMatch`.
+    #[serde(default)]
+    #[schemars(schema_with = "crate::schema::patterns")]
+    deny_zones: Vec<String>,
+
+    /// Records which must *not* be delegated, even if allowed by `records`.
+    ///
+    /// Deny rules take precedence over allow rules of every delegation
+    /// covering the same namespace, not just this one, unless the zone's
+    /// `delegationPolicy` is `FirstMatch`. Deny rules match on pattern and
+    /// type only, and must not set `minTtl` or `maxTtl`.
+    #[serde(default)]
+    deny_records: Vec<RecordDelegation>,
+
+    /// Records allowed only by this Delegation must be approved by the owner
+    /// of the zone, through the `approvals` in its status, before they are
+    /// published. Approvals are tied to the `metadata.generation` of the
+    /// record, so any change to an approved record requires a new approval.
+    #[serde(default)]
+    require_approval: bool,
+}
+
+impl TryFrom<RawDelegation> for Delegation {
+    type Error = DelegationPatternError;
+
+    fn try_from(value: RawDelegation) -> Result<Self, Self::Error> {
+        Ok(Delegation {
+            zones: ZonePatterns::parse(value.zone_pattern_type, &value.zones, &value.deny_zones)?,
+            namespaces: value.namespaces,
+            namespace_selector: value.namespace_selector,
+            records: value.records,
+            deny_records: value.deny_records,
+            require_approval: value.require_approval,
+        })
+    }
+}
+
+impl From<Delegation> for RawDelegation {
+    fn from(value: Delegation) -> Self {
+        let serialize = |patterns: Vec<DelegationPattern>| {
+            patterns.iter().map(DelegationPattern::to_string).collect()
+        };
+
+        RawDelegation {
+            namespaces: value.namespaces,
+            namespace_selector: value.namespace_selector,
+            zone_pattern_type: value.zones.pattern_type(),
+            zones: serialize(value.zones.allowed()),
+            records: value.records,
+            deny_zones: serialize(value.zones.denied()),
+            deny_records: value.deny_records,
+            require_approval: value.require_approval,
+        }
+    }
+}
+
+impl JsonSchema for Delegation {
+    fn schema_name() -> String {
+        String::from("Delegation")
+    }
+
+    fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        RawDelegation::json_schema(gen)
+    }
+}
+
+impl Delegation {
+    /// Check if the given namespace is covered by this Delegation.
+    ///
+    /// The `namespaceSelector` can't be evaluated without the namespace's labels,
+    /// so namespaces are only covered by name. Use [`Delegation::covers_labelled_namespace`]
+    /// to take the selector into account.
+    pub fn covers_namespace(&self, namespace: &str) -> bool {
+        self.covers(namespace, None)
+    }
+
+    /// Check if the given namespace is covered by this Delegation, either
+    /// by name or by its labels matching the `namespaceSelector`.
+    pub fn covers_labelled_namespace(
+        &self,
+        namespace: &str,
+        labels: &BTreeMap<String, String>,
+    ) -> bool {
+        self.covers(namespace, Some(labels))
+    }
+
+    /// Check if this delegation might cover `namespace` through its
+    /// `namespaceSelector`, which can only be decided given its labels.
+    pub(super) fn needs_labels(&self, namespace: &str) -> bool {
+        self.namespace_selector.is_some()
+            && !self
+                .namespaces
+                .iter()
+                .any(|pattern| namespace_matches_pattern(pattern, namespace))
+    }
+
+    pub(super) fn covers(
+        &self,
+        namespace: &str,
+        labels: Option<&BTreeMap<String, String>>,
+    ) -> bool {
+        if self.namespaces.is_empty() && self.namespace_selector.is_none() {
+            return true;
+        }
+
+        if self
+            .namespaces
+            .iter()
+            .any(|pattern| namespace_matches_pattern(pattern, namespace))
+        {
+            return true;
+        }
+
+        if let (Some(selector), Some(labels)) = (&self.namespace_selector, labels) {
+            if selector.matches(labels) {
+                return true;
+            }
+        }
 
         trace!("delegation {self:?} does not cover {namespace}");
         false
     }
 
-    /// Verify that a (record type, domain) pair matches the delegation
-    /// rules of this delegation.
+    /// Verify that a (record type, domain, ttl) triple matches the
+    /// delegation rules of this delegation.
     pub fn validate_record(
         &self,
         zone_fqdn: &FullyQualifiedDomainName,
         record_type: Type,
         domain: &FullyQualifiedDomainName,
+        ttl: u32,
     ) -> bool {
+        if self.denies_record(zone_fqdn, record_type, domain) {
+            trace!("{domain} ({record_type}) is explicitly denied by {self:?}");
+            return false;
+        }
+
         for record_delegation in &self.records {
             trace!(
                 "{:?} {} matches {} ? {}",
                 record_delegation,
                 record_type,
                 domain,
-                record_delegation.validate(zone_fqdn, record_type, domain)
+                record_delegation.validate(zone_fqdn, record_type, domain, ttl)
             );
-            if record_delegation.validate(zone_fqdn, record_type, domain) {
+            if record_delegation.validate(zone_fqdn, record_type, domain, ttl) {
                 return true;
             }
         }
@@ -412,7 +1910,12 @@ impl Delegation {
         parent_fqdn: &FullyQualifiedDomainName,
         domain: &FullyQualifiedDomainName,
     ) -> bool {
-        for zone_delegation in &self.zones {
+        if self.denies_zone(parent_fqdn, domain) {
+            trace!("{domain} is explicitly denied by {self:?}");
+            return false;
+        }
+
+        for zone_delegation in self.zones.allowed() {
             if zone_delegation.with_origin(parent_fqdn).matches(domain) {
                 return true;
             }
@@ -421,16 +1924,56 @@ impl Delegation {
         // If no zone delegations exist, deny.
         false
     }
+
+    /// Check if a (record type, domain) pair is explicitly denied by this delegation.
+    pub fn denies_record(
+        &self,
+        zone_fqdn: &FullyQualifiedDomainName,
+        record_type: Type,
+        domain: &FullyQualifiedDomainName,
+    ) -> bool {
+        self.deny_records
+            .iter()
+            .any(|rule| rule.matches(zone_fqdn, record_type, domain))
+    }
+
+    /// Check if a domain is explicitly denied as a sub-zone by this delegation.
+    pub fn denies_zone(
+        &self,
+        parent_fqdn: &FullyQualifiedDomainName,
+        domain: &FullyQualifiedDomainName,
+    ) -> bool {
+        self.zones
+            .denied()
+            .iter()
+            .any(|pattern| pattern.with_origin(parent_fqdn).matches(domain))
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use kube::core::ObjectMeta;
+    use std::collections::BTreeMap;
+
+    use chrono::{DateTime, Utc};
+    use kube::{core::ObjectMeta, ResourceExt};
     use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, Pattern, Type};
 
-    use crate::v1alpha1::{Record, RecordSpec, RecordStatus, ZoneStatus};
+    use crate::{
+        errors::{OwnerError, SubstitutionError, ValidationError},
+        set_owner_zone, set_parent_annotation,
+        v1alpha1::{
+            defaults, DomainExt, LabelSelector, Record, RecordSpec, RecordStatus, RoutingPolicy,
+            ZoneRef, ZoneStatus,
+        },
+        watch_annotation_reference, watch_reference_owned, PARENT_ZONE_ANNOTATION,
+        PARENT_ZONE_LABEL,
+    };
 
-    use super::{Delegation, RecordDelegation, Zone, ZoneSpec};
+    use super::{
+        ConversionError, Delegation, DelegationDenied, DelegationPolicy, EntriesBudget,
+        EntrySource, HashAlgorithm, RecordDelegation, TooLarge, Zone, ZoneEntry, ZonePatterns,
+        ZoneSpec, MIN_TTL,
+    };
 
     #[test]
     fn test_record_delegation() {
@@ -442,11 +1985,188 @@ mod tests {
                 zone_ref: None,
                 delegations: vec![Delegation {
                     namespaces: vec![String::from("default")],
-                    zones: vec![],
+                    zones: ZonePatterns::default(),
+                    records: vec![RecordDelegation {
+                        pattern: Pattern::try_from("*").unwrap().into(),
+                        types: vec![],
+                        min_ttl: None,
+                        max_ttl: None,
+                    }],
+                    namespace_selector: None,
+                    deny_records: vec![],
+                    require_approval: false,
+                }],
+                ..Default::default()
+            },
+            status: Some(ZoneStatus {
+                fqdn: Some(FullyQualifiedDomainName::try_from("example.org.").unwrap()),
+                ..Default::default()
+            }),
+            metadata: kube::core::ObjectMeta::default(),
+        };
+
+        // Record in delegated namespace should be allowed.
+        assert!(zone.allows_record(&Record {
+            metadata: ObjectMeta {
+                namespace: Some(String::from("default")),
+                ..Default::default()
+            },
+            spec: RecordSpec {
+                domain_name: DomainName::try_from("www.example.org.").unwrap(),
+                zone_ref: None,
+                type_: Type::A,
+                class: Class::IN,
+                ttl: None,
+                rdata: String::from("192.168.0.1"),
+                ..Default::default()
+            },
+            status: Some(RecordStatus {
+                fqdn: Some(FullyQualifiedDomainName::try_from("www.example.org.").unwrap()),
+                ..Default::default()
+            })
+        }));
+
+        // Record in non-delegated namespace should fail.
+        assert!(!zone.allows_record(&Record {
+            metadata: ObjectMeta {
+                namespace: Some(String::from("not-default")),
+                ..Default::default()
+            },
+            spec: RecordSpec {
+                domain_name: DomainName::try_from("www.example.org.").unwrap(),
+                zone_ref: None,
+                type_: Type::A,
+                class: Class::IN,
+                ttl: None,
+                rdata: String::from("192.168.0.1"),
+                ..Default::default()
+            },
+            status: None
+        }));
+
+        // Record in delegated namespace, with invalid super-domain should fail.
+        assert!(!zone.allows_record(&Record {
+            metadata: ObjectMeta {
+                namespace: Some(String::from("default")),
+                ..Default::default()
+            },
+            spec: RecordSpec {
+                domain_name: DomainName::try_from("www.test.com.").unwrap(),
+                zone_ref: None,
+                type_: Type::A,
+                class: Class::IN,
+                ttl: None,
+                rdata: String::from("192.168.0.1"),
+                ..Default::default()
+            },
+            status: None
+        }))
+    }
+
+    #[test]
+    fn test_record_type_limit() {
+        let zone = Zone {
+            spec: ZoneSpec {
+                domain_name: DomainName::try_from("example.org.").unwrap(),
+                zone_ref: None,
+                delegations: vec![Delegation {
+                    namespaces: vec![String::from("default")],
+                    zones: ZonePatterns::default(),
+                    records: vec![RecordDelegation {
+                        pattern: Pattern::origin().into(),
+                        types: vec![Type::MX],
+                        min_ttl: None,
+                        max_ttl: None,
+                    }],
+                    namespace_selector: None,
+                    deny_records: vec![],
+                    require_approval: false,
+                }],
+                ..Default::default()
+            },
+            status: Some(ZoneStatus {
+                fqdn: Some(FullyQualifiedDomainName::try_from("example.org.").unwrap()),
+                ..Default::default()
+            }),
+            metadata: kube::core::ObjectMeta::default(),
+        };
+
+        println!("{zone:#?}");
+
+        // Record in delegated namespace with delegated record type
+        // (MX) should be allowed.
+        assert!(zone.allows_record(&Record {
+            metadata: ObjectMeta {
+                namespace: Some(String::from("default")),
+                ..Default::default()
+            },
+            spec: RecordSpec {
+                domain_name: DomainName::try_from("example.org.").unwrap(),
+                zone_ref: None,
+                type_: Type::MX,
+                class: Class::IN,
+                ttl: None,
+                rdata: String::from("10 mail1.example.org."),
+                ..Default::default()
+            },
+            status: Some(RecordStatus {
+                fqdn: Some(FullyQualifiedDomainName::try_from("example.org.").unwrap()),
+                ..Default::default()
+            })
+        }));
+
+        // Record in delegated namespace with non-delegated record type
+        // (A) should not be allowed.
+        assert_eq!(
+            zone.validate_record(&Record {
+                metadata: ObjectMeta {
+                    namespace: Some(String::from("default")),
+                    ..Default::default()
+                },
+                spec: RecordSpec {
+                    domain_name: DomainName::try_from("example.org.").unwrap(),
+                    zone_ref: None,
+                    type_: Type::A,
+                    class: Class::IN,
+                    ttl: None,
+                    rdata: String::from("192.168.0.1"),
+                    ..Default::default()
+                },
+                status: Some(RecordStatus {
+                    fqdn: Some(FullyQualifiedDomainName::try_from("example.org.").unwrap()),
+                    ..Default::default()
+                })
+            }),
+            Err(DelegationDenied::TypeNotAllowed {
+                type_: Type::A,
+                allowed: vec![Type::MX]
+            })
+        );
+    }
+
+    #[test]
+    fn test_deny_overrides_allow() {
+        let zone = Zone {
+            spec: ZoneSpec {
+                domain_name: DomainName::try_from("example.org.").unwrap(),
+                delegations: vec![Delegation {
+                    namespaces: vec![],
+                    zones: ZonePatterns::default(),
                     records: vec![RecordDelegation {
-                        pattern: Pattern::try_from("*").unwrap(),
+                        pattern: Pattern::try_from("*").unwrap().into(),
+                        types: vec![],
+                        min_ttl: None,
+                        max_ttl: None,
+                    }],
+                    namespace_selector: None,
+                    deny_records: vec![RecordDelegation {
+                        pattern: Pattern::try_from("api").unwrap().into(),
                         types: vec![],
+                        min_ttl: None,
+                        max_ttl: None,
                     }],
+
+                    require_approval: false,
                 }],
                 ..Default::default()
             },
@@ -457,74 +2177,52 @@ mod tests {
             metadata: kube::core::ObjectMeta::default(),
         };
 
-        // Record in delegated namespace should be allowed.
-        assert!(zone.validate_record(&Record {
+        let record = |name: &str| Record {
             metadata: ObjectMeta {
                 namespace: Some(String::from("default")),
                 ..Default::default()
             },
             spec: RecordSpec {
-                domain_name: DomainName::try_from("www.example.org.").unwrap(),
-                zone_ref: None,
+                domain_name: DomainName::try_from(name).unwrap(),
                 type_: Type::A,
-                class: Class::IN,
-                ttl: None,
-                rdata: String::from("192.168.0.1")
+                rdata: String::from("192.168.0.1"),
+                ..Default::default()
             },
             status: Some(RecordStatus {
-                fqdn: Some(FullyQualifiedDomainName::try_from("www.example.org.").unwrap())
-            })
-        }));
-
-        // Record in non-delegated namespace should fail.
-        assert!(!zone.validate_record(&Record {
-            metadata: ObjectMeta {
-                namespace: Some(String::from("not-default")),
+                fqdn: Some(FullyQualifiedDomainName::try_from(name).unwrap()),
                 ..Default::default()
-            },
-            spec: RecordSpec {
-                domain_name: DomainName::try_from("www.example.org.").unwrap(),
-                zone_ref: None,
-                type_: Type::A,
-                class: Class::IN,
-                ttl: None,
-                rdata: String::from("192.168.0.1")
-            },
-            status: None
-        }));
+            }),
+        };
 
-        // Record in delegated namespace, with invalid super-domain should fail.
-        assert!(!zone.validate_record(&Record {
-            metadata: ObjectMeta {
-                namespace: Some(String::from("default")),
-                ..Default::default()
-            },
-            spec: RecordSpec {
-                domain_name: DomainName::try_from("www.test.com.").unwrap(),
-                zone_ref: None,
-                type_: Type::A,
-                class: Class::IN,
-                ttl: None,
-                rdata: String::from("192.168.0.1")
-            },
-            status: None
-        }))
+        assert!(zone.allows_record(&record("www.example.org.")));
+        assert_eq!(
+            zone.validate_record(&record("api.example.org.")),
+            Err(DelegationDenied::ExplicitlyDenied {
+                fqdn: FullyQualifiedDomainName::try_from("api.example.org.").unwrap(),
+                pattern: Pattern::try_from("api").unwrap().into(),
+            })
+        );
     }
 
     #[test]
-    fn test_record_type_limit() {
+    fn test_ttl_bounds() {
         let zone = Zone {
             spec: ZoneSpec {
                 domain_name: DomainName::try_from("example.org.").unwrap(),
-                zone_ref: None,
                 delegations: vec![Delegation {
-                    namespaces: vec![String::from("default")],
-                    zones: vec![],
+                    namespaces: vec![],
+                    namespace_selector: None,
+                    zones: ZonePatterns::default(),
                     records: vec![RecordDelegation {
-                        pattern: Pattern::origin(),
-                        types: vec![Type::MX],
+                        pattern: Pattern::try_from("*").unwrap().into(),
+                        types: vec![],
+                        min_ttl: Some(60),
+                        max_ttl: Some(300),
                     }],
+                    deny_records: vec![],
+                    require_approval: false,
                 }],
+                ttl: 3600,
                 ..Default::default()
             },
             status: Some(ZoneStatus {
@@ -534,44 +2232,828 @@ mod tests {
             metadata: kube::core::ObjectMeta::default(),
         };
 
-        println!("{zone:#?}");
-
-        // Record in delegated namespace with delegated record type
-        // (MX) should be allowed.
-        assert!(zone.validate_record(&Record {
+        let record = |ttl: Option<u32>| Record {
             metadata: ObjectMeta {
                 namespace: Some(String::from("default")),
                 ..Default::default()
             },
             spec: RecordSpec {
-                domain_name: DomainName::try_from("example.org.").unwrap(),
-                zone_ref: None,
-                type_: Type::MX,
-                class: Class::IN,
-                ttl: None,
-                rdata: String::from("10 mail1.example.org.")
+                domain_name: DomainName::try_from("www.example.org.").unwrap(),
+                type_: Type::A,
+                ttl,
+                rdata: String::from("192.168.0.1"),
+                ..Default::default()
             },
             status: Some(RecordStatus {
-                fqdn: Some(FullyQualifiedDomainName::try_from("example.org.").unwrap())
+                fqdn: Some(FullyQualifiedDomainName::try_from("www.example.org.").unwrap()),
+                ..Default::default()
+            }),
+        };
+
+        assert!(zone.allows_record(&record(Some(60))));
+        assert!(zone.allows_record(&record(Some(300))));
+        assert!(!zone.allows_record(&record(Some(30))));
+
+        // Records without a TTL are held to the zone's TTL.
+        assert_eq!(
+            zone.validate_record(&record(None)),
+            Err(DelegationDenied::TtlOutOfRange {
+                ttl: 3600,
+                min: Some(60),
+                max: Some(300),
             })
-        }));
+        );
+        assert_eq!(
+            zone.validate_record(&record(None)).unwrap_err().to_string(),
+            "ttl 3600 is outside of the delegated range 60..=300"
+        );
+    }
 
-        // Record in delegated namespace with non-delegated record type
-        // (A) should not be allowed.
-        assert!(!zone.validate_record(&Record {
-            metadata: ObjectMeta {
-                namespace: Some(String::from("default")),
+    #[test]
+    fn test_regex_delegation() {
+        let delegation: Delegation = serde_json::from_value(serde_json::json!({
+            "zonePatternType": "regex",
+            "zones": [r"[a-z]+\.teams\.example\.org\."],
+            "records": [{
+                "pattern": r"[a-z]{3}-\d+\.internal\.example\.org\.",
+                "patternType": "regex",
+            }, {
+                "pattern": "www",
+            }],
+        }))
+        .unwrap();
+
+        let origin = FullyQualifiedDomainName::try_from("example.org.").unwrap();
+        let fqdn = |name: &str| FullyQualifiedDomainName::try_from(name).unwrap();
+
+        assert!(delegation.validate_zone(&origin, &fqdn("blue.teams.example.org.")));
+        assert!(!delegation.validate_zone(&origin, &fqdn("blue-1.teams.example.org.")));
+        assert!(delegation.validate_record(
+            &origin,
+            Type::A,
+            &fqdn("abc-12.internal.example.org."),
+            300
+        ));
+        assert!(delegation.validate_record(&origin, Type::A, &fqdn("www.example.org."), 300));
+        assert!(!delegation.validate_record(
+            &origin,
+            Type::A,
+            &fqdn("www.internal.example.org."),
+            300
+        ));
+
+        // Round trip preserves pattern types.
+        let serialized = serde_json::to_value(&delegation).unwrap();
+        assert_eq!(
+            serde_json::from_value::<Delegation>(serialized.clone()).unwrap(),
+            delegation
+        );
+        assert_eq!(serialized["zonePatternType"], "regex");
+    }
+
+    #[test]
+    fn test_status_timestamps() {
+        let time = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let mut status = ZoneStatus::default();
+
+        status.mark_updated(time("2024-03-05T12:00:00Z"));
+        assert_eq!(status.last_updated, Some(time("2024-03-05T12:00:00Z")));
+        assert_eq!(status.last_serial_change, None);
+
+        assert!(status.set_serial(2024030501, time("2024-03-05T12:00:00Z")));
+        assert!(!status.set_serial(2024030501, time("2024-03-05T13:00:00Z")));
+        assert_eq!(
+            status.last_serial_change,
+            Some(time("2024-03-05T12:00:00Z"))
+        );
+
+        assert!(status.set_serial(2024030502, time("2024-03-05T14:00:00Z")));
+        assert_eq!(status.serial, Some(2024030502));
+        assert_eq!(
+            status.last_serial_change,
+            Some(time("2024-03-05T14:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_serial_override() {
+        let time = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let mut status = ZoneStatus::default();
+
+        assert!(!status.apply_serial_override(None, time("2024-03-05T12:00:00Z")));
+        assert!(status.apply_serial_override(Some(2024030507), time("2024-03-05T12:00:00Z")));
+        assert_eq!(status.serial, Some(2024030507));
+        assert_eq!(status.serial_override, Some(2024030507));
+
+        // Serials continue from the override, which is not applied again.
+        status.set_serial(2024030508, time("2024-03-05T13:00:00Z"));
+        assert!(!status.apply_serial_override(Some(2024030507), time("2024-03-05T14:00:00Z")));
+        assert_eq!(status.serial, Some(2024030508));
+
+        // Overrides behind the current serial are ignored.
+        assert!(!status.apply_serial_override(Some(2024030500), time("2024-03-05T14:00:00Z")));
+        assert_eq!(status.serial_override, Some(2024030507));
+
+        assert!(status.apply_serial_override(Some(2024040100), time("2024-03-05T15:00:00Z")));
+        assert_eq!(status.serial, Some(2024040100));
+        assert_eq!(
+            status.last_serial_change,
+            Some(time("2024-03-05T15:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn test_recompute_stats() {
+        let time = |s: &str| s.parse::<DateTime<Utc>>().unwrap();
+        let entry = |name: &str, type_: Type, namespace: Option<&str>| ZoneEntry {
+            fqdn: FullyQualifiedDomainName::try_from(name).unwrap(),
+            type_,
+            class: Class::IN,
+            ttl: 300,
+            rdata: String::new(),
+            response_policy: None,
+            weight: None,
+            policy: None,
+            views: Vec::new(),
+            source: namespace.map(|namespace| EntrySource {
+                kind: String::from("Record"),
+                namespace: Some(namespace.to_string()),
+                name: String::from("record"),
+                uid: None,
+            }),
+        };
+
+        let mut status = ZoneStatus {
+            entries: vec![
+                entry("www.example.org.", Type::A, Some("web")),
+                entry("www.example.org.", Type::AAAA, Some("web")),
+                entry("example.org.", Type::MX, Some("mail")),
+                entry("example.org.", Type::NS, None),
+            ],
+            hash: Some(String::from("first")),
+            ..Default::default()
+        };
+
+        status.recompute_stats(time("2024-03-05T12:00:00Z"));
+        let stats = status.stats.clone().unwrap();
+        assert_eq!(stats.entries, 4);
+        assert_eq!(stats.by_type["A"], 1);
+        assert_eq!(stats.by_type["MX"], 1);
+        assert_eq!(stats.by_namespace["web"], 2);
+        assert_eq!(stats.by_namespace.len(), 2);
+
+        assert_eq!(stats.last_change, Some(time("2024-03-05T12:00:00Z")));
+
+        // Unchanged hash keeps the last change timestamp.
+        status.recompute_stats(time("2024-03-05T13:00:00Z"));
+        assert_eq!(
+            status.stats.as_ref().unwrap().last_change,
+            stats.last_change
+        );
+
+        status.entries.pop();
+        status.hash = Some(String::from("second"));
+        status.recompute_stats(time("2024-03-05T14:00:00Z"));
+        let updated = status.stats.unwrap();
+        assert_eq!(updated.entries, 3);
+        assert_eq!(updated.last_change, Some(time("2024-03-05T14:00:00Z")));
+    }
+
+    #[test]
+    fn test_ns_entries() {
+        let mut zone = Zone {
+            spec: ZoneSpec {
+                domain_name: DomainName::try_from("example.org.").unwrap(),
+                nameservers: vec![
+                    FullyQualifiedDomainName::try_from("ns1.example.org.").unwrap(),
+                    FullyQualifiedDomainName::try_from("ns2.example.net.").unwrap(),
+                ],
                 ..Default::default()
             },
-            spec: RecordSpec {
+            status: Some(ZoneStatus {
+                fqdn: Some(FullyQualifiedDomainName::try_from("example.org.").unwrap()),
+                ..Default::default()
+            }),
+            metadata: ObjectMeta::default(),
+        };
+
+        assert!(zone.ns_entries().is_empty());
+
+        zone.spec.generate_ns_records = true;
+        let entries = zone.ns_entries();
+        assert_eq!(entries.len(), 2);
+        assert!(entries
+            .iter()
+            .all(|entry| entry.type_ == Type::NS && entry.fqdn == *"example.org."));
+        assert_eq!(entries[1].rdata, "ns2.example.net.");
+    }
+
+    #[test]
+    fn test_entry_from_record() {
+        let mut zone = Zone::builder("example.org.")
+            .namespace("dns")
+            .ttl(600)
+            .build()
+            .unwrap();
+        zone.spec.variables = [(String::from("owner"), String::from("web"))].into();
+
+        let mut record = Record::builder("www", Type::TXT, "owner=${owner}")
+            .namespace("dns")
+            .zone_ref("example-org".parse().unwrap())
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            ZoneEntry::from_record(&record, &zone).unwrap_err(),
+            ConversionError::MissingZoneFqdn(zone.to_string())
+        );
+
+        zone.set_fqdn(FullyQualifiedDomainName::try_from("example.org.").unwrap());
+        let entry = ZoneEntry::from_record(&record, &zone).unwrap();
+        assert_eq!(
+            entry.to_string(),
+            r#"www.example.org. 600 IN TXT "owner=web""#
+        );
+        assert_eq!(entry.source, Some(EntrySource::new(&record)));
+
+        // The resolved name takes precedence over the domain name.
+        record.spec.ttl = Some(60);
+        record.set_fqdn(FullyQualifiedDomainName::try_from("www.example.com.").unwrap());
+        assert!(matches!(
+            ZoneEntry::from_record(&record, &zone),
+            Err(ConversionError::OutsideZone { .. })
+        ));
+
+        record.status = None;
+        record.spec.type_ = Type::A;
+        assert!(matches!(
+            ZoneEntry::from_record(&record, &zone),
+            Err(ConversionError::InvalidRecord(_))
+        ));
+
+        record.spec.rdata = String::from(" 192.0.2.1 ");
+        let entry = ZoneEntry::from_record(&record, &zone).unwrap();
+        assert_eq!(entry.to_string(), "www.example.org. 60 IN A 192.0.2.1");
+    }
+
+    #[test]
+    fn test_entry_type_and_class() {
+        let entry = |type_: &str, class: &str| {
+            serde_json::from_value::<ZoneEntry>(serde_json::json!({
+                "fqdn": "example.org.",
+                "type": type_,
+                "class": class,
+                "ttl": 300,
+                "rdata": "0 issue \"letsencrypt.org\"",
+            }))
+        };
+
+        for (type_, class) in [("CAA", "IN"), ("caa", "in"), ("TYPE257", "CLASS1")] {
+            let entry = entry(type_, class).unwrap();
+            assert_eq!((entry.type_, entry.class), (Type::CAA, Class::IN));
+        }
+
+        assert!(entry("FOO", "IN").is_err());
+        assert!(entry("TYPE0", "IN").is_err());
+        assert!(entry("CAA", "CLASS2").is_err());
+
+        // Always serialized as the uppercase mnemonic.
+        let serialized = serde_json::to_value(entry("type257", "in").unwrap()).unwrap();
+        assert_eq!(serialized["type"], "CAA");
+        assert_eq!(serialized["class"], "IN");
+    }
+
+    #[test]
+    fn test_compute_hash() {
+        let entry = |name: &str, type_: Type, rdata: &str| ZoneEntry {
+            fqdn: FullyQualifiedDomainName::try_from(name).unwrap(),
+            type_,
+            class: Class::IN,
+            ttl: 300,
+            rdata: rdata.to_string(),
+            response_policy: None,
+            weight: None,
+            policy: None,
+            views: Vec::new(),
+            source: None,
+        };
+
+        let entries = vec![
+            entry("www.example.org.", Type::A, "192.168.0.1"),
+            entry("example.org.", Type::MX, "10 mail.example.org."),
+        ];
+
+        // Golden values, which must never change between releases.
+        assert_eq!(ZoneStatus::compute_hash(&[]), "ef46db3751d8e999");
+        assert_eq!(ZoneStatus::compute_hash(&entries), "a63fb0179a6e4007");
+
+        // Order, case, surrounding whitespace and sources are irrelevant.
+        let mut normalized = vec![
+            entry("example.org.", Type::MX, " 10 mail.example.org.  "),
+            entry("WWW.example.org.", Type::A, "192.168.0.1"),
+        ];
+        normalized[0].source = Some(EntrySource {
+            kind: String::from("Record"),
+            namespace: None,
+            name: String::from("mx"),
+            uid: None,
+        });
+        assert_eq!(
+            ZoneStatus::compute_hash(&normalized),
+            ZoneStatus::compute_hash(&entries)
+        );
+
+        let mut changed = entries.clone();
+        changed[0].ttl = 301;
+        assert_ne!(
+            ZoneStatus::compute_hash(&changed),
+            ZoneStatus::compute_hash(&entries)
+        );
+
+        let mut routed = entries.clone();
+        routed[0].policy = Some(RoutingPolicy {
+            regions: vec![String::from("DK")],
+            ..Default::default()
+        });
+        assert_ne!(
+            ZoneStatus::compute_hash(&routed),
+            ZoneStatus::compute_hash(&entries)
+        );
+
+        assert_eq!(
+            ZoneStatus::compute_hash_with(&entries, HashAlgorithm::XxHash64),
+            ZoneStatus::compute_hash(&entries)
+        );
+        assert_eq!(
+            ZoneStatus::compute_hash_with(&[], HashAlgorithm::Sha256),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            ZoneStatus::compute_hash_with(&[], HashAlgorithm::Blake3),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+        assert_eq!(
+            ZoneStatus::compute_hash_with(&entries, HashAlgorithm::Sha256),
+            "c0a53e16244a4a3cea6600b816babd542b77e305b36bef1f41100f63f1d90c09"
+        );
+        assert_eq!(
+            ZoneStatus::compute_hash_with(&entries, HashAlgorithm::Blake3),
+            "cf1e9c318641a623a283b86720ffff99567d11cef51063b1a0cbaef11302e9bc"
+        );
+    }
+
+    #[test]
+    fn test_providers() {
+        let mut zone = Zone::new(
+            "example-org",
+            ZoneSpec {
                 domain_name: DomainName::try_from("example.org.").unwrap(),
-                zone_ref: None,
-                type_: Type::A,
-                class: Class::IN,
-                ttl: None,
-                rdata: String::from("192.168.0.1")
+                ..Default::default()
             },
-            status: None
-        }));
+        );
+
+        assert!(!zone.has_provider("coredns"));
+        assert!(zone.is_served_by("coredns"));
+
+        zone.spec.providers = vec![String::from("route53")];
+        assert!(zone.has_provider("route53"));
+        assert!(zone.is_served_by("route53"));
+        assert!(!zone.is_served_by("coredns"));
+    }
+
+    #[test]
+    fn test_entries_budget() {
+        let entries: Vec<ZoneEntry> = (0..10)
+            .map(|i| {
+                format!("host{i}.example.org. 300 IN A 192.0.2.{i}")
+                    .parse()
+                    .unwrap()
+            })
+            .collect();
+        let size = serde_json::to_vec(&entries).unwrap().len();
+
+        let mut status = ZoneStatus::default();
+        status.try_set_entries(entries.clone()).unwrap();
+        assert_eq!(status.entries.len(), 10);
+        assert!(!status.entries_truncated);
+
+        let budget = EntriesBudget {
+            max_size: size - 1,
+            truncate: false,
+        };
+        assert_eq!(
+            status.try_set_entries_with(entries[..5].to_vec(), budget),
+            Ok(())
+        );
+        assert_eq!(
+            status.try_set_entries_with(entries.clone(), budget),
+            Err(TooLarge {
+                size,
+                budget: size - 1
+            })
+        );
+        assert_eq!(status.entries.len(), 5);
+
+        let budget = EntriesBudget {
+            truncate: true,
+            ..budget
+        };
+        status.try_set_entries_with(entries, budget).unwrap();
+        assert_eq!(status.entries.len(), 9);
+        assert!(status.entries_truncated);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut spec = ZoneSpec {
+            domain_name: DomainName::try_from("example.org.").unwrap(),
+            ttl: defaults::TTL,
+            refresh: defaults::REFRESH,
+            retry: defaults::RETRY,
+            expire: defaults::EXPIRE,
+            negative_response_cache: defaults::NEGATIVE_RESPONSE_CACHE,
+            ..Default::default()
+        };
+        assert_eq!(spec.validate(), Ok(()));
+
+        // SOA timers are left to the operator.
+        spec.retry = spec.refresh;
+        spec.expire = spec.refresh;
+        spec.negative_response_cache = 0;
+        assert_eq!(spec.validate(), Ok(()));
+
+        spec.ttl = 10;
+        assert_eq!(
+            spec.validate(),
+            Err(ValidationError::TtlTooLow {
+                ttl: 10,
+                minimum: MIN_TTL
+            })
+        );
+
+        spec.ttl = defaults::TTL;
+        spec.delegations = vec![Delegation {
+            deny_records: vec![
+                RecordDelegation {
+                    pattern: Pattern::try_from("api").unwrap().into(),
+                    types: vec![],
+                    min_ttl: None,
+                    max_ttl: None,
+                },
+                RecordDelegation {
+                    pattern: Pattern::try_from("*").unwrap().into(),
+                    types: vec![],
+                    min_ttl: None,
+                    max_ttl: Some(60),
+                },
+            ],
+            ..serde_json::from_str("{}").unwrap()
+        }];
+        assert_eq!(
+            spec.validate(),
+            Err(ValidationError::DenyRuleTtl {
+                delegation: 0,
+                rule: 1
+            })
+        );
+
+        spec.delegations.clear();
+        spec.zone_ref = Some("parent".parse().unwrap());
+        assert!(matches!(
+            spec.validate(),
+            Err(ValidationError::ConflictingParent(_))
+        ));
+    }
+
+    #[test]
+    fn test_parent_label() {
+        let parent = ZoneRef {
+            name: String::from("example.org"),
+            namespace: Some(String::from("dns")),
+        };
+        assert_eq!(parent.as_label().as_deref(), Some("dns_example.org"));
+
+        let mut zone = Zone::new("sub", ZoneSpec::default());
+        for label in [parent.as_label().unwrap(), parent.as_legacy_label()] {
+            zone.metadata.labels = Some([(PARENT_ZONE_LABEL.to_string(), label)].into());
+            assert_eq!(zone.parent(), Some(parent.clone()));
+        }
+
+        // The annotation takes precedence over the label.
+        let annotated = ZoneRef {
+            name: String::from("a-very-long-zone-name-which-would-not-fit-into-a-label-value"),
+            namespace: None,
+        };
+        set_parent_annotation(&mut zone, &annotated);
+        assert_eq!(zone.parent(), Some(annotated));
+
+        zone.metadata.namespace = Some(String::from("dns"));
+        let parent_ref = watch_annotation_reference::<Zone, Zone>(PARENT_ZONE_ANNOTATION)(zone);
+        assert_eq!(parent_ref.unwrap().namespace.as_deref(), Some("dns"));
+    }
+
+    #[cfg(feature = "otel")]
+    #[test]
+    fn test_parent_span_without_namespace() {
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_test_writer()
+            .finish();
+
+        // Spans must not rely on the Display impls, which expect a namespace.
+        tracing::subscriber::with_default(subscriber, || {
+            assert_eq!(Zone::new("orphan", ZoneSpec::default()).parent(), None);
+            assert_eq!(Record::new("orphan", RecordSpec::default()).parent(), None);
+        });
+    }
+
+    #[test]
+    fn test_owner_zone() {
+        let zone = crate::test_util::delegated_zone();
+
+        let mut record = crate::test_util::delegated_record();
+        assert!(matches!(
+            set_owner_zone(&mut record, &zone),
+            Err(OwnerError::CrossNamespace { .. })
+        ));
+
+        record.metadata.namespace = zone.metadata.namespace.clone();
+        set_owner_zone(&mut record, &zone).unwrap();
+        set_owner_zone(&mut record, &zone).unwrap();
+        assert_eq!(record.metadata.owner_references.as_ref().unwrap().len(), 1);
+        assert_eq!(record.parent(), Some(zone.zone_ref()));
+
+        let owner = watch_reference_owned::<Zone, Record>()(record).unwrap();
+        assert_eq!(owner.name, zone.metadata.name.unwrap());
+    }
+
+    #[test]
+    fn test_validate_records() {
+        let zone = crate::test_util::delegated_zone();
+        let delegated = crate::test_util::delegated_record();
+        let orphan = crate::test_util::orphan_record();
+
+        let report = zone.validate_records([&delegated, &orphan], |_| None);
+        assert!(!report.is_clean());
+        assert_eq!(report.accepted.len(), 1);
+        assert_eq!(
+            report.accepted[0].spec.domain_name,
+            delegated.spec.domain_name
+        );
+        assert!(matches!(
+            report.rejected[..],
+            [(_, DelegationDenied::NotSubdomain { .. })]
+        ));
+
+        let mut pending = zone.clone();
+        pending.status = None;
+        let report = pending.validate_records([&delegated], |_| None);
+        assert!(matches!(
+            report.rejected[..],
+            [(_, DelegationDenied::ParentFqdnMissing)]
+        ));
+
+        // Namespaces selected by label are looked up per record.
+        let mut selective = zone.clone();
+        selective.spec.delegations[0].namespaces.clear();
+        selective.spec.delegations[0].namespace_selector = Some(LabelSelector {
+            match_labels: BTreeMap::from([(String::from("tenant"), String::from("blue"))]),
+            match_expressions: vec![],
+        });
+
+        let mut other = delegated.clone();
+        other.metadata.namespace = Some(String::from("other"));
+
+        let labels = BTreeMap::from([(
+            delegated.namespace().unwrap(),
+            BTreeMap::from([(String::from("tenant"), String::from("blue"))]),
+        )]);
+
+        let report =
+            selective.validate_records([&delegated, &other], |namespace| labels.get(namespace));
+        assert_eq!(report.accepted.len(), 1);
+        assert!(matches!(
+            report.rejected[..],
+            [(_, DelegationDenied::NamespaceLabelsRequired { .. })]
+        ));
+    }
+
+    #[test]
+    fn test_namespace_patterns() {
+        let mut zone = crate::test_util::delegated_zone();
+        zone.spec.delegations[0].namespaces = vec![String::from("team-*"), String::from("web")];
+
+        let delegation = &zone.spec.delegations[0];
+        assert!(delegation.covers_namespace("team-payments"));
+        assert!(delegation.covers_namespace("web"));
+        assert!(!delegation.covers_namespace("webshop"));
+        assert!(!delegation.covers_namespace("kube-system"));
+
+        let mut record = crate::test_util::delegated_record();
+        record.metadata.namespace = Some(String::from("team-search"));
+        assert!(zone.validate_record(&record).is_ok());
+
+        record.metadata.namespace = Some(String::from("teams"));
+        assert!(matches!(
+            zone.validate_record(&record),
+            Err(DelegationDenied::NamespaceNotDelegated { .. })
+        ));
+    }
+
+    #[test]
+    fn test_namespace_selector() {
+        let mut zone = crate::test_util::delegated_zone();
+        zone.spec.delegations[0].namespaces = vec![String::from("web")];
+        zone.spec.delegations[0].namespace_selector = Some(LabelSelector {
+            match_labels: BTreeMap::from([(String::from("tenant"), String::from("blue"))]),
+            match_expressions: vec![],
+        });
+
+        let blue = BTreeMap::from([(String::from("tenant"), String::from("blue"))]);
+        let red = BTreeMap::from([(String::from("tenant"), String::from("red"))]);
+
+        // Namespaces listed by name don't need their labels evaluated.
+        let mut record = crate::test_util::delegated_record();
+        record.metadata.namespace = Some(String::from("web"));
+        assert!(zone.validate_record(&record).is_ok());
+
+        record.metadata.namespace = Some(String::from("shop"));
+        assert!(zone.validate_record_with_labels(&record, &blue).is_ok());
+        assert_eq!(
+            zone.validate_record_with_labels(&record, &red),
+            Err(DelegationDenied::NamespaceNotDelegated {
+                namespace: String::from("shop")
+            })
+        );
+        assert_eq!(
+            zone.validate_record(&record),
+            Err(DelegationDenied::NamespaceLabelsRequired {
+                namespace: String::from("shop")
+            })
+        );
+    }
+
+    #[test]
+    fn test_first_match_policy() {
+        let mut zone = crate::test_util::delegated_zone();
+        let record = crate::test_util::delegated_record();
+
+        let mut deny_www = zone.spec.delegations[0].clone();
+        deny_www.records.clear();
+        deny_www.deny_records = vec![RecordDelegation {
+            pattern: Pattern::try_from("www").unwrap().into(),
+            types: vec![],
+            min_ttl: None,
+            max_ttl: None,
+        }];
+        zone.spec.delegations.push(deny_www);
+
+        // Deny rules of later delegations still apply when any match decides.
+        assert!(matches!(
+            zone.validate_record(&record),
+            Err(DelegationDenied::ExplicitlyDenied { .. })
+        ));
+
+        zone.spec.delegation_policy = DelegationPolicy::FirstMatch;
+        assert!(zone.validate_record(&record).is_ok());
+
+        zone.spec.delegations.reverse();
+        assert!(matches!(
+            zone.validate_record(&record),
+            Err(DelegationDenied::ExplicitlyDenied { .. })
+        ));
+    }
+
+    #[test]
+    fn test_require_approval() {
+        let mut zone = crate::test_util::delegated_zone();
+        let mut record = crate::test_util::delegated_record();
+        record.metadata.generation = Some(1);
+
+        zone.spec.delegations[0].require_approval = true;
+        assert_eq!(
+            zone.validate_record(&record),
+            Err(DelegationDenied::ApprovalRequired {
+                fqdn: FullyQualifiedDomainName::try_from("www.example.org.").unwrap(),
+            })
+        );
+
+        // Approvals written onto the record by its author grant nothing.
+        record.annotations_mut().insert(
+            String::from("kubi.zone/approval"),
+            String::from(r#"{"approvedBy":"mallory","generation":1}"#),
+        );
+        assert!(zone.validate_record(&record).is_err());
+
+        zone.status
+            .get_or_insert_with(ZoneStatus::default)
+            .approve(&record, "alice");
+        assert!(zone.validate_record(&record).is_ok());
+
+        // Approvals don't carry over to later generations.
+        record.metadata.generation = Some(2);
+        assert!(zone.validate_record(&record).is_err());
+
+        // Delegations without approval take precedence.
+        let mut open = zone.spec.delegations[0].clone();
+        open.require_approval = false;
+        zone.spec.delegations.push(open);
+        assert!(zone.validate_record(&record).is_ok());
+    }
+
+    #[test]
+    fn test_zone_variables() {
+        let mut zone = crate::test_util::delegated_zone();
+
+        // Zones without variables leave rdata alone.
+        let mut txt = crate::test_util::delegated_record().spec;
+        txt.rdata = String::from("\"price: $$5 ${sku}\"");
+        assert_eq!(zone.render_record(&txt).unwrap(), txt);
+
+        zone.spec
+            .variables
+            .insert(String::from("lb"), String::from("192.0.2.10"));
+
+        let mut spec = crate::test_util::delegated_record().spec;
+        spec.rdata = String::from("${lb}");
+        assert_eq!(zone.render_record(&spec).unwrap().rdata, "192.0.2.10");
+
+        spec.rdata = String::from("${lb6}");
+        assert_eq!(
+            zone.render_record(&spec),
+            Err(SubstitutionError::Undefined(String::from("lb6")))
+        );
+
+        spec.rdata = String::from("$${lb} $$$$");
+        assert_eq!(zone.render_record(&spec).unwrap().rdata, "${lb} $$");
+    }
+
+    #[test]
+    fn test_max_child_depth() {
+        let mut zone = crate::test_util::delegated_zone();
+        let sub_zone = crate::test_util::nested_sub_zone();
+
+        let mut deep_zone = sub_zone.clone();
+        deep_zone.metadata.uid = None;
+        deep_zone.set_fqdn(FullyQualifiedDomainName::try_from("api.sub.example.org.").unwrap());
+
+        assert!(zone.validate_zone(&deep_zone).is_ok());
+
+        zone.spec.max_child_depth = Some(1);
+        assert!(zone.validate_zone(&sub_zone).is_ok());
+        assert_eq!(
+            zone.validate_zone(&deep_zone),
+            Err(DelegationDenied::TooDeep {
+                fqdn: FullyQualifiedDomainName::try_from("api.sub.example.org.").unwrap(),
+                depth: 2,
+                max: 1,
+            })
+        );
+
+        // Grandchildren are validated by their direct parent, but
+        // ancestors can still enforce their limits.
+        assert!(sub_zone.validate_depth(&deep_zone).is_ok());
+        assert!(zone.validate_depth(&deep_zone).is_err());
+    }
+
+    #[test]
+    fn test_domain_ext_setters() {
+        fn reparent<K: DomainExt>(resource: &mut K, parent: ZoneRef) {
+            resource.set_fqdn(FullyQualifiedDomainName::try_from("www.example.org.").unwrap());
+            resource.set_parent(parent);
+        }
+
+        let zone = crate::test_util::delegated_zone();
+        let mut record = crate::test_util::delegated_record();
+        record.metadata.namespace = zone.metadata.namespace.clone();
+        set_owner_zone(&mut record, &zone).unwrap();
+
+        let parent: ZoneRef = "example-org.dns".parse().unwrap();
+        reparent(&mut record, parent.clone());
+        assert_eq!(record.fqdn().unwrap().to_string(), "www.example.org.");
+        assert_eq!(
+            record.metadata.labels.as_ref().unwrap()[PARENT_ZONE_LABEL],
+            "dns_example-org"
+        );
+        assert_eq!(record.parent(), Some(parent));
+
+        let long = ZoneRef {
+            name: String::from("a-very-long-zone-name-which-would-not-fit-into-a-label-value"),
+            namespace: Some(String::from("dns")),
+        };
+        let mut sub_zone = Zone::new("sub", ZoneSpec::default());
+        reparent(&mut sub_zone, long.clone());
+        assert!(!sub_zone
+            .metadata
+            .labels
+            .as_ref()
+            .unwrap()
+            .contains_key(PARENT_ZONE_LABEL));
+        assert_eq!(sub_zone.parent(), Some(long));
+
+        record.clear_parent();
+        sub_zone.clear_parent();
+        assert_eq!(record.parent(), None);
+        assert_eq!(sub_zone.parent(), None);
     }
 }
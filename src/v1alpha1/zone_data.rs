@@ -0,0 +1,320 @@
+use std::fmt::Display;
+
+use kube::{CustomResource, ResourceExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{errors::OwnerError, set_owner_zone};
+
+use super::{zone::serialized_size, Zone, ZoneEntry, ZoneRef};
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum ZoneDataError {
+    #[error("zone has no status yet")]
+    MissingStatus,
+    #[error("zone does not reference any zone data")]
+    NotSplit,
+    #[error("entries of the zone are already stored in zone data, and must be joined first")]
+    AlreadySplit,
+    #[error("zone references zone data {0}, which was not provided")]
+    MissingShard(String),
+    #[error("zone data {0} is not referenced by the zone")]
+    UnexpectedShard(String),
+    #[error("zone references entries with hash {expected}, but zone data has hash {actual}")]
+    HashMismatch { expected: String, actual: String },
+    #[error(transparent)]
+    Owner(#[from] OwnerError),
+}
+
+/// Entries of a [`Zone`], stored outside of its status.
+///
+/// Zones with thousands of entries can exceed the object size limit of
+/// etcd when storing them inline in [`ZoneStatus::entries`](super::ZoneStatus::entries).
+/// Such zones instead move their entries into one or more `ZoneData` shards
+/// in the same namespace, owned by the zone, and reference them through
+/// [`ZoneStatus::data_ref`](super::ZoneStatus::data_ref).
+/// See [`Zone::split_entries`] and [`Zone::join_entries`].
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha1",
+        kind = "ZoneData",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        group = "kubi.zone",
+        version = "v1alpha1",
+        kind = "ZoneData",
+        namespaced
+    )
+)]
+#[kube(printcolumn = r#"{"name":"zone", "jsonPath": ".spec.zoneRef.name", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"hash", "jsonPath": ".spec.hash", "type": "string"}"#)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoneDataSpec {
+    /// Zone the entries belong to.
+    pub zone_ref: ZoneRef,
+
+    /// Hash of the entries of all shards combined, computed with the hash
+    /// algorithm of the zone.
+    pub hash: String,
+
+    #[serde(default)]
+    pub entries: Vec<ZoneEntry>,
+}
+
+/// Reference to the [`ZoneData`] shards holding the entries of a zone.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub struct ZoneDataRef {
+    /// Names of the shards, in order.
+    pub shards: Vec<String>,
+
+    /// Hash of the referenced entries, which the shards combined must match.
+    pub hash: String,
+}
+
+impl Zone {
+    /// Move the entries of the zone out of its status, into [`ZoneData`]
+    /// shards of at most `max_size` bytes of serialized entries each, named
+    /// after the zone and referenced by [`ZoneStatus::data_ref`](super::ZoneStatus::data_ref).
+    ///
+    /// The shards are owned by the zone, so they are garbage collected along
+    /// with it. Zones whose entries are already split are left untouched, and
+    /// must be joined before being split again.
+    pub fn split_entries(&mut self, max_size: usize) -> Result<Vec<ZoneData>, ZoneDataError> {
+        let name = self.name_any();
+        let zone_ref = self.zone_ref();
+
+        let status = self.status.as_ref().ok_or(ZoneDataError::MissingStatus)?;
+        if status.data_ref.is_some() {
+            return Err(ZoneDataError::AlreadySplit);
+        }
+        let hash = self.compute_hash(&status.entries);
+
+        let mut shards: Vec<Vec<ZoneEntry>> = Vec::new();
+        let mut size = 0;
+        for entry in &status.entries {
+            let entry_size = serialized_size(entry);
+            match shards.last_mut() {
+                Some(shard) if size + entry_size <= max_size => shard.push(entry.clone()),
+                _ => {
+                    shards.push(vec![entry.clone()]);
+                    size = 0;
+                }
+            }
+            size += entry_size;
+        }
+
+        let shards = shards
+            .into_iter()
+            .enumerate()
+            .map(|(index, entries)| {
+                let mut data = ZoneData::new(
+                    &format!("{name}-{index}"),
+                    ZoneDataSpec {
+                        zone_ref: zone_ref.clone(),
+                        hash: hash.clone(),
+                        entries,
+                    },
+                );
+                data.metadata.namespace = self.namespace();
+                set_owner_zone(&mut data, self)?;
+                Ok(data)
+            })
+            .collect::<Result<Vec<_>, ZoneDataError>>()?;
+
+        // Unwrap safety: Checked above.
+        let status = self.status.as_mut().unwrap();
+        status.entries.clear();
+        status.data_ref = Some(ZoneDataRef {
+            shards: shards.iter().map(ResourceExt::name_any).collect(),
+            hash,
+        });
+
+        Ok(shards)
+    }
+
+    /// Move the entries of the referenced `shards` back into the status of the zone.
+    ///
+    /// Fails if any referenced shard is missing, any other zone data is
+    /// given, or the combined entries no longer match the referenced hash.
+    pub fn join_entries(
+        &mut self,
+        shards: impl IntoIterator<Item = ZoneData>,
+    ) -> Result<(), ZoneDataError> {
+        let data_ref = self
+            .status
+            .as_ref()
+            .and_then(|status| status.data_ref.clone())
+            .ok_or(ZoneDataError::NotSplit)?;
+
+        let mut shards: Vec<ZoneData> = shards.into_iter().collect();
+        if let Some(unexpected) = shards
+            .iter()
+            .find(|shard| !data_ref.shards.contains(&shard.name_any()))
+        {
+            return Err(ZoneDataError::UnexpectedShard(unexpected.name_any()));
+        }
+
+        let mut entries = Vec::new();
+        for name in &data_ref.shards {
+            let Some(index) = shards.iter().position(|shard| shard.name_any() == *name) else {
+                return Err(ZoneDataError::MissingShard(name.clone()));
+            };
+            entries.append(&mut shards.swap_remove(index).spec.entries);
+        }
+
+        let hash = self.compute_hash(&entries);
+        if data_ref.hash != hash {
+            return Err(ZoneDataError::HashMismatch {
+                expected: data_ref.hash,
+                actual: hash,
+            });
+        }
+
+        // Unwrap safety: Zones with a data reference have a status.
+        let status = self.status.as_mut().unwrap();
+        status.entries = entries;
+        status.data_ref = None;
+
+        Ok(())
+    }
+}
+
+impl Display for ZoneData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: ZoneData is namespaced and therefore always has a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kube::{core::ObjectMeta, ResourceExt};
+    use kubizone_common::DomainName;
+
+    use crate::v1alpha1::{Zone, ZoneEntry, ZoneSpec, ZoneStatus};
+
+    use super::ZoneDataError;
+
+    #[test]
+    fn test_split_join_entries() {
+        let entries: Vec<ZoneEntry> = [
+            "www.example.org. 300 IN A 192.0.2.1",
+            "mail.example.org. 300 IN A 192.0.2.25",
+            "ftp.example.org. 300 IN A 192.0.2.21",
+        ]
+        .into_iter()
+        .map(|line| line.parse().unwrap())
+        .collect();
+
+        let mut zone = Zone {
+            metadata: ObjectMeta {
+                name: Some(String::from("example-org")),
+                namespace: Some(String::from("default")),
+                uid: Some(String::from("1234")),
+                ..Default::default()
+            },
+            spec: ZoneSpec {
+                domain_name: DomainName::try_from("example.org.").unwrap(),
+                ..Default::default()
+            },
+            status: Some(ZoneStatus {
+                entries: entries.clone(),
+                ..Default::default()
+            }),
+        };
+
+        let hash = ZoneStatus::compute_hash(&entries);
+
+        // Every entry exceeds the size limit, so each gets its own shard.
+        let data = zone.split_entries(1).unwrap();
+        assert_eq!(data.len(), 3);
+        assert_eq!(data[0].to_string(), "default/example-org-0");
+        assert!(data.iter().all(|shard| shard.spec.hash == hash));
+        assert!(data.iter().all(|shard| {
+            shard.owner_references()[0].uid == "1234" && shard.owner_references()[0].kind == "Zone"
+        }));
+
+        let status = zone.status.as_ref().unwrap();
+        assert!(status.entries.is_empty());
+        assert_eq!(status.data_ref.as_ref().unwrap().hash, hash);
+        assert_eq!(
+            status.data_ref.as_ref().unwrap().shards,
+            ["example-org-0", "example-org-1", "example-org-2"]
+        );
+
+        // Splitting again keeps the existing reference.
+        assert!(matches!(
+            zone.split_entries(1),
+            Err(ZoneDataError::AlreadySplit)
+        ));
+        assert_eq!(
+            zone.status
+                .as_ref()
+                .unwrap()
+                .data_ref
+                .as_ref()
+                .unwrap()
+                .hash,
+            hash
+        );
+
+        let mut tampered = data.clone();
+        tampered[1].spec.entries.pop();
+        assert!(matches!(
+            zone.join_entries(tampered),
+            Err(ZoneDataError::HashMismatch { .. })
+        ));
+
+        assert_eq!(
+            zone.join_entries(data[..2].to_vec()),
+            Err(ZoneDataError::MissingShard(String::from("example-org-2")))
+        );
+
+        let mut other = data[0].clone();
+        other.metadata.name = Some(String::from("other"));
+        assert_eq!(
+            zone.join_entries([other]),
+            Err(ZoneDataError::UnexpectedShard(String::from("other")))
+        );
+
+        // Shards are joined in the referenced order, regardless of the given order.
+        zone.join_entries(data.iter().rev().cloned()).unwrap();
+        let status = zone.status.as_ref().unwrap();
+        assert_eq!(status.entries.len(), 3);
+        assert_eq!(status.entries[0].fqdn, entries[0].fqdn);
+        assert!(status.data_ref.is_none());
+
+        assert_eq!(zone.join_entries(data), Err(ZoneDataError::NotSplit));
+
+        // Everything fits within a single shard given enough room.
+        assert_eq!(zone.split_entries(usize::MAX).unwrap().len(), 1);
+
+        zone.status = Some(ZoneStatus {
+            entries,
+            ..Default::default()
+        });
+        zone.metadata.uid = None;
+        assert!(matches!(
+            zone.split_entries(usize::MAX),
+            Err(ZoneDataError::Owner(_))
+        ));
+    }
+}
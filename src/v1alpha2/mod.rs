@@ -0,0 +1,30 @@
+//! Second iteration of the kubizone API.
+//!
+//! Compared to [`v1alpha1`](crate::v1alpha1):
+//!
+//! * Records carry typed [`RData`](crate::rdata::RData) instead of a
+//!   `type` and free-form `rdata` string, so malformed record data is
+//!   rejected by the API server rather than by the controller.
+//! * SOA timers and names of Zones are grouped into a `soa` block.
+//!
+//! Status types and nested types such as [`ZoneRef`] and [`Delegation`] are
+//! shared between the two versions. `v1alpha1` remains the storage version,
+//! and conversions in both directions are provided through `From`/`TryFrom`.
+
+mod record;
+mod zone;
+
+pub use record::*;
+pub use zone::*;
+
+pub use crate::v1alpha1::{
+    ActiveWindow, Condition, ConditionError, ConditionReason, ConditionStatus, Delegation,
+    DelegationPattern, DelegationPolicy, DenialOfExistence, Dnssec, DnssecAlgorithm, DnssecError,
+    DnssecStatus, DomainExt, EntriesBudget, EntrySource, HashAlgorithm, KeyStrategy, LabelSelector,
+    LabelSelectorOperator, LabelSelectorRequirement, NotifyTarget, NotifyTargetError, Nsec3,
+    ObservedGeneration, PatternType, RecordConditionType, RecordDelegation, RecordStatus,
+    Recurrence, ResponsePolicy, Rname, RnameError, RoutingPolicy, RoutingPolicyError,
+    SerialStrategy, TooLarge, TransferPeer, TransferPeerError, TransferSecurity, TsigAlgorithm,
+    TsigError, TsigSecretRef, ViewRef, ZoneConditionType, ZoneDataRef, ZoneEntry, ZoneRef,
+    ZoneStats, ZoneStatus, Zonemd, ZonemdHashAlgorithm, ZONEMD_SCHEME_SIMPLE,
+};
@@ -0,0 +1,224 @@
+use std::fmt::Display;
+
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::{Class, DomainName, FullyQualifiedDomainName};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    parent_reference,
+    rdata::{RData, RDataError},
+    v1alpha1,
+};
+
+use super::{
+    ActiveWindow, DomainExt, RecordStatus, ResponsePolicy, RoutingPolicy, ViewRef, ZoneRef,
+};
+
+#[derive(CustomResource, Deserialize, Serialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha2",
+        kind = "Record",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(group = "kubi.zone", version = "v1alpha2", kind = "Record", namespaced)
+)]
+#[kube(status = "RecordStatus")]
+#[kube(printcolumn = r#"{"name":"domain name", "jsonPath": ".spec.domainName", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"class", "jsonPath": ".spec.class", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"fqdn", "jsonPath": ".status.fqdn", "type": "string"}"#)]
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        printcolumn = r#"{"name":"parent", "jsonPath": ".metadata.labels.dev\\.kubi\\.zone/parent-zone", "type": "string"}"#
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        printcolumn = r#"{"name":"parent", "jsonPath": ".metadata.labels.kubi\\.zone/parent-zone", "type": "string"}"#
+    )
+)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordSpec {
+    #[schemars(schema_with = "crate::schema::domain_name")]
+    pub domain_name: DomainName,
+    pub zone_ref: Option<ZoneRef>,
+    #[serde(default = "crate::v1alpha1::defaults::class")]
+    pub class: Class,
+    pub ttl: Option<u32>,
+
+    /// Typed record data. The record's type is implied by the variant.
+    pub rdata: RData,
+
+    /// Order in which answers for this record's name and type should be
+    /// returned, for serializers which support answer rotation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_policy: Option<ResponsePolicy>,
+
+    /// Restricts publication of the record to a (possibly recurring) window
+    /// of time. Records without a window are always active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_window: Option<ActiveWindow>,
+
+    /// Lease on the record in seconds, counted from its creation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_after: Option<u32>,
+
+    /// Disabled records are left out of their zone, without deleting them.
+    #[serde(default = "crate::v1alpha1::defaults::enabled")]
+    pub enabled: bool,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy: Option<RoutingPolicy>,
+
+    #[serde(default)]
+    pub generate_ptr: bool,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub views: Vec<ViewRef>,
+}
+
+impl DomainExt for Record {
+    fn fqdn(&self) -> Option<&FullyQualifiedDomainName> {
+        self.status.as_ref().and_then(|status| status.fqdn.as_ref())
+    }
+
+    fn set_fqdn(&mut self, fqdn: FullyQualifiedDomainName) {
+        self.status.get_or_insert_with(RecordStatus::default).fqdn = Some(fqdn);
+    }
+
+    fn parent(&self) -> Option<ZoneRef> {
+        parent_reference(self)
+    }
+}
+
+impl Display for Record {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: Records are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+impl From<RecordSpec> for v1alpha1::RecordSpec {
+    fn from(value: RecordSpec) -> Self {
+        v1alpha1::RecordSpec {
+            domain_name: value.domain_name,
+            zone_ref: value.zone_ref,
+            type_: value.rdata.type_(),
+            class: value.class,
+            ttl: value.ttl,
+            rdata: value.rdata.to_string(),
+            response_policy: value.response_policy,
+            active_window: value.active_window,
+            expires_after: value.expires_after,
+            enabled: value.enabled,
+            policy: value.policy,
+            generate_ptr: value.generate_ptr,
+            views: value.views,
+        }
+    }
+}
+
+impl TryFrom<v1alpha1::RecordSpec> for RecordSpec {
+    type Error = RDataError;
+
+    fn try_from(value: v1alpha1::RecordSpec) -> Result<Self, Self::Error> {
+        Ok(RecordSpec {
+            rdata: value.parse_rdata()?,
+            domain_name: value.domain_name,
+            zone_ref: value.zone_ref,
+            class: value.class,
+            ttl: value.ttl,
+            response_policy: value.response_policy,
+            active_window: value.active_window,
+            expires_after: value.expires_after,
+            enabled: value.enabled,
+            policy: value.policy,
+            generate_ptr: value.generate_ptr,
+            views: value.views,
+        })
+    }
+}
+
+impl From<Record> for v1alpha1::Record {
+    fn from(value: Record) -> Self {
+        v1alpha1::Record {
+            metadata: value.metadata,
+            spec: value.spec.into(),
+            status: value.status,
+        }
+    }
+}
+
+impl TryFrom<v1alpha1::Record> for Record {
+    type Error = RDataError;
+
+    fn try_from(value: v1alpha1::Record) -> Result<Self, Self::Error> {
+        Ok(Record {
+            metadata: value.metadata,
+            spec: value.spec.try_into()?,
+            status: value.status,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kubizone_common::{DomainName, Type};
+
+    use crate::{rdata::RData, v1alpha1};
+
+    use super::RecordSpec;
+
+    #[test]
+    fn test_v1alpha1_conversion() {
+        let v1alpha1 = v1alpha1::RecordSpec {
+            domain_name: DomainName::try_from("example.org.").unwrap(),
+            type_: Type::MX,
+            rdata: String::from("10  mail1.example.org."),
+            ..Default::default()
+        };
+
+        let v1alpha2 = RecordSpec::try_from(v1alpha1.clone()).unwrap();
+        assert_eq!(
+            v1alpha2.rdata,
+            RData::MX {
+                preference: 10,
+                exchange: DomainName::try_from("mail1.example.org.").unwrap()
+            }
+        );
+
+        // Rdata is normalized on the way back.
+        let roundtrip = v1alpha1::RecordSpec::from(v1alpha2);
+        assert_eq!(roundtrip.type_, Type::MX);
+        assert_eq!(roundtrip.rdata, "10 mail1.example.org.");
+
+        // Canonical rdata survives the round trip unchanged.
+        assert_eq!(
+            v1alpha1::RecordSpec::from(RecordSpec::try_from(roundtrip.clone()).unwrap()),
+            roundtrip
+        );
+
+        assert!(RecordSpec::try_from(v1alpha1::RecordSpec {
+            type_: Type::A,
+            rdata: String::from("not-an-address"),
+            ..v1alpha1
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,348 @@
+use std::{collections::BTreeMap, fmt::Display};
+
+use kube::{CustomResource, ResourceExt};
+use kubizone_common::{DomainName, FullyQualifiedDomainName};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{parent_reference, v1alpha1, v1alpha1::defaults};
+
+use super::{
+    Delegation, DelegationPolicy, Dnssec, DomainExt, HashAlgorithm, NotifyTarget, Rname,
+    SerialStrategy, TransferPeer, TransferSecurity, ZoneRef, ZoneStatus,
+};
+
+#[derive(
+    Default,
+    CustomResource,
+    Deserialize,
+    Serialize,
+    Clone,
+    Debug,
+    JsonSchema,
+    Hash,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+)]
+// The 'dev' feature flag puts the resource in a separate dev.kubi.zone group,
+// instead of the real one. This way you can have the production and dev versions
+// of kubizone resources running side by side, without interfering with each other.
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        group = "dev.kubi.zone",
+        version = "v1alpha2",
+        kind = "Zone",
+        namespaced
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(group = "kubi.zone", version = "v1alpha2", kind = "Zone", namespaced)
+)]
+#[kube(status = "ZoneStatus")]
+#[kube(printcolumn = r#"{"name":"domain name", "jsonPath": ".spec.domainName", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"fqdn", "jsonPath": ".status.fqdn", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"hash", "jsonPath": ".status.hash", "type": "string"}"#)]
+#[kube(printcolumn = r#"{"name":"serial", "jsonPath": ".status.serial", "type": "string"}"#)]
+#[kube(
+    printcolumn = r#"{"name":"entries", "jsonPath": ".status.stats.entries", "type": "integer"}"#
+)]
+#[kube(
+    printcolumn = r#"{"name":"last change", "jsonPath": ".status.stats.lastChange", "type": "date", "priority": 1}"#
+)]
+#[cfg_attr(
+    feature = "dev",
+    kube(
+        printcolumn = r#"{"name":"parent", "jsonPath": ".metadata.labels.dev\\.kubi\\.zone/parent-zone", "type": "string"}"#
+    )
+)]
+#[cfg_attr(
+    not(feature = "dev"),
+    kube(
+        printcolumn = r#"{"name":"parent", "jsonPath": ".metadata.labels.kubi\\.zone/parent-zone", "type": "string"}"#
+    )
+)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoneSpec {
+    #[schemars(schema_with = "crate::schema::domain_name")]
+    pub domain_name: DomainName,
+
+    /// Optional reference to a parent zone which this zone is a sub-zone of.
+    ///
+    /// Zones must have *either* a zoneRef, or end in a '.', making it a fully
+    /// qualified domain name. It cannot have both.
+    pub zone_ref: Option<ZoneRef>,
+
+    /// List of namespaced records and zones which are allowed to "insert"
+    /// themselves into this zone. See the [`Delegation`] type for more information.
+    #[serde(default)]
+    pub delegations: Vec<Delegation>,
+
+    /// How `delegations` are combined, see [`DelegationPolicy`].
+    #[serde(default)]
+    pub delegation_policy: DelegationPolicy,
+
+    /// Freeze the zone, so its entries and serial are not recomputed until
+    /// it is unpaused. Records can still be edited in the meantime, and are
+    /// picked up once the zone resumes.
+    #[serde(default)]
+    pub paused: bool,
+
+    /// Maximum number of labels a sub-zone may add beneath this zone, such
+    /// as `1` for `dev.example.org.` beneath `example.org.`, which would
+    /// reject `api.dev.example.org.`. Sub-zones may be nested arbitrarily
+    /// deep if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_child_depth: Option<u32>,
+
+    /// Authoritative name servers of the zone.
+    #[serde(default)]
+    #[schemars(schema_with = "crate::schema::fully_qualified_domain_names")]
+    pub nameservers: Vec<FullyQualifiedDomainName>,
+
+    /// Publish an `NS` entry at the apex of the zone for each of its `nameservers`.
+    #[serde(default)]
+    pub generate_ns_records: bool,
+
+    /// How the serial of the zone is advanced when its entries change.
+    #[serde(default)]
+    pub serial_strategy: SerialStrategy,
+
+    /// Serial to adopt as the new baseline, if it is greater than the
+    /// computed one, see [`ZoneStatus::apply_serial_override`]. Lets zones
+    /// migrated from an external primary continue from its serial.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub serial_override: Option<u32>,
+
+    /// Algorithm used for computing the zone's hash.
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
+
+    /// Time-to-Live. Represents how long (in seconds) recursive resolvers should
+    /// keep this record in their cache.
+    ///
+    /// Must be at least 30 seconds, since many resolvers will
+    /// ignore or clamp lower values anyway.
+    #[serde(default = "defaults::ttl")]
+    #[schemars(range(min = 30))]
+    pub ttl: u32,
+
+    /// Timers published in the zone's SOA record.
+    #[serde(default)]
+    pub soa: Soa,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dnssec: Option<Dnssec>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transfer_security: Option<TransferSecurity>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_transfer: Vec<TransferPeer>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notify_targets: Vec<NotifyTarget>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub providers: Vec<String>,
+
+    /// Values shared by the records of the zone, which records reference
+    /// as `${name}` in their rdata. Records are only subject to
+    /// substitution once at least one variable is defined, after which a
+    /// literal `${` or `$$` must be written as `$${` or `$$$$`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+}
+
+/// SOA timers of a zone. See the equivalent fields of
+/// [`v1alpha1::ZoneSpec`] for their meaning and recommended values.
+#[derive(
+    Serialize, Deserialize, Clone, Debug, JsonSchema, Hash, PartialEq, Eq, PartialOrd, Ord,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct Soa {
+    #[serde(default = "defaults::refresh")]
+    pub refresh: u32,
+    #[serde(default = "defaults::retry")]
+    pub retry: u32,
+    #[serde(default = "defaults::expire")]
+    pub expire: u32,
+    #[serde(default = "defaults::negative_response_cache")]
+    #[schemars(range(min = 30))]
+    pub negative_response_cache: u32,
+
+    /// Primary name server of the zone.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mname: Option<FullyQualifiedDomainName>,
+
+    /// Mailbox of the person responsible for the zone, either as an
+    /// email address or in its domain name form.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rname: Option<Rname>,
+}
+
+impl Default for Soa {
+    fn default() -> Self {
+        Soa {
+            refresh: defaults::REFRESH,
+            retry: defaults::RETRY,
+            expire: defaults::EXPIRE,
+            negative_response_cache: defaults::NEGATIVE_RESPONSE_CACHE,
+            mname: None,
+            rname: None,
+        }
+    }
+}
+
+impl DomainExt for Zone {
+    fn fqdn(&self) -> Option<&FullyQualifiedDomainName> {
+        self.status.as_ref().and_then(|status| status.fqdn.as_ref())
+    }
+
+    fn set_fqdn(&mut self, fqdn: FullyQualifiedDomainName) {
+        self.status.get_or_insert_with(ZoneStatus::default).fqdn = Some(fqdn);
+    }
+
+    fn parent(&self) -> Option<ZoneRef> {
+        parent_reference(self)
+    }
+}
+
+impl Display for Zone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Unwrap safety: Zones are namespaced and therefore always have a name.
+        write!(
+            f,
+            "{}/{}",
+            self.metadata.namespace.as_ref().unwrap(),
+            self.name_any()
+        )
+    }
+}
+
+impl From<ZoneSpec> for v1alpha1::ZoneSpec {
+    fn from(value: ZoneSpec) -> Self {
+        v1alpha1::ZoneSpec {
+            domain_name: value.domain_name,
+            zone_ref: value.zone_ref,
+            delegations: value.delegations,
+            delegation_policy: value.delegation_policy,
+            paused: value.paused,
+            max_child_depth: value.max_child_depth,
+            nameservers: value.nameservers,
+            generate_ns_records: value.generate_ns_records,
+            serial_strategy: value.serial_strategy,
+            serial_override: value.serial_override,
+            hash_algorithm: value.hash_algorithm,
+            ttl: value.ttl,
+            refresh: value.soa.refresh,
+            retry: value.soa.retry,
+            expire: value.soa.expire,
+            negative_response_cache: value.soa.negative_response_cache,
+            mname: value.soa.mname,
+            rname: value.soa.rname,
+            dnssec: value.dnssec,
+            transfer_security: value.transfer_security,
+            allow_transfer: value.allow_transfer,
+            notify_targets: value.notify_targets,
+            providers: value.providers,
+            variables: value.variables,
+        }
+    }
+}
+
+impl From<v1alpha1::ZoneSpec> for ZoneSpec {
+    fn from(value: v1alpha1::ZoneSpec) -> Self {
+        ZoneSpec {
+            domain_name: value.domain_name,
+            zone_ref: value.zone_ref,
+            delegations: value.delegations,
+            delegation_policy: value.delegation_policy,
+            paused: value.paused,
+            max_child_depth: value.max_child_depth,
+            nameservers: value.nameservers,
+            generate_ns_records: value.generate_ns_records,
+            serial_strategy: value.serial_strategy,
+            serial_override: value.serial_override,
+            hash_algorithm: value.hash_algorithm,
+            ttl: value.ttl,
+            soa: Soa {
+                refresh: value.refresh,
+                retry: value.retry,
+                expire: value.expire,
+                negative_response_cache: value.negative_response_cache,
+                mname: value.mname,
+                rname: value.rname,
+            },
+            dnssec: value.dnssec,
+            transfer_security: value.transfer_security,
+            allow_transfer: value.allow_transfer,
+            notify_targets: value.notify_targets,
+            providers: value.providers,
+            variables: value.variables,
+        }
+    }
+}
+
+impl From<Zone> for v1alpha1::Zone {
+    fn from(value: Zone) -> Self {
+        v1alpha1::Zone {
+            metadata: value.metadata,
+            spec: value.spec.into(),
+            status: value.status,
+        }
+    }
+}
+
+impl From<v1alpha1::Zone> for Zone {
+    fn from(value: v1alpha1::Zone) -> Self {
+        Zone {
+            metadata: value.metadata,
+            spec: value.spec.into(),
+            status: value.status,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use kubizone_common::{DomainName, FullyQualifiedDomainName};
+
+    use crate::v1alpha1;
+
+    use super::ZoneSpec;
+
+    #[test]
+    fn test_v1alpha1_roundtrip() {
+        let v1alpha1 = v1alpha1::ZoneSpec {
+            domain_name: DomainName::try_from("example.org.").unwrap(),
+            nameservers: vec![FullyQualifiedDomainName::try_from("ns1.example.org.").unwrap()],
+            ttl: 600,
+            refresh: 7200,
+            retry: 900,
+            expire: 86400,
+            negative_response_cache: 300,
+            mname: Some(FullyQualifiedDomainName::try_from("ns1.example.org.").unwrap()),
+            variables: BTreeMap::from([(String::from("env"), String::from("prod"))]),
+            ..Default::default()
+        };
+
+        let v1alpha2 = ZoneSpec::from(v1alpha1.clone());
+        assert_eq!(v1alpha2.soa.refresh, 7200);
+        assert_eq!(v1alpha2.soa.retry, 900);
+        assert_eq!(v1alpha2.soa.expire, 86400);
+        assert_eq!(v1alpha2.soa.negative_response_cache, 300);
+        assert_eq!(v1alpha2.soa.mname, v1alpha1.mname);
+
+        assert_eq!(v1alpha1::ZoneSpec::from(v1alpha2.clone()), v1alpha1);
+        assert_eq!(
+            ZoneSpec::from(v1alpha1::ZoneSpec::from(v1alpha2.clone())),
+            v1alpha2
+        );
+    }
+}
@@ -0,0 +1,520 @@
+//! Validating admission webhook for Zones and Records.
+//!
+//! Rejects records and sub-zones which would not be allowed into their parent
+//! zone by its delegations, at admission time rather than having them silently
+//! ignored by the controller.
+//!
+//! The [`router`] can be served directly, or merged into an existing axum
+//! application. The `ValidatingWebhookConfiguration` should route
+//! Record and Zone requests to [`RECORDS_PATH`] and [`ZONES_PATH`] respectively.
+
+use std::collections::BTreeMap;
+
+use axum::{extract::State, routing::post, Json, Router};
+use kube::{
+    api::ListParams,
+    core::{
+        admission::{AdmissionRequest, AdmissionResponse, AdmissionReview},
+        DynamicObject, Status,
+    },
+    Api, Client, ResourceExt,
+};
+use kubizone_common::{DomainName, FullyQualifiedDomainName};
+use tracing::*;
+
+use crate::{
+    authorization::namespace_labels,
+    v1alpha1::{DelegationDenied, DomainExt, Record, RecordStatus, Zone, ZoneRef, ZoneStatus},
+};
+
+/// Path on which Record admission reviews are served.
+pub const RECORDS_PATH: &str = "/validate/records";
+
+/// Path on which Zone admission reviews are served.
+pub const ZONES_PATH: &str = "/validate/zones";
+
+/// Outcome of validating an object for admission.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Admit,
+    /// The object must be rejected, for the given reason.
+    Deny(String),
+}
+
+/// Construct a router serving the Record and Zone validation endpoints.
+pub fn router(client: Client) -> Router {
+    Router::new()
+        .route(RECORDS_PATH, post(review_record))
+        .route(ZONES_PATH, post(review_zone))
+        .with_state(client)
+}
+
+async fn review_record(
+    State(client): State<Client>,
+    Json(review): Json<AdmissionReview<Record>>,
+) -> Json<AdmissionReview<DynamicObject>> {
+    let request: AdmissionRequest<Record> = match review.try_into() {
+        Ok(request) => request,
+        Err(err) => return Json(AdmissionResponse::invalid(err).into_review()),
+    };
+
+    let response = AdmissionResponse::from(&request);
+    let Some(mut record) = request.object else {
+        return Json(response.into_review());
+    };
+
+    if record.metadata.namespace.is_none() {
+        record.metadata.namespace = request.namespace;
+    }
+
+    Json(
+        match admit_record(client, &record).await {
+            Ok(Verdict::Admit) => response,
+            Ok(Verdict::Deny(reason)) => response.deny(reason),
+            Err(err) => internal_error(response, err),
+        }
+        .into_review(),
+    )
+}
+
+async fn review_zone(
+    State(client): State<Client>,
+    Json(review): Json<AdmissionReview<Zone>>,
+) -> Json<AdmissionReview<DynamicObject>> {
+    let request: AdmissionRequest<Zone> = match review.try_into() {
+        Ok(request) => request,
+        Err(err) => return Json(AdmissionResponse::invalid(err).into_review()),
+    };
+
+    let response = AdmissionResponse::from(&request);
+    let Some(mut zone) = request.object else {
+        return Json(response.into_review());
+    };
+
+    if zone.metadata.namespace.is_none() {
+        zone.metadata.namespace = request.namespace;
+    }
+
+    Json(
+        match admit_zone(client, &zone).await {
+            Ok(Verdict::Admit) => response,
+            Ok(Verdict::Deny(reason)) => response.deny(reason),
+            Err(err) => internal_error(response, err),
+        }
+        .into_review(),
+    )
+}
+
+/// Deny a request which could not be validated, because the API server
+/// could not be queried for its parent zone or namespace.
+///
+/// This is reported as an internal error rather than an invalid request,
+/// since the object itself may well be valid.
+fn internal_error(response: AdmissionResponse, err: kube::Error) -> AdmissionResponse {
+    warn!("failed to look up admission request dependencies: {err}");
+
+    let mut response = response.deny(err);
+    response.result = Status::failure(&response.result.message, "InternalError").with_code(500);
+    response
+}
+
+/// Decide whether `record` should be admitted.
+///
+/// Records with malformed rdata, see
+/// [`RecordSpec::validate`](crate::v1alpha1::RecordSpec::validate), or a
+/// malformed routing policy are always denied. Records whose parent zone
+/// does not exist yet, or has not yet been assigned a fully qualified
+/// domain name, are admitted, since there is nothing to validate them
+/// against.
+pub async fn admit_record(client: Client, record: &Record) -> Result<Verdict, kube::Error> {
+    let namespace = record.namespace().unwrap_or_default();
+
+    let parent = find_parent(
+        client.clone(),
+        &namespace,
+        &record.spec.domain_name,
+        record.spec.zone_ref.as_ref(),
+        record.parent(),
+        None,
+    )
+    .await?;
+
+    let labels = match parent {
+        Some(_) => namespace_labels(client, &namespace).await?,
+        None => BTreeMap::new(),
+    };
+
+    Ok(record_verdict(record, parent, &labels))
+}
+
+/// Decide whether `record` should be admitted into its `parent` zone, if
+/// any, given the labels of the record's namespace.
+///
+/// Records awaiting approval are admitted, since approvals refer to stored
+/// generations of a record, and only control whether it is published.
+fn record_verdict(
+    record: &Record,
+    parent: Option<(Zone, FullyQualifiedDomainName)>,
+    labels: &BTreeMap<String, String>,
+) -> Verdict {
+    if let Err(err) = record.spec.validate() {
+        return Verdict::Deny(format!(
+            "invalid {} record {:?}: {err}",
+            record.spec.type_, record.spec.rdata
+        ));
+    }
+
+    let Some((zone, fqdn)) = parent else {
+        if record.spec.zone_ref.is_none() && record.spec.domain_name.is_partially_qualified() {
+            return Verdict::Deny(format!(
+                "record {} must either reference a zone, or use a fully qualified domain name",
+                record.spec.domain_name
+            ));
+        }
+
+        return Verdict::Admit;
+    };
+
+    let mut candidate = record.clone();
+    candidate.status = Some(RecordStatus {
+        fqdn: Some(fqdn.clone()),
+        ..Default::default()
+    });
+
+    match zone.validate_record_with_labels(&candidate, labels) {
+        Ok(()) | Err(DelegationDenied::ApprovalRequired { .. }) => Verdict::Admit,
+        Err(denied) => Verdict::Deny(format!(
+            "zone {zone} does not delegate {fqdn} ({}) to namespace {}: {denied}",
+            record.spec.type_,
+            record.namespace().unwrap_or_default()
+        )),
+    }
+}
+
+/// Decide whether `zone` should be admitted.
+///
+/// Zones failing [`ZoneSpec::validate`](crate::v1alpha1::ZoneSpec::validate),
+/// such as ones with an invalid DNSSEC configuration, are always denied. Top-level
+/// zones, and zones whose parent does not exist yet or has not yet been
+/// assigned a fully qualified domain name, are admitted.
+pub async fn admit_zone(client: Client, zone: &Zone) -> Result<Verdict, kube::Error> {
+    let namespace = zone.namespace().unwrap_or_default();
+
+    let parent = find_parent(
+        client.clone(),
+        &namespace,
+        &zone.spec.domain_name,
+        zone.spec.zone_ref.as_ref(),
+        zone.parent(),
+        Some(zone),
+    )
+    .await?;
+
+    let labels = match parent {
+        Some(_) => namespace_labels(client, &namespace).await?,
+        None => BTreeMap::new(),
+    };
+
+    Ok(zone_verdict(zone, parent, &labels))
+}
+
+/// Decide whether `zone` should be admitted into its `parent` zone, if
+/// any, given the labels of the zone's namespace.
+fn zone_verdict(
+    zone: &Zone,
+    parent: Option<(Zone, FullyQualifiedDomainName)>,
+    labels: &BTreeMap<String, String>,
+) -> Verdict {
+    if let Err(err) = zone.spec.validate() {
+        return Verdict::Deny(err.to_string());
+    }
+
+    let Some((parent, fqdn)) = parent else {
+        return Verdict::Admit;
+    };
+
+    let mut candidate = zone.clone();
+    candidate.status = Some(ZoneStatus {
+        fqdn: Some(fqdn.clone()),
+        ..Default::default()
+    });
+
+    match parent.validate_zone_with_labels(&candidate, labels) {
+        Ok(()) => Verdict::Admit,
+        Err(denied) => Verdict::Deny(format!(
+            "zone {parent} does not delegate {fqdn} to namespace {}: {denied}",
+            zone.namespace().unwrap_or_default()
+        )),
+    }
+}
+
+/// Locate the parent zone of a record or zone, along with the
+/// fully qualified domain name the object would end up with.
+///
+/// Without a `zoneRef`, the parent is the closest enclosing zone. The
+/// `hint` (the parent previously recorded on the object by the controller,
+/// see [`DomainExt::parent`]) is tried first with a single GET, so that
+/// updates to already adopted objects do not cost a LIST. Only objects
+/// without a usable hint, typically ones being created, fall back to
+/// listing every Zone in the cluster.
+async fn find_parent(
+    client: Client,
+    namespace: &str,
+    domain_name: &DomainName,
+    zone_ref: Option<&ZoneRef>,
+    hint: Option<ZoneRef>,
+    exclude: Option<&Zone>,
+) -> Result<Option<(Zone, FullyQualifiedDomainName)>, kube::Error> {
+    if let Some(zone_ref) = zone_ref {
+        let zones = Api::<Zone>::namespaced(client, zone_ref.namespace_or(namespace));
+
+        let Some(zone) = zones.get_opt(&zone_ref.name).await? else {
+            debug!("referenced zone {zone_ref} does not exist (yet)");
+            return Ok(None);
+        };
+
+        let Some(zone_fqdn) = zone.fqdn() else {
+            debug!("referenced zone {zone_ref} has no fqdn (yet)");
+            return Ok(None);
+        };
+
+        let fqdn = match domain_name {
+            DomainName::Full(full) => full.clone(),
+            DomainName::Partial(partial) => partial.with_origin(zone_fqdn),
+        };
+
+        return Ok(Some((zone, fqdn)));
+    }
+
+    let Some(fqdn) = domain_name.as_full() else {
+        return Ok(None);
+    };
+
+    if let Some(hint) = hint {
+        let zones = Api::<Zone>::namespaced(client.clone(), hint.namespace_or(namespace));
+
+        if let Some(zone) = zones.get_opt(&hint.name).await? {
+            if is_candidate_parent(&zone, fqdn, exclude) {
+                return Ok(Some((zone, fqdn.clone())));
+            }
+        }
+
+        debug!("recorded parent zone {hint} does not enclose {fqdn}, searching all zones");
+    }
+
+    let zones = Api::<Zone>::all(client)
+        .list(&ListParams::default())
+        .await?;
+
+    Ok(closest_parent(zones, fqdn, exclude).map(|parent| (parent, fqdn.clone())))
+}
+
+/// Whether `zone` could be the parent of an object named `fqdn`, that is
+/// it encloses `fqdn` and is not the `exclude`d object itself.
+fn is_candidate_parent(
+    zone: &Zone,
+    fqdn: &FullyQualifiedDomainName,
+    exclude: Option<&Zone>,
+) -> bool {
+    let excluded = exclude.is_some_and(|exclude| {
+        zone.namespace() == exclude.namespace() && zone.name_any() == exclude.name_any()
+    });
+
+    !excluded
+        && zone
+            .fqdn()
+            .is_some_and(|parent| fqdn == parent || fqdn.is_subdomain_of(parent))
+}
+
+/// Pick the closest enclosing zone of `fqdn` among `zones`.
+fn closest_parent(
+    zones: impl IntoIterator<Item = Zone>,
+    fqdn: &FullyQualifiedDomainName,
+    exclude: Option<&Zone>,
+) -> Option<Zone> {
+    zones
+        .into_iter()
+        .filter(|zone| is_candidate_parent(zone, fqdn, exclude))
+        .max_by_key(|zone| zone.fqdn().map(|fqdn| fqdn.as_ref().len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use kube::{core::admission::AdmissionResponse, error::ErrorResponse, ResourceExt};
+    use kubizone_common::{DomainName, FullyQualifiedDomainName};
+
+    use crate::{
+        test_util::{delegated_record, delegated_zone, nested_sub_zone},
+        v1alpha1::{LabelSelector, RoutingPolicy},
+    };
+
+    use super::{
+        closest_parent, internal_error, is_candidate_parent, record_verdict, zone_verdict, Verdict,
+    };
+
+    fn fqdn(name: &str) -> FullyQualifiedDomainName {
+        FullyQualifiedDomainName::try_from(name).unwrap()
+    }
+
+    #[test]
+    fn test_admit_record() {
+        let zone = delegated_zone();
+        let record = delegated_record();
+        let parent = || Some((zone.clone(), fqdn("www.example.org.")));
+
+        assert_eq!(
+            record_verdict(&record, parent(), &BTreeMap::new()),
+            Verdict::Admit
+        );
+
+        // Approval only controls publishing, and can't exist for new records.
+        let mut approving = zone.clone();
+        approving.spec.delegations[0].require_approval = true;
+        assert_eq!(
+            record_verdict(
+                &record,
+                Some((approving, fqdn("www.example.org."))),
+                &BTreeMap::new()
+            ),
+            Verdict::Admit
+        );
+
+        // Records are admitted when there is no parent to validate against.
+        assert_eq!(
+            record_verdict(&record, None, &BTreeMap::new()),
+            Verdict::Admit
+        );
+
+        let mut undelegated = record.clone();
+        undelegated.metadata.namespace = Some(String::from("other"));
+        assert!(matches!(
+            record_verdict(&undelegated, parent(), &BTreeMap::new()),
+            Verdict::Deny(_)
+        ));
+
+        let mut malformed = record.clone();
+        malformed.spec.rdata = String::from("not-an-address");
+        assert!(matches!(
+            record_verdict(&malformed, None, &BTreeMap::new()),
+            Verdict::Deny(_)
+        ));
+
+        let mut misrouted = record.clone();
+        misrouted.spec.policy = Some(RoutingPolicy {
+            subnets: vec![String::from("192.0.2.0/33")],
+            ..Default::default()
+        });
+        assert!(matches!(
+            record_verdict(&misrouted, parent(), &BTreeMap::new()),
+            Verdict::Deny(_)
+        ));
+
+        // Partially qualified names can't be placed in a zone on their own.
+        let mut partial = record;
+        partial.spec.domain_name = DomainName::try_from("www").unwrap();
+        assert!(matches!(
+            record_verdict(&partial, None, &BTreeMap::new()),
+            Verdict::Deny(_)
+        ));
+    }
+
+    #[test]
+    fn test_admit_record_namespace_selector() {
+        let mut zone = delegated_zone();
+        zone.spec.delegations[0].namespaces = vec![];
+        zone.spec.delegations[0].namespace_selector = Some(LabelSelector {
+            match_labels: BTreeMap::from([(String::from("tenant"), String::from("blue"))]),
+            match_expressions: vec![],
+        });
+
+        let record = delegated_record();
+        let parent = || Some((zone.clone(), fqdn("www.example.org.")));
+
+        let blue = BTreeMap::from([(String::from("tenant"), String::from("blue"))]);
+        assert_eq!(record_verdict(&record, parent(), &blue), Verdict::Admit);
+
+        let red = BTreeMap::from([(String::from("tenant"), String::from("red"))]);
+        assert!(matches!(
+            record_verdict(&record, parent(), &red),
+            Verdict::Deny(_)
+        ));
+    }
+
+    #[test]
+    fn test_admit_zone() {
+        let parent = delegated_zone();
+        let zone = nested_sub_zone();
+        let with_parent = || Some((parent.clone(), fqdn("sub.example.org.")));
+
+        assert_eq!(
+            zone_verdict(&zone, with_parent(), &BTreeMap::new()),
+            Verdict::Admit
+        );
+        assert_eq!(
+            zone_verdict(&parent, None, &BTreeMap::new()),
+            Verdict::Admit
+        );
+
+        let mut undelegated = zone;
+        undelegated.metadata.namespace = Some(String::from("other"));
+        let Verdict::Deny(reason) = zone_verdict(&undelegated, with_parent(), &BTreeMap::new())
+        else {
+            panic!("zone in undelegated namespace admitted");
+        };
+        assert!(reason.contains(&parent.name_any()), "{reason}");
+    }
+
+    #[test]
+    fn test_closest_parent() {
+        let parent = delegated_zone();
+        let sub = nested_sub_zone();
+        let zones = || vec![parent.clone(), sub.clone()];
+
+        let closest = |name: &str, exclude| {
+            closest_parent(zones(), &fqdn(name), exclude).map(|zone| zone.name_any())
+        };
+
+        assert_eq!(closest("www.sub.example.org.", None), Some(sub.name_any()));
+        assert_eq!(closest("www.example.org.", None), Some(parent.name_any()));
+        assert_eq!(closest("www.example.com.", None), None);
+
+        // Zones are never their own parent.
+        assert_eq!(
+            closest("sub.example.org.", Some(&sub)),
+            Some(parent.name_any())
+        );
+
+        // A recorded parent which no longer encloses the name is ignored.
+        assert!(is_candidate_parent(
+            &sub,
+            &fqdn("www.sub.example.org."),
+            None
+        ));
+        assert!(!is_candidate_parent(&sub, &fqdn("www.example.org."), None));
+    }
+
+    #[test]
+    fn test_internal_error() {
+        let mut response = AdmissionResponse::invalid("");
+        response.uid = String::from("705ab4f5-6393-11e8-b7cc-42010a800002");
+
+        let response = internal_error(
+            response,
+            kube::Error::Api(ErrorResponse {
+                status: String::from("Failure"),
+                message: String::from("etcdserver: request timed out"),
+                reason: String::from("Timeout"),
+                code: 504,
+            }),
+        );
+
+        // The uid must be kept, for the API server to match the response to its request.
+        assert_eq!(response.uid, "705ab4f5-6393-11e8-b7cc-42010a800002");
+        assert!(!response.allowed);
+        assert_eq!(response.result.code, 500);
+        assert_eq!(response.result.reason, "InternalError");
+        assert!(response.result.message.contains("request timed out"));
+    }
+}
@@ -0,0 +1,336 @@
+//! Aggregation of the entries gathered for a zone.
+//!
+//! Controllers collect entries from many records and child zones, which
+//! may well describe the same resource record more than once, or disagree
+//! on the TTL of a record set. [`merge`] reduces them to the set of entries
+//! to publish, in [canonical order](crate::canonical), and [`ZoneDiff`]
+//! describes how that set changed since the previous reconciliation.
+//!
+//! ```rust
+//! # use kubizone_crds::{v1alpha1::ZoneEntry, zone_entries};
+//! let entries: Vec<ZoneEntry> = [
+//!     "www.example.org. 300 IN A 192.0.2.2",
+//!     "www.example.org. 60 IN A 192.0.2.1",
+//!     "www.example.org. 300 IN A 192.0.2.2",
+//! ]
+//! .iter()
+//! .map(|line| line.parse().unwrap())
+//! .collect();
+//!
+//! let merged = zone_entries::merge(entries);
+//! assert_eq!(merged.len(), 2);
+//! assert!(merged.iter().all(|entry| entry.ttl == 60));
+//! ```
+
+use std::{cmp::Ordering, fmt::Display};
+
+use kubizone_common::{Class, FullyQualifiedDomainName, Type};
+use thiserror::Error;
+
+use crate::{
+    canonical::{canonical_cmp, canonical_name_cmp, metadata_cmp, record_cmp},
+    v1alpha1::ZoneEntry,
+};
+
+/// Record set whose entries were gathered with differing TTLs.
+///
+/// All records of a set must share a TTL
+/// ([RFC 2181 section 5.2](https://datatracker.ietf.org/doc/html/rfc2181#section-5.2)),
+/// so the lowest one is applied to all of them.
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("{fqdn} {class} {type_} records have differing TTLs {ttls:?}, using {ttl}")]
+pub struct TtlConflict {
+    pub fqdn: FullyQualifiedDomainName,
+    pub class: Class,
+    pub type_: Type,
+    /// Distinct TTLs found among the entries, in ascending order.
+    pub ttls: Vec<u32>,
+    /// TTL applied to every entry of the set.
+    pub ttl: u32,
+}
+
+/// Result of [`merge_flagged`].
+#[derive(Clone, Debug, Default)]
+pub struct Merged {
+    /// Deduplicated entries, in canonical order.
+    pub entries: Vec<ZoneEntry>,
+    /// Record sets whose TTLs were lowered to agree.
+    pub ttl_conflicts: Vec<TtlConflict>,
+}
+
+/// Merge `entries` into the entries to publish, discarding the record
+/// set TTL conflicts reported by [`merge_flagged`].
+pub fn merge(entries: impl IntoIterator<Item = ZoneEntry>) -> Vec<ZoneEntry> {
+    merge_flagged(entries).entries
+}
+
+/// Merge `entries` into the entries to publish.
+///
+/// Entries are grouped into record sets by name, class and type, and every
+/// entry of a set is given the lowest TTL found within it, which is
+/// reported as a [`TtlConflict`] if the set disagreed. Entries which then
+/// describe the same record, in the same views and with the same routing
+/// metadata, are deduplicated, keeping the [`source`](ZoneEntry::source)
+/// of the first one. The result is sorted canonically.
+pub fn merge_flagged(entries: impl IntoIterator<Item = ZoneEntry>) -> Merged {
+    let mut entries: Vec<ZoneEntry> = entries.into_iter().collect();
+
+    // Stable, so the first of several identical entries stays in front.
+    entries.sort_by(|a, b| canonical_cmp(a, b).then_with(|| metadata_cmp(a, b)));
+
+    let mut ttl_conflicts = Vec::new();
+    let mut start = 0;
+
+    while start < entries.len() {
+        let end = start
+            + entries[start..]
+                .iter()
+                .take_while(|entry| same_rrset(entry, &entries[start]))
+                .count();
+
+        let mut ttls: Vec<u32> = entries[start..end].iter().map(|entry| entry.ttl).collect();
+        ttls.sort_unstable();
+        ttls.dedup();
+
+        if ttls.len() > 1 {
+            let ttl = ttls[0];
+            for entry in &mut entries[start..end] {
+                entry.ttl = ttl;
+            }
+
+            ttl_conflicts.push(TtlConflict {
+                fqdn: entries[start].fqdn.clone(),
+                class: entries[start].class,
+                type_: entries[start].type_,
+                ttls,
+                ttl,
+            });
+        }
+
+        start = end;
+    }
+
+    // Lowering TTLs may have brought identical entries out of order.
+    entries.sort_by(|a, b| canonical_cmp(a, b).then_with(|| metadata_cmp(a, b)));
+    entries.dedup_by(|a, b| canonical_cmp(a, b).is_eq() && metadata_cmp(a, b).is_eq());
+
+    Merged {
+        entries,
+        ttl_conflicts,
+    }
+}
+
+/// Difference between two sets of zone entries, such as the entries of a
+/// zone before and after its serial was bumped.
+///
+/// Entries are matched up by name, class, type, data and views. Matched
+/// entries whose TTL, response policy, weight or routing policy differ are
+/// `changed`, which incremental zone transfers express as the removal of
+/// the old entry and the addition of the new one. Sources are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct ZoneDiff {
+    /// Entries only present in the new set, in canonical order.
+    pub added: Vec<ZoneEntry>,
+    /// Entries only present in the old set, in canonical order.
+    pub removed: Vec<ZoneEntry>,
+    /// Old and new versions of changed entries, in canonical order.
+    pub changed: Vec<(ZoneEntry, ZoneEntry)>,
+}
+
+impl ZoneDiff {
+    /// Compute the difference from `old` to `new`.
+    pub fn between(old: &[ZoneEntry], new: &[ZoneEntry]) -> Self {
+        let (old, new) = (sorted_by_identity(old), sorted_by_identity(new));
+        let (mut i, mut j) = (0, 0);
+        let mut diff = ZoneDiff::default();
+
+        while i < old.len() || j < new.len() {
+            let ordering = match (old.get(i), new.get(j)) {
+                (Some(a), Some(b)) => identity_cmp(a, b),
+                (Some(_), None) => Ordering::Less,
+                _ => Ordering::Greater,
+            };
+
+            match ordering {
+                Ordering::Less => {
+                    diff.removed.push(old[i].clone());
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    diff.added.push(new[j].clone());
+                    j += 1;
+                }
+                Ordering::Equal => {
+                    if is_changed(old[i], new[j]) {
+                        diff.changed.push((old[i].clone(), new[j].clone()));
+                    }
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+
+        diff
+    }
+
+    /// Check if the two sets of entries are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Short description of the difference, such as `2 added, 1 removed, 0 changed`,
+    /// for use in events and logs.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )
+    }
+}
+
+/// Lists the difference as master file lines prefixed by `-` for removed
+/// entries and `+` for added ones, with changed entries both removed and
+/// added.
+impl Display for ZoneDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for entry in &self.removed {
+            writeln!(f, "-{entry}")?;
+        }
+
+        for (old, new) in &self.changed {
+            writeln!(f, "-{old}")?;
+            writeln!(f, "+{new}")?;
+        }
+
+        for entry in &self.added {
+            writeln!(f, "+{entry}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn sorted_by_identity(entries: &[ZoneEntry]) -> Vec<&ZoneEntry> {
+    let mut entries: Vec<&ZoneEntry> = entries.iter().collect();
+    entries.sort_by(|a, b| identity_cmp(a, b));
+    entries
+}
+
+fn identity_cmp(a: &ZoneEntry, b: &ZoneEntry) -> Ordering {
+    record_cmp(a, b).then_with(|| a.views.cmp(&b.views))
+}
+
+fn is_changed(old: &ZoneEntry, new: &ZoneEntry) -> bool {
+    old.ttl != new.ttl
+        || old.response_policy != new.response_policy
+        || old.weight != new.weight
+        || old.policy != new.policy
+}
+
+fn same_rrset(a: &ZoneEntry, b: &ZoneEntry) -> bool {
+    canonical_name_cmp(&a.fqdn, &b.fqdn).is_eq() && a.class == b.class && a.type_ == b.type_
+}
+
+#[cfg(test)]
+mod tests {
+    use kubizone_common::{Class, FullyQualifiedDomainName, Type};
+
+    use crate::v1alpha1::{EntrySource, ViewRef, ZoneEntry};
+
+    use super::{merge, merge_flagged, TtlConflict, ZoneDiff};
+
+    fn view(name: &str) -> ViewRef {
+        ViewRef {
+            name: name.to_string(),
+            namespace: Some(String::from("default")),
+        }
+    }
+
+    fn entry(line: &str) -> ZoneEntry {
+        line.parse().unwrap()
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut first = entry("www.example.org. 300 IN A 192.0.2.1");
+        first.source = Some(EntrySource {
+            kind: String::from("Record"),
+            namespace: Some(String::from("web")),
+            name: String::from("www"),
+            uid: None,
+        });
+
+        let mut in_view = entry("www.example.org. 300 IN A 192.0.2.1");
+        in_view.views = vec![view("internal")];
+
+        let merged = merge_flagged([
+            entry("www.example.org. 300 IN AAAA 2001:db8::1"),
+            first.clone(),
+            in_view.clone(),
+            entry("WWW.example.org. 300 IN A 192.0.2.1"),
+            entry("www.example.org. 60 IN A 192.0.2.2"),
+            entry("example.org. 300 IN MX 10 mail.example.org."),
+        ]);
+
+        let lines: Vec<_> = merged.entries.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "example.org. 300 IN MX 10 mail.example.org.",
+                "www.example.org. 60 IN A 192.0.2.1",
+                "www.example.org. 60 IN A 192.0.2.1",
+                "www.example.org. 60 IN A 192.0.2.2",
+                "www.example.org. 300 IN AAAA 2001:db8::1",
+            ]
+        );
+        assert_eq!(merged.entries[1].source, first.source);
+        assert_eq!(merged.entries[2].views, in_view.views);
+
+        assert_eq!(
+            merged.ttl_conflicts,
+            [TtlConflict {
+                fqdn: FullyQualifiedDomainName::try_from("www.example.org.").unwrap(),
+                class: Class::IN,
+                type_: Type::A,
+                ttls: vec![60, 300],
+                ttl: 60,
+            }]
+        );
+
+        assert!(merge([]).is_empty());
+    }
+
+    #[test]
+    fn test_zone_diff() {
+        let old = [
+            entry("example.org. 300 IN MX 10 mail.example.org."),
+            entry("www.example.org. 300 IN A 192.0.2.1"),
+            entry("www.example.org. 300 IN A 192.0.2.2"),
+        ];
+        let mut new = [
+            entry("www.example.org. 60 IN A 192.0.2.2"),
+            entry("WWW.example.org. 300 IN A 192.0.2.1"),
+            entry("www.example.org. 300 IN AAAA 2001:db8::1"),
+        ];
+
+        assert!(ZoneDiff::between(&old, &old).is_empty());
+
+        let diff = ZoneDiff::between(&old, &new);
+        assert_eq!(diff.summary(), "1 added, 1 removed, 1 changed");
+        assert_eq!(
+            diff.to_string(),
+            "-example.org. 300 IN MX 10 mail.example.org.\n\
+             -www.example.org. 300 IN A 192.0.2.2\n\
+             +www.example.org. 60 IN A 192.0.2.2\n\
+             +www.example.org. 300 IN AAAA 2001:db8::1\n"
+        );
+
+        // Entries in different views are different entries.
+        new[1].views = vec![view("internal")];
+        let diff = ZoneDiff::between(&old, &new);
+        assert_eq!(diff.added.len(), 2);
+        assert_eq!(diff.removed.len(), 2);
+    }
+}
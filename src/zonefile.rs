@@ -0,0 +1,983 @@
+//! Rendering and parsing of [RFC 1035](https://datatracker.ietf.org/doc/html/rfc1035#section-5)
+//! master files.
+//!
+//! [`render_zonefile`] produces a complete master file for a zone, while
+//! [`parse`] converts existing master files, such as ones exported from
+//! BIND, into [`RecordSpec`]s for bulk conversion into Record resources.
+//! Individual [`RecordSpec`]s and [`ZoneEntry`]s can be formatted as, and
+//! parsed from, single lines through `Display` and `FromStr`.
+//!
+//! ```
+//! # use kubizone_crds::zonefile::render_zonefile;
+//! # use kubizone_crds::v1alpha1::{Zone, ZoneSpec, ZoneStatus};
+//! # use kubizone_crds::kubizone_common::{DomainName, FullyQualifiedDomainName};
+//! let mut zone = Zone::new("example-org", ZoneSpec {
+//!     domain_name: DomainName::try_from("example.org.").unwrap(),
+//!     ..Default::default()
+//! });
+//! zone.status = Some(ZoneStatus {
+//!     fqdn: Some(FullyQualifiedDomainName::try_from("example.org.").unwrap()),
+//!     serial: Some(2024030501),
+//!     ..Default::default()
+//! });
+//!
+//! let zonefile = render_zonefile(&zone).unwrap();
+//! assert!(zonefile.starts_with("$ORIGIN example.org.\n"));
+//! ```
+
+use std::{
+    fmt::{Display, Write},
+    str::FromStr,
+};
+
+use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, Type};
+use serde::{de::IntoDeserializer, Deserialize};
+use thiserror::Error;
+
+use crate::{
+    canonical::{class_from_code, type_from_code},
+    domain_name::parse_domain_name,
+    errors::RenderError,
+    rdata::{split_txt, RData, RDataError},
+    v1alpha1::{defaults, RecordSpec, Zone, ZoneEntry},
+};
+
+/// Produced when a master file cannot be parsed.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("line {line}: {kind}")]
+pub struct ParseError {
+    /// Line on which the offending record or directive starts.
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    #[error("unterminated quoted string")]
+    UnterminatedString,
+    #[error("unbalanced parentheses")]
+    UnbalancedParentheses,
+    #[error("unsupported directive {0}")]
+    UnsupportedDirective(String),
+    #[error("missing argument to {0}")]
+    MissingArgument(String),
+    #[error("record has no owner name")]
+    MissingOwner,
+    #[error("record has no type")]
+    MissingType,
+    #[error("unknown record type {0}")]
+    UnknownType(String),
+    #[error("record has no data")]
+    MissingRdata,
+    #[error("invalid ttl {0}")]
+    InvalidTtl(String),
+    #[error("invalid domain name {name}: {reason}")]
+    InvalidDomainName { name: String, reason: String },
+    #[error("relative name {0} used without an $ORIGIN")]
+    MissingOrigin(String),
+    #[error("{0}")]
+    InvalidRdata(#[from] RDataError),
+    #[error("record has no ttl")]
+    MissingTtl,
+    #[error("expected a single record, found {0}")]
+    ExpectedSingleRecord(usize),
+}
+
+/// Render a complete master file for the zone.
+///
+/// The file starts with `$ORIGIN` and `$TTL` directives, followed by the
+/// zone's SOA record, its generated NS records (see [`Zone::ns_entries`]),
+/// and finally the entries of its status. Owner names of entries are always
+/// written fully qualified.
+///
+/// Fails if the zone has not yet been assigned a fully qualified domain
+/// name or serial, or if its entries have been moved into
+/// [`ZoneData`](crate::v1alpha1::ZoneData) and must be joined first.
+///
+/// Missing SOA fields are filled in as follows:
+///
+/// * `MNAME` falls back to the first of the zone's `nameservers`, and
+///   then to the zone's own apex.
+/// * `RNAME` falls back to `hostmaster` at the zone's apex, as recommended by
+///   [RFC 2142](https://datatracker.ietf.org/doc/html/rfc2142#section-7).
+pub fn render_zonefile(zone: &Zone) -> Result<String, RenderError> {
+    let Some(status) = zone.status.as_ref() else {
+        return Err(RenderError::MissingFqdn);
+    };
+
+    if let Some(data_ref) = &status.data_ref {
+        return Err(RenderError::EntriesInZoneData(data_ref.shards.join(", ")));
+    }
+
+    let Some(fqdn) = &status.fqdn else {
+        return Err(RenderError::MissingFqdn);
+    };
+
+    let Some(serial) = status.serial else {
+        return Err(RenderError::MissingSerial);
+    };
+
+    let mut out = String::new();
+
+    // Unwrap safety: Writing to a String cannot fail.
+    write_zonefile(&mut out, zone, fqdn, serial).unwrap();
+
+    Ok(out)
+}
+
+fn write_zonefile<W: Write>(
+    f: &mut W,
+    zone: &Zone,
+    fqdn: &FullyQualifiedDomainName,
+    serial: u32,
+) -> std::fmt::Result {
+    writeln!(f, "$ORIGIN {fqdn}")?;
+    writeln!(f, "$TTL {}", zone.spec.ttl)?;
+
+    write_entry(f, &soa_entry(zone, fqdn, serial))?;
+    f.write_char('\n')?;
+
+    let entries = zone
+        .status
+        .as_ref()
+        .map(|status| status.entries.as_slice())
+        .unwrap_or_default();
+
+    let ns_entries = zone.ns_entries();
+    for entry in ns_entries.iter().chain(entries) {
+        write_entry(f, entry)?;
+        f.write_char('\n')?;
+    }
+
+    Ok(())
+}
+
+/// SOA record published at the apex `fqdn` of `zone`, with fallbacks for
+/// missing fields as described on [`render_zonefile`].
+///
+/// Shared with [`zone_digest`](crate::zonemd::zone_digest), so that the
+/// digest covers exactly the SOA record of the rendered zonefile.
+pub(crate) fn soa_entry(zone: &Zone, fqdn: &FullyQualifiedDomainName, serial: u32) -> ZoneEntry {
+    let mname = zone
+        .spec
+        .mname
+        .as_ref()
+        .or(zone.spec.nameservers.first())
+        .unwrap_or(fqdn);
+
+    let rname = zone
+        .spec
+        .rname
+        .as_ref()
+        .map(ToString::to_string)
+        .unwrap_or_else(|| format!("hostmaster.{fqdn}"));
+
+    ZoneEntry {
+        fqdn: fqdn.clone(),
+        type_: Type::SOA,
+        class: defaults::CLASS,
+        ttl: zone.spec.ttl,
+        rdata: format!(
+            "{mname} {rname} {serial} {} {} {} {}",
+            zone.spec.refresh, zone.spec.retry, zone.spec.expire, zone.spec.negative_response_cache
+        ),
+        response_policy: None,
+        weight: None,
+        policy: None,
+        views: Vec::new(),
+        source: None,
+    }
+}
+
+/// Write a single entry as a master file line, without the trailing newline.
+pub(crate) fn write_entry<W: Write>(f: &mut W, entry: &ZoneEntry) -> std::fmt::Result {
+    write!(
+        f,
+        "{} {} {} {} ",
+        entry.fqdn, entry.ttl, entry.class, entry.type_
+    )?;
+
+    write_rdata(f, entry.type_, &entry.rdata)
+}
+
+/// Write record data, quoting TXT data which was written without quotes,
+/// so that it is published as a single value.
+///
+/// TXT strings longer than 255 octets are split into several
+/// character-strings, which would otherwise produce an invalid zonefile.
+pub(crate) fn write_rdata<W: Write>(f: &mut W, type_: Type, rdata: &str) -> std::fmt::Result {
+    let rdata = rdata.trim();
+
+    if type_ != Type::TXT {
+        return f.write_str(rdata);
+    }
+
+    if !rdata.starts_with('"') {
+        return write!(f, "{}", RData::txt(rdata));
+    }
+
+    match RData::parse(type_, rdata) {
+        Ok(RData::TXT(strings)) => write!(f, "{}", RData::TXT(split_txt(&strings))),
+        Ok(parsed) => write!(f, "{parsed}"),
+        Err(_) => f.write_str(rdata),
+    }
+}
+
+/// Parse a master file into record specs.
+///
+/// Supports the `$ORIGIN` and `$TTL` directives, `@`, relative owner names
+/// and owners inherited from the previous record, records spanning multiple
+/// lines using parentheses, comments, and TTLs with BIND-style unit
+/// suffixes such as `1h30m`.
+///
+/// Relative names, both owners and domain names within the record data of
+/// `CNAME`, `DNAME`, `NS`, `PTR`, `MX` and `SRV` records, are qualified
+/// using the current `$ORIGIN`. Without an origin, owner names are left
+/// partially qualified, for use with a `zoneRef`.
+///
+/// Records without an explicit TTL use the one set by `$TTL`, if any, and
+/// otherwise inherit the TTL of their zone. `SOA` records are skipped,
+/// since they are generated from the zone itself.
+///
+/// Record data is checked with [`RData::validate`], with or without an origin.
+pub fn parse(input: &str) -> Result<Vec<RecordSpec>, ParseError> {
+    Ok(parse_numbered(input)?
+        .into_iter()
+        .map(|(_, record)| record)
+        .collect())
+}
+
+/// Like [`parse`], but also returns the line number each record starts on.
+fn parse_numbered(input: &str) -> Result<Vec<(usize, RecordSpec)>, ParseError> {
+    let mut origin: Option<FullyQualifiedDomainName> = None;
+    let mut default_ttl = None;
+    let mut last_owner = None;
+    let mut last_class = None;
+    let mut records = Vec::new();
+
+    for line in logical_lines(input)? {
+        let error = |kind| ParseError {
+            line: line.number,
+            kind,
+        };
+
+        let mut tokens = line.tokens.iter().map(String::as_str).peekable();
+
+        if !line.indented {
+            if let Some(directive) = tokens.next_if(|token| token.starts_with('$')) {
+                let argument = tokens
+                    .next()
+                    .ok_or_else(|| error(ParseErrorKind::MissingArgument(directive.to_string())))?;
+
+                match directive.to_ascii_uppercase().as_str() {
+                    "$ORIGIN" => {
+                        origin = match resolve_name(argument, origin.as_ref()).map_err(error)? {
+                            DomainName::Full(full) => Some(full),
+                            DomainName::Partial(_) => {
+                                return Err(error(ParseErrorKind::MissingOrigin(
+                                    argument.to_string(),
+                                )))
+                            }
+                        }
+                    }
+                    "$TTL" => default_ttl = Some(parse_ttl(argument).map_err(error)?),
+                    _ => {
+                        return Err(error(ParseErrorKind::UnsupportedDirective(
+                            directive.to_string(),
+                        )))
+                    }
+                }
+
+                continue;
+            }
+        }
+
+        let owner = if line.indented {
+            last_owner
+                .clone()
+                .ok_or_else(|| error(ParseErrorKind::MissingOwner))?
+        } else {
+            // Unwrap safety: Logical lines always contain at least one token.
+            resolve_name(tokens.next().unwrap(), origin.as_ref()).map_err(error)?
+        };
+
+        let mut ttl = None;
+        let mut class = None;
+        while let Some(token) = tokens.peek() {
+            if ttl.is_none() && token.starts_with(|c: char| c.is_ascii_digit()) {
+                ttl = Some(parse_ttl(token).map_err(error)?);
+            } else if let (None, Some(parsed)) = (class, parse_class(token)) {
+                class = Some(parsed);
+            } else {
+                break;
+            }
+
+            tokens.next();
+        }
+
+        let type_token = tokens
+            .next()
+            .ok_or_else(|| error(ParseErrorKind::MissingType))?;
+        let type_ = parse_type(type_token)
+            .ok_or_else(|| error(ParseErrorKind::UnknownType(type_token.to_string())))?;
+
+        let rdata: Vec<&str> = tokens.collect();
+        if rdata.is_empty() {
+            return Err(error(ParseErrorKind::MissingRdata));
+        }
+
+        last_owner = Some(owner.clone());
+        last_class = class.or(last_class);
+
+        if type_ == Type::SOA {
+            continue;
+        }
+
+        records.push((
+            line.number,
+            RecordSpec {
+                domain_name: owner,
+                type_,
+                class: last_class.unwrap_or(defaults::CLASS),
+                ttl: ttl.or(default_ttl),
+                rdata: qualify_rdata(type_, &rdata, origin.as_ref()).map_err(error)?,
+                ..Default::default()
+            },
+        ));
+    }
+
+    Ok(records)
+}
+
+/// Parse an input which must contain exactly one record, returning the
+/// line number it starts on along with the record.
+fn parse_single(input: &str) -> Result<(usize, RecordSpec), ParseError> {
+    let mut records = parse_numbered(input)?;
+
+    if records.len() != 1 {
+        // Point at the first superfluous record, or the end of the input
+        // if there are none at all.
+        let line = records
+            .get(1)
+            .map_or_else(|| input.lines().count().max(1), |(line, _)| *line);
+
+        return Err(ParseError {
+            line,
+            kind: ParseErrorKind::ExpectedSingleRecord(records.len()),
+        });
+    }
+
+    // Unwrap safety: Length is checked above.
+    Ok(records.pop().unwrap())
+}
+
+/// Formats the entry as a single master file line.
+impl Display for ZoneEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_entry(f, self)
+    }
+}
+
+/// Parses a single master file line, which must have a fully qualified
+/// owner name and an explicit TTL.
+impl FromStr for ZoneEntry {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (line, spec) = parse_single(s)?;
+
+        let error = |kind| ParseError { line, kind };
+
+        Ok(ZoneEntry {
+            fqdn: spec.domain_name.as_full().cloned().ok_or_else(|| {
+                error(ParseErrorKind::MissingOrigin(spec.domain_name.to_string()))
+            })?,
+            ttl: spec.ttl.ok_or_else(|| error(ParseErrorKind::MissingTtl))?,
+            type_: spec.type_,
+            class: spec.class,
+            rdata: spec.rdata,
+            response_policy: None,
+            weight: None,
+            policy: None,
+            views: Vec::new(),
+            source: None,
+        })
+    }
+}
+
+/// Formats the spec as a single master file line. The TTL is left out
+/// if the record inherits the TTL of its zone.
+impl Display for RecordSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ", self.domain_name)?;
+        if let Some(ttl) = self.ttl {
+            write!(f, "{ttl} ")?;
+        }
+        write!(f, "{} {} ", self.class, self.type_)?;
+
+        write_rdata(f, self.type_, &self.rdata)
+    }
+}
+
+/// Parses a single master file record, see [`parse`].
+impl FromStr for RecordSpec {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_single(s).map(|(_, record)| record)
+    }
+}
+
+/// Record or directive, after joining parenthesized continuation lines
+/// and removing comments.
+struct Line {
+    number: usize,
+    /// Lines starting with whitespace inherit the owner of the previous record.
+    indented: bool,
+    /// Whitespace separated tokens, with quotes and escapes left intact.
+    tokens: Vec<String>,
+}
+
+fn logical_lines(input: &str) -> Result<Vec<Line>, ParseError> {
+    let mut lines = Vec::new();
+    let mut number = 1;
+    let mut line = Line {
+        number,
+        indented: input.starts_with([' ', '\t']),
+        tokens: Vec::new(),
+    };
+    let mut token = String::new();
+    let mut depth = 0usize;
+    let mut quoted = false;
+
+    let error = |line: usize, kind| ParseError { line, kind };
+
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                match chars.next() {
+                    Some('\n') | None if quoted => {
+                        return Err(error(line.number, ParseErrorKind::UnterminatedString))
+                    }
+                    // Escaped newlines continue the logical line.
+                    Some('\n') => number += 1,
+                    Some(escaped) => {
+                        token.push(c);
+                        token.push(escaped);
+                    }
+                    None => token.push(c),
+                }
+            }
+            '"' => {
+                token.push(c);
+                quoted = !quoted;
+            }
+            '\n' if quoted => return Err(error(line.number, ParseErrorKind::UnterminatedString)),
+            _ if quoted => token.push(c),
+            ';' => while chars.next_if(|c| *c != '\n').is_some() {},
+            '(' => depth += 1,
+            ')' => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or_else(|| error(number, ParseErrorKind::UnbalancedParentheses))?;
+            }
+            '\n' => {
+                number += 1;
+                if depth == 0 {
+                    flush(&mut token, &mut line.tokens);
+
+                    let next = Line {
+                        number,
+                        indented: chars.peek().is_some_and(|c| *c == ' ' || *c == '\t'),
+                        tokens: Vec::new(),
+                    };
+
+                    let finished = std::mem::replace(&mut line, next);
+                    if !finished.tokens.is_empty() {
+                        lines.push(finished);
+                    }
+                }
+            }
+            _ if c.is_whitespace() => {}
+            _ => {
+                token.push(c);
+                continue;
+            }
+        }
+
+        // Everything except regular characters ends the current token,
+        // but quotes and escapes are part of it.
+        if !quoted && !matches!(c, '"' | '\\') {
+            flush(&mut token, &mut line.tokens);
+        }
+    }
+
+    if quoted {
+        return Err(error(line.number, ParseErrorKind::UnterminatedString));
+    }
+
+    if depth > 0 {
+        return Err(error(line.number, ParseErrorKind::UnbalancedParentheses));
+    }
+
+    flush(&mut token, &mut line.tokens);
+    if !line.tokens.is_empty() {
+        lines.push(line);
+    }
+
+    Ok(lines)
+}
+
+fn flush(token: &mut String, tokens: &mut Vec<String>) {
+    if !token.is_empty() {
+        tokens.push(std::mem::take(token));
+    }
+}
+
+fn resolve_name(
+    name: &str,
+    origin: Option<&FullyQualifiedDomainName>,
+) -> Result<DomainName, ParseErrorKind> {
+    if name == "@" {
+        return origin
+            .cloned()
+            .map(DomainName::Full)
+            .ok_or_else(|| ParseErrorKind::MissingOrigin(name.to_string()));
+    }
+
+    let domain = parse_domain_name(name).map_err(|err| ParseErrorKind::InvalidDomainName {
+        name: name.to_string(),
+        reason: err.to_string(),
+    })?;
+
+    Ok(match (domain, origin) {
+        (DomainName::Partial(partial), Some(origin)) => {
+            DomainName::Full(partial.with_origin(origin))
+        }
+        (domain, _) => domain,
+    })
+}
+
+/// Parse a TTL, either as plain seconds or with unit suffixes (`s`, `m`,
+/// `h`, `d`, `w`), as accepted by BIND.
+fn parse_ttl(value: &str) -> Result<u32, ParseErrorKind> {
+    let invalid = || ParseErrorKind::InvalidTtl(value.to_string());
+
+    if let Ok(seconds) = value.parse() {
+        return Ok(seconds);
+    }
+
+    let mut total: u32 = 0;
+    let mut digits = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+
+        let unit = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            'w' => 604800,
+            _ => return Err(invalid()),
+        };
+
+        let amount: u32 = std::mem::take(&mut digits).parse().map_err(|_| invalid())?;
+        total = amount
+            .checked_mul(unit)
+            .and_then(|seconds| total.checked_add(seconds))
+            .ok_or_else(invalid)?;
+    }
+
+    if !digits.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(total)
+}
+
+/// Parse a class mnemonic case-insensitively, also accepting the generic
+/// `CLASS<n>` notation of RFC 3597 for known classes.
+pub(crate) fn parse_class(value: &str) -> Option<Class> {
+    let value = value.to_ascii_uppercase();
+    if let Some(code) = value.strip_prefix("CLASS") {
+        return class_from_code(code.parse().ok()?);
+    }
+
+    Class::deserialize(value.into_deserializer())
+        .map_err(|_: serde::de::value::Error| ())
+        .ok()
+}
+
+/// Parse a type mnemonic case-insensitively, also accepting the generic
+/// `TYPE<n>` notation of RFC 3597 for known types.
+pub(crate) fn parse_type(value: &str) -> Option<Type> {
+    let value = value.to_ascii_uppercase();
+    if let Some(code) = value.strip_prefix("TYPE") {
+        return type_from_code(code.parse().ok()?);
+    }
+
+    Type::deserialize(value.into_deserializer())
+        .map_err(|_: serde::de::value::Error| ())
+        .ok()
+}
+
+/// Qualify relative domain names within record data using the origin, and
+/// validate the result.
+fn qualify_rdata(
+    type_: Type,
+    tokens: &[&str],
+    origin: Option<&FullyQualifiedDomainName>,
+) -> Result<String, ParseErrorKind> {
+    let rdata = tokens.join(" ");
+
+    let Some(origin) = origin else {
+        RData::validate(type_, &rdata)?;
+        return Ok(rdata);
+    };
+
+    let qualify = |name: DomainName| match name {
+        DomainName::Partial(partial) => DomainName::Full(partial.with_origin(origin)),
+        full => full,
+    };
+
+    let rdata = tokens
+        .iter()
+        .map(|token| {
+            if *token == "@" {
+                origin.to_string()
+            } else {
+                token.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let parsed = match RData::parse(type_, &rdata)? {
+        RData::CNAME(name) => RData::CNAME(qualify(name)),
+        RData::DNAME(name) => RData::DNAME(qualify(name)),
+        RData::NS(name) => RData::NS(qualify(name)),
+        RData::PTR(name) => RData::PTR(qualify(name)),
+        RData::MX {
+            preference,
+            exchange,
+        } => RData::MX {
+            preference,
+            exchange: qualify(exchange),
+        },
+        RData::SRV {
+            priority,
+            weight,
+            port,
+            target,
+        } => RData::SRV {
+            priority,
+            weight,
+            port,
+            target: qualify(target),
+        },
+        _ => {
+            RData::validate(type_, &rdata)?;
+            return Ok(rdata);
+        }
+    };
+
+    let rdata = parsed.to_string();
+    RData::validate(type_, &rdata)?;
+    Ok(rdata)
+}
+
+#[cfg(test)]
+mod tests {
+    use kubizone_common::{Class, DomainName, FullyQualifiedDomainName, Type};
+
+    use crate::v1alpha1::{RecordSpec, Rname, Zone, ZoneEntry, ZoneSpec, ZoneStatus};
+
+    use crate::{errors::RenderError, rdata::RDataError};
+
+    use super::{parse, render_zonefile, ParseError, ParseErrorKind};
+
+    fn entry(name: &str, type_: Type, rdata: &str) -> ZoneEntry {
+        ZoneEntry {
+            fqdn: FullyQualifiedDomainName::try_from(name).unwrap(),
+            type_,
+            class: Class::IN,
+            ttl: 300,
+            rdata: rdata.to_string(),
+            response_policy: None,
+            weight: None,
+            policy: None,
+            views: Vec::new(),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn test_render_zonefile() {
+        let mut zone = Zone::new(
+            "example-org",
+            ZoneSpec {
+                domain_name: DomainName::try_from("example.org.").unwrap(),
+                mname: Some(FullyQualifiedDomainName::try_from("ns1.example.org.").unwrap()),
+                rname: Some(Rname::try_from("admin@example.org").unwrap()),
+                nameservers: vec![FullyQualifiedDomainName::try_from("ns1.example.org.").unwrap()],
+                generate_ns_records: true,
+                ttl: 3600,
+                refresh: 86400,
+                retry: 7200,
+                expire: 3600000,
+                negative_response_cache: 360,
+                ..Default::default()
+            },
+        );
+
+        zone.status = Some(ZoneStatus {
+            fqdn: Some(FullyQualifiedDomainName::try_from("example.org.").unwrap()),
+            serial: Some(2024030501),
+            entries: vec![
+                entry("www.example.org.", Type::A, "192.168.0.1"),
+                entry("example.org.", Type::TXT, "v=spf1 -all"),
+                entry("example.org.", Type::TXT, r#""quote \" here"  "second""#),
+            ],
+            ..Default::default()
+        });
+
+        assert_eq!(
+            render_zonefile(&zone).unwrap(),
+            concat!(
+                "$ORIGIN example.org.\n",
+                "$TTL 3600\n",
+                "example.org. 3600 IN SOA ns1.example.org. admin.example.org. 2024030501 86400 7200 3600000 360\n",
+                "example.org. 3600 IN NS ns1.example.org.\n",
+                "www.example.org. 300 IN A 192.168.0.1\n",
+                "example.org. 300 IN TXT \"v=spf1 -all\"\n",
+                "example.org. 300 IN TXT \"quote \\\" here\" \"second\"\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_render_zonefile_defaults() {
+        let mut zone = Zone::new(
+            "example-org",
+            ZoneSpec {
+                domain_name: DomainName::try_from("example.org.").unwrap(),
+                ttl: 360,
+                ..Default::default()
+            },
+        );
+        assert_eq!(render_zonefile(&zone), Err(RenderError::MissingFqdn));
+
+        zone.status = Some(ZoneStatus {
+            fqdn: Some(FullyQualifiedDomainName::try_from("example.org.").unwrap()),
+            ..Default::default()
+        });
+        assert_eq!(render_zonefile(&zone), Err(RenderError::MissingSerial));
+
+        zone.status.as_mut().unwrap().serial = Some(2024030501);
+        let zonefile = render_zonefile(&zone).unwrap();
+        assert!(zonefile.starts_with("$ORIGIN example.org.\n"));
+        assert!(zonefile
+            .contains("example.org. 360 IN SOA example.org. hostmaster.example.org. 2024030501 "));
+
+        zone.metadata.uid = Some(String::from("1234"));
+        zone.status.as_mut().unwrap().entries =
+            vec!["www.example.org. 300 IN A 192.0.2.1".parse().unwrap()];
+        zone.split_entries(usize::MAX).unwrap();
+        assert_eq!(
+            render_zonefile(&zone),
+            Err(RenderError::EntriesInZoneData(String::from(
+                "example-org-0"
+            )))
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let records = parse(
+            r#"$ORIGIN example.org.
+$TTL 1h
+@   IN  SOA ns1 hostmaster (
+            2024030501 ; serial
+            86400 7200 3600000 360 )
+    IN  NS  ns1
+    IN  MX  10 mail
+www 300 IN A 192.168.0.1
+        AAAA 2001:db8::1 ; same owner
+mail    CNAME www
+txt     TXT "v=spf1 -all" "semi;colon"
+ftp.example.net. 1d CNAME @
+"#,
+        )
+        .unwrap();
+
+        let lines: Vec<String> = records
+            .iter()
+            .map(|record| {
+                format!(
+                    "{} {:?} {} {} {}",
+                    record.domain_name, record.ttl, record.class, record.type_, record.rdata
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "example.org. Some(3600) IN NS ns1.example.org.",
+                "example.org. Some(3600) IN MX 10 mail.example.org.",
+                "www.example.org. Some(300) IN A 192.168.0.1",
+                "www.example.org. Some(3600) IN AAAA 2001:db8::1",
+                "mail.example.org. Some(3600) IN CNAME www.example.org.",
+                r#"txt.example.org. Some(3600) IN TXT "v=spf1 -all" "semi;colon""#,
+                "ftp.example.net. Some(86400) IN CNAME example.org.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_without_origin() {
+        let records = parse("www A 192.168.0.1\n").unwrap();
+        assert_eq!(records[0].domain_name, DomainName::try_from("www").unwrap());
+        assert_eq!(records[0].ttl, None);
+
+        assert_eq!(
+            parse("@ A 192.168.0.1"),
+            Err(ParseError {
+                line: 1,
+                kind: ParseErrorKind::MissingOrigin(String::from("@"))
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let kind = |input: &str| parse(input).unwrap_err().kind;
+
+        assert_eq!(
+            kind("www TXT \"unterminated"),
+            ParseErrorKind::UnterminatedString
+        );
+        assert_eq!(
+            kind("www A ( 192.168.0.1"),
+            ParseErrorKind::UnbalancedParentheses
+        );
+        assert_eq!(
+            kind("$INCLUDE other.zone"),
+            ParseErrorKind::UnsupportedDirective(String::from("$INCLUDE"))
+        );
+        assert_eq!(kind("www 300 IN"), ParseErrorKind::MissingType);
+        assert_eq!(
+            kind("www BOGUS data"),
+            ParseErrorKind::UnknownType(String::from("BOGUS"))
+        );
+        assert_eq!(
+            kind("www 1x A 192.168.0.1"),
+            ParseErrorKind::InvalidTtl(String::from("1x"))
+        );
+        assert_eq!(kind("  A 192.168.0.1"), ParseErrorKind::MissingOwner);
+        assert!(matches!(
+            kind("www A 192.0.2"),
+            ParseErrorKind::InvalidRdata(RDataError::InvalidIpv4Address(_))
+        ));
+        assert!(matches!(
+            kind("$ORIGIN example.org.\nwww A 192.0.2"),
+            ParseErrorKind::InvalidRdata(RDataError::InvalidIpv4Address(_))
+        ));
+        assert!(matches!(
+            kind("www CAA 0 issue \"not a domain\""),
+            ParseErrorKind::InvalidRdata(RDataError::InvalidCaaValue { .. })
+        ));
+        assert!(matches!(
+            kind("-x 300 IN A 192.0.2.1\n"),
+            ParseErrorKind::InvalidDomainName { name, .. } if name == "-x"
+        ));
+
+        assert_eq!(parse("\n\nwww A").unwrap_err().line, 3);
+    }
+
+    #[test]
+    fn test_line_roundtrip() {
+        let entry: ZoneEntry = r#"txt.example.org. 300 IN TXT "quote \" here" "second""#
+            .parse()
+            .unwrap();
+        assert_eq!(entry.type_, Type::TXT);
+        assert_eq!(entry.ttl, 300);
+        assert_eq!(
+            entry.to_string(),
+            r#"txt.example.org. 300 IN TXT "quote \" here" "second""#
+        );
+
+        assert_eq!(
+            "www 300 IN A 192.168.0.1"
+                .parse::<ZoneEntry>()
+                .unwrap_err()
+                .kind,
+            ParseErrorKind::MissingOrigin(String::from("www"))
+        );
+        assert_eq!(
+            "www.example.org. A 192.168.0.1"
+                .parse::<ZoneEntry>()
+                .unwrap_err()
+                .kind,
+            ParseErrorKind::MissingTtl
+        );
+
+        let spec: RecordSpec = "www CNAME web.example.org.".parse().unwrap();
+        assert_eq!(spec.to_string(), "www IN CNAME web.example.org.");
+        assert_eq!(spec.to_string().parse::<RecordSpec>().unwrap(), spec);
+
+        // Unquoted TXT data is rendered as a single string.
+        let spec = RecordSpec {
+            domain_name: DomainName::try_from("txt").unwrap(),
+            type_: Type::TXT,
+            ttl: Some(60),
+            rdata: String::from("v=spf1 -all"),
+            ..Default::default()
+        };
+        assert_eq!(spec.to_string(), r#"txt 60 IN TXT "v=spf1 -all""#);
+
+        // Strings too long for a single character-string are split.
+        let spec = RecordSpec {
+            rdata: format!(r#""{}" "b""#, "a".repeat(300)),
+            ..spec
+        };
+        assert_eq!(
+            spec.to_string(),
+            format!(
+                r#"txt 60 IN TXT "{}" "{}" "b""#,
+                "a".repeat(255),
+                "a".repeat(45)
+            )
+        );
+
+        assert_eq!(
+            "www A 192.168.0.1\nweb A 192.168.0.2".parse::<RecordSpec>(),
+            Err(ParseError {
+                line: 2,
+                kind: ParseErrorKind::ExpectedSingleRecord(2)
+            })
+        );
+
+        // Errors point at the record, not the first line of the input.
+        assert_eq!(
+            "; comment\n\nwww.example.org. A 192.168.0.1"
+                .parse::<ZoneEntry>()
+                .unwrap_err(),
+            ParseError {
+                line: 3,
+                kind: ParseErrorKind::MissingTtl
+            }
+        );
+        assert_eq!(
+            "; comment\n".parse::<RecordSpec>(),
+            Err(ParseError {
+                line: 1,
+                kind: ParseErrorKind::ExpectedSingleRecord(0)
+            })
+        );
+    }
+}
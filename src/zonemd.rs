@@ -0,0 +1,224 @@
+//! [RFC 8976](https://datatracker.ietf.org/doc/html/rfc8976) message digests
+//! of zones, using the `SIMPLE` scheme.
+//!
+//! Records are digested in their canonical wire format, which is produced
+//! through hickory-dns. The same digest function is used for computing the
+//! [`Zonemd`] published in a zone's status, and for verifying the records a
+//! secondary received against it, see [`verify`].
+
+use std::collections::BTreeMap;
+
+use hickory_proto::{
+    error::ProtoError,
+    rr::{Name, Record, RecordType},
+    serialize::binary::{BinEncodable, BinEncoder},
+};
+use sha2::{Digest, Sha384, Sha512};
+use thiserror::Error;
+
+use crate::{
+    hickory::HickoryError,
+    v1alpha1::{hex, DomainExt, Zone, Zonemd, ZonemdHashAlgorithm},
+    zonefile::soa_entry,
+};
+
+const ZONEMD: u16 = 63;
+
+#[derive(Error, Debug)]
+pub enum ZonemdError {
+    #[error("zone has no fully qualified domain name yet")]
+    MissingFqdn,
+    #[error("zone has no serial yet")]
+    MissingSerial,
+    #[error("invalid zone origin {name}: {source}")]
+    InvalidOrigin { name: String, source: ProtoError },
+    #[error(transparent)]
+    Record(#[from] HickoryError),
+    #[error("failed to encode record: {0}")]
+    Encoding(#[from] ProtoError),
+}
+
+/// Compute the digest of the records of the zone at `origin`.
+///
+/// Records outside of the zone, duplicate records, and the apex `ZONEMD`
+/// records and their signatures are ignored, so the order of `records`
+/// does not matter.
+pub fn digest(
+    origin: &Name,
+    records: &[Record],
+    algorithm: ZonemdHashAlgorithm,
+) -> Result<Vec<u8>, ProtoError> {
+    // Canonical RR order: owner name, type, then record data.
+    let mut canonical = BTreeMap::new();
+
+    for record in records {
+        if !origin.zone_of(record.name()) {
+            continue;
+        }
+
+        let mut rdata = Vec::new();
+        if let Some(data) = record.data() {
+            let mut encoder = BinEncoder::new(&mut rdata);
+            encoder.set_canonical_names(true);
+            data.emit(&mut encoder)?;
+        }
+
+        let type_ = u16::from(record.record_type());
+        if record.name() == origin && is_zonemd(type_, &rdata) {
+            continue;
+        }
+
+        canonical.insert((record.name().to_lowercase(), type_, rdata), record);
+    }
+
+    let mut buffer = Vec::new();
+    let mut encoder = BinEncoder::new(&mut buffer);
+    encoder.set_canonical_names(true);
+
+    for ((name, _, _), record) in canonical {
+        let mut record = record.clone();
+        record.set_name(name);
+        record.emit(&mut encoder)?;
+    }
+
+    Ok(match algorithm {
+        ZonemdHashAlgorithm::Sha384 => Sha384::digest(&buffer).to_vec(),
+        ZonemdHashAlgorithm::Sha512 => Sha512::digest(&buffer).to_vec(),
+    })
+}
+
+/// ZONEMD records, and signatures covering them, are excluded from the digest.
+fn is_zonemd(type_: u16, rdata: &[u8]) -> bool {
+    type_ == ZONEMD
+        || (type_ == u16::from(RecordType::RRSIG)
+            && rdata.get(..2) == Some(ZONEMD.to_be_bytes().as_slice()))
+}
+
+/// Check that the records received for the zone at `origin` match `expected`.
+pub fn verify(origin: &Name, records: &[Record], expected: &Zonemd) -> Result<bool, ProtoError> {
+    Ok(hex(&digest(origin, records, expected.hash_algorithm)?)
+        .eq_ignore_ascii_case(&expected.digest))
+}
+
+/// Compute the digest of `zone`, as rendered by
+/// [`render_zonefile`](crate::zonefile::render_zonefile).
+pub fn zone_digest(zone: &Zone, algorithm: ZonemdHashAlgorithm) -> Result<Zonemd, ZonemdError> {
+    let fqdn = zone.fqdn().ok_or(ZonemdError::MissingFqdn)?;
+    let serial = zone.serial().ok_or(ZonemdError::MissingSerial)?;
+
+    let origin =
+        Name::from_ascii(fqdn.to_string()).map_err(|source| ZonemdError::InvalidOrigin {
+            name: fqdn.to_string(),
+            source,
+        })?;
+
+    let soa = soa_entry(zone, fqdn, serial);
+
+    let entries = zone
+        .status
+        .as_ref()
+        .map(|status| status.entries.as_slice())
+        .unwrap_or_default();
+
+    let records = std::iter::once(&soa)
+        .chain(&zone.ns_entries())
+        .chain(entries)
+        .map(TryInto::try_into)
+        .collect::<Result<Vec<Record>, _>>()?;
+
+    Ok(Zonemd {
+        serial,
+        hash_algorithm: algorithm,
+        digest: hex(&digest(&origin, &records, algorithm)?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use hickory_proto::rr::{Name, Record};
+    use kubizone_common::{DomainName, FullyQualifiedDomainName, Type};
+
+    use crate::v1alpha1::{Rname, Zone, ZoneEntry, ZoneSpec, ZoneStatus, ZonemdHashAlgorithm};
+
+    use super::{verify, zone_digest};
+
+    // Example zone of RFC 8976 appendix A.1.
+    fn example_zone() -> Zone {
+        let mut zone = Zone::new(
+            "example",
+            ZoneSpec {
+                domain_name: DomainName::try_from("example.").unwrap(),
+                mname: Some(FullyQualifiedDomainName::try_from("ns1.example.").unwrap()),
+                rname: Some(Rname::try_from("admin@example").unwrap()),
+                nameservers: vec![
+                    FullyQualifiedDomainName::try_from("ns1.example.").unwrap(),
+                    FullyQualifiedDomainName::try_from("ns2.example.").unwrap(),
+                ],
+                generate_ns_records: true,
+                ttl: 86400,
+                refresh: 1800,
+                retry: 900,
+                expire: 604800,
+                negative_response_cache: 86400,
+                ..Default::default()
+            },
+        );
+
+        zone.status = Some(ZoneStatus {
+            fqdn: Some(FullyQualifiedDomainName::try_from("example.").unwrap()),
+            serial: Some(2018031900),
+            entries: vec![
+                "ns2.example. 3600 IN AAAA 2001:db8::63".parse().unwrap(),
+                "ns1.example. 3600 IN A 203.0.113.63".parse().unwrap(),
+            ],
+            ..Default::default()
+        });
+
+        zone
+    }
+
+    #[test]
+    fn test_zone_digest() {
+        let zone = example_zone();
+        let zonemd = zone_digest(&zone, ZonemdHashAlgorithm::Sha384).unwrap();
+
+        assert_eq!(
+            zonemd.to_string(),
+            concat!(
+                "2018031900 1 1 ",
+                "c68090d90a7aed716bc459f9340e3d7c1370d4d24b7e2fc3",
+                "a1ddc0b9a87153b9a9713b3c9ae5cc27777f98b8e730044c"
+            )
+        );
+
+        // SOA records are not accepted by the zonefile parser.
+        let mut soa: ZoneEntry = "example. 86400 IN TXT soa".parse().unwrap();
+        soa.type_ = Type::SOA;
+        soa.rdata = String::from("ns1.example. admin.example. 2018031900 1800 900 604800 86400");
+
+        let origin = Name::from_ascii("example.").unwrap();
+        let mut records: Vec<Record> = [
+            "example.org. 300 IN A 192.168.0.1",
+            "ns1.example. 3600 IN A 203.0.113.63",
+            "ns2.example. 3600 IN AAAA 2001:db8::63",
+            "example. 86400 IN NS ns2.example.",
+            "example. 86400 IN NS ns1.example.",
+            "example. 86400 IN NS ns1.example.",
+        ]
+        .into_iter()
+        .map(|line| line.parse::<ZoneEntry>().unwrap())
+        .chain([soa])
+        .map(|entry| (&entry).try_into().unwrap())
+        .collect();
+
+        assert!(verify(&origin, &records, &zonemd).unwrap());
+
+        // Digests are compared regardless of the case of their hex digits.
+        let mut uppercase = zonemd.clone();
+        uppercase.digest = uppercase.digest.to_uppercase();
+        assert!(verify(&origin, &records, &uppercase).unwrap());
+
+        records.pop();
+        assert!(!verify(&origin, &records, &zonemd).unwrap());
+    }
+}